@@ -0,0 +1,64 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use minect::{Command, ExecutionStrategy, MinecraftConnection};
+use std::{env::temp_dir, fs::remove_dir_all};
+
+fn commands(count: usize) -> Vec<Command> {
+    (0..count)
+        .map(|index| {
+            Command::new(format!(
+                "scoreboard players set index{} bench {}",
+                index, index
+            ))
+        })
+        .collect()
+}
+
+/// Measures generating a structure file with the given number of commands, including gzip
+/// compression and writing it to disk.
+fn bench_execute_commands(c: &mut Criterion) {
+    let world_dir = temp_dir().join("minect_bench");
+    let _ = remove_dir_all(&world_dir);
+    let mut connection = MinecraftConnection::builder("bench", &world_dir).build();
+
+    let mut group = c.benchmark_group("execute_commands");
+    group.sample_size(10);
+    for count in [1, 100, connection.max_commands_per_batch()] {
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter_batched(
+                || commands(count),
+                |commands| {
+                    connection
+                        .execute_commands_with_strategy(commands, ExecutionStrategy::CommandBlocks)
+                        .unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+
+    drop(connection);
+    let _ = remove_dir_all(&world_dir);
+}
+
+criterion_group!(benches, bench_execute_commands);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    placement::{generate_building, CommandBlockArea},
+    BlockPos, ChunkPos, Region,
+};
+use std::collections::BTreeMap;
+
+/// Describes where the connection building of a [MinecraftConnection](crate::MinecraftConnection)
+/// is located in the world and what it consists of.
+///
+/// This can be used by external tools to show the connection building on a map or to warn players
+/// before they dig into it. It is obtained with
+/// [MinecraftConnection::building_info](crate::MinecraftConnection::building_info).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuildingInfo {
+    /// The chunk that contains the connection building.
+    pub chunk: ChunkPos,
+    /// The smallest [Region] that contains all blocks of the connection building and the area in
+    /// which command blocks are placed. Note that the protection around the activator rail reaches
+    /// one block into the neighbouring chunks.
+    pub bounding_box: Region,
+    /// The area in which command blocks are placed when executing commands.
+    pub command_block_area: Region,
+    /// The number of blocks of each kind that the connection building consists of, not counting
+    /// the command blocks, which change with every batch of commands.
+    pub blocks: BTreeMap<String, usize>,
+    _private: (),
+}
+
+impl BuildingInfo {
+    pub(crate) fn new(
+        connection_id: &str,
+        origin: BlockPos,
+        area: &CommandBlockArea,
+        hide_building: bool,
+    ) -> BuildingInfo {
+        let area_min = origin + BlockPos::from(area.offset);
+        let area_max = area_min + BlockPos::from(area.size) - BlockPos::new(1, 1, 1);
+        let command_block_area = Region::new(area_min, area_max);
+
+        let mut min = command_block_area.min();
+        let mut max = command_block_area.max();
+        let mut blocks = BTreeMap::new();
+        for block in generate_building(connection_id, hide_building) {
+            let pos = origin + BlockPos::from(block.pos);
+            min = BlockPos::new(min.x.min(pos.x), min.y.min(pos.y), min.z.min(pos.z));
+            max = BlockPos::new(max.x.max(pos.x), max.y.max(pos.y), max.z.max(pos.z));
+            *blocks.entry(block.name).or_insert(0) += 1;
+        }
+
+        BuildingInfo {
+            chunk: origin.chunk(),
+            bounding_box: Region::new(min, max),
+            command_block_area,
+            blocks,
+            _private: (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        // given:
+        let origin = ChunkPos::new(2, -1).min_block(1);
+
+        // when:
+        let info = BuildingInfo::new("test", origin, &CommandBlockArea::default(), false);
+
+        // then:
+        assert_eq!(info.chunk, ChunkPos::new(2, -1));
+        assert_eq!(
+            info.command_block_area,
+            Region::new((32, 1, -8), (47, 255, -1))
+        );
+        assert_eq!(info.bounding_box, Region::new((31, 1, -17), (47, 255, -1)));
+        assert_eq!(info.blocks["minecraft:stone"], 6);
+        assert_eq!(info.blocks["minecraft:structure_block"], 2);
+        assert_eq!(info.blocks["minecraft:activator_rail"], 1);
+    }
+}
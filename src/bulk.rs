@@ -0,0 +1,215 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{loaded::BatchLoaded, retry::is_after};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+/// A large number of commands that is executed in chunks with
+/// [MinecraftConnection::start_bulk_job](crate::MinecraftConnection::start_bulk_job). The progress
+/// of the job is updated whenever Minecraft acknowledges that it loaded a chunk.
+///
+/// Dropping a [BulkJob] does not cancel the execution of its commands, it only stops tracking the
+/// progress.
+pub struct BulkJob {
+    state: Arc<BulkJobState>,
+}
+
+/// The progress of a [BulkJob].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BulkProgress {
+    /// The number of commands of the job that were loaded by Minecraft.
+    pub completed: usize,
+    /// The total number of commands of the job.
+    pub total: usize,
+    _private: (),
+}
+
+impl BulkJob {
+    pub(crate) fn new(total: usize) -> BulkJob {
+        BulkJob {
+            state: Arc::new(BulkJobState {
+                total,
+                inner: Mutex::new(BulkJobInner {
+                    chunks: Vec::new(),
+                    last_loaded: None,
+                    completed: 0,
+                }),
+                listeners: RwLock::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Returns the number of completed commands and the total number of commands of this job.
+    pub fn progress(&self) -> (usize, usize) {
+        let completed = self.state.inner.lock().unwrap().completed;
+        (completed, self.state.total)
+    }
+
+    /// Whether Minecraft loaded all commands of this job.
+    pub fn is_complete(&self) -> bool {
+        let (completed, total) = self.progress();
+        completed == total
+    }
+
+    /// Returns a [Stream] of [BulkProgress] updates, which are sent whenever Minecraft loaded
+    /// another chunk of this job. The stream ends after the job is complete. To remove the listener
+    /// simply drop the stream.
+    pub fn add_progress_listener(&self) -> impl Stream<Item = BulkProgress> {
+        let (sender, receiver) = unbounded_channel();
+        if !self.is_complete() {
+            self.state.listeners.write().unwrap().push(sender);
+        }
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Returns a function that updates the progress of this job for each [BatchLoaded] event. The
+    /// function returns `false` once it is no longer needed.
+    pub(crate) fn observer(&self) -> impl Fn(&BatchLoaded) -> bool + Send + Sync + 'static {
+        let state = Arc::downgrade(&self.state);
+        move |event| match state.upgrade() {
+            Some(state) => state.loaded(event.id),
+            None => false,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn chunk_lens(&self) -> Vec<usize> {
+        let inner = self.state.inner.lock().unwrap();
+        inner.chunks.iter().map(|(_, len)| *len).collect()
+    }
+
+    /// Records that the chunk with the given structure `id` and `len` commands was written.
+    pub(crate) fn add_chunk(&self, id: u64, len: usize) {
+        let mut inner = self.state.inner.lock().unwrap();
+        inner.chunks.push((id, len));
+        self.state.update(&mut inner);
+    }
+}
+
+struct BulkJobState {
+    total: usize,
+    inner: Mutex<BulkJobInner>,
+    listeners: RwLock<Vec<UnboundedSender<BulkProgress>>>,
+}
+
+struct BulkJobInner {
+    /// The structure id and number of commands of each written chunk.
+    chunks: Vec<(u64, usize)>,
+    /// The structure id of the last batch that was loaded since the job started. Because structures
+    /// are loaded in order, all chunks up to this id are completed.
+    last_loaded: Option<u64>,
+    completed: usize,
+}
+
+impl BulkJobState {
+    /// Returns `false` if the job is complete.
+    fn loaded(&self, id: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let is_new = match inner.last_loaded {
+            Some(last_loaded) => is_after(id, last_loaded),
+            None => true,
+        };
+        if is_new {
+            inner.last_loaded = Some(id);
+            self.update(&mut inner);
+        }
+        inner.completed < self.total
+    }
+
+    fn update(&self, inner: &mut BulkJobInner) {
+        let last_loaded = match inner.last_loaded {
+            Some(last_loaded) => last_loaded,
+            None => return,
+        };
+        let completed = inner
+            .chunks
+            .iter()
+            .filter(|(id, _)| !is_after(*id, last_loaded))
+            .map(|(_, len)| len)
+            .sum();
+        if completed == inner.completed {
+            return;
+        }
+        inner.completed = completed;
+        let progress = BulkProgress {
+            completed,
+            total: self.total,
+            _private: (),
+        };
+        let mut listeners = self.listeners.write().unwrap();
+        listeners.retain(|sender| sender.send(progress).is_ok());
+        if completed == self.total {
+            listeners.clear(); // End all streams
+        }
+    }
+}
+
+/// Splits `commands` into chunks of at most `chunk_size` commands.
+pub(crate) fn split_into_chunks<T>(commands: Vec<T>, chunk_size: usize) -> Vec<Vec<T>> {
+    let mut chunks = Vec::new();
+    let mut commands = commands.into_iter().peekable();
+    while commands.peek().is_some() {
+        chunks.push(commands.by_ref().take(chunk_size).collect());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_progress_counts_chunks_up_to_loaded_id() {
+        // given:
+        let job = BulkJob::new(25);
+        let observer = job.observer();
+        let events = job.add_progress_listener();
+        observer(&loaded(9)); // Loaded before the job started
+        job.add_chunk(10, 10);
+        job.add_chunk(11, 10);
+        job.add_chunk(12, 5);
+
+        // when:
+        let observe_11 = observer(&loaded(11));
+        let observe_12 = observer(&loaded(12));
+
+        // then:
+        assert!(observe_11);
+        assert!(!observe_12);
+        assert_eq!(job.progress(), (25, 25));
+        let actual = block_on(events.map(|event| event.completed).collect::<Vec<_>>());
+        assert_eq!(actual, [20, 25]);
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        assert_eq!(
+            split_into_chunks(vec![1, 2, 3, 4, 5], 2),
+            [vec![1, 2], vec![3, 4], vec![5]]
+        );
+        assert!(split_into_chunks(Vec::<u8>::new(), 2).is_empty());
+    }
+
+    fn loaded(id: u64) -> BatchLoaded {
+        BatchLoaded::new(id, crate::LoadedSource::Log)
+    }
+}
@@ -55,8 +55,39 @@
 //! * [summon_named_entity_command] -> [SummonNamedEntityOutput]
 //! * [add_tag_command] -> [AddTagOutput]
 //! * [query_scoreboard_command] -> [QueryScoreboardOutput]
+//! * [data_get_entity_command] -> [DataGetEntityOutput]
+//! * [time_query_command] -> [TimeQueryOutput]
+//! * [time_set_command] -> [TimeSetOutput]
+//! * [weather_command] -> [WeatherOutput]
+//! * [worldborder_get_command] -> [WorldborderGetOutput]
+//! * [worldborder_set_command] -> [WorldborderSetOutput]
+//! * [teleport_command] -> [TeleportOutput]
+//! * [list_players_command] -> [ListPlayersOutput]
+//! * [seed_command] -> [SeedOutput]
+//! * [difficulty_query_command] -> [DifficultyQueryOutput]
+//! * [difficulty_set_command] -> [DifficultySetOutput]
+//! * [locate_structure_command] -> [LocateOutput]
+//! * [locate_biome_command] -> [LocateOutput]
+//!
+//! # Executing Commands as a Player
+//!
+//! Commands can be attributed to a specific player with [execute_as_player_command]. The output of
+//! such a command is logged with the display name of the player as
+//! [executor](crate::log::LogEvent::executor), so it can be filtered with [player_executed] or
+//! parsed with [PlayerFeedback::from_log_event]. A named listener for the player's name misses the
+//! output if the player is in a team with a prefix or suffix.
+
+mod classify;
+
+pub use self::classify::{classify, CommandKind};
 
-use crate::json::{create_json_text_component, escape_json};
+pub(crate) use self::classify::has_store_subcommand;
+
+use crate::{
+    json::{create_json_text_component, escape_json},
+    log::LogEvent,
+    BlockPos, ChunkPos, Region,
+};
 use std::{
     fmt::{self, Display},
     str::FromStr,
@@ -92,6 +123,42 @@ pub fn reset_logging_command() -> String {
     "function minect:reset_logging".to_string()
 }
 
+/// Generates three Minecraft commands that execute the given command without any output. Neither
+/// chat feedback nor log output is produced for the command, even if logging is enabled through an
+/// [enable_logging_command]. This is useful for bulk operations like thousands of `fill` commands
+/// that would otherwise drown the log file.
+///
+/// The three commands temporarily set the gamerules `commandBlockOutput`, `logAdminCommands` and
+/// `sendCommandFeedback` to `false` and restore them afterwards. The last command itself can
+/// produce a single line of output, so to silence many commands at once, use [silent_commands].
+pub fn silent_command(command: impl Into<String>) -> [String; 3] {
+    [
+        "function minect:silence".to_string(),
+        command.into(),
+        "function minect:reset_silence".to_string(),
+    ]
+}
+
+/// The same as [silent_command], but for multiple commands that are silenced together.
+pub fn silent_commands(commands: impl IntoIterator<Item = impl Into<String>>) -> Vec<String> {
+    let mut result = vec!["function minect:silence".to_string()];
+    result.extend(commands.into_iter().map(Into::into));
+    result.push("function minect:reset_silence".to_string());
+    result
+}
+
+/// Generates a Minecraft command that advises all players to rotate the log file, because it grew
+/// by `log_bytes` (see [LogVolume](crate::LogVolume)). Minecraft only starts a new `latest.log`
+/// when it is restarted, so the message suggests a restart.
+pub fn log_rotation_advice_command(log_bytes: u64) -> String {
+    let megabytes = log_bytes / (1024 * 1024);
+    let message = format!(
+        "The log file grew by {} MB. Restart Minecraft to start a new log file.",
+        megabytes
+    );
+    format!("tellraw @a {}", create_json_text_component(&message))
+}
+
 /// Generates two Minecraft commands that cause the given command to be executed from a command
 /// block. This can be used to log the output of a command when running in a `mcfunction`.
 ///
@@ -264,15 +331,20 @@ fn build_logged_cart_command(name: Option<&str>, command: &str) -> String {
         summon command_block_minecart ~ ~ ~ {{\
             {}\
             Command:\"{}\",\
-            Tags:[minect,minect_impulse],\
+            Tags:[{}],\
             LastExecution:1L,\
             TrackOutput:false,\
         }}",
         custom_name_entry,
         escape_json(command),
+        MINECART_TAGS.join(","),
     )
 }
 
+/// The tags of command block minecarts that execute once and are then removed by the Minect
+/// datapack.
+pub(crate) const MINECART_TAGS: [&str; 2] = ["minect", "minect_impulse"];
+
 /// Generates a Minecraft command that summons an area effect cloud with the given `name`.
 ///
 /// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
@@ -332,12 +404,97 @@ impl Display for SummonNamedEntityOutput {
     }
 }
 
+/// Generates a Minecraft command that summons a marker entity with the given `name`. Unlike the
+/// entity of [summon_named_entity_command] it stays in the world until it is killed, so it can be
+/// used as a condition flag that a datapack removes (see
+/// [await_entity_removed](crate::MinecraftConnection::await_entity_removed)). Markers require
+/// Minecraft 1.17 or newer.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [SummonNamedEntityOutput].
+pub fn summon_marker_command(name: &str) -> String {
+    let custom_name = create_json_text_component(name);
+    format!(
+        "summon marker ~ ~ ~ {{\"CustomName\":\"{}\"}}",
+        escape_json(&custom_name)
+    )
+}
+
+/// Generates a Minecraft command that kills all marker entities with the given `name` (see
+/// [summon_marker_command]).
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [KillOutput]. If no marker has the `name`, the command fails and nothing is logged.
+pub fn kill_marker_command(name: &str) -> String {
+    format!(
+        "kill @e[type=marker,name={}]",
+        escape_selector_argument(name)
+    )
+}
+
+/// The output of a [kill_marker_command] or any other `kill` command. This can be parsed from a
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has one of the following formats:
+/// ```none
+/// Killed <entity>
+/// Killed <count> entities
+/// ```
+///
+/// For example:
+/// ```none
+/// Killed my_marker
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KillOutput {
+    /// The custom name or UUID of the killed entity, if exactly one entity was killed.
+    pub entity: Option<String>,
+    /// The number of killed entities.
+    pub count: u32,
+    _private: (),
+}
+impl FromStr for KillOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<KillOutput> {
+            let suffix = output.strip_prefix("Killed ")?;
+            let count = suffix
+                .strip_suffix(" entities")
+                .and_then(|count| count.parse().ok());
+            let (entity, count) = match count {
+                Some(count) => (None, count),
+                None => (Some(suffix.to_string()), 1),
+            };
+
+            Some(KillOutput {
+                entity,
+                count,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for KillOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.entity {
+            Some(entity) => write!(f, "Killed {}", entity),
+            None => write!(f, "Killed {} entities", self.count),
+        }
+    }
+}
+
 /// Generates a Minecraft command that adds the given `tag` to the given `entity`.
 ///
 /// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into an
 /// [AddTagOutput].
 ///
-/// `entity` can be any selector or name.
+/// `entity` can be any selector or name. `tag` has to be a
+/// [valid unquoted string](is_valid_unquoted_string).
+///
+/// Both are inserted verbatim, so untrusted input like player names from chat should be passed as a
+/// [PlayerName] or [player_selector] to prevent it from altering the command.
 ///
 /// For a [logged_cart_command] that only uses this tag as a means to know when/if the command is
 /// executed (for example inside an `execute if` command) it can be useful to add a tag to the `@s`
@@ -398,10 +555,32 @@ impl Display for AddTagOutput {
 /// [QueryScoreboardOutput].
 ///
 /// `entity` can be any selector or name.
+///
+/// `entity` is inserted verbatim, so untrusted input like player names from chat should be passed as
+/// a [PlayerName] or [player_selector] to prevent it from altering the command.
 pub fn query_scoreboard_command(entity: impl Display, scoreboard: impl Display) -> String {
     format!("scoreboard players add {} {} 0", entity, scoreboard)
 }
 
+/// Generates a Minecraft command that executes `command` and stores its result in the score of
+/// `entity` in `objective`.
+///
+/// `entity` can be any selector or name. If `command` fails, a score of `0` is stored.
+///
+/// The stored score can be retrieved with a [query_scoreboard_command].
+pub fn store_result_in_score(
+    command: impl AsRef<str>,
+    entity: impl Display,
+    objective: impl Display,
+) -> String {
+    format!(
+        "execute store result score {} {} run {}",
+        entity,
+        objective,
+        command.as_ref()
+    )
+}
+
 /// The output of a [query_scoreboard_command]. This can be parsed from
 /// [LogEvent::output](crate::log::LogEvent::output).
 ///
@@ -462,5 +641,1784 @@ impl Display for QueryScoreboardOutput {
     }
 }
 
+/// Generates a Minecraft command that queries the NBT data at `path` of `entity`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [DataGetEntityOutput].
+///
+/// `entity` can be any selector or name that selects a single entity. `path` is an NBT path like
+/// `Inventory` or `Pos[0]`.
+pub fn data_get_entity_command(entity: impl Display, path: impl Display) -> String {
+    format!("data get entity {} {}", entity, path)
+}
+
+/// The output of a [data_get_entity_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// <entity> has the following entity data: <data>
+/// ```
+///
+/// For example:
+/// ```none
+/// Herobrine has the following entity data: [0.5d, 64.0d, 0.5d]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DataGetEntityOutput {
+    /// The custom name or UUID of the entity.
+    pub entity: String,
+    /// The queried NBT data in SNBT format.
+    pub data: String,
+    _private: (),
+}
+impl FromStr for DataGetEntityOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<DataGetEntityOutput> {
+            let (entity, data) = output.split_once(" has the following entity data: ")?;
+
+            Some(DataGetEntityOutput {
+                entity: entity.to_string(),
+                data: data.to_string(),
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for DataGetEntityOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has the following entity data: {}",
+            self.entity, self.data
+        )
+    }
+}
+
+/// The kind of time that is queried by a [time_query_command].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TimeQuery {
+    /// The number of game ticks since dawn of the current day.
+    Daytime,
+    /// The number of game ticks since the world was created.
+    Gametime,
+    /// The number of days since the world was created.
+    Day,
+}
+impl Display for TimeQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeQuery::Daytime => write!(f, "daytime"),
+            TimeQuery::Gametime => write!(f, "gametime"),
+            TimeQuery::Day => write!(f, "day"),
+        }
+    }
+}
+
+/// Generates a Minecraft command that queries the time of the world.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TimeQueryOutput].
+pub fn time_query_command(query: TimeQuery) -> String {
+    format!("time query {}", query)
+}
+
+/// The output of a [time_query_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// The time is <time>
+/// ```
+///
+/// For example:
+/// ```none
+/// The time is 1000
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeQueryOutput {
+    /// The queried time.
+    pub time: i32,
+    _private: (),
+}
+impl FromStr for TimeQueryOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<TimeQueryOutput> {
+            let time = output.strip_prefix("The time is ")?;
+            let time = time.parse().ok()?;
+
+            Some(TimeQueryOutput { time, _private: () })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for TimeQueryOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The time is {}", self.time)
+    }
+}
+
+/// Generates a Minecraft command that sets the time of day.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TimeSetOutput].
+///
+/// `time` can be a number of ticks like `1000`, a duration with unit like `0.5d` or one of `day`,
+/// `night`, `noon` and `midnight`.
+pub fn time_set_command(time: impl Display) -> String {
+    format!("time set {}", time)
+}
+
+/// The output of a [time_set_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Set the time to <time>
+/// ```
+///
+/// For example:
+/// ```none
+/// Set the time to 1000
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeSetOutput {
+    /// The new time of day in ticks.
+    pub time: i32,
+    _private: (),
+}
+impl FromStr for TimeSetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<TimeSetOutput> {
+            let time = output.strip_prefix("Set the time to ")?;
+            let time = time.parse().ok()?;
+
+            Some(TimeSetOutput { time, _private: () })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for TimeSetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Set the time to {}", self.time)
+    }
+}
+
+/// The weather of a world.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Weather {
+    /// No rain and no thunder.
+    Clear,
+    /// Rain without thunder.
+    Rain,
+    /// Rain with thunder.
+    Thunder,
+}
+
+/// Generates a Minecraft command that sets the weather.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [WeatherOutput].
+pub fn weather_command(weather: Weather) -> String {
+    let weather = match weather {
+        Weather::Clear => "clear",
+        Weather::Rain => "rain",
+        Weather::Thunder => "thunder",
+    };
+    format!("weather {}", weather)
+}
+
+/// The output of a [weather_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Set the weather to <clear|rain|rain & thunder>
+/// ```
+///
+/// For example:
+/// ```none
+/// Set the weather to rain & thunder
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WeatherOutput {
+    /// The new weather.
+    pub weather: Weather,
+    _private: (),
+}
+impl FromStr for WeatherOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<WeatherOutput> {
+            let weather = match output.strip_prefix("Set the weather to ")? {
+                "clear" => Weather::Clear,
+                "rain" => Weather::Rain,
+                "rain & thunder" => Weather::Thunder,
+                _ => return None,
+            };
+
+            Some(WeatherOutput {
+                weather,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for WeatherOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weather = match self.weather {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Thunder => "rain & thunder",
+        };
+        write!(f, "Set the weather to {}", weather)
+    }
+}
+
+/// Generates a Minecraft command that queries the width of the world border.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [WorldborderGetOutput].
+pub fn worldborder_get_command() -> String {
+    "worldborder get".to_string()
+}
+
+/// The output of a [worldborder_get_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// The world border is currently <width> block(s) wide
+/// ```
+///
+/// For example:
+/// ```none
+/// The world border is currently 59999968 block(s) wide
+/// ```
+///
+/// Older versions of Minecraft write `blocks` instead of `block(s)`, this is also supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldborderGetOutput {
+    /// The width of the world border in blocks.
+    pub width: f64,
+    _private: (),
+}
+impl FromStr for WorldborderGetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<WorldborderGetOutput> {
+            let suffix = output.strip_prefix("The world border is currently ")?;
+            let width = strip_blocks_wide_suffix(suffix)?;
+            let width = width.parse().ok()?;
+
+            Some(WorldborderGetOutput {
+                width,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for WorldborderGetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The world border is currently {} block(s) wide",
+            self.width
+        )
+    }
+}
+
+/// Generates a Minecraft command that sets the width of the world border to `width` blocks.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [WorldborderSetOutput].
+pub fn worldborder_set_command(width: f64) -> String {
+    format!("worldborder set {}", width)
+}
+
+/// The output of a [worldborder_set_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Set the world border to <width> block(s) wide
+/// ```
+///
+/// For example:
+/// ```none
+/// Set the world border to 1000 block(s) wide
+/// ```
+///
+/// Older versions of Minecraft write `blocks` instead of `block(s)`, this is also supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldborderSetOutput {
+    /// The new width of the world border in blocks.
+    pub width: f64,
+    _private: (),
+}
+impl FromStr for WorldborderSetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<WorldborderSetOutput> {
+            let suffix = output.strip_prefix("Set the world border to ")?;
+            let width = strip_blocks_wide_suffix(suffix)?;
+            let width = width.parse().ok()?;
+
+            Some(WorldborderSetOutput {
+                width,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for WorldborderSetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Set the world border to {} block(s) wide", self.width)
+    }
+}
+
+fn strip_blocks_wide_suffix(output: &str) -> Option<&str> {
+    output
+        .strip_suffix(" block(s) wide")
+        .or_else(|| output.strip_suffix(" blocks wide"))
+}
+
+/// Generates a Minecraft command that teleports `entity` to `destination`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TeleportOutput]. If no entity is found, the command fails and no
+/// [LogEvent](crate::log::LogEvent) is produced.
+///
+/// `entity` can be any selector or name. `destination` can either be coordinates like `0 64 0` or
+/// `~ ~10 ~` or a selector or name of a single target entity.
+pub fn teleport_command(entity: impl Display, destination: impl Display) -> String {
+    format!("tp {} {}", entity, destination)
+}
+
+/// The output of a [teleport_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Teleported <entity> to <destination>
+/// ```
+///
+/// For example:
+/// ```none
+/// Teleported Herobrine to 0.500000, 64.000000, 0.500000
+/// ```
+///
+/// If multiple entities are teleported, `<entity>` is something like `3 entities`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TeleportOutput {
+    /// The custom name or UUID of the teleported entity or the number of teleported entities.
+    pub entity: String,
+    /// The coordinates or the name of the target entity that `entity` was teleported to.
+    pub destination: String,
+    _private: (),
+}
+impl FromStr for TeleportOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<TeleportOutput> {
+            let suffix = output.strip_prefix("Teleported ")?;
+            let (entity, destination) = suffix.split_once(" to ")?;
+
+            Some(TeleportOutput {
+                entity: entity.to_string(),
+                destination: destination.to_string(),
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for TeleportOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Teleported {} to {}", self.entity, self.destination)
+    }
+}
+
+/// Generates a Minecraft command that lists the names of all online players.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [ListPlayersOutput].
+pub fn list_players_command() -> String {
+    "list".to_string()
+}
+
+/// Generates a Minecraft command that lists the names and UUIDs of all online players.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [ListPlayersOutput].
+pub fn list_players_uuids_command() -> String {
+    "list uuids".to_string()
+}
+
+/// The output of a [list_players_command] or [list_players_uuids_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// There are <count> of a max of <max> players online: <players>
+/// ```
+///
+/// For example:
+/// ```none
+/// There are 2 of a max of 20 players online: Herobrine, Notch
+/// ```
+///
+/// Or with UUIDs:
+/// ```none
+/// There are 1 of a max of 20 players online: Herobrine (f84c6a79-0a4e-45e0-879b-cd49ebd4c4e2)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListPlayersOutput {
+    /// The number of online players.
+    pub count: u32,
+    /// The maximum number of players that can be online at the same time.
+    pub max: u32,
+    /// The online players.
+    pub players: Vec<OnlinePlayer>,
+    _private: (),
+}
+impl FromStr for ListPlayersOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<ListPlayersOutput> {
+            let suffix = output.strip_prefix("There are ")?;
+            let (count, suffix) = suffix.split_once(" of a max of ")?;
+            let (max, players) = suffix.split_once(" players online:")?;
+            let count = count.parse().ok()?;
+            let max = max.parse().ok()?;
+            let players = players.trim_start();
+            let players = if players.is_empty() {
+                Vec::new()
+            } else {
+                players
+                    .split(", ")
+                    .map(OnlinePlayer::from_list_entry)
+                    .collect()
+            };
+
+            Some(ListPlayersOutput {
+                count,
+                max,
+                players,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for ListPlayersOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "There are {} of a max of {} players online: ",
+            self.count, self.max
+        )?;
+        for (index, player) in self.players.iter().enumerate() {
+            if index != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", player)?;
+        }
+        Ok(())
+    }
+}
+
+/// An online player as listed in a [ListPlayersOutput].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OnlinePlayer {
+    /// The name of the player.
+    pub name: String,
+    /// The UUID of the player. This is only available when using [list_players_uuids_command].
+    pub uuid: Option<String>,
+    _private: (),
+}
+impl OnlinePlayer {
+    fn from_list_entry(entry: &str) -> OnlinePlayer {
+        let (name, uuid) = entry
+            .strip_suffix(')')
+            .and_then(|entry| entry.rsplit_once(" ("))
+            .map_or((entry, None), |(name, uuid)| (name, Some(uuid.to_string())));
+        OnlinePlayer {
+            name: name.to_string(),
+            uuid,
+            _private: (),
+        }
+    }
+}
+impl Display for OnlinePlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(uuid) = &self.uuid {
+            write!(f, "{} ({})", self.name, uuid)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// The kind of datapacks that are listed by a [datapack_list_command].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DatapackList {
+    /// Datapacks that are currently active.
+    Enabled,
+    /// Datapacks that are installed, but not active.
+    Available,
+}
+impl Display for DatapackList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatapackList::Enabled => write!(f, "enabled"),
+            DatapackList::Available => write!(f, "available"),
+        }
+    }
+}
+
+/// Generates a Minecraft command that lists the datapacks of the world.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [DatapackListOutput].
+pub fn datapack_list_command(list: DatapackList) -> String {
+    format!("datapack list {}", list)
+}
+
+/// The output of a [datapack_list_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has one of the following formats:
+/// ```none
+/// There are <count> data pack(s) <list>: [<datapack>], [<datapack>]
+/// There are no data packs enabled
+/// There are no more data packs available
+/// ```
+///
+/// For example:
+/// ```none
+/// There are 2 data pack(s) enabled: [vanilla (built-in)], [file/minect (world)]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DatapackListOutput {
+    /// Whether enabled or available datapacks were listed.
+    pub list: DatapackList,
+    /// The names of the listed datapacks without their source, for example `file/minect`.
+    pub datapacks: Vec<String>,
+    _private: (),
+}
+impl FromStr for DatapackListOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<DatapackListOutput> {
+            let suffix = output.strip_prefix("There are ")?;
+            let (list, datapacks) = match suffix {
+                "no data packs enabled" => (DatapackList::Enabled, Vec::new()),
+                "no more data packs available" => (DatapackList::Available, Vec::new()),
+                _ => {
+                    let (count, suffix) = suffix.split_once(' ')?;
+                    let count = count.parse::<usize>().ok()?;
+                    let (list, datapacks) =
+                        if let Some((_, datapacks)) = suffix.split_once(" enabled: ") {
+                            (DatapackList::Enabled, datapacks)
+                        } else {
+                            let (_, datapacks) = suffix.split_once(" available: ")?;
+                            (DatapackList::Available, datapacks)
+                        };
+                    let datapacks = datapacks
+                        .strip_prefix('[')?
+                        .strip_suffix(']')?
+                        .split("], [")
+                        .map(|datapack| {
+                            datapack
+                                .strip_suffix(')')
+                                .and_then(|datapack| datapack.rsplit_once(" ("))
+                                .map_or(datapack, |(name, _source)| name)
+                                .to_string()
+                        })
+                        .collect::<Vec<_>>();
+                    if datapacks.len() != count {
+                        return None;
+                    }
+                    (list, datapacks)
+                }
+            };
+            Some(DatapackListOutput {
+                list,
+                datapacks,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for DatapackListOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.list, self.datapacks.is_empty()) {
+            (DatapackList::Enabled, true) => write!(f, "There are no data packs enabled"),
+            (DatapackList::Available, true) => write!(f, "There are no more data packs available"),
+            (list, false) => {
+                write!(
+                    f,
+                    "There are {} data pack(s) {}: [{}]",
+                    self.datapacks.len(),
+                    list,
+                    self.datapacks.join("], [")
+                )
+            }
+        }
+    }
+}
+
+/// Generates a Minecraft command that enables the datapack with the given `name`, for example
+/// `file/minect`.
+pub fn datapack_enable_command(name: impl AsRef<str>) -> String {
+    format!("datapack enable \"{}\"", escape_json(name.as_ref()))
+}
+
+/// Generates a Minecraft command that queries the seed of the world.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [SeedOutput].
+pub fn seed_command() -> String {
+    "seed".to_string()
+}
+
+/// The output of a [seed_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Seed: [<seed>]
+/// ```
+///
+/// For example:
+/// ```none
+/// Seed: [-4172144997902289642]
+/// ```
+///
+/// Before Minecraft 1.16 the seed is not surrounded by square brackets, this is also supported.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SeedOutput {
+    /// The seed of the world.
+    pub seed: i64,
+    _private: (),
+}
+impl FromStr for SeedOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<SeedOutput> {
+            let seed = output.strip_prefix("Seed: ")?;
+            let seed = seed
+                .strip_prefix('[')
+                .and_then(|seed| seed.strip_suffix(']'))
+                .unwrap_or(seed);
+            let seed = seed.parse().ok()?;
+
+            Some(SeedOutput { seed, _private: () })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for SeedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Seed: [{}]", self.seed)
+    }
+}
+
+/// The difficulty of a world.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    Peaceful,
+    Easy,
+    Normal,
+    Hard,
+}
+impl Difficulty {
+    fn from_name(name: &str) -> Option<Difficulty> {
+        match name {
+            "Peaceful" => Some(Difficulty::Peaceful),
+            "Easy" => Some(Difficulty::Easy),
+            "Normal" => Some(Difficulty::Normal),
+            "Hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Peaceful => "Peaceful",
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+/// Generates a Minecraft command that queries the difficulty of the world.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [DifficultyQueryOutput].
+pub fn difficulty_query_command() -> String {
+    "difficulty".to_string()
+}
+
+/// The output of a [difficulty_query_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// The difficulty is <difficulty>
+/// ```
+///
+/// For example:
+/// ```none
+/// The difficulty is Normal
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DifficultyQueryOutput {
+    /// The difficulty of the world.
+    pub difficulty: Difficulty,
+    _private: (),
+}
+impl FromStr for DifficultyQueryOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<DifficultyQueryOutput> {
+            let difficulty = output.strip_prefix("The difficulty is ")?;
+            let difficulty = Difficulty::from_name(difficulty)?;
+
+            Some(DifficultyQueryOutput {
+                difficulty,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for DifficultyQueryOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The difficulty is {}", self.difficulty.name())
+    }
+}
+
+/// Generates a Minecraft command that sets the difficulty of the world.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [DifficultySetOutput]. If the world already has the given `difficulty`, the command fails and no
+/// [LogEvent](crate::log::LogEvent) is produced.
+pub fn difficulty_set_command(difficulty: Difficulty) -> String {
+    format!("difficulty {}", difficulty.name().to_lowercase())
+}
+
+/// The output of a [difficulty_set_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// The difficulty has been set to <difficulty>
+/// ```
+///
+/// For example:
+/// ```none
+/// The difficulty has been set to Hard
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DifficultySetOutput {
+    /// The new difficulty of the world.
+    pub difficulty: Difficulty,
+    _private: (),
+}
+impl FromStr for DifficultySetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<DifficultySetOutput> {
+            let difficulty = output.strip_prefix("The difficulty has been set to ")?;
+            let difficulty = Difficulty::from_name(difficulty)?;
+
+            Some(DifficultySetOutput {
+                difficulty,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for DifficultySetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The difficulty has been set to {}",
+            self.difficulty.name()
+        )
+    }
+}
+
+/// Generates a Minecraft command that queries the value of the gamerule `rule`, for example
+/// `doDaylightCycle`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [GameruleOutput].
+pub fn gamerule_query_command(rule: impl Display) -> String {
+    format!("gamerule {}", rule)
+}
+
+/// Generates a Minecraft command that sets the gamerule `rule` to `value`. For example
+/// `gamerule_set_command("doDaylightCycle", false)` stops the time of day, which is useful for
+/// deterministic tests on Minecraft versions without the `tick` command.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [GameruleOutput].
+pub fn gamerule_set_command(rule: impl Display, value: impl Display) -> String {
+    format!("gamerule {} {}", rule, value)
+}
+
+/// The output of a [gamerule_query_command] or [gamerule_set_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Gamerule <rule> is <currently|now> set to: <value>
+/// ```
+///
+/// For example:
+/// ```none
+/// Gamerule doDaylightCycle is now set to: false
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameruleOutput {
+    /// The name of the gamerule.
+    pub rule: String,
+    /// The value of the gamerule, for example `true` or `20`.
+    pub value: String,
+    /// Whether the gamerule was set by the command, rather than queried.
+    pub set: bool,
+    _private: (),
+}
+impl FromStr for GameruleOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<GameruleOutput> {
+            let output = output.strip_prefix("Gamerule ")?;
+            let (rule, output) = output.split_once(" is ")?;
+            let (set, value) = if let Some(value) = output.strip_prefix("now set to: ") {
+                (true, value)
+            } else {
+                (false, output.strip_prefix("currently set to: ")?)
+            };
+
+            Some(GameruleOutput {
+                rule: rule.to_string(),
+                value: value.to_string(),
+                set,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for GameruleOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.set { "now" } else { "currently" };
+        write!(
+            f,
+            "Gamerule {} is {} set to: {}",
+            self.rule, state, self.value
+        )
+    }
+}
+
+/// Generates a Minecraft command that freezes all game ticks, except for players. Frozen ticks can
+/// be advanced with [tick_step_command], which allows deterministic tests of tick-sensitive logic.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TickFreezeOutput].
+///
+/// This command requires Minecraft 1.20.3 or later. In earlier versions
+/// [gamerule_set_command] can be used to disable `doDaylightCycle` instead.
+pub fn tick_freeze_command() -> String {
+    "tick freeze".to_string()
+}
+
+/// Generates a Minecraft command that resumes game ticks after a [tick_freeze_command].
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TickFreezeOutput].
+///
+/// This command requires Minecraft 1.20.3 or later.
+pub fn tick_unfreeze_command() -> String {
+    "tick unfreeze".to_string()
+}
+
+/// The output of a [tick_freeze_command] or [tick_unfreeze_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has one of the following formats:
+/// ```none
+/// The game is frozen
+/// The game is running normally
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TickFreezeOutput {
+    /// Whether game ticks are frozen now.
+    pub frozen: bool,
+    _private: (),
+}
+impl FromStr for TickFreezeOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        let frozen = match output {
+            "The game is frozen" => true,
+            "The game is running normally" => false,
+            _ => return Err(()),
+        };
+        Ok(TickFreezeOutput {
+            frozen,
+            _private: (),
+        })
+    }
+}
+impl Display for TickFreezeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.frozen {
+            write!(f, "The game is frozen")
+        } else {
+            write!(f, "The game is running normally")
+        }
+    }
+}
+
+/// Generates a Minecraft command that advances frozen game ticks by the given number of `ticks`
+/// (see [tick_freeze_command]). The ticks are executed in the following game ticks, so commands in
+/// the same batch still see the previous state.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [TickStepOutput]. If the game is not frozen, the command fails and no
+/// [LogEvent](crate::log::LogEvent) is produced.
+///
+/// This command requires Minecraft 1.20.3 or later.
+pub fn tick_step_command(ticks: u32) -> String {
+    format!("tick step {}", ticks)
+}
+
+/// The output of a [tick_step_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Stepping <ticks> tick(s)
+/// ```
+///
+/// For example:
+/// ```none
+/// Stepping 20 tick(s)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TickStepOutput {
+    /// The number of ticks that are stepped.
+    pub ticks: u32,
+    _private: (),
+}
+impl FromStr for TickStepOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<TickStepOutput> {
+            let ticks = output.strip_prefix("Stepping ")?.strip_suffix(" tick(s)")?;
+            let ticks = ticks.parse().ok()?;
+
+            Some(TickStepOutput {
+                ticks,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for TickStepOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stepping {} tick(s)", self.ticks)
+    }
+}
+
+/// Generates a Minecraft command that locates the nearest `structure`, for example
+/// `minecraft:village_plains` or `#minecraft:village`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [LocateOutput].
+///
+/// This command requires Minecraft 1.19 or later. In earlier versions the command is
+/// `locate <structure>`.
+pub fn locate_structure_command(structure: impl Display) -> String {
+    format!("locate structure {}", structure)
+}
+
+/// Generates a Minecraft command that locates the nearest `biome`, for example `minecraft:plains`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [LocateOutput].
+///
+/// This command requires Minecraft 1.19 or later. In earlier versions the command is
+/// `locatebiome <biome>`.
+pub fn locate_biome_command(biome: impl Display) -> String {
+    format!("locate biome {}", biome)
+}
+
+/// The output of a [locate_structure_command] or [locate_biome_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// The nearest <name> is at [<x>, <y>, <z>] (<distance> blocks away)
+/// ```
+///
+/// For example:
+/// ```none
+/// The nearest minecraft:village_plains is at [-480, ~, 208] (523 blocks away)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LocateOutput {
+    /// The name of the located structure or biome.
+    pub name: String,
+    /// The x coordinate.
+    pub x: i32,
+    /// The y coordinate. This is `None` for structures, because Minecraft only locates their
+    /// horizontal position.
+    pub y: Option<i32>,
+    /// The z coordinate.
+    pub z: i32,
+    /// The horizontal distance in blocks.
+    pub distance: i32,
+    _private: (),
+}
+impl FromStr for LocateOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<LocateOutput> {
+            let suffix = output.strip_prefix("The nearest ")?;
+            const IS_AT: &str = " is at [";
+            let index = suffix.rfind(IS_AT)?;
+            let (name, suffix) = suffix.split_at(index);
+            let suffix = suffix.strip_prefix(IS_AT)?;
+            let (coordinates, suffix) = suffix.split_once("] (")?;
+            let distance = suffix.strip_suffix(" blocks away)")?;
+
+            let mut coordinates = coordinates.split(", ");
+            let x = coordinates.next()?.parse().ok()?;
+            let y = match coordinates.next()? {
+                "~" => None,
+                y => Some(y.parse().ok()?),
+            };
+            let z = coordinates.next()?.parse().ok()?;
+            if coordinates.next().is_some() {
+                return None;
+            }
+
+            Some(LocateOutput {
+                name: name.to_string(),
+                x,
+                y,
+                z,
+                distance: distance.parse().ok()?,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for LocateOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The nearest {} is at [{}, ", self.name, self.x)?;
+        if let Some(y) = self.y {
+            write!(f, "{}", y)?;
+        } else {
+            write!(f, "~")?;
+        }
+        write!(f, ", {}] ({} blocks away)", self.z, self.distance)
+    }
+}
+impl LocateOutput {
+    /// The located position or `None` if Minecraft did not locate the y coordinate.
+    pub fn pos(&self) -> Option<BlockPos> {
+        Some(BlockPos::new(self.x, self.y?, self.z))
+    }
+
+    /// The chunk that contains the located position.
+    pub fn chunk(&self) -> ChunkPos {
+        BlockPos::new(self.x, 0, self.z).chunk()
+    }
+}
+
+/// Generates a Minecraft command that places `block` at `pos`, for example
+/// `setblock_command(BlockPos::new(0, 64, 0), "minecraft:stone")`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [SetblockOutput]. If the block at `pos` already is `block`, the command fails and no
+/// [LogEvent](crate::log::LogEvent) is produced.
+pub fn setblock_command(pos: BlockPos, block: impl Display) -> String {
+    format!("setblock {} {}", pos, block)
+}
+
+/// The output of a [setblock_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Changed the block at <x>, <y>, <z>
+/// ```
+///
+/// For example:
+/// ```none
+/// Changed the block at 0, 64, 0
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SetblockOutput {
+    /// The position of the changed block.
+    pub pos: BlockPos,
+    _private: (),
+}
+impl FromStr for SetblockOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<SetblockOutput> {
+            let suffix = output.strip_prefix("Changed the block at ")?;
+            let pos = suffix.replace(", ", " ").parse().ok()?;
+            Some(SetblockOutput { pos, _private: () })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for SetblockOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let BlockPos { x, y, z } = self.pos;
+        write!(f, "Changed the block at {}, {}, {}", x, y, z)
+    }
+}
+
+/// How a [place_template_command] rotates a structure around its origin, when viewed from above.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StructureRotation {
+    /// The structure is not rotated.
+    #[default]
+    None,
+    /// The structure is rotated by 90 degrees clockwise, so east becomes south.
+    Clockwise90,
+    /// The structure is rotated by 180 degrees, so east becomes west.
+    Rotate180,
+    /// The structure is rotated by 90 degrees counterclockwise, so east becomes north.
+    Counterclockwise90,
+}
+impl Display for StructureRotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureRotation::None => write!(f, "none"),
+            StructureRotation::Clockwise90 => write!(f, "clockwise_90"),
+            StructureRotation::Rotate180 => write!(f, "180"),
+            StructureRotation::Counterclockwise90 => write!(f, "counterclockwise_90"),
+        }
+    }
+}
+
+/// How a [place_template_command] mirrors a structure. The structure is mirrored before it is
+/// [rotated](StructureRotation).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StructureMirror {
+    /// The structure is not mirrored.
+    #[default]
+    None,
+    /// The Z axis of the structure is flipped, so north and south are swapped.
+    LeftRight,
+    /// The X axis of the structure is flipped, so east and west are swapped.
+    FrontBack,
+}
+impl Display for StructureMirror {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureMirror::None => write!(f, "none"),
+            StructureMirror::LeftRight => write!(f, "left_right"),
+            StructureMirror::FrontBack => write!(f, "front_back"),
+        }
+    }
+}
+
+/// Generates a Minecraft command that places the structure `template` with its origin at `pos`,
+/// for example `place_template_command("minecraft:igloo/top", BlockPos::new(0, 64, 0),
+/// StructureRotation::None, StructureMirror::None)`. This requires Minecraft 1.19 or newer.
+///
+/// Minecraft mirrors and rotates the structure around its origin and also updates the block states
+/// and entities in it, for example the `facing` of stairs or the `rotation` of signs.
+///
+/// If the template was placed, the [LogEvent::output](crate::log::LogEvent::output) starts with
+/// `Loaded template`.
+pub fn place_template_command(
+    template: impl Display,
+    pos: BlockPos,
+    rotation: StructureRotation,
+    mirror: StructureMirror,
+) -> String {
+    format!(
+        "place template {} {} {} {}",
+        template, pos, rotation, mirror
+    )
+}
+
+/// Generates a Minecraft command that fills the `region` with `block`, for example
+/// `fill_command(Region::new((0, 64, 0), (15, 64, 15)), "minecraft:stone")`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into a
+/// [FillOutput]. Minecraft limits the [volume](Region::volume) of the `region` to 32768 blocks.
+pub fn fill_command(region: Region, block: impl Display) -> String {
+    format!("fill {} {}", region, block)
+}
+
+/// The output of a [fill_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Successfully filled <blocks> block(s)
+/// ```
+///
+/// For example:
+/// ```none
+/// Successfully filled 256 block(s)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FillOutput {
+    /// The number of blocks that were changed.
+    pub blocks: u32,
+    _private: (),
+}
+impl FromStr for FillOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<FillOutput> {
+            let suffix = output.strip_prefix("Successfully filled ")?;
+            let blocks = suffix.strip_suffix(" block(s)")?.parse().ok()?;
+            Some(FillOutput {
+                blocks,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for FillOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Successfully filled {} block(s)", self.blocks)
+    }
+}
+
+/// The color of a team, which is also used for the team specific sidebar
+/// [DisplaySlot::SidebarTeam].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TeamColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+}
+impl TeamColor {
+    /// The name of the color in commands, for example `dark_blue`.
+    pub fn name(self) -> &'static str {
+        match self {
+            TeamColor::Black => "black",
+            TeamColor::DarkBlue => "dark_blue",
+            TeamColor::DarkGreen => "dark_green",
+            TeamColor::DarkAqua => "dark_aqua",
+            TeamColor::DarkRed => "dark_red",
+            TeamColor::DarkPurple => "dark_purple",
+            TeamColor::Gold => "gold",
+            TeamColor::Gray => "gray",
+            TeamColor::DarkGray => "dark_gray",
+            TeamColor::Blue => "blue",
+            TeamColor::Green => "green",
+            TeamColor::Aqua => "aqua",
+            TeamColor::Red => "red",
+            TeamColor::LightPurple => "light_purple",
+            TeamColor::Yellow => "yellow",
+            TeamColor::White => "white",
+        }
+    }
+
+    /// The formatting code of the color, for example `§1` for [DarkBlue](Self::DarkBlue). Prefixing
+    /// a score holder name with it colors the name in the sidebar.
+    pub fn formatting_code(self) -> &'static str {
+        match self {
+            TeamColor::Black => "§0",
+            TeamColor::DarkBlue => "§1",
+            TeamColor::DarkGreen => "§2",
+            TeamColor::DarkAqua => "§3",
+            TeamColor::DarkRed => "§4",
+            TeamColor::DarkPurple => "§5",
+            TeamColor::Gold => "§6",
+            TeamColor::Gray => "§7",
+            TeamColor::DarkGray => "§8",
+            TeamColor::Blue => "§9",
+            TeamColor::Green => "§a",
+            TeamColor::Aqua => "§b",
+            TeamColor::Red => "§c",
+            TeamColor::LightPurple => "§d",
+            TeamColor::Yellow => "§e",
+            TeamColor::White => "§f",
+        }
+    }
+}
+
+/// Where a scoreboard objective is displayed, see [scoreboard_setdisplay_command].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DisplaySlot {
+    /// The player list that is shown while holding the tab key.
+    List,
+    /// The sidebar on the right side of the screen.
+    Sidebar,
+    /// The sidebar, but only for players in a team with the given color.
+    SidebarTeam(TeamColor),
+    /// Below the name of players. Requires Minecraft 1.20.2 or newer, before it was called
+    /// `belowName`.
+    BelowName,
+}
+impl Display for DisplaySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplaySlot::List => write!(f, "list"),
+            DisplaySlot::Sidebar => write!(f, "sidebar"),
+            DisplaySlot::SidebarTeam(color) => write!(f, "sidebar.team.{}", color.name()),
+            DisplaySlot::BelowName => write!(f, "below_name"),
+        }
+    }
+}
+
+/// Generates a Minecraft command that displays the scoreboard `objective` in the given `slot`. If
+/// `objective` is [None], the `slot` is cleared.
+///
+/// `objective` is inserted verbatim, so it has to be a [valid unquoted string](is_valid_unquoted_string).
+pub fn scoreboard_setdisplay_command(slot: DisplaySlot, objective: Option<&str>) -> String {
+    match objective {
+        Some(objective) => format!("scoreboard objectives setdisplay {} {}", slot, objective),
+        None => format!("scoreboard objectives setdisplay {}", slot),
+    }
+}
+
+/// Generates a Minecraft command that sets the display name of the scoreboard `objective`, which is
+/// the title of the sidebar.
+///
+/// `objective` is inserted verbatim, so it has to be a [valid unquoted string](is_valid_unquoted_string).
+pub fn scoreboard_displayname_command(objective: &str, display_name: &str) -> String {
+    format!(
+        "scoreboard objectives modify {} displayname {}",
+        objective,
+        create_json_text_component(display_name)
+    )
+}
+
+/// Generates a Minecraft command that sets the color of the given `team`.
+///
+/// `team` is inserted verbatim, so it has to be a [valid unquoted string](is_valid_unquoted_string).
+pub fn team_color_command(team: &str, color: TeamColor) -> String {
+    format!("team modify {} color {}", team, color.name())
+}
+
+/// How long an effect given with [effect_give_command] lasts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EffectDuration {
+    /// The effect lasts for the given number of seconds.
+    Seconds(u32),
+    /// The effect lasts until it is cleared. Requires Minecraft 1.19.4 or newer.
+    Infinite,
+}
+impl Display for EffectDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EffectDuration::Seconds(seconds) => write!(f, "{}", seconds),
+            EffectDuration::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+/// Generates a Minecraft command that gives the `effect` (for example `minecraft:speed`) to
+/// `entity`. An `amplifier` of 0 corresponds to level I of the effect.
+pub fn effect_give_command(
+    entity: impl Display,
+    effect: impl Display,
+    duration: EffectDuration,
+    amplifier: u8,
+    hide_particles: bool,
+) -> String {
+    format!(
+        "effect give {} {} {} {} {}",
+        entity, effect, duration, amplifier, hide_particles
+    )
+}
+
+/// Generates a Minecraft command that removes the `effect` from `entity`. If `effect` is [None], all
+/// effects are removed.
+pub fn effect_clear_command(entity: impl Display, effect: Option<&str>) -> String {
+    match effect {
+        Some(effect) => format!("effect clear {} {}", entity, effect),
+        None => format!("effect clear {}", entity),
+    }
+}
+
+/// Generates a Minecraft command that queries the value of the `attribute` (for example
+/// `minecraft:generic.max_health`) of `entity`, including all modifiers.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into an
+/// [AttributeGetOutput].
+pub fn attribute_get_command(entity: impl Display, attribute: impl Display) -> String {
+    format!("attribute {} {} get", entity, attribute)
+}
+
+/// Generates a Minecraft command that queries the base value of the `attribute` of `entity`,
+/// ignoring all modifiers.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into an
+/// [AttributeGetOutput].
+pub fn attribute_base_get_command(entity: impl Display, attribute: impl Display) -> String {
+    format!("attribute {} {} base get", entity, attribute)
+}
+
+/// The output of an [attribute_get_command] or an [attribute_base_get_command]. This can be parsed
+/// from [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has one of the following formats:
+/// ```none
+/// Value of attribute <attribute> for entity <entity> is <value>
+/// Base value of attribute <attribute> for entity <entity> is <value>
+/// ```
+///
+/// For example:
+/// ```none
+/// Value of attribute Max Health for entity Herobrine is 20.0
+/// ```
+///
+/// Note that `<attribute>` is the display name of the attribute, not its id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeGetOutput {
+    /// The display name of the attribute, for example `Max Health`.
+    pub attribute: String,
+    /// The name of the entity.
+    pub entity: String,
+    /// The value of the attribute.
+    pub value: f64,
+    /// Whether this is the output of an [attribute_base_get_command].
+    pub base: bool,
+    _private: (),
+}
+impl FromStr for AttributeGetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<AttributeGetOutput> {
+            let (base, suffix) = match output.strip_prefix("Base value of attribute ") {
+                Some(suffix) => (true, suffix),
+                None => (false, output.strip_prefix("Value of attribute ")?),
+            };
+            let (attribute, suffix) = suffix.split_once(" for entity ")?;
+            let (entity, value) = suffix.rsplit_once(" is ")?;
+            let value = value.parse().ok()?;
+
+            Some(AttributeGetOutput {
+                attribute: attribute.to_string(),
+                entity: entity.to_string(),
+                value,
+                base,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for AttributeGetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = if self.base { "Base value" } else { "Value" };
+        write!(
+            f,
+            "{} of attribute {} for entity {} is {}",
+            prefix,
+            self.attribute,
+            self.entity,
+            JavaDouble(self.value)
+        )
+    }
+}
+
+/// Generates a Minecraft command that sets the base value of the `attribute` of `entity` to `value`.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into an
+/// [AttributeBaseSetOutput].
+pub fn attribute_base_set_command(
+    entity: impl Display,
+    attribute: impl Display,
+    value: f64,
+) -> String {
+    format!("attribute {} {} base set {}", entity, attribute, value)
+}
+
+/// The output of an [attribute_base_set_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// Base value for attribute <attribute> for entity <entity> set to <value>
+/// ```
+///
+/// For example:
+/// ```none
+/// Base value for attribute Movement Speed for entity Herobrine set to 0.15
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeBaseSetOutput {
+    /// The display name of the attribute, for example `Movement Speed`.
+    pub attribute: String,
+    /// The name of the entity.
+    pub entity: String,
+    /// The new base value of the attribute.
+    pub value: f64,
+    _private: (),
+}
+impl FromStr for AttributeBaseSetOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        fn from_str_opt(output: &str) -> Option<AttributeBaseSetOutput> {
+            let suffix = output.strip_prefix("Base value for attribute ")?;
+            let (attribute, suffix) = suffix.split_once(" for entity ")?;
+            let (entity, value) = suffix.rsplit_once(" set to ")?;
+            let value = value.parse().ok()?;
+
+            Some(AttributeBaseSetOutput {
+                attribute: attribute.to_string(),
+                entity: entity.to_string(),
+                value,
+                _private: (),
+            })
+        }
+        from_str_opt(output).ok_or(())
+    }
+}
+impl Display for AttributeBaseSetOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Base value for attribute {} for entity {} set to {}",
+            self.attribute,
+            self.entity,
+            JavaDouble(self.value)
+        )
+    }
+}
+
+/// Formats a double like Java does for values in a typical range, so whole numbers keep a trailing
+/// `.0` (for example `20.0` instead of `20`).
+struct JavaDouble(f64);
+impl Display for JavaDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_finite() && self.0.fract() == 0.0 {
+            write!(f, "{:.1}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+/// Generates a Minecraft command that executes the given `command` as and at the online player with
+/// the given name.
+///
+/// The `player` name is quoted and escaped, so it can not alter the semantics of the generated
+/// selector. If the player is not online, the command is not executed.
+///
+/// The output of the command is logged with the display name of the player as
+/// [executor](crate::log::LogEvent::executor), which includes the prefix and suffix of the player's
+/// team, if the player is in a team. Use [player_executed] or [PlayerFeedback::from_log_event] to
+/// check whether a [LogEvent](crate::log::LogEvent) was produced by such a command, because they
+/// take the team decoration into account.
+///
+/// For example `execute_as_player_command("Herobrine", "time set day")` generates:
+/// ```none
+/// execute as @a[name="Herobrine",limit=1] at @s run time set day
+/// ```
+pub fn execute_as_player_command(player: impl AsRef<str>, command: impl AsRef<str>) -> String {
+    format!(
+        "execute as {} at @s run {}",
+        player_selector(player.as_ref()),
+        command.as_ref()
+    )
+}
+
+/// Generates a selector that selects the online player with the given `name`. The `name` is quoted
+/// and escaped, so it can not alter the semantics of the selector.
+pub fn player_selector(name: &str) -> String {
+    format!("@a[name={},limit=1]", escape_selector_argument(name))
+}
+
+/// Quotes and escapes `value`, so it can be used as the value of a selector argument that accepts
+/// quoted strings, like `name`, without altering the semantics of the selector.
+///
+/// Some selector arguments like `tag` and `scores` do not accept quoted strings. Values for these
+/// have to be checked with [is_valid_unquoted_string] instead.
+pub fn escape_selector_argument(value: &str) -> String {
+    format!("\"{}\"", escape_json(value))
+}
+
+/// Returns `true` if `value` is a non empty string that only contains the characters `0-9`, `A-Z`,
+/// `a-z`, `_`, `-`, `.` and `+`. Such strings can be used without quotes in commands and selectors,
+/// for example as a tag or scoreboard objective.
+pub fn is_valid_unquoted_string(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+/// The name of a player that is guaranteed to be valid.
+///
+/// A valid player name consists of 1 to 16 characters that are `0-9`, `A-Z`, `a-z` or `_`. This
+/// means a [PlayerName] can safely be used anywhere an entity is expected, for example in an
+/// [add_tag_command] or [query_scoreboard_command], without the risk that a malicious name like
+/// `@e[type=!player]` selects other entities.
+///
+/// A [PlayerName] is created by parsing it from a string:
+/// ```
+/// # use minect::command::PlayerName;
+/// assert!("Herobrine".parse::<PlayerName>().is_ok());
+/// assert!("@e[type=!player]".parse::<PlayerName>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PlayerName(String);
+impl PlayerName {
+    /// The name as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Generates a selector that selects the online player with this name, see [player_selector].
+    pub fn selector(&self) -> String {
+        player_selector(&self.0)
+    }
+}
+impl FromStr for PlayerName {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let is_valid_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        if (1..=16).contains(&name.len()) && name.chars().all(is_valid_char) {
+            Ok(PlayerName(name.to_string()))
+        } else {
+            Err(())
+        }
+    }
+}
+impl Display for PlayerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+impl AsRef<str> for PlayerName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Returns `true` if the given [LogEvent](crate::log::LogEvent) was produced by a command executed
+/// as the given `player`, for instance via [execute_as_player_command].
+///
+/// This is the case if the [executor](crate::log::LogEvent::executor) is the display name of the
+/// player, which is the name of the player with the prefix and suffix of the player's team. Because
+/// the log does not separate the name from its decoration or players from other entities, this also
+/// matches a word in a team prefix or an entity with a custom name like `Guard Herobrine`.
+pub fn player_executed(event: &LogEvent, player: &str) -> bool {
+    split_display_name(&event.executor, player).is_some()
+}
+
+/// The feedback of a command that was executed as a player, for instance via
+/// [execute_as_player_command]. This can be parsed from a [LogEvent](crate::log::LogEvent) with
+/// [from_log_event](Self::from_log_event).
+///
+/// For example, if the player `Herobrine` is in a team with the prefix `[Red] `:
+/// ```none
+/// [[Red] Herobrine: Set the time to 1000]
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlayerFeedback {
+    /// The name of the player.
+    pub player: String,
+    /// The prefix of the player's team or an empty string.
+    pub team_prefix: String,
+    /// The suffix of the player's team or an empty string.
+    pub team_suffix: String,
+    /// The output of the command.
+    pub output: String,
+    _private: (),
+}
+impl PlayerFeedback {
+    /// Returns the feedback in the `event`, if it was produced by a command executed as the given
+    /// `player` (see [player_executed]).
+    pub fn from_log_event(event: &LogEvent, player: &str) -> Option<PlayerFeedback> {
+        let (team_prefix, team_suffix) = split_display_name(&event.executor, player)?;
+        Some(PlayerFeedback {
+            player: player.to_string(),
+            team_prefix: team_prefix.to_string(),
+            team_suffix: team_suffix.to_string(),
+            output: event.output.clone(),
+            _private: (),
+        })
+    }
+}
+
+/// Splits the `display_name` of the `player` into the prefix and suffix of the player's team.
+/// Returns [None] if the `display_name` does not contain the `player` name. The name must not be
+/// part of a longer word, so `Herobrine` is not found in `Herobrine_2`.
+fn split_display_name<'l>(display_name: &'l str, player: &str) -> Option<(&'l str, &'l str)> {
+    if player.is_empty() {
+        return None;
+    }
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    display_name
+        .match_indices(player)
+        .map(|(index, _)| display_name.split_at(index))
+        .map(|(prefix, rest)| (prefix, &rest[player.len()..]))
+        .find(|(prefix, suffix)| {
+            !prefix.chars().next_back().is_some_and(is_name_char)
+                && !suffix.chars().next().is_some_and(is_name_char)
+        })
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,522 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{BlockPos, Region};
+
+/// The kind of a Minecraft command together with its most important arguments, see [classify].
+///
+/// All arguments are borrowed from the classified command. Selectors, NBT and JSON arguments are
+/// kept as a single argument, even if they contain spaces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CommandKind<'c> {
+    /// An `execute` command.
+    Execute {
+        /// The subcommands before `run`, for example `as @a at @s`.
+        subcommands: &'c str,
+        /// The classified command after `run` or `None` if the command only checks conditions.
+        run: Option<Box<CommandKind<'c>>>,
+    },
+    /// A `scoreboard players` command.
+    ScoreboardPlayers {
+        /// The action, for example `set`, `add` or `get`.
+        action: &'c str,
+        /// The selector or name of the score holders (if any).
+        targets: Option<&'c str>,
+        /// The scoreboard objective (if any).
+        objective: Option<&'c str>,
+        /// The argument after the objective (if any), for example the score `5` in
+        /// `scoreboard players set @s points 5`.
+        value: Option<&'c str>,
+    },
+    /// A `scoreboard objectives` command.
+    ScoreboardObjectives {
+        /// The action, for example `add`, `remove` or `setdisplay`.
+        action: &'c str,
+        /// The scoreboard objective (if any).
+        objective: Option<&'c str>,
+    },
+    /// A `data` command.
+    Data {
+        /// The action, for example `get`, `merge` or `modify`.
+        action: &'c str,
+        /// The kind of target: `block`, `entity` or `storage`.
+        target_kind: &'c str,
+        /// The target: a position, a selector or the id of a storage.
+        target: &'c str,
+    },
+    /// A `summon` command.
+    Summon {
+        /// The type of the summoned entity, for example `minecraft:pig`.
+        entity_type: &'c str,
+    },
+    /// A `kill` command.
+    Kill {
+        /// The selector of the killed entities or `None` for the executing entity.
+        targets: Option<&'c str>,
+    },
+    /// A `tag` command.
+    Tag {
+        /// The selector of the entities.
+        targets: &'c str,
+        /// The action: `add`, `remove` or `list`.
+        action: &'c str,
+        /// The tag (if any).
+        tag: Option<&'c str>,
+    },
+    /// A `setblock` command.
+    Setblock {
+        /// The position or `None` if it is relative.
+        pos: Option<BlockPos>,
+        /// The block, for example `minecraft:stone`.
+        block: &'c str,
+        /// The mode, for example `destroy` or `keep` (if any).
+        mode: Option<&'c str>,
+    },
+    /// A `fill` command.
+    Fill {
+        /// The filled region or `None` if it is relative.
+        region: Option<Region>,
+        /// The number of filled blocks or `None` if the corners use different notations (absolute,
+        /// `~` or `^`) on an axis.
+        volume: Option<u64>,
+        /// The block, for example `minecraft:stone`.
+        block: &'c str,
+        /// The mode, for example `destroy` or `replace` (if any).
+        mode: Option<&'c str>,
+    },
+    /// A `clone` command.
+    Clone {
+        /// The source region or `None` if it is relative.
+        region: Option<Region>,
+        /// The number of cloned blocks or `None` if the corners use different notations (absolute,
+        /// `~` or `^`) on an axis.
+        volume: Option<u64>,
+        /// The mask mode, for example `masked` or `filtered` (if any).
+        mask_mode: Option<&'c str>,
+        /// The clone mode, for example `move` or `force` (if any).
+        clone_mode: Option<&'c str>,
+    },
+    /// A `function` command.
+    Function {
+        /// The name of the function or function tag, for example `my_namespace:my_function`.
+        name: &'c str,
+    },
+    /// A `gamerule` command.
+    Gamerule {
+        /// The name of the gamerule, for example `doDaylightCycle`.
+        rule: &'c str,
+        /// The new value or `None` if the gamerule is queried.
+        value: Option<&'c str>,
+    },
+    /// Any other command or a command with missing arguments.
+    Unknown {
+        /// The first word of the command.
+        name: &'c str,
+        /// The remaining arguments.
+        args: Vec<&'c str>,
+    },
+}
+
+/// Classifies the given `command` and extracts its most important arguments. A leading `/` is
+/// ignored.
+///
+/// This does not validate the command, so a command that is classified successfully may still fail
+/// in Minecraft.
+///
+/// ```
+/// # use minect::command::{classify, CommandKind};
+/// assert_eq!(
+///     classify("scoreboard players set @s points 5"),
+///     CommandKind::ScoreboardPlayers {
+///         action: "set",
+///         targets: Some("@s"),
+///         objective: Some("points"),
+///         value: Some("5"),
+///     }
+/// );
+/// ```
+pub fn classify(command: &str) -> CommandKind<'_> {
+    let command = command.trim().trim_start_matches('/');
+    if let Some(subcommands) = command.strip_prefix("execute ") {
+        return match split_run(subcommands) {
+            Some((subcommands, run)) => CommandKind::Execute {
+                subcommands,
+                run: Some(Box::new(classify(run))),
+            },
+            None => CommandKind::Execute {
+                subcommands,
+                run: None,
+            },
+        };
+    }
+    let args = split_args(command);
+    classify_args(command, &args).unwrap_or_else(|| {
+        let (name, args) = args.split_first().map_or(("", &[][..]), |(n, a)| (*n, a));
+        CommandKind::Unknown {
+            name,
+            args: args.to_vec(),
+        }
+    })
+}
+
+fn classify_args<'c>(command: &'c str, args: &[&'c str]) -> Option<CommandKind<'c>> {
+    Some(match args {
+        ["scoreboard", "players", action, rest @ ..] => CommandKind::ScoreboardPlayers {
+            action,
+            targets: rest.first().copied(),
+            objective: rest.get(1).copied(),
+            value: rest.get(2).copied(),
+        },
+        ["scoreboard", "objectives", action, rest @ ..] => {
+            let objective = match *action {
+                "setdisplay" => rest.get(1),
+                _ => rest.first(),
+            };
+            CommandKind::ScoreboardObjectives {
+                action,
+                objective: objective.copied(),
+            }
+        }
+        ["data", action, target_kind, rest @ ..] => {
+            let target = if *target_kind == "block" {
+                span(command, rest.get(..3)?)
+            } else {
+                rest.first()?
+            };
+            CommandKind::Data {
+                action,
+                target_kind,
+                target,
+            }
+        }
+        ["summon", entity_type, ..] => CommandKind::Summon { entity_type },
+        ["kill"] => CommandKind::Kill { targets: None },
+        ["kill", targets] => CommandKind::Kill {
+            targets: Some(targets),
+        },
+        ["tag", targets, action, rest @ ..] => CommandKind::Tag {
+            targets,
+            action,
+            tag: rest.first().copied(),
+        },
+        ["setblock", x, y, z, block, rest @ ..] => CommandKind::Setblock {
+            pos: parse_block_pos(x, y, z),
+            block,
+            mode: rest.first().copied(),
+        },
+        ["fill", rest @ ..] if rest.len() >= 7 => {
+            let (corners, rest) = rest.split_at(6);
+            CommandKind::Fill {
+                region: parse_region(corners),
+                volume: volume(corners),
+                block: rest[0],
+                mode: rest.get(1).copied(),
+            }
+        }
+        ["clone", rest @ ..] if rest.len() >= 9 => {
+            let (corners, rest) = rest.split_at(6);
+            let modes = &rest[3..];
+            let clone_mode = match modes.first() {
+                Some(&"filtered") => modes.get(2),
+                _ => modes.get(1),
+            };
+            CommandKind::Clone {
+                region: parse_region(corners),
+                volume: volume(corners),
+                mask_mode: modes.first().copied(),
+                clone_mode: clone_mode.copied(),
+            }
+        }
+        ["function", name, ..] => CommandKind::Function { name },
+        ["gamerule", rule] => CommandKind::Gamerule { rule, value: None },
+        ["gamerule", rule, value] => CommandKind::Gamerule {
+            rule,
+            value: Some(value),
+        },
+        _ => return None,
+    })
+}
+
+fn parse_region(corners: &[&str]) -> Option<Region> {
+    match corners {
+        [x1, y1, z1, x2, y2, z2] => parse_block_pos(x1, y1, z1)
+            .zip(parse_block_pos(x2, y2, z2))
+            .map(|(a, b)| Region::new(a, b)),
+        _ => None,
+    }
+}
+
+/// The number of blocks in the cuboid spanned by the six `corners`, if both corners use the same
+/// notation (absolute, `~` or `^`) on each axis.
+fn volume(corners: &[&str]) -> Option<u64> {
+    fn parse(coordinate: &str) -> Option<(&str, f64)> {
+        let prefix_len = coordinate.starts_with(['~', '^']) as usize;
+        let (prefix, number) = coordinate.split_at(prefix_len);
+        let number = if number.is_empty() && prefix_len == 1 {
+            0.0
+        } else {
+            number.parse().ok()?
+        };
+        Some((prefix, number))
+    }
+    if corners.len() != 6 {
+        return None;
+    }
+    (0..3).try_fold(1, |volume, axis| {
+        let (prefix_a, a) = parse(corners[axis])?;
+        let (prefix_b, b) = parse(corners[axis + 3])?;
+        if prefix_a != prefix_b {
+            return None;
+        }
+        let size = (b.floor() - a.floor()).abs() as u64 + 1;
+        Some(volume * size)
+    })
+}
+
+fn parse_block_pos(x: &str, y: &str, z: &str) -> Option<BlockPos> {
+    Some(BlockPos::new(
+        x.parse().ok()?,
+        y.parse().ok()?,
+        z.parse().ok()?,
+    ))
+}
+
+/// Returns the part of `command` that spans the given `args`, which must be slices of `command`.
+fn span<'c>(command: &'c str, args: &[&'c str]) -> &'c str {
+    let offset = |arg: &str| arg.as_ptr() as usize - command.as_ptr() as usize;
+    match (args.first(), args.last()) {
+        (Some(first), Some(last)) => &command[offset(first)..offset(last) + last.len()],
+        _ => "",
+    }
+}
+
+/// Splits the subcommands of an `execute` command at the `run` argument that ends them.
+///
+/// The arguments of known subcommands are skipped, so a score holder, tag or objective called `run`
+/// does not end the subcommands early. After an unknown subcommand the first `run` argument is used.
+fn split_run(subcommands: &str) -> Option<(&str, &str)> {
+    let args = split_args(subcommands);
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if *arg == "run" {
+            break;
+        }
+        match subcommand_len(&args[index..]) {
+            Some(len) => index += len,
+            None => {
+                index += args[index..].iter().position(|arg| *arg == "run")?;
+                break;
+            }
+        }
+    }
+    let run = args.get(index)?;
+    let offset = run.as_ptr() as usize - subcommands.as_ptr() as usize + run.len();
+    Some((
+        span(subcommands, &args[..index]),
+        subcommands[offset..].trim(),
+    ))
+}
+
+/// Whether the subcommands of an `execute` command contain a `store` subcommand.
+///
+/// Like in [split_run] the arguments of known subcommands are skipped. After an unknown subcommand
+/// any `store` argument counts.
+pub(crate) fn has_store_subcommand(subcommands: &str) -> bool {
+    let args = split_args(subcommands);
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if *arg == "store" {
+            return true;
+        }
+        match subcommand_len(&args[index..]) {
+            Some(len) => index += len,
+            None => return args[index..].contains(&"store"),
+        }
+    }
+    false
+}
+
+/// The number of arguments of the `execute` subcommand at the start of `args`, including its name.
+fn subcommand_len(args: &[&str]) -> Option<usize> {
+    Some(match args {
+        ["align" | "anchored" | "as" | "at" | "in" | "on" | "summon", ..] => 2,
+        ["facing", ..] => 4,
+        ["positioned", "as" | "over", ..] => 3,
+        ["positioned", ..] => 4,
+        ["rotated", ..] => 3,
+        ["store", _, target, ..] => match *target {
+            "block" => 9,
+            "bossbar" | "score" => 5,
+            "entity" | "storage" => 7,
+            _ => return None,
+        },
+        ["if" | "unless", condition, rest @ ..] => match (*condition, rest) {
+            ("block" | "biome", _) => 6,
+            ("blocks", _) => 12,
+            ("data", ["block", ..]) => 7,
+            ("data", _) => 5,
+            ("entity" | "predicate" | "dimension" | "function", _) => 3,
+            ("loaded", _) => 5,
+            ("score", [_, _, "matches", ..]) => 6,
+            ("score", _) => 7,
+            ("items", ["block", ..]) => 8,
+            ("items", _) => 6,
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+/// Splits `command` at spaces that are not inside of quotes or brackets. This keeps selectors, NBT
+/// and JSON arguments intact.
+fn split_args(command: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0_usize;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in command.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '[' | '{' | '(' => depth += 1,
+            ']' | '}' | ')' => depth = depth.saturating_sub(1),
+            ' ' if depth == 0 => {
+                if start < index {
+                    args.push(&command[start..index]);
+                }
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < command.len() {
+        args.push(&command[start..]);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_execute() {
+        // given:
+        let command = r#"execute as @e[type=pig, tag="a run b"] at @s run summon minecraft:cow ~ ~ ~ {Tags:["x y"]}"#;
+
+        // when:
+        let actual = classify(command);
+
+        // then:
+        assert_eq!(
+            actual,
+            CommandKind::Execute {
+                subcommands: r#"as @e[type=pig, tag="a run b"] at @s"#,
+                run: Some(Box::new(CommandKind::Summon {
+                    entity_type: "minecraft:cow"
+                })),
+            }
+        );
+    }
+
+    #[test]
+    fn test_classify_execute_with_run_argument() {
+        // given:
+        let command = "execute if score run list matches 1 run setblock 0 64 0 stone";
+
+        // when:
+        let actual = classify(command);
+
+        // then:
+        assert_eq!(
+            actual,
+            CommandKind::Execute {
+                subcommands: "if score run list matches 1",
+                run: Some(Box::new(CommandKind::Setblock {
+                    pos: Some(BlockPos::new(0, 64, 0)),
+                    block: "stone",
+                    mode: None,
+                })),
+            }
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("/data get block 1 64 -3 Items"),
+            CommandKind::Data {
+                action: "get",
+                target_kind: "block",
+                target: "1 64 -3",
+            }
+        );
+        assert_eq!(
+            classify("fill 0 64 0 15 70 15 stone"),
+            CommandKind::Fill {
+                region: Some(Region::new((0, 64, 0), (15, 70, 15))),
+                volume: Some(1792),
+                block: "stone",
+                mode: None,
+            }
+        );
+        assert_eq!(
+            classify("setblock ~ ~1 ~ stone"),
+            CommandKind::Setblock {
+                pos: None,
+                block: "stone",
+                mode: None,
+            }
+        );
+        assert_eq!(
+            classify("fill ~ ~ ~ ~9 ~ ~9 air replace stone"),
+            CommandKind::Fill {
+                region: None,
+                volume: Some(100),
+                block: "air",
+                mode: Some("replace"),
+            }
+        );
+        assert_eq!(
+            classify("clone 0 64 0 9 64 9 0 80 0 filtered stone move"),
+            CommandKind::Clone {
+                region: Some(Region::new((0, 64, 0), (9, 64, 9))),
+                volume: Some(100),
+                mask_mode: Some("filtered"),
+                clone_mode: Some("move"),
+            }
+        );
+        assert_eq!(
+            classify("say hello world"),
+            CommandKind::Unknown {
+                name: "say",
+                args: vec!["hello", "world"],
+            }
+        );
+    }
+}
@@ -25,3 +25,453 @@ fn test_named_logged_cart_command() {
     // then:
     assert!(actual.chars().all(|c| c != '\n'));
 }
+
+#[test]
+fn test_datapack_list_output() {
+    // given:
+    let output = "There are 2 data pack(s) enabled: [vanilla (built-in)], [file/minect (world)]";
+
+    // when:
+    let actual = output.parse::<DatapackListOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.list, DatapackList::Enabled);
+    assert_eq!(actual.datapacks, ["vanilla", "file/minect"]);
+}
+
+#[test]
+fn test_datapack_list_output_none_available() {
+    // given:
+    let output = "There are no more data packs available";
+
+    // when:
+    let actual = output.parse::<DatapackListOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.list, DatapackList::Available);
+    assert!(actual.datapacks.is_empty());
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_silent_commands() {
+    // given:
+    let commands = ["fill 0 0 0 1 1 1 stone", "fill 2 2 2 3 3 3 stone"];
+
+    // when:
+    let actual = silent_commands(commands);
+
+    // then:
+    assert_eq!(
+        actual,
+        [
+            "function minect:silence",
+            "fill 0 0 0 1 1 1 stone",
+            "fill 2 2 2 3 3 3 stone",
+            "function minect:reset_silence",
+        ]
+    );
+}
+
+#[test]
+fn test_execute_as_player_command_escapes_player_name() {
+    // given:
+    let player = "Hero\"] run kill @e[\"";
+    let command = "say hi";
+
+    // when:
+    let actual = execute_as_player_command(player, command);
+
+    // then:
+    assert_eq!(
+        actual,
+        "execute as @a[name=\"Hero\\\"] run kill @e[\\\"\",limit=1] at @s run say hi"
+    );
+}
+
+#[test]
+fn test_player_executed_with_team_decoration() {
+    // given:
+    let event = "[12:00:00] [Server thread/INFO]: [[Red] Herobrine: Set the time to 1000]"
+        .parse::<LogEvent>()
+        .unwrap();
+
+    // when:
+    let actual = PlayerFeedback::from_log_event(&event, "Herobrine").unwrap();
+
+    // then:
+    assert!(player_executed(&event, "Herobrine"));
+    assert!(!player_executed(&event, "Hero"));
+    assert_eq!(actual.team_prefix, "[Red] ");
+    assert_eq!(actual.team_suffix, "");
+    assert_eq!(actual.output, "Set the time to 1000");
+}
+
+#[test]
+fn test_time_query_output() {
+    // given:
+    let output = "The time is 1000";
+
+    // when:
+    let actual = output.parse::<TimeQueryOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.time, 1000);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_weather_output_thunder() {
+    // given:
+    let output = "Set the weather to rain & thunder";
+
+    // when:
+    let actual = output.parse::<WeatherOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.weather, Weather::Thunder);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_worldborder_get_output() {
+    // given:
+    let output = "The world border is currently 59999968 block(s) wide";
+
+    // when:
+    let actual = output.parse::<WorldborderGetOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.width, 59999968.0);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_worldborder_get_output_before_1_19() {
+    // given:
+    let output = "The world border is currently 60000000 blocks wide";
+
+    // when:
+    let actual = output.parse::<WorldborderGetOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.width, 60000000.0);
+}
+
+#[test]
+fn test_teleport_output() {
+    // given:
+    let output = "Teleported Herobrine to 0.500000, 64.000000, 0.500000";
+
+    // when:
+    let actual = output.parse::<TeleportOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.entity, "Herobrine");
+    assert_eq!(actual.destination, "0.500000, 64.000000, 0.500000");
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_store_result_in_score() {
+    // when:
+    let actual = store_result_in_score("time query daytime", "result", "my_objective");
+
+    // then:
+    assert_eq!(
+        actual,
+        "execute store result score result my_objective run time query daytime"
+    );
+}
+
+#[test]
+fn test_list_players_output() {
+    // given:
+    let output = "There are 2 of a max of 20 players online: Herobrine, Notch";
+
+    // when:
+    let actual = output.parse::<ListPlayersOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.count, 2);
+    assert_eq!(actual.max, 20);
+    let names = actual
+        .players
+        .iter()
+        .map(|p| p.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Herobrine", "Notch"]);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_list_players_output_with_uuids() {
+    // given:
+    let output = "There are 1 of a max of 20 players online: Herobrine (f84c6a79-0a4e-45e0-879b-cd49ebd4c4e2)";
+
+    // when:
+    let actual = output.parse::<ListPlayersOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.players[0].name, "Herobrine");
+    assert_eq!(
+        actual.players[0].uuid.as_deref(),
+        Some("f84c6a79-0a4e-45e0-879b-cd49ebd4c4e2")
+    );
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_list_players_output_without_players() {
+    // given:
+    let output = "There are 0 of a max of 20 players online: ";
+
+    // when:
+    let actual = output.parse::<ListPlayersOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.players, []);
+}
+
+#[test]
+fn test_seed_output() {
+    // given:
+    let output = "Seed: [-4172144997902289642]";
+
+    // when:
+    let actual = output.parse::<SeedOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.seed, -4172144997902289642);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_seed_output_before_1_16() {
+    // given:
+    let output = "Seed: 42";
+
+    // when:
+    let actual = output.parse::<SeedOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.seed, 42);
+}
+
+#[test]
+fn test_difficulty_set_output() {
+    // given:
+    let output = "The difficulty has been set to Hard";
+
+    // when:
+    let actual = output.parse::<DifficultySetOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.difficulty, Difficulty::Hard);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_locate_output_structure() {
+    // given:
+    let output = "The nearest minecraft:village_plains is at [-480, ~, 208] (523 blocks away)";
+
+    // when:
+    let actual = output.parse::<LocateOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.name, "minecraft:village_plains");
+    assert_eq!((actual.x, actual.y, actual.z), (-480, None, 208));
+    assert_eq!(actual.distance, 523);
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_locate_output_biome() {
+    // given:
+    let output = "The nearest minecraft:plains is at [16, 72, -32] (35 blocks away)";
+
+    // when:
+    let actual = output.parse::<LocateOutput>().unwrap();
+
+    // then:
+    assert_eq!((actual.x, actual.y, actual.z), (16, Some(72), -32));
+    assert_eq!(actual.pos(), Some(BlockPos::new(16, 72, -32)));
+    assert_eq!(actual.chunk(), ChunkPos::new(1, -2));
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_setblock_output() {
+    // given:
+    let output = "Changed the block at -8, 64, 12";
+
+    // when:
+    let actual = output.parse::<SetblockOutput>().unwrap();
+
+    // then:
+    assert_eq!(actual.pos, BlockPos::new(-8, 64, 12));
+    assert_eq!(actual.to_string(), output);
+}
+
+#[test]
+fn test_place_template_command() {
+    // given:
+    let pos = BlockPos::new(1, 64, -2);
+
+    // when:
+    let rotation = StructureRotation::Counterclockwise90;
+    let actual = place_template_command("minect:a/b", pos, rotation, StructureMirror::LeftRight);
+
+    // then:
+    assert_eq!(
+        actual,
+        "place template minect:a/b 1 64 -2 counterclockwise_90 left_right"
+    );
+}
+
+#[test]
+fn test_fill_command() {
+    // given:
+    let region = Region::new((15, 64, 15), (0, 64, 0));
+
+    // when:
+    let actual = fill_command(region, "minecraft:stone");
+
+    // then:
+    assert_eq!(actual, "fill 0 64 0 15 64 15 minecraft:stone");
+    let output = "Successfully filled 256 block(s)"
+        .parse::<FillOutput>()
+        .unwrap();
+    assert_eq!(output.blocks, 256);
+}
+
+#[test]
+fn test_player_name() {
+    assert!("Herobrine".parse::<PlayerName>().is_ok());
+    assert!("Steve_1".parse::<PlayerName>().is_ok());
+    assert!("".parse::<PlayerName>().is_err());
+    assert!("Her obrine".parse::<PlayerName>().is_err());
+    assert!("@a".parse::<PlayerName>().is_err());
+    assert!("a_very_long_player_name".parse::<PlayerName>().is_err());
+}
+
+#[test]
+fn test_escape_selector_argument() {
+    // given:
+    let name = "\",limit=1] @e[\"";
+
+    // when:
+    let actual = player_selector(name);
+
+    // then:
+    assert_eq!(actual, r#"@a[name="\",limit=1] @e[\"",limit=1]"#);
+    assert!(!is_valid_unquoted_string("tag] @e[tag=x"));
+    assert!(is_valid_unquoted_string("my_tag.v2"));
+}
+
+#[test]
+fn test_gamerule_output() {
+    // given:
+    let query = "Gamerule doDaylightCycle is currently set to: true";
+    let set = "Gamerule doDaylightCycle is now set to: false";
+
+    // when:
+    let query = query.parse::<GameruleOutput>().unwrap();
+    let set = set.parse::<GameruleOutput>().unwrap();
+
+    // then:
+    assert_eq!(
+        (query.rule.as_str(), query.value.as_str()),
+        ("doDaylightCycle", "true")
+    );
+    assert!(!query.set);
+    assert_eq!(
+        (set.rule.as_str(), set.value.as_str()),
+        ("doDaylightCycle", "false")
+    );
+    assert!(set.set);
+}
+
+#[test]
+fn test_tick_outputs() {
+    // given:
+    let frozen = "The game is frozen";
+    let step = "Stepping 20 tick(s)";
+
+    // when:
+    let frozen = frozen.parse::<TickFreezeOutput>().unwrap();
+    let step = step.parse::<TickStepOutput>().unwrap();
+
+    // then:
+    assert!(frozen.frozen);
+    assert_eq!(step.ticks, 20);
+    assert_eq!(step.to_string(), "Stepping 20 tick(s)");
+}
+
+#[test]
+fn test_kill_output() {
+    // given:
+    let single = "Killed my_marker";
+    let multiple = "Killed 3 entities";
+
+    // when:
+    let single_actual = single.parse::<KillOutput>().unwrap();
+    let multiple_actual = multiple.parse::<KillOutput>().unwrap();
+
+    // then:
+    assert_eq!(single_actual.entity.as_deref(), Some("my_marker"));
+    assert_eq!(single_actual.count, 1);
+    assert_eq!(single_actual.to_string(), single);
+    assert_eq!(multiple_actual.entity, None);
+    assert_eq!(multiple_actual.count, 3);
+    assert_eq!(multiple_actual.to_string(), multiple);
+}
+
+#[test]
+fn test_scoreboard_setdisplay_command() {
+    // given:
+    let slot = DisplaySlot::SidebarTeam(TeamColor::DarkBlue);
+
+    // when:
+    let show = scoreboard_setdisplay_command(slot, Some("stats"));
+    let clear = scoreboard_setdisplay_command(DisplaySlot::BelowName, None);
+
+    // then:
+    assert_eq!(
+        show,
+        "scoreboard objectives setdisplay sidebar.team.dark_blue stats"
+    );
+    assert_eq!(clear, "scoreboard objectives setdisplay below_name");
+}
+
+#[test]
+fn test_attribute_get_output() {
+    // given:
+    let value = "Value of attribute Max Health for entity Herobrine is 20.0";
+    let base = "Base value of attribute Movement Speed for entity Herobrine is 0.10000000149011612";
+
+    // when:
+    let value_actual = value.parse::<AttributeGetOutput>().unwrap();
+    let base_actual = base.parse::<AttributeGetOutput>().unwrap();
+
+    // then:
+    assert_eq!(value_actual.attribute, "Max Health");
+    assert_eq!(value_actual.entity, "Herobrine");
+    assert_eq!(value_actual.value, 20.0);
+    assert!(!value_actual.base);
+    assert_eq!(value_actual.to_string(), value);
+    assert_eq!(base_actual.attribute, "Movement Speed");
+    assert_eq!(base_actual.value, 0.10000000149011612);
+    assert!(base_actual.base);
+    assert_eq!(base_actual.to_string(), base);
+}
+
+#[test]
+fn test_effect_give_command() {
+    // when:
+    let actual = effect_give_command("@s", "minecraft:speed", EffectDuration::Infinite, 1, true);
+
+    // then:
+    assert_eq!(actual, "effect give @s minecraft:speed infinite 1 true");
+}
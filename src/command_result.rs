@@ -0,0 +1,128 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of failed commands, see
+//! [detect_command_results](crate::MinecraftConnectionBuilder::detect_command_results).
+
+use crate::{command::QueryScoreboardOutput, log::LogEvent, names, Command};
+
+const RESULT_HOLDER: &str = "command_result";
+const RESULT_OBJECTIVE: &str = "minect_global";
+
+/// The number of commands that are executed for each command when detecting command results.
+pub(crate) const COMMANDS_PER_RESULT: usize = 3;
+
+/// Whether a [Command] passed to
+/// [MinecraftConnection::execute_commands](crate::MinecraftConnection::execute_commands) succeeded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommandResult {
+    /// The command succeeded. Minecraft only stores whether a command succeeded, so the number of
+    /// affected entities or the result of a query is not available.
+    Succeeded,
+    /// The command failed, for example because it could not be parsed or because no entity was
+    /// affected.
+    Failed,
+}
+
+/// The [CommandResult] of a [Command] passed to
+/// [MinecraftConnection::execute_commands](crate::MinecraftConnection::execute_commands).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandResultEvent {
+    /// The id of the batch that contained the command.
+    pub batch: u64,
+    /// The index of the command within its batch.
+    pub index: usize,
+    /// Whether the command succeeded.
+    pub result: CommandResult,
+    _private: (),
+}
+
+impl CommandResultEvent {
+    pub(crate) fn from_log_event(event: &LogEvent) -> Option<CommandResultEvent> {
+        let suffix = event.executor.strip_prefix(names::COMMAND_RESULT_PREFIX)?;
+        let (batch, index) = suffix.split_once('_')?;
+        let output = event.output.parse::<QueryScoreboardOutput>().ok()?;
+        if output.entity != RESULT_HOLDER {
+            return None;
+        }
+        let result = if output.score > 0 {
+            CommandResult::Succeeded
+        } else {
+            CommandResult::Failed
+        };
+        Some(CommandResultEvent {
+            batch: batch.parse().ok()?,
+            index: index.parse().ok()?,
+            result,
+            _private: (),
+        })
+    }
+}
+
+/// Wraps every command to store whether it succeeded and log the result afterwards.
+pub(crate) fn add_result_commands(commands: Vec<Command>, batch: u64) -> Vec<Command> {
+    let mut result = Vec::with_capacity(commands.len() * COMMANDS_PER_RESULT);
+    for (index, command) in commands.into_iter().enumerate() {
+        let wrapped = format!(
+            "execute store success score {} {} run {}",
+            RESULT_HOLDER,
+            RESULT_OBJECTIVE,
+            command.get_command()
+        );
+        let mut wrapped = match command.get_name() {
+            Some(name) => Command::named(name, wrapped),
+            None => Command::new(wrapped),
+        };
+        wrapped.chain = command.chain;
+        result.push(wrapped);
+        result.push(Command::named(
+            format!("{}{}_{}", names::COMMAND_RESULT_PREFIX, batch, index),
+            format!(
+                "scoreboard players add {} {} 0",
+                RESULT_HOLDER, RESULT_OBJECTIVE
+            ),
+        ));
+        result.push(Command::new(format!(
+            "scoreboard players reset {} {}",
+            RESULT_HOLDER, RESULT_OBJECTIVE
+        )));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_log_event() {
+        // given:
+        let succeeded = "[21:05:40] [Server thread/INFO]: [minect_result_5_1: Added 0 to [minect_global] for command_result (now 1)]";
+        let failed = "[21:05:40] [Server thread/INFO]: [minect_result_5_2: Added 0 to [minect_global] for command_result (now 0)]";
+
+        // when:
+        let succeeded = CommandResultEvent::from_log_event(&succeeded.parse().unwrap());
+        let failed = CommandResultEvent::from_log_event(&failed.parse().unwrap());
+
+        // then:
+        let succeeded = succeeded.unwrap();
+        assert_eq!((succeeded.batch, succeeded.index), (5, 1));
+        assert_eq!(succeeded.result, CommandResult::Succeeded);
+        assert_eq!(failed.unwrap().result, CommandResult::Failed);
+    }
+}
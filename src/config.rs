@@ -0,0 +1,314 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    installer::Lang, log::LogFormat, ExecutionStrategy, HostingLayout, LeaseConflict,
+    LogVolumeAction, MinecraftConnectionBuilder, MinecraftVersion,
+};
+#[cfg(feature = "serde")]
+use crate::{
+    io::{io_error, IoErrorAtPath},
+    utils::io_invalid_data,
+};
+#[cfg(feature = "serde")]
+use std::{fs::read_to_string, io, path::Path};
+use std::{ops::RangeInclusive, path::PathBuf, time::Duration};
+
+/// The settings of a [MinecraftConnectionBuilder] as plain data. With the feature `serde` this can
+/// be loaded from a config file, so end users can configure a connection without recompiling the
+/// application.
+///
+/// Every field corresponds to the builder method of the same name. Fields that are `None` keep the
+/// default of the builder. Durations are given in milliseconds. Settings that can't be represented
+/// as data, like a [remote_log_source](MinecraftConnectionBuilder::remote_log_source), a
+/// [file_backend](MinecraftConnectionBuilder::file_backend) or a
+/// [command_policy](MinecraftConnectionBuilder::command_policy), have to be set on the builder.
+///
+/// Because fields may be added in the future, a [ConnectionConfig] can only be created with
+/// [Default] or by deserializing it. Its fields can be set afterwards.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct ConnectionConfig {
+    pub identifier: String,
+    pub world_dir: PathBuf,
+    pub log_file: Option<PathBuf>,
+    pub log_format: Option<LogFormat>,
+    pub hosting_layout: Option<HostingLayout>,
+    pub enable_logging_automatically: Option<bool>,
+    pub log_volume_threshold: Option<u64>,
+    pub log_volume_action: Option<LogVolumeAction>,
+    pub retry_unacknowledged_after_millis: Option<u64>,
+    pub regenerate_skipped_batches: Option<bool>,
+    pub query_gametick: Option<bool>,
+    pub execution_strategy: Option<ExecutionStrategy>,
+    pub detect_command_results: Option<bool>,
+    pub protect_building: Option<bool>,
+    pub building_y: Option<i32>,
+    pub hide_building: Option<bool>,
+    pub force_overwrite: Option<bool>,
+    pub validate_placement: Option<bool>,
+    pub namespace_ids_per_run: Option<bool>,
+    pub share_identifier: Option<bool>,
+    pub lease_millis: Option<u64>,
+    pub lease_conflict: Option<LeaseConflict>,
+    pub file_mode: Option<u32>,
+    pub dir_mode: Option<u32>,
+    pub file_owner: Option<u32>,
+    pub file_group: Option<u32>,
+    pub command_block_offset: Option<(i32, i32, i32)>,
+    pub command_block_area_size: Option<(i32, i32, i32)>,
+    pub minecraft_version: Option<MinecraftVersion>,
+    pub install_at: Option<(i32, i32)>,
+    pub installer_language: Option<Lang>,
+    pub installer_title: Option<String>,
+    pub installer_description: Option<String>,
+    pub compression_level: Option<u32>,
+    pub update_delay: Option<u32>,
+    pub adaptive_update_delay: Option<RangeInclusive<u32>>,
+    pub adaptive_update_delay_idle_after_millis: Option<u64>,
+    pub log_poll_interval_millis: Option<u64>,
+    pub acknowledge_every: Option<u64>,
+    pub spool_dir: Option<PathBuf>,
+    pub max_pending_structures: Option<usize>,
+    pub merge_spooled_batches: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _private: (),
+}
+
+impl MinecraftConnectionBuilder {
+    /// Creates a builder with the settings of the given [ConnectionConfig]. Methods called on the
+    /// returned builder override the settings of the config.
+    pub fn from_config(config: ConnectionConfig) -> MinecraftConnectionBuilder {
+        MinecraftConnectionBuilder::from(config)
+    }
+
+    /// Creates a builder with the settings of the config file at `path` (see [ConnectionConfig]).
+    /// Relative paths in the config file are resolved relative to the directory of the config file.
+    ///
+    /// Files with the extension `toml` are parsed as TOML, which requires the feature `toml`. All
+    /// other files are parsed as JSON.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file can't be read or is not a valid config.
+    #[cfg(feature = "serde")]
+    pub fn from_config_file(
+        path: impl AsRef<Path>,
+    ) -> Result<MinecraftConnectionBuilder, IoErrorAtPath> {
+        let path = path.as_ref();
+        let content = read_to_string(path).map_err(io_error("Failed to read config file", path))?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension == "toml");
+        let mut config = if is_toml {
+            parse_toml(&content)
+        } else {
+            serde_json::from_str::<ConnectionConfig>(&content).map_err(io_invalid_data)
+        }
+        .map_err(io_error("Failed to parse config file", path))?;
+        if let Some(dir) = path.parent() {
+            config.resolve_paths(dir);
+        }
+        Ok(MinecraftConnectionBuilder::from(config))
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "toml"))]
+fn parse_toml(content: &str) -> io::Result<ConnectionConfig> {
+    toml::from_str(content).map_err(io_invalid_data)
+}
+
+#[cfg(all(feature = "serde", not(feature = "toml")))]
+fn parse_toml(_content: &str) -> io::Result<ConnectionConfig> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "TOML config files require the feature toml",
+    ))
+}
+
+impl ConnectionConfig {
+    /// Makes all relative paths of this config relative to `dir`.
+    #[cfg(feature = "serde")]
+    fn resolve_paths(&mut self, dir: &Path) {
+        self.world_dir = dir.join(&self.world_dir);
+        let hosted_log_file = match &mut self.hosting_layout {
+            Some(HostingLayout::Hosted { log_file }) => Some(log_file),
+            _ => None,
+        };
+        for path in [
+            self.log_file.as_mut(),
+            self.spool_dir.as_mut(),
+            hosted_log_file,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = dir.join(&*path);
+        }
+    }
+}
+
+impl From<ConnectionConfig> for MinecraftConnectionBuilder {
+    fn from(config: ConnectionConfig) -> MinecraftConnectionBuilder {
+        let mut builder = MinecraftConnectionBuilder::new(config.identifier, config.world_dir);
+        macro_rules! apply {
+            ($field:ident) => {
+                if let Some(value) = config.$field {
+                    builder = builder.$field(value);
+                }
+            };
+            ($field:ident, $method:ident, $map:expr) => {
+                if let Some(value) = config.$field {
+                    builder = builder.$method($map(value));
+                }
+            };
+        }
+        apply!(log_file);
+        apply!(log_format);
+        apply!(hosting_layout);
+        apply!(enable_logging_automatically);
+        apply!(log_volume_threshold);
+        apply!(log_volume_action);
+        apply!(
+            retry_unacknowledged_after_millis,
+            retry_unacknowledged_after,
+            Duration::from_millis
+        );
+        apply!(regenerate_skipped_batches);
+        apply!(query_gametick);
+        apply!(execution_strategy);
+        apply!(detect_command_results);
+        apply!(protect_building);
+        apply!(building_y);
+        apply!(hide_building);
+        apply!(force_overwrite);
+        apply!(validate_placement);
+        apply!(namespace_ids_per_run);
+        apply!(share_identifier);
+        apply!(lease_millis, lease, Duration::from_millis);
+        apply!(lease_conflict);
+        apply!(file_mode);
+        apply!(dir_mode);
+        apply!(file_owner);
+        apply!(file_group);
+        apply!(command_block_offset);
+        apply!(command_block_area_size);
+        apply!(minecraft_version);
+        if let Some((chunk_x, chunk_z)) = config.install_at {
+            builder = builder.install_at(chunk_x, chunk_z);
+        }
+        apply!(installer_language);
+        apply!(installer_title);
+        apply!(installer_description);
+        apply!(compression_level);
+        apply!(update_delay);
+        apply!(adaptive_update_delay);
+        apply!(
+            adaptive_update_delay_idle_after_millis,
+            adaptive_update_delay_idle_after,
+            Duration::from_millis
+        );
+        apply!(
+            log_poll_interval_millis,
+            log_poll_interval,
+            Duration::from_millis
+        );
+        apply!(acknowledge_every);
+        apply!(spool_dir);
+        apply!(max_pending_structures);
+        apply!(merge_spooled_batches);
+        builder
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn test_builder_from_json_config() {
+        // given:
+        let json = r#"{
+            "identifier": "example",
+            "world_dir": "server/world",
+            "minecraft_version": "1.20.4",
+            "execution_strategy": "FunctionFile",
+            "install_at": [2, -3],
+            "log_poll_interval_millis": 5
+        }"#;
+
+        // when:
+        let config = serde_json::from_str::<ConnectionConfig>(json).unwrap();
+        let builder = MinecraftConnectionBuilder::from(config);
+
+        // then:
+        assert_eq!(builder.identifier, "example");
+        assert_eq!(builder.world_dir, PathBuf::from("server/world"));
+        assert_eq!(
+            builder.minecraft_version,
+            Some(MinecraftVersion::new(1, 20, 4))
+        );
+        assert_eq!(builder.execution_strategy, ExecutionStrategy::FunctionFile);
+        assert_eq!(builder.install_at, Some((2, -3)));
+        assert_eq!(builder.log_poll_interval, Duration::from_millis(5));
+        assert!(builder.enable_logging_automatically);
+    }
+
+    #[test]
+    fn test_from_config_file_resolves_relative_paths() {
+        // given:
+        let dir = TempDir::new("config");
+        let path = dir.join("minect.json");
+        let json = r#"{"identifier": "example", "world_dir": "world", "spool_dir": "/spool"}"#;
+        std::fs::write(&path, json).unwrap();
+
+        // when:
+        let builder = MinecraftConnectionBuilder::from_config_file(&path).unwrap();
+
+        // then:
+        assert_eq!(builder.world_dir, dir.join("world"));
+        assert_eq!(builder.spool_dir, Some(PathBuf::from("/spool")));
+        assert_eq!(builder.log_file, None);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_from_config_file_parses_toml() {
+        // given:
+        let dir = TempDir::new("toml_config");
+        let path = dir.join("minect.toml");
+        let toml = r#"
+            identifier = "example"
+            world_dir = "world"
+            execution_strategy = "FunctionFile"
+            install_at = [2, -3]
+        "#;
+        std::fs::write(&path, toml).unwrap();
+
+        // when:
+        let builder = MinecraftConnectionBuilder::from_config_file(&path).unwrap();
+
+        // then:
+        assert_eq!(builder.identifier, "example");
+        assert_eq!(builder.world_dir, dir.join("world"));
+        assert_eq!(builder.execution_strategy, ExecutionStrategy::FunctionFile);
+        assert_eq!(builder.install_at, Some((2, -3)));
+    }
+}
@@ -17,25 +17,44 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    command::{summon_named_entity_command, AddTagOutput, SummonNamedEntityOutput},
-    io::{create_dir_all, io_error, remove_dir, remove_dir_all, write, IoErrorAtPath},
+    command::{summon_named_entity_command, AddTagOutput, SeedOutput, SummonNamedEntityOutput},
+    datapack_conflict::CreateDatapackErrorInner,
+    datapack_layout::DatapackLayout,
+    file_backend::FileBackend,
+    hosting::HostingLayout,
+    installer::{expand_chunk, InstallerTexts},
+    io::{
+        create_dir_all_with_permissions, io_error, write_with_permissions, FilePermissions,
+        IoErrorAtPath,
+    },
+    lease::{LeaseConflict, LeaseHeld},
     log::LogEvent,
+    names, next_query_name,
     on_drop::OnDrop,
-    read_incremented_id, Command, ExecuteCommandsError, ExecuteCommandsErrorInner,
-    MinecraftConnection,
+    placement::protection_block,
+    purge::execute_pending_purges,
+    read_incremented_id,
+    session::{
+        handshake_commands, new_session_id, Handshake, IdentifierInUse, ACTIVE_SESSION_TICKS,
+    },
+    structure_id::format_structure_id,
+    world::{WorldFingerprint, WrongWorld},
+    Command, CreateDatapackError, DatapackConflict, ExecuteCommandsError,
+    ExecuteCommandsErrorInner, MinecraftConnection, PlacementError,
 };
+use futures::future::{select, Either};
 use indexmap::IndexSet;
-use log::error;
+use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::Display,
-    fs::{File, OpenOptions},
-    io::{BufReader, BufWriter, Seek, SeekFrom},
-    path::Path,
+    path::{Path, PathBuf},
+    pin::pin,
     sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
 };
+use tokio::time::sleep;
 use tokio_stream::StreamExt;
-use walkdir::WalkDir;
 
 /// The error returned from [MinecraftConnection::connect].
 #[derive(Debug)]
@@ -45,7 +64,15 @@ pub struct ConnectError {
 #[derive(Debug)]
 enum ConnectErrorInner {
     Io(IoErrorAtPath),
+    DatapackConflict(DatapackConflict),
     Cancelled,
+    IdentifierInUse(IdentifierInUse),
+    LeaseHeld(LeaseHeld),
+    HostingLayoutMismatch(String),
+    Placement(PlacementError),
+    WrongWorld(WrongWorld),
+    Denied(Vec<Command>),
+    ReadOnly,
 }
 impl ConnectError {
     fn new(inner: ConnectErrorInner) -> ConnectError {
@@ -57,16 +84,130 @@ impl ConnectError {
     pub fn is_cancelled(&self) -> bool {
         matches!(self.inner, ConnectErrorInner::Cancelled)
     }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because an existing Minect
+    /// datapack differs from the datapack it would create (see [DatapackConflict]).
+    pub fn is_datapack_conflict(&self) -> bool {
+        matches!(self.inner, ConnectErrorInner::DatapackConflict(_))
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because another process
+    /// actively uses the same identifier (see
+    /// [share_identifier](crate::MinecraftConnectionBuilder::share_identifier)).
+    pub fn is_identifier_in_use(&self) -> bool {
+        self.identifier_in_use().is_some()
+    }
+
+    /// Returns the other process that actively uses the same identifier, if this is why
+    /// [connect](MinecraftConnection::connect) failed.
+    pub fn identifier_in_use(&self) -> Option<&IdentifierInUse> {
+        match &self.inner {
+            ConnectErrorInner::IdentifierInUse(in_use) => Some(in_use),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because another process
+    /// holds the [lease](crate::MinecraftConnectionBuilder::lease) of the identifier.
+    pub fn is_lease_held(&self) -> bool {
+        self.lease_held().is_some()
+    }
+
+    /// Returns the lease of another process, if this is why [connect](MinecraftConnection::connect)
+    /// failed.
+    pub fn lease_held(&self) -> Option<&LeaseHeld> {
+        match &self.inner {
+            ConnectErrorInner::LeaseHeld(held) => Some(held),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because the directories
+    /// around the world directory don't match the
+    /// [hosting_layout](crate::MinecraftConnectionBuilder::hosting_layout).
+    pub fn is_hosting_layout_mismatch(&self) -> bool {
+        matches!(self.inner, ConnectErrorInner::HostingLayoutMismatch(_))
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because a player confirmed
+    /// the interactive installer in a different world than the world directory of the connection.
+    pub fn is_wrong_world(&self) -> bool {
+        self.wrong_world().is_some()
+    }
+
+    /// Returns the expected and the actual world, if this is why
+    /// [connect](MinecraftConnection::connect) failed.
+    pub fn wrong_world(&self) -> Option<&WrongWorld> {
+        match &self.inner {
+            ConnectErrorInner::WrongWorld(wrong_world) => Some(wrong_world),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because the
+    /// [command_policy](crate::MinecraftConnectionBuilder::command_policy) denied at least one
+    /// command.
+    pub fn is_denied(&self) -> bool {
+        matches!(self.inner, ConnectErrorInner::Denied(_))
+    }
+
+    /// The commands that were denied by the
+    /// [command_policy](crate::MinecraftConnectionBuilder::command_policy). This is empty if the
+    /// error has a different cause.
+    pub fn denied_commands(&self) -> &[Command] {
+        match &self.inner {
+            ConnectErrorInner::Denied(commands) => commands,
+            _ => &[],
+        }
+    }
+
+    /// Returns `true` if [connect](MinecraftConnection::connect) failed because another process
+    /// took over the [lease](crate::MinecraftConnectionBuilder::lease) of the identifier while
+    /// connecting.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.inner, ConnectErrorInner::ReadOnly)
+    }
 }
 impl From<IoErrorAtPath> for ConnectError {
     fn from(value: IoErrorAtPath) -> ConnectError {
         ConnectError::new(ConnectErrorInner::Io(value))
     }
 }
+impl From<CreateDatapackError> for ConnectError {
+    fn from(value: CreateDatapackError) -> ConnectError {
+        match value.into_inner() {
+            CreateDatapackErrorInner::Io(error) => error.into(),
+            CreateDatapackErrorInner::Conflict(conflict) => {
+                ConnectError::new(ConnectErrorInner::DatapackConflict(conflict))
+            }
+        }
+    }
+}
 impl From<ExecuteCommandsError> for ConnectError {
     fn from(value: ExecuteCommandsError) -> ConnectError {
         match value.inner {
             ExecuteCommandsErrorInner::Io(error) => error.into(),
+            ExecuteCommandsErrorInner::DatapackConflict(conflict) => {
+                ConnectError::new(ConnectErrorInner::DatapackConflict(conflict))
+            }
+            ExecuteCommandsErrorInner::Placement(error) => {
+                ConnectError::new(ConnectErrorInner::Placement(error))
+            }
+            ExecuteCommandsErrorInner::Denied(commands) => {
+                ConnectError::new(ConnectErrorInner::Denied(commands))
+            }
+            ExecuteCommandsErrorInner::ReadOnly => ConnectError::new(ConnectErrorInner::ReadOnly),
+            // connect only executes internal commands, which are not named, not conditional and
+            // don't use the urgent lane, stream their output, load structures or wait for a
+            // confirmation
+            inner @ (ExecuteCommandsErrorInner::ReservedName(_)
+            | ExecuteCommandsErrorInner::UnsupportedConditional(_)
+            | ExecuteCommandsErrorInner::TooManyCommands { .. }
+            | ExecuteCommandsErrorInner::StructureNotLoaded(_)
+            | ExecuteCommandsErrorInner::UrgentLaneNotConnected
+            | ExecuteCommandsErrorInner::TimedOut) => {
+                unreachable!("Unexpected check of internal commands: {:?}", inner)
+            }
         }
     }
 }
@@ -74,7 +215,23 @@ impl Display for ConnectError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.inner {
             ConnectErrorInner::Io(error) => error.fmt(f),
+            ConnectErrorInner::DatapackConflict(conflict) => conflict.fmt(f),
             ConnectErrorInner::Cancelled => write!(f, "Cancelled"),
+            ConnectErrorInner::IdentifierInUse(in_use) => in_use.fmt(f),
+            ConnectErrorInner::LeaseHeld(held) => held.fmt(f),
+            ConnectErrorInner::HostingLayoutMismatch(message) => message.fmt(f),
+            ConnectErrorInner::Placement(error) => error.fmt(f),
+            ConnectErrorInner::WrongWorld(wrong_world) => wrong_world.fmt(f),
+            ConnectErrorInner::Denied(commands) => {
+                write!(f, "Commands denied by the command policy:")?;
+                for command in commands {
+                    write!(f, "\n{}", command)?;
+                }
+                Ok(())
+            }
+            ConnectErrorInner::ReadOnly => {
+                write!(f, "Another process took over the lease while connecting")
+            }
         }
     }
 }
@@ -83,14 +240,190 @@ impl From<ConnectError> for std::io::Error {
     fn from(value: ConnectError) -> std::io::Error {
         match value.inner {
             ConnectErrorInner::Io(error) => std::io::Error::from(error),
+            ConnectErrorInner::DatapackConflict(_) => {
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, value)
+            }
             ConnectErrorInner::Cancelled => {
                 std::io::Error::new(std::io::ErrorKind::ConnectionRefused, value)
             }
+            ConnectErrorInner::IdentifierInUse(_) | ConnectErrorInner::LeaseHeld(_) => {
+                std::io::Error::new(std::io::ErrorKind::AddrInUse, value)
+            }
+            ConnectErrorInner::HostingLayoutMismatch(_) | ConnectErrorInner::WrongWorld(_) => {
+                std::io::Error::new(std::io::ErrorKind::NotFound, value)
+            }
+            ConnectErrorInner::Placement(_) => {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, value)
+            }
+            ConnectErrorInner::Denied(_) => {
+                std::io::Error::new(std::io::ErrorKind::PermissionDenied, value)
+            }
+            ConnectErrorInner::ReadOnly => {
+                std::io::Error::new(std::io::ErrorKind::AddrInUse, value)
+            }
         }
     }
 }
 
+/// The progress of [MinecraftConnection::connect], which is reported to the streams returned by
+/// [MinecraftConnection::add_connect_progress_listener].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConnectProgress {
+    /// The world directory does not exist yet, for example because the world is still being
+    /// generated. The check is repeated after `retry_in`, which grows with every `attempt`.
+    WaitingForWorld {
+        world_dir: PathBuf,
+        attempt: u32,
+        retry_in: Duration,
+    },
+    /// The log file does not exist yet, for example because Minecraft was not started yet. The
+    /// check is repeated after `retry_in`, which grows with every `attempt`.
+    WaitingForLogFile {
+        log_file: PathBuf,
+        attempt: u32,
+        retry_in: Duration,
+    },
+    /// The datapack and the interactive installer were created and a player needs to execute
+    /// `/reload` to start the installation, unless the connection building already exists.
+    WaitingForInstallation,
+    /// Another process holds the [lease](crate::MinecraftConnectionBuilder::lease) of the
+    /// identifier (see [LeaseConflict::Wait]). The lease is checked again after `retry_in`.
+    WaitingForLease { held: LeaseHeld, retry_in: Duration },
+    /// The connection was established.
+    Connected,
+}
+
+/// Instructions for the player to set up the connection, generated from the configuration of a
+/// [MinecraftConnection] (see [MinecraftConnection::connect_or_instruct]). Their [Display]
+/// implementation renders them as English text, while the installer texts are taken from the
+/// [installer_language](crate::MinecraftConnectionBuilder::installer_language).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectInstructions {
+    /// The name of the world that has to be opened in Minecraft.
+    pub world_name: String,
+    /// Where the world is hosted, which determines where the datapack has to be reloaded.
+    pub hosting_layout: HostingLayout,
+    /// The command that loads the Minect datapack. This is `minecraft:reload` on
+    /// [Bukkit](HostingLayout::Bukkit) servers, because their `reload` command reloads plugins.
+    pub reload_command: &'static str,
+    /// The chat message that the interactive installer shows after the reload.
+    pub installer_prompt: String,
+    /// The button in the [installer_prompt](Self::installer_prompt) that the player has to click.
+    pub installer_button: String,
+    _private: (),
+}
+
+impl ConnectInstructions {
+    pub(crate) fn new(connection: &MinecraftConnection) -> ConnectInstructions {
+        let hosting_layout = connection.hosting_layout.clone().unwrap_or_default();
+        let reload_command = match hosting_layout {
+            HostingLayout::Bukkit => "minecraft:reload",
+            _ => "reload",
+        };
+        let (installer_prompt, installer_button) = connection
+            .installer_texts
+            .plain_prompt(&connection.identifier, connection.install_at);
+        ConnectInstructions {
+            world_name: connection
+                .world_dir
+                .file_name()
+                .unwrap_or(connection.world_dir.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            hosting_layout,
+            reload_command,
+            installer_prompt,
+            installer_button,
+            _private: (),
+        }
+    }
+}
+
+impl Display for ConnectInstructions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.hosting_layout {
+            HostingLayout::Singleplayer => writeln!(
+                f,
+                "Open the world '{}' in Minecraft with cheats enabled (for example via 'Open to \
+                LAN') and execute /{} in the chat.",
+                self.world_name, self.reload_command
+            )?,
+            _ => writeln!(
+                f,
+                "Make sure the server runs the world '{}' and execute {} in the server console or \
+                /{} in the chat as an operator.",
+                self.world_name, self.reload_command, self.reload_command
+            )?,
+        }
+        writeln!(
+            f,
+            "Minecraft will then show the following message in the chat:"
+        )?;
+        writeln!(f, "    {}", self.installer_prompt)?;
+        write!(
+            f,
+            "Click on {} to create the connection building.",
+            self.installer_button
+        )
+    }
+}
+
+/// How long [MinecraftConnection::connect_or_instruct] waits for an existing connection building,
+/// before the player is instructed to install one.
+const INSTRUCT_AFTER: Duration = Duration::from_secs(1);
+
+pub(crate) async fn connect_or_instruct(
+    connection: &mut MinecraftConnection,
+    printer: impl FnOnce(&ConnectInstructions),
+) -> Result<(), ConnectError> {
+    let instructions = ConnectInstructions::new(connection);
+    let mut progress = connection.add_connect_progress_listener();
+    let instruct = async move {
+        while let Some(progress) = progress.next().await {
+            match progress {
+                ConnectProgress::WaitingForWorld { .. }
+                | ConnectProgress::WaitingForLogFile { .. } => {}
+                ConnectProgress::WaitingForInstallation => sleep(INSTRUCT_AFTER).await,
+                _ => continue,
+            }
+            printer(&instructions);
+            return;
+        }
+    };
+    let connect = pin!(connect(connection));
+    match select(connect, pin!(instruct)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), connect)) => connect.await,
+    }
+}
+
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 pub(crate) async fn connect(connection: &mut MinecraftConnection) -> Result<(), ConnectError> {
+    connection.read_only = false;
+    wait_for_world(connection).await;
+    let backend = connection.file_backend.clone();
+    if let Some(layout) = &connection.hosting_layout {
+        layout
+            .validate(&connection.world_dir, &*backend)
+            .map_err(|message| {
+                ConnectError::new(ConnectErrorInner::HostingLayoutMismatch(message))
+            })?;
+    }
+    let permissions = connection.file_permissions;
+    // The lease file is stored in the structures directory
+    create_dir_all_with_permissions(&*backend, &connection.structures_dir, &permissions)?;
+    let session = connection.session.unwrap_or_else(new_session_id);
+    // The lease is acquired before anything is written, so a connection without it never interferes
+    // with the owner
+    if connection.lease.is_some() && !acquire_lease(connection, session).await? {
+        connection.report_connect_progress(ConnectProgress::Connected);
+        return Ok(());
+    }
+    if let Some(datapacks_dir) = connection.datapack_dir.parent() {
+        create_dir_all_with_permissions(&*backend, datapacks_dir, &permissions)?;
+    }
     connection.create_datapack()?;
 
     let success = AtomicBool::new(false);
@@ -99,45 +432,141 @@ pub(crate) async fn connect(connection: &mut MinecraftConnection) -> Result<(),
     // Has to be stored to a variable that is not named _ to ensure it is dropped at the end of the function and not right away.
     let _on_drop = OnDrop::new(|| {
         // TODO: use block_on to allow concurrency
-        remove_connector(&identifier, &datapack_dir);
+        remove_connector(&*backend, &identifier, &datapack_dir);
         if !success.load(Ordering::Relaxed) {
-            remove_disconnector(&identifier, &datapack_dir);
+            remove_disconnector(&*backend, &identifier, &datapack_dir);
         }
-        remove_empty_dirs(&datapack_dir);
+        remove_empty_dirs(&*backend, &datapack_dir);
     });
 
+    let fingerprint = WorldFingerprint::read(&*backend, &connection.world_dir);
     let structure_id = {
         let path = connection.structures_dir.join("id.txt");
-        match File::open(&path) {
-            Ok(mut file) => read_incremented_id(&mut file, &path)?,
-            Err(_) => 0,
+        if backend.is_file(&path) {
+            let mut file = backend
+                .lock(&path)
+                .map_err(io_error("Failed to open file", &path))?;
+            read_incremented_id(&mut *file, &path)?
+        } else {
+            0
         }
     };
 
-    create_connector(&identifier, structure_id, &datapack_dir)?;
-    create_disconnector(&identifier, &datapack_dir)?;
+    create_connector(
+        &*backend,
+        &permissions,
+        &identifier,
+        structure_id,
+        connection.install_at,
+        connection.building_y,
+        connection.hide_building,
+        &WorldFingerprint::seed_range(fingerprint.as_ref()),
+        &connection.installer_texts,
+        &datapack_dir,
+    )?;
+    create_disconnector(
+        &*backend,
+        &permissions,
+        &identifier,
+        &connection.installer_texts,
+        &datapack_dir,
+    )?;
 
-    wait_for_connection(connection).await?;
+    connection.report_connect_progress(ConnectProgress::WaitingForInstallation);
+    wait_for_connection(connection, fingerprint).await?;
     success.store(true, Ordering::Relaxed);
+    claim_session(connection, session).await?;
+    execute_pending_purges(connection)?;
+    connection.report_connect_progress(ConnectProgress::Connected);
 
     Ok(())
 }
 
+/// Waits until the world directory and the log file exist, with an increasing delay between checks.
+async fn wait_for_world(connection: &MinecraftConnection) {
+    let backend = connection.file_backend.clone();
+    let mut attempt = 0;
+    let mut retry_in = INITIAL_RETRY_DELAY;
+    loop {
+        let progress = if !backend.is_dir(&connection.world_dir) {
+            ConnectProgress::WaitingForWorld {
+                world_dir: connection.world_dir.clone(),
+                attempt,
+                retry_in,
+            }
+        } else if connection.remote_log_source.is_none() && !connection.log_file.is_file() {
+            ConnectProgress::WaitingForLogFile {
+                log_file: connection.log_file.clone(),
+                attempt,
+                retry_in,
+            }
+        } else {
+            return;
+        };
+        if attempt == 0 {
+            info!("{}", progress);
+        }
+        connection.report_connect_progress(progress);
+        sleep(retry_in).await;
+        attempt += 1;
+        retry_in = (retry_in * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+impl Display for ConnectProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectProgress::WaitingForWorld { world_dir, .. } => {
+                write!(f, "Waiting for world {} to be created", world_dir.display())
+            }
+            ConnectProgress::WaitingForLogFile { log_file, .. } => {
+                write!(
+                    f,
+                    "Waiting for log file {} to be created",
+                    log_file.display()
+                )
+            }
+            ConnectProgress::WaitingForInstallation => write!(f, "Waiting for installation"),
+            ConnectProgress::WaitingForLease { held, .. } => {
+                write!(f, "Waiting for lease: {}", held)
+            }
+            ConnectProgress::Connected => write!(f, "Connected"),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn create_connector(
+    backend: &dyn FileBackend,
+    permissions: &FilePermissions,
     identifier: &str,
     structure_id: u64,
+    install_at: Option<(i32, i32)>,
+    building_y: i32,
+    hide_building: bool,
+    world_seed: &str,
+    installer_texts: &InstallerTexts,
     datapack_dir: impl AsRef<Path>,
 ) -> Result<(), IoErrorAtPath> {
     let expand_template = |template: &str| {
-        expand_template(template, identifier).replace("-structure_id-", &structure_id.to_string())
+        let template = expand_template(&installer_texts.expand(template), identifier);
+        expand_chunk(&template, install_at.unwrap_or_default())
+            .replace("-structure_id-", &format_structure_id(structure_id))
+            .replace("-building_y_1-", &(building_y + 1).to_string())
+            .replace("-building_y_5-", &(building_y + 5).to_string())
+            .replace("-building_y_6-", &(building_y + 6).to_string())
+            .replace("-building_y-", &building_y.to_string())
+            .replace("-protection_block-", protection_block(hide_building))
+            .replace("-world_seed-", world_seed)
     };
     let datapack_dir = datapack_dir.as_ref();
+    let layout = DatapackLayout::detect(backend, datapack_dir);
 
     macro_rules! add_to_function_tag {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join($relative_path);
+            let path = layout.join(datapack_dir, $relative_path);
             let template = expand_template(include_datapack_template!($relative_path));
-            add_to_function_tag(path, &template)
+            add_to_function_tag(backend, path, &template)
         }};
     }
     add_to_function_tag!("data/minect_internal/tags/functions/connect/choose_chunk.json")?;
@@ -145,9 +574,9 @@ fn create_connector(
 
     macro_rules! expand {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join(expand_template($relative_path));
+            let path = layout.join(datapack_dir, &expand_template($relative_path));
             let contents = expand_template(include_datapack_template!($relative_path));
-            write(path, &contents)
+            write_with_permissions(backend, path, &contents, permissions)
         }};
     }
     expand!("data/minect_internal/functions/connection/-connection_id-/connect/cancel_cleanup.mcfunction")?;
@@ -158,55 +587,82 @@ fn create_connector(
     expand!(
         "data/minect_internal/functions/connection/-connection_id-/connect/choose_chunk.mcfunction"
     )?;
+    expand!("data/minect_internal/functions/connection/-connection_id-/connect/confirm_chunk_checked.mcfunction")?;
     expand!("data/minect_internal/functions/connection/-connection_id-/connect/confirm_chunk.mcfunction")?;
-    expand!("data/minect_internal/functions/connection/-connection_id-/connect/prompt_unchecked.mcfunction")?;
+    expand!("data/minect_internal/functions/connection/-connection_id-/connect/wrong_world_cleanup.mcfunction")?;
+    expand!(
+        "data/minect_internal/functions/connection/-connection_id-/connect/wrong_world.mcfunction"
+    )?;
+    if install_at.is_some() {
+        let path = layout.join(datapack_dir, &expand_template(
+            "data/minect_internal/functions/connection/-connection_id-/connect/prompt_unchecked.mcfunction",
+        ));
+        let contents = expand_template(include_datapack_template!(
+            "data/minect_internal/functions/connection/-connection_id-/connect/prompt_install_chunk_unchecked.mcfunction"
+        ));
+        write_with_permissions(backend, path, &contents, permissions)?;
+        expand!("data/minect_internal/functions/connection/-connection_id-/connect/confirm_install_chunk_loaded.mcfunction")?;
+        expand!("data/minect_internal/functions/connection/-connection_id-/connect/confirm_install_chunk.mcfunction")?;
+    } else {
+        expand!("data/minect_internal/functions/connection/-connection_id-/connect/prompt_unchecked.mcfunction")?;
+    }
     expand!("data/minect_internal/functions/connection/-connection_id-/connect/prompt.mcfunction")?;
 
     Ok(())
 }
 
-fn remove_connector(identifier: &str, datapack_dir: impl AsRef<Path>) {
+fn remove_connector(backend: &dyn FileBackend, identifier: &str, datapack_dir: impl AsRef<Path>) {
     let expand_template = |template: &str| expand_template(template, identifier);
     let datapack_dir = datapack_dir.as_ref();
+    let layout = DatapackLayout::detect(backend, datapack_dir);
 
     macro_rules! remove_from_function_tag {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join($relative_path);
+            let path = layout.join(datapack_dir, $relative_path);
             let template = expand_template(include_datapack_template!($relative_path));
-            log_cleanup_error(remove_from_function_tag(path, &template))
+            log_cleanup_error(remove_from_function_tag(backend, path, &template))
         }};
     }
     remove_from_function_tag!("data/minect_internal/tags/functions/connect/choose_chunk.json");
     remove_from_function_tag!("data/minect_internal/tags/functions/connect/prompt.json");
 
     let remove = |template_path| {
-        let path = datapack_dir.join(expand_template(template_path));
-        log_cleanup_error(remove_dir_all(path));
+        let path = layout.join(datapack_dir, &expand_template(template_path));
+        log_cleanup_error(
+            backend
+                .remove_dir_all(&path)
+                .map_err(io_error("Failed to remove directory", &path)),
+        );
     };
     remove("data/minect_internal/functions/connection/-connection_id-/connect");
 }
 
 fn create_disconnector(
+    backend: &dyn FileBackend,
+    permissions: &FilePermissions,
     identifier: &str,
+    installer_texts: &InstallerTexts,
     datapack_dir: impl AsRef<Path>,
 ) -> Result<(), IoErrorAtPath> {
-    let expand_template = |template: &str| expand_template(template, identifier);
+    let expand_template =
+        |template: &str| expand_template(&installer_texts.expand(template), identifier);
     let datapack_dir = datapack_dir.as_ref();
+    let layout = DatapackLayout::detect(backend, datapack_dir);
 
     macro_rules! add_to_function_tag {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join($relative_path);
+            let path = layout.join(datapack_dir, $relative_path);
             let template = expand_template(include_datapack_template!($relative_path));
-            add_to_function_tag(path, &template)
+            add_to_function_tag(backend, path, &template)
         }};
     }
     add_to_function_tag!("data/minect_internal/tags/functions/disconnect/prompt.json")?;
 
     macro_rules! expand {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join(expand_template($relative_path));
+            let path = layout.join(datapack_dir, &expand_template($relative_path));
             let contents = expand_template(include_datapack_template!($relative_path));
-            write(path, &contents)
+            write_with_permissions(backend, path, &contents, permissions)
         }};
     }
     expand!(
@@ -216,34 +672,42 @@ fn create_disconnector(
     Ok(())
 }
 
-fn remove_disconnector(identifier: &str, datapack_dir: impl AsRef<Path>) {
+fn remove_disconnector(
+    backend: &dyn FileBackend,
+    identifier: &str,
+    datapack_dir: impl AsRef<Path>,
+) {
     let expand_template = |template: &str| expand_template(template, identifier);
     let datapack_dir = datapack_dir.as_ref();
+    let layout = DatapackLayout::detect(backend, datapack_dir);
 
     macro_rules! remove_from_function_tag {
         ($relative_path:expr) => {{
-            let path = datapack_dir.join($relative_path);
+            let path = layout.join(datapack_dir, $relative_path);
             let template = expand_template(include_datapack_template!($relative_path));
-            log_cleanup_error(remove_from_function_tag(path, &template))
+            log_cleanup_error(remove_from_function_tag(backend, path, &template))
         }};
     }
     remove_from_function_tag!("data/minect_internal/tags/functions/disconnect/prompt.json");
 
     let remove = |template_path| {
-        let path = datapack_dir.join(expand_template(template_path));
-        log_cleanup_error(remove_dir_all(path));
+        let path = layout.join(datapack_dir, &expand_template(template_path));
+        log_cleanup_error(
+            backend
+                .remove_dir_all(&path)
+                .map_err(io_error("Failed to remove directory", &path)),
+        );
     };
     remove("data/minect_internal/functions/connection/-connection_id-/disconnect");
 }
 
-fn remove_empty_dirs(datapack_dir: impl AsRef<Path>) {
-    for entry in WalkDir::new(&datapack_dir).contents_first(true) {
-        if let Ok(entry) = entry {
-            if entry.file_type().is_dir() {
-                let _ = remove_dir(entry.path());
-            }
+fn remove_empty_dirs(backend: &dyn FileBackend, dir: &Path) {
+    for entry in backend.read_dir(dir).unwrap_or_default() {
+        if backend.is_dir(&entry) {
+            remove_empty_dirs(backend, &entry);
         }
     }
+    let _ = backend.remove_dir(dir);
 }
 
 // If we fail to clean something up we still want to try to clean up the rest
@@ -253,28 +717,35 @@ fn log_cleanup_error(result: Result<(), impl Display>) {
     }
 }
 
-fn expand_template(template: &str, identifier: &str) -> String {
+pub(crate) fn expand_template(template: &str, identifier: &str) -> String {
     template.replace("-connection_id-", identifier)
 }
 
-async fn wait_for_connection(connection: &mut MinecraftConnection) -> Result<(), ConnectError> {
+async fn wait_for_connection(
+    connection: &mut MinecraftConnection,
+    fingerprint: Option<WorldFingerprint>,
+) -> Result<(), ConnectError> {
     const CONNECT_OUTPUT_PREFIX: &str = "minect_connect_";
-    const LISTENER_NAME: &str = "minect_connect";
 
-    let events = connection.add_named_listener(LISTENER_NAME);
+    let events = connection.add_named_listener(names::CONNECT);
 
-    connection.execute_commands([Command::named(
-        LISTENER_NAME,
+    connection.execute_internal_commands([Command::named(
+        names::CONNECT,
         summon_named_entity_command(&format!("{}success", CONNECT_OUTPUT_PREFIX)),
     )])?;
 
     enum Output {
         Success,
         Cancelled,
+        /// The installer was aborted in a world with this seed.
+        WrongWorld(i64),
     }
     impl TryFrom<LogEvent> for Output {
         type Error = ();
         fn try_from(event: LogEvent) -> Result<Self, Self::Error> {
+            if let Ok(output) = event.output.parse::<SeedOutput>() {
+                return Ok(Output::WrongWorld(output.seed));
+            }
             let output = if let Ok(output) = event.output.parse::<SummonNamedEntityOutput>() {
                 output.name
             } else if let Ok(output) = event.output.parse::<AddTagOutput>() {
@@ -290,13 +761,113 @@ async fn wait_for_connection(connection: &mut MinecraftConnection) -> Result<(),
             }
         }
     }
-    let output = events
-        .filter_map(|event| event.try_into().ok())
-        .next()
-        .await;
-    match output.expect("LogObserver panicked") {
-        Output::Success => Ok(()),
-        Output::Cancelled => Err(ConnectError::new(ConnectErrorInner::Cancelled)),
+    let mut outputs = events.filter_map(|event| event.try_into().ok());
+    loop {
+        match outputs.next().await.expect("LogObserver panicked") {
+            Output::Success => return Ok(()),
+            Output::Cancelled => return Err(ConnectError::new(ConnectErrorInner::Cancelled)),
+            Output::WrongWorld(actual_seed) => {
+                // Without a fingerprint the installer does not check the world
+                if let Some(expected) = &fingerprint {
+                    let wrong_world = WrongWorld::new(expected.clone(), actual_seed);
+                    return Err(ConnectError::new(ConnectErrorInner::WrongWorld(
+                        wrong_world,
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Fails if another session actively uses the identifier, unless
+/// [share_identifier](crate::MinecraftConnectionBuilder::share_identifier) is enabled or the
+/// connection holds a [lease](crate::MinecraftConnectionBuilder::lease). Otherwise all following
+/// batches record the session of this connection.
+async fn claim_session(
+    connection: &mut MinecraftConnection,
+    session: i32,
+) -> Result<(), ConnectError> {
+    if let Some(lease) = &connection.lease {
+        // Waiting for the installation may have taken longer than the lease
+        let backend = &*connection.file_backend;
+        if let Err(held) = lease.acquire(backend, &connection.identifier, session)? {
+            connection.lease_renewed = None;
+            return Err(ConnectError::new(ConnectErrorInner::LeaseHeld(held)));
+        }
+        connection.lease_renewed = Some(Instant::now());
+    } else if !connection.share_identifier {
+        if let Some(in_use) = active_session(connection, ACTIVE_SESSION_TICKS).await? {
+            return Err(ConnectError::new(ConnectErrorInner::IdentifierInUse(
+                in_use,
+            )));
+        }
+    }
+    if connection.session.replace(session).is_none() {
+        // Record the session right away, so other processes detect it before the first batch
+        connection.execute_internal_commands([])?;
+    }
+    Ok(())
+}
+
+/// Acquires the [lease](crate::MinecraftConnectionBuilder::lease) according to the
+/// [LeaseConflict] of the connection. Returns `false` if the connection became read-only.
+async fn acquire_lease(
+    connection: &mut MinecraftConnection,
+    session: i32,
+) -> Result<bool, ConnectError> {
+    let lease = connection.lease.clone().unwrap(); // Unwrap is safe because the caller checks it
+    let backend = connection.file_backend.clone();
+    let mut retry_in = INITIAL_RETRY_DELAY;
+    loop {
+        let held = match lease.acquire(&*backend, &connection.identifier, session)? {
+            Ok(()) => {
+                connection.lease_renewed = Some(Instant::now());
+                return Ok(true);
+            }
+            Err(held) => held,
+        };
+        match connection.lease_conflict {
+            LeaseConflict::Fail => {
+                return Err(ConnectError::new(ConnectErrorInner::LeaseHeld(held)));
+            }
+            LeaseConflict::ReadOnly => {
+                connection.read_only = true;
+                return Ok(false);
+            }
+            LeaseConflict::Wait => {
+                let wait = held.expires_in.max(retry_in);
+                connection.report_connect_progress(ConnectProgress::WaitingForLease {
+                    held,
+                    retry_in: wait,
+                });
+                sleep(wait).await;
+                retry_in = (retry_in * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// Echoes the last session of the identifier through the log and returns it, if it is not the
+/// session of this connection and executed a batch within the last `active_ticks`.
+async fn active_session(
+    connection: &mut MinecraftConnection,
+    active_ticks: i64,
+) -> Result<Option<IdentifierInUse>, ConnectError> {
+    let name = next_query_name();
+    let events = connection.add_named_listener(name.clone());
+    // Without a session the batch does not overwrite the queried scores
+    let own_session = connection.session.take();
+    let result =
+        connection.execute_internal_commands(handshake_commands(&connection.identifier, &name));
+    connection.session = own_session;
+    result?;
+    let mut handshake = Handshake::new(&connection.identifier);
+    let mut events = Box::pin(events);
+    loop {
+        let event = events.next().await.expect("LogObserver panicked");
+        if handshake.push(&event) {
+            return Ok(handshake.other_session(connection.session, active_ticks));
+        }
     }
 }
 
@@ -313,66 +884,87 @@ impl FunctionTag {
     }
 }
 
-fn add_to_function_tag(path: impl AsRef<Path>, template: &str) -> Result<(), IoErrorAtPath> {
+pub(crate) fn add_to_function_tag(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+    template: &str,
+) -> Result<(), IoErrorAtPath> {
     let tag_template: FunctionTag = serde_json::from_str(template).unwrap(); // Our templates are valid, so this can't fail
 
-    modify_function_tag(path, |tag| {
+    modify_function_tag(backend, path, |tag| {
         tag.values.extend(tag_template.values);
     })
 }
 
-fn remove_from_function_tag(path: impl AsRef<Path>, template: &str) -> Result<(), IoErrorAtPath> {
+pub(crate) fn remove_from_function_tag(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+    template: &str,
+) -> Result<(), IoErrorAtPath> {
     let tag_template: FunctionTag = serde_json::from_str(template).unwrap(); // Our templates are valid, so this can't fail
 
-    modify_function_tag(path, |tag| {
+    modify_function_tag(backend, path, |tag| {
         tag.values
             .retain(|value| !tag_template.values.contains(value))
     })
 }
 
+/// Reads the values of the function tag at `path`.
+pub(crate) fn read_function_tag(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+) -> Result<Vec<String>, IoErrorAtPath> {
+    let path = path.as_ref();
+    let contents = backend
+        .read(path)
+        .map_err(io_error("Failed to read file", path))?;
+    let tag: FunctionTag = serde_json::from_slice(&contents)
+        .map_err(|e| IoErrorAtPath::new("Failed to parse file", path, e))?;
+    Ok(tag.values.into_iter().collect())
+}
+
+/// Removes all values from the function tag at `path` for which `keep` returns `false`.
+pub(crate) fn retain_in_function_tag(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+    mut keep: impl FnMut(&str) -> bool,
+) -> Result<(), IoErrorAtPath> {
+    modify_function_tag(backend, path, |tag| tag.values.retain(|value| keep(value)))
+}
+
+/// Modifies the function tag at `path` while holding a [lock](FileBackend::lock) on it, so
+/// concurrent modifications by other connections are not lost.
 fn modify_function_tag(
+    backend: &dyn FileBackend,
     path: impl AsRef<Path>,
     modify: impl FnOnce(&mut FunctionTag),
 ) -> Result<(), IoErrorAtPath> {
-    let (mut tag, mut file) = read_function_tag(&path)?;
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        backend
+            .create_dir_all(parent)
+            .map_err(io_error("Failed to create directory", parent))?;
+    }
+    let mut file = backend
+        .lock(path)
+        .map_err(io_error("Failed to open file", path))?;
+    let contents = file
+        .read_contents()
+        .map_err(io_error("Failed to read file", path))?;
+    let mut tag = if contents.is_empty() {
+        FunctionTag::new()
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|e| IoErrorAtPath::new("Failed to parse file", path, e))?
+    };
     let old_len = tag.values.len();
     modify(&mut tag);
     let modified = old_len != tag.values.len();
     if modified {
-        write_function_tag(&tag, &mut file, path)?;
+        let contents =
+            serde_json::to_vec_pretty(&tag).map_err(io_error("Failed to write to file", path))?;
+        file.write_contents(&contents)
+            .map_err(io_error("Failed to write to file", path))?;
     }
     Ok(())
 }
-
-fn read_function_tag(path: &impl AsRef<Path>) -> Result<(FunctionTag, File), IoErrorAtPath> {
-    if let Some(parent) = path.as_ref().parent() {
-        create_dir_all(parent)?;
-    }
-    let file = OpenOptions::new()
-        .create(true)
-        .read(true)
-        .write(true)
-        .open(path)
-        .map_err(io_error("Failed to open file", path.as_ref()))?;
-    let reader = BufReader::new(&file);
-    let tag = match serde_json::from_reader(reader) {
-        Ok(tag) => tag,
-        Err(e) if e.is_eof() && e.line() == 1 && e.column() == 0 => FunctionTag::new(),
-        Err(e) => return Err(IoErrorAtPath::new("Failed to parse file", path.as_ref(), e)),
-    };
-    Ok((tag, file))
-}
-
-fn write_function_tag(
-    tag: &FunctionTag,
-    file: &mut File,
-    path: impl AsRef<Path>,
-) -> Result<(), IoErrorAtPath> {
-    file.set_len(0)
-        .map_err(io_error("Failed to truncate file", path.as_ref()))?;
-    file.seek(SeekFrom::Start(0))
-        .map_err(io_error("Failed to seek beginning of file", path.as_ref()))?;
-    let mut writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(&mut writer, tag)
-        .map_err(io_error("Failed to write to file", path.as_ref()))
-}
@@ -0,0 +1,256 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Structures of custom blocks, see
+//! [MinecraftConnection::load_structure](crate::MinecraftConnection::load_structure).
+
+use crate::{
+    geometry::Coordinate3,
+    snbt::Snbt,
+    structure::{nbt::Structure, Block, Entity, StructureBuilder},
+    BlockPos,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+/// A structure of blocks and entities that a program places in the world with
+/// [MinecraftConnection::load_structure](crate::MinecraftConnection::load_structure).
+///
+/// Positions are relative to the origin of the structure and must not be negative.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomStructure {
+    blocks: BTreeMap<(i32, i32, i32), CustomBlock>,
+    entities: Vec<CustomEntity>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct CustomBlock {
+    name: String,
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CustomEntity {
+    pos: Coordinate3<f64>,
+    nbt: Snbt,
+}
+
+impl CustomStructure {
+    /// Creates an empty [CustomStructure].
+    pub fn new() -> CustomStructure {
+        CustomStructure::default()
+    }
+
+    /// Adds a block at `pos`, replacing any block that was added at the same position before.
+    /// `block` is a block state like in the `setblock` command, for example
+    /// `minecraft:oak_stairs[facing=east,half=top]`. Block entity data is not supported.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error and leave the structure unchanged if a coordinate of
+    /// `pos` is negative or if `block` is not a valid block state.
+    pub fn add_block(
+        &mut self,
+        pos: impl Into<BlockPos>,
+        block: impl AsRef<str>,
+    ) -> Result<(), InvalidCustomStructure> {
+        let pos = pos.into();
+        if pos.x < 0 || pos.y < 0 || pos.z < 0 {
+            let inner = InvalidCustomStructureInner::BlockPos(pos);
+            return Err(InvalidCustomStructure { inner });
+        }
+        let block = block.as_ref();
+        let block = parse_block_state(block).ok_or_else(|| {
+            let inner = InvalidCustomStructureInner::BlockState(block.to_string());
+            InvalidCustomStructure { inner }
+        })?;
+        self.blocks.insert(pos.into(), block);
+        Ok(())
+    }
+
+    /// Adds an entity of the type `id` at `pos`, for example an armor stand, an item frame or a
+    /// marker. `nbt` is the SNBT of additional entity data like in the `summon` command, for
+    /// example `{Invisible: 1b}`, or `{}`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error and leave the structure unchanged if a coordinate of
+    /// `pos` is negative or if `nbt` is not a valid SNBT compound.
+    pub fn add_entity(
+        &mut self,
+        pos: (f64, f64, f64),
+        id: impl Into<String>,
+        nbt: impl AsRef<str>,
+    ) -> Result<(), InvalidCustomStructure> {
+        let (x, y, z) = pos;
+        // Written like this, so NaN coordinates are rejected as well
+        if !(x >= 0.0 && y >= 0.0 && z >= 0.0) {
+            let inner = InvalidCustomStructureInner::EntityPos(Coordinate3(x, y, z));
+            return Err(InvalidCustomStructure { inner });
+        }
+        let nbt = nbt.as_ref();
+        let mut entries = match nbt.parse() {
+            Ok(Snbt::Compound(entries)) => entries,
+            _ => {
+                let inner = InvalidCustomStructureInner::EntityData(nbt.to_string());
+                return Err(InvalidCustomStructure { inner });
+            }
+        };
+        entries.retain(|(key, _)| key != "id");
+        entries.push(("id".to_string(), Snbt::String(id.into())));
+        self.entities.push(CustomEntity {
+            pos: Coordinate3(x, y, z),
+            nbt: Snbt::Compound(entries),
+        });
+        Ok(())
+    }
+
+    /// Whether no block or entity was added to this structure.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.entities.is_empty()
+    }
+
+    pub(crate) fn to_structure(&self) -> Structure {
+        let mut builder = StructureBuilder::with_capacity(self.blocks.len());
+        for (pos, block) in &self.blocks {
+            builder.add_block(Block {
+                name: block.name.clone(),
+                pos: Coordinate3::from(*pos),
+                properties: block.properties.clone(),
+                nbt: None,
+            });
+        }
+        for entity in &self.entities {
+            builder.add_entity(Entity {
+                pos: entity.pos,
+                nbt: entity.nbt.clone().into(),
+            });
+        }
+        builder.build()
+    }
+}
+
+/// The error returned from [CustomStructure::add_block] and [CustomStructure::add_entity].
+#[derive(Debug)]
+pub struct InvalidCustomStructure {
+    inner: InvalidCustomStructureInner,
+}
+#[derive(Debug)]
+enum InvalidCustomStructureInner {
+    BlockPos(BlockPos),
+    BlockState(String),
+    EntityPos(Coordinate3<f64>),
+    EntityData(String),
+}
+impl Display for InvalidCustomStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            InvalidCustomStructureInner::BlockPos(pos) => {
+                write!(f, "Invalid position of block: {}", pos)
+            }
+            InvalidCustomStructureInner::BlockState(block) => {
+                write!(f, "Invalid block state: {}", block)
+            }
+            InvalidCustomStructureInner::EntityPos(Coordinate3(x, y, z)) => {
+                write!(f, "Invalid position of entity: {} {} {}", x, y, z)
+            }
+            InvalidCustomStructureInner::EntityData(nbt) => {
+                write!(f, "Invalid entity data: {}", nbt)
+            }
+        }
+    }
+}
+impl std::error::Error for InvalidCustomStructure {}
+
+fn parse_block_state(block: &str) -> Option<CustomBlock> {
+    let (name, properties) = match block.split_once('[') {
+        Some((name, properties)) => (name, properties.strip_suffix(']')?),
+        None => (block, ""),
+    };
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ']') {
+        return None;
+    }
+    let properties = properties
+        .split(',')
+        .filter(|property| !property.trim().is_empty())
+        .map(|property| {
+            let (key, value) = property.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect::<Option<_>>()?;
+    Some(CustomBlock {
+        name: name.to_string(),
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_state() {
+        // when:
+        let actual = parse_block_state("minecraft:oak_stairs[facing=east, half=top]").unwrap();
+
+        // then:
+        assert_eq!(actual.name, "minecraft:oak_stairs");
+        assert_eq!(actual.properties["facing"], "east");
+        assert_eq!(actual.properties["half"], "top");
+        assert_eq!(parse_block_state("minecraft:stone[facing"), None);
+    }
+
+    #[test]
+    fn test_to_structure_with_entity() {
+        // given:
+        let mut structure = CustomStructure::new();
+        structure
+            .add_entity((1.5, 0.0, 0.5), "minecraft:armor_stand", "{Invisible: 1b}")
+            .unwrap();
+
+        // when:
+        let actual = structure.to_structure();
+
+        // then:
+        assert_eq!(actual.entities.len(), 1);
+        assert_eq!(actual.entities[0].block_pos, vec![1, 0, 0]);
+        let expected = "{Invisible: 1b, id: \"minecraft:armor_stand\"}".parse::<Snbt>();
+        assert_eq!(actual.entities[0].nbt, expected.unwrap().into());
+    }
+
+    #[test]
+    fn test_add_invalid_block_and_entity() {
+        // given:
+        let mut structure = CustomStructure::new();
+
+        // when:
+        let negative_block = structure.add_block((0, -1, 0), "minecraft:stone");
+        let invalid_block = structure.add_block((0, 0, 0), "minecraft:stone[facing");
+        let negative_entity = structure.add_entity((0.0, -0.5, 0.0), "minecraft:marker", "{}");
+        let invalid_entity = structure.add_entity((0.0, 0.0, 0.0), "minecraft:marker", "[]");
+
+        // then:
+        assert!(negative_block.is_err());
+        assert!(invalid_block.is_err());
+        assert!(negative_entity.is_err());
+        assert!(invalid_entity.is_err());
+        assert!(structure.is_empty());
+    }
+}
@@ -17,6 +17,7 @@
 # If not, see <http://www.gnu.org/licenses/>.
 
 execute at @s run fill ~ ~-5 ~ ~ ~1 ~ air
+execute at @s run fill ~-1 ~-5 ~-1 ~1 ~2 ~1 air replace barrier
 execute at @s run forceload remove ~ ~
 kill @s
 tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" Removed connection "},{"selector":"@s"}]
@@ -0,0 +1,24 @@
+# Minect is library that allows a program to connect to a running Minecraft instance without
+# requiring any Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of Minect.
+#
+# Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+# General Public License as published by the Free Software Foundation, either version 3 of the
+# License, or (at your option) any later version.
+#
+# Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+# the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+# Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with Minect.
+# If not, see <http://www.gnu.org/licenses/>.
+
+execute store result score silence_commandBlockOutput minect_global run gamerule commandBlockOutput
+execute store result score silence_logAdminCommands minect_global run gamerule logAdminCommands
+execute store result score silence_sendCommandFeedback minect_global run gamerule sendCommandFeedback
+gamerule commandBlockOutput false
+gamerule logAdminCommands false
+gamerule sendCommandFeedback false
@@ -17,6 +17,7 @@
 # If not, see <http://www.gnu.org/licenses/>.
 
 execute as @e[type=area_effect_cloud,tag=minect_connection] run function minect:disconnect_self
+function #minect_internal:remove_objectives
 
 function minect_internal:v3_uninstall
 
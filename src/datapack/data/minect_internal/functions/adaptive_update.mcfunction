@@ -0,0 +1,24 @@
+# Minect is library that allows a program to connect to a running Minecraft instance without
+# requiring any Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of Minect.
+#
+# Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+# General Public License as published by the Free Software Foundation, either version 3 of the
+# License, or (at your option) any later version.
+#
+# Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+# the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+# Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with Minect.
+# If not, see <http://www.gnu.org/licenses/>.
+
+# Executed as and at a connection entity with an adaptive update delay every game tick
+
+scoreboard players add @s minect_timer 1
+scoreboard players remove @s[scores={minect_idle=1..}] minect_idle 1
+execute if score @s minect_idle matches 0 run scoreboard players operation @s minect_delay = @s minect_max_delay
+execute if score @s minect_timer >= @s minect_delay run function minect_internal:adaptive_pulse
@@ -19,7 +19,7 @@
 execute at @e[type=command_block_minecart,tag=minect_connect_canceller] run fill ~ ~-1 ~ ~ ~ ~ air
 kill @e[type=command_block_minecart,tag=minect_connect_canceller]
 
-tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" Cancelled adding connection -connection_id-"}]
+tellraw @a [{"text":""},{"text":"-message_info-","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" -message_cancelled-"}]
 
 # This loads the removal of the connect functions on disk
 schedule function minect_internal:reload 1t
@@ -22,7 +22,7 @@ execute as @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] run f
 
 execute at @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] run setblock ~ ~ ~ structure_block{mode: LOAD, showboundingbox: true, sizeX: 16, sizeY: 48, sizeZ: 16}
 
-tellraw @s [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" This chunk will be force loaded to keep the connection active when no player is around.\n "},{"text":"[Confirm]","clickEvent":{"action":"run_command","value":"/execute as @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] at @s run function minect_internal:connection/-connection_id-/connect/confirm_chunk"},"hoverEvent":{"action":"show_text","contents":"Click to execute"},"color":"green"},{"text":" "},{"text":"[Choose different chunk]","clickEvent":{"action":"suggest_command","value":"/execute positioned ~ ~ ~ run function minect:connect/choose_chunk"},"hoverEvent":{"action":"show_text","contents":"Click for suggestion"},"color":"yellow"},{"text":" "},{"text":"[Cancel]","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/cancel"},"hoverEvent":{"action":"show_text","contents":"Click to execute"},"color":"red"}]
+tellraw @s [{"text":""},{"text":"-message_info-","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" -message_force_load_notice-\n "},{"text":"-message_confirm-","clickEvent":{"action":"run_command","value":"/execute as @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] at @s run function minect_internal:connection/-connection_id-/connect/confirm_chunk"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_execute-"},"color":"green"},{"text":" "},{"text":"-message_choose_different_chunk-","clickEvent":{"action":"suggest_command","value":"/execute positioned ~ ~ ~ run function minect:connect/choose_chunk"},"hoverEvent":{"action":"show_text","contents":"-message_click_for_suggestion-"},"color":"yellow"},{"text":" "},{"text":"-message_cancel-","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/cancel"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_execute-"},"color":"red"}]
 
 # Only one choose_chunk at a time
 scoreboard players reset connect_choose_chunk minect_global
@@ -0,0 +1,22 @@
+# Minect is library that allows a program to connect to a running Minecraft instance without
+# requiring any Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of Minect.
+#
+# Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+# General Public License as published by the Free Software Foundation, either version 3 of the
+# License, or (at your option) any later version.
+#
+# Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+# the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+# Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with Minect.
+# If not, see <http://www.gnu.org/licenses/>.
+# The chunk may not be loaded yet, in which case the summon command fails and we try again later
+execute as @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] run function minect_internal:connect/remove_connector
+summon area_effect_cloud -block_x- -building_y- -block_z- {Duration: 2147483647, Tags: [minect, minect_connector+-connection_id-]}
+execute unless entity @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] run schedule function minect_internal:connection/-connection_id-/connect/confirm_install_chunk_loaded 1t
+execute as @e[type=area_effect_cloud,tag=minect_connector+-connection_id-] at @s run function minect_internal:connection/-connection_id-/connect/confirm_chunk
@@ -0,0 +1,20 @@
+# Minect is library that allows a program to connect to a running Minecraft instance without
+# requiring any Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of Minect.
+#
+# Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+# General Public License as published by the Free Software Foundation, either version 3 of the
+# License, or (at your option) any later version.
+#
+# Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+# the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+# Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with Minect.
+# If not, see <http://www.gnu.org/licenses/>.
+tellraw @a [{"text":""},{"text":"-message_info-","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" -message_prompt_install_chunk-"},{"text":"-installer_description-"},{"text":"\n "},{"text":"-message_confirm-","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/confirm_install_chunk"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_execute-"},"color":"green"},{"text":" "},{"text":"-message_cancel-","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/cancel"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_execute-"},"color":"red"}]
+
+scoreboard players reset connect_prompt minect_global
@@ -16,7 +16,7 @@
 # You should have received a copy of the GNU General Public License along with Minect.
 # If not, see <http://www.gnu.org/licenses/>.
 
-tellraw @a [{"text":""},{"text":"[Info]","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" An external application wants to establish a connection named '-connection_id-'. You can click on the colored text below to choose a chunk in which to generate the connection structure. The chunk may be cleared by the connection, so make sure it does not contain anything important.\n "},{"text":"[Choose a chunk]","clickEvent":{"action":"suggest_command","value":"/execute positioned ~ ~ ~ run function minect:connect/choose_chunk"},"hoverEvent":{"action":"show_text","contents":"Click for suggestion"},"color":"green"},{"text":" "},{"text":"[Cancel]","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/cancel"},"hoverEvent":{"action":"show_text","contents":"Click to execute"},"color":"red"}]
+tellraw @a [{"text":""},{"text":"-message_info-","color":"blue","hoverEvent":{"action":"show_text","contents":"Minect"}},{"text":" -message_prompt-"},{"text":"-installer_description-"},{"text":"\n "},{"text":"-message_choose_chunk-","clickEvent":{"action":"suggest_command","value":"/execute positioned ~ ~ ~ run function minect:connect/choose_chunk"},"hoverEvent":{"action":"show_text","contents":"-message_click_for_suggestion-"},"color":"green"},{"text":" "},{"text":"-message_cancel-","clickEvent":{"action":"run_command","value":"/function minect_internal:connection/-connection_id-/connect/cancel"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_execute-"},"color":"red"}]
 
 # Only one prompt at a time
 scoreboard players reset connect_prompt minect_global
@@ -16,4 +16,4 @@
 # You should have received a copy of the GNU General Public License along with Minect.
 # If not, see <http://www.gnu.org/licenses/>.
 
-execute if entity @e[type=area_effect_cloud,tag=minect_connection,tag=minect_connection+-connection_id-] run tellraw @s [{"text":" - "},{"text":"[-connection_id-]","clickEvent":{"action":"run_command","value":"/execute as @e[type=area_effect_cloud,tag=minect_connection,tag=minect_connection+-connection_id-] run function minect:disconnect_self"},"hoverEvent":{"action":"show_text","contents":"Click to remove"},"color":"aqua"}]
+execute if entity @e[type=area_effect_cloud,tag=minect_connection,tag=minect_connection+-connection_id-] run tellraw @s [{"text":" - "},{"text":"[-connection_id-]","clickEvent":{"action":"run_command","value":"/execute as @e[type=area_effect_cloud,tag=minect_connection,tag=minect_connection+-connection_id-] run function minect:disconnect_self"},"hoverEvent":{"action":"show_text","contents":"-message_click_to_remove-"},"color":"aqua"}]
@@ -0,0 +1,25 @@
+# Minect is library that allows a program to connect to a running Minecraft instance without
+# requiring any Minecraft mods.
+#
+# © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+#
+# This file is part of Minect.
+#
+# Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+# General Public License as published by the Free Software Foundation, either version 3 of the
+# License, or (at your option) any later version.
+#
+# Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+# the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+# Public License for more details.
+#
+# You should have received a copy of the GNU General Public License along with Minect.
+# If not, see <http://www.gnu.org/licenses/>.
+
+# Called by the marker of every signed batch after it stored its id and the id of the next batch.
+# A batch that was not announced by the previous batch was replayed or reordered.
+scoreboard players set integrity_changed+-connection_id- minect_global 0
+execute if data storage minect:integrity "-connection_id-".expected store success score integrity_changed+-connection_id- minect_global run data modify storage minect:integrity "-connection_id-".expected set from storage minect:integrity "-connection_id-".id
+execute if score integrity_changed+-connection_id- minect_global matches 1 run scoreboard players add integrity_violations+-connection_id- minect_global 1
+execute if score integrity_changed+-connection_id- minect_global matches 1 run tellraw @a[tag=minect_moderator] [{"text":"Minect batch ","color":"red"},{"storage":"minect:integrity","nbt":"\"-connection_id-\".id"},{"text":" of connection -connection_id- was not announced by the previous batch. It may have been replayed."}]
+data modify storage minect:integrity "-connection_id-".expected set from storage minect:integrity "-connection_id-".next
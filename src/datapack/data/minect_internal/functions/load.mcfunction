@@ -22,6 +22,18 @@ execute if score version minect_version matches 2 as @e[type=area_effect_cloud,t
 execute if score version minect_version matches 2 run function minect_internal:v2_uninstall
 execute unless score version minect_version matches 3.. run function minect_internal:v3_install
 
+# Connections with an adaptive update delay are updated with their own delay
+scoreboard objectives add minect_delay dummy
+scoreboard objectives add minect_idle dummy
+scoreboard objectives add minect_max_delay dummy
+scoreboard objectives add minect_timer dummy
+
 # TODO: Instead of using function tags we could patch this function. That way there is a bit less clutter that is alphabetically before the functions in the minect namespace.
 scoreboard players set connect_prompt minect_global 1
 function #minect_internal:connect/prompt
+
+# Remove the connections of purged identifiers
+function #minect_internal:purge
+
+# Execute batches of commands that were written to function files
+function #minect_internal:batch
@@ -20,3 +20,4 @@ kill @e[type=command_block_minecart,tag=minect_impulse,nbt=!{LastExecution: 1L}]
 
 scoreboard players add update_timer minect_global 1
 execute if score update_timer minect_global >= update_delay minect_config run function minect_internal:update
+execute as @e[type=area_effect_cloud,tag=minect_connection,tag=minect_adaptive,tag=!minect_paused] at @s run function minect_internal:adaptive_update
@@ -17,4 +17,4 @@
 # If not, see <http://www.gnu.org/licenses/>.
 
 scoreboard players set update_timer minect_global 0
-execute at @e[type=area_effect_cloud,tag=minect_connection] positioned ~ ~-4 ~ run function minect_internal:pulse_redstone
+execute at @e[type=area_effect_cloud,tag=minect_connection,tag=!minect_adaptive,tag=!minect_paused] positioned ~ ~-4 ~ run function minect_internal:pulse_redstone
@@ -22,7 +22,11 @@ scoreboard objectives remove minect_const
 scoreboard objectives remove minect_cursor_x
 scoreboard objectives remove minect_cursor_y
 scoreboard objectives remove minect_cursor_z
+scoreboard objectives remove minect_delay
 scoreboard objectives remove minect_even_y
 scoreboard objectives remove minect_even_z
 scoreboard objectives remove minect_global
+scoreboard objectives remove minect_idle
+scoreboard objectives remove minect_max_delay
 scoreboard objectives remove minect_self_pos
+scoreboard objectives remove minect_timer
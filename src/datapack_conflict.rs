@@ -0,0 +1,264 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{file_backend::FileBackend, io::IoErrorAtPath, utils::io_invalid_data};
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+const DESCRIPTION_PREFIX: &str = "generated minect datapack ";
+const IDENTIFIER_SEPARATOR: &str = " for ";
+
+/// The description of the `pack.mcmeta` file, which records the version of Minect and the
+/// identifier of the connection that created the datapack.
+pub(crate) fn pack_description(identifier: &str) -> String {
+    format!(
+        "{}{}{}{}",
+        DESCRIPTION_PREFIX,
+        env!("CARGO_PKG_VERSION"),
+        IDENTIFIER_SEPARATOR,
+        identifier
+    )
+}
+
+/// An existing Minect datapack that was created by another program and differs from the datapack
+/// that [create_datapack](crate::MinecraftConnection::create_datapack) would create. This usually
+/// means that the other program bundles a different version of Minect.
+///
+/// A datapack that was created by a connection with the same identifier is never a conflict, so
+/// upgrading Minect simply overwrites the datapack of the previous version.
+#[derive(Clone, Debug)]
+pub struct DatapackConflict {
+    /// The directory of the existing datapack.
+    pub datapack_dir: PathBuf,
+    /// The identifier of the connection that created the existing datapack.
+    pub existing_identifier: String,
+    /// The version of Minect that created the existing datapack.
+    pub existing_version: String,
+    /// The version of Minect that tried to create the datapack.
+    pub version: String,
+    /// The files that have different contents, relative to the
+    /// [datapack_dir](Self::datapack_dir).
+    pub changed_files: Vec<PathBuf>,
+    _private: (),
+}
+
+impl DatapackConflict {
+    /// Compares the existing files in `datapack_dir` with the `files` that would be written by the
+    /// connection with the given `identifier`. Only a datapack created by a connection with another
+    /// identifier can be a conflict. Datapacks created before Minect recorded the identifier are
+    /// never a conflict. Files that do not exist yet are no conflict and `pack.mcmeta` is ignored,
+    /// because its description always differs between programs.
+    pub(crate) fn find(
+        backend: &dyn FileBackend,
+        datapack_dir: &Path,
+        identifier: &str,
+        files: &[(PathBuf, String)],
+    ) -> Option<DatapackConflict> {
+        let (existing_version, existing_identifier) = read_creator(backend, datapack_dir)?;
+        if existing_identifier == identifier {
+            return None;
+        }
+        let pack_mcmeta = datapack_dir.join("pack.mcmeta");
+        let changed_files = files
+            .iter()
+            .filter(|(path, _)| *path != pack_mcmeta)
+            .filter(|(path, contents)| {
+                read_to_string(backend, path).is_ok_and(|existing| existing != *contents)
+            })
+            .map(|(path, _)| {
+                path.strip_prefix(datapack_dir)
+                    .unwrap_or(path)
+                    .to_path_buf()
+            })
+            .collect::<Vec<_>>();
+        if changed_files.is_empty() {
+            return None;
+        }
+        Some(DatapackConflict {
+            datapack_dir: datapack_dir.to_path_buf(),
+            existing_identifier,
+            existing_version,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            changed_files,
+            _private: (),
+        })
+    }
+}
+
+fn read_to_string(backend: &dyn FileBackend, path: &Path) -> io::Result<String> {
+    String::from_utf8(backend.read(path)?).map_err(io_invalid_data)
+}
+
+/// Reads the version of Minect and the identifier of the connection that created the existing
+/// datapack from its `pack.mcmeta`.
+fn read_creator(backend: &dyn FileBackend, datapack_dir: &Path) -> Option<(String, String)> {
+    let pack_mcmeta = read_to_string(backend, &datapack_dir.join("pack.mcmeta")).ok()?;
+    let pack_mcmeta = serde_json::from_str::<serde_json::Value>(&pack_mcmeta).ok()?;
+    let description = pack_mcmeta.get("pack")?.get("description")?.as_str()?;
+    let creator = description.strip_prefix(DESCRIPTION_PREFIX)?;
+    let (version, identifier) = creator.split_once(IDENTIFIER_SEPARATOR)?;
+    Some((version.to_string(), identifier.to_string()))
+}
+
+impl Display for DatapackConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The datapack {} was created by Minect {} for the connection '{}' and differs from \
+            Minect {} in {} files:",
+            self.datapack_dir.display(),
+            self.existing_version,
+            self.existing_identifier,
+            self.version,
+            self.changed_files.len()
+        )?;
+        for file in &self.changed_files {
+            write!(f, "\n{}", file.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned from [MinecraftConnection::create_datapack](crate::MinecraftConnection::create_datapack).
+#[derive(Debug)]
+pub struct CreateDatapackError {
+    inner: CreateDatapackErrorInner,
+}
+#[derive(Debug)]
+pub(crate) enum CreateDatapackErrorInner {
+    Io(IoErrorAtPath),
+    Conflict(DatapackConflict),
+}
+impl CreateDatapackError {
+    pub(crate) fn new(inner: CreateDatapackErrorInner) -> CreateDatapackError {
+        CreateDatapackError { inner }
+    }
+
+    pub(crate) fn into_inner(self) -> CreateDatapackErrorInner {
+        self.inner
+    }
+
+    /// Returns `true` if an existing datapack was not overwritten, because it differs from the
+    /// datapack that would be created (see
+    /// [force_overwrite](crate::MinecraftConnectionBuilder::force_overwrite)).
+    pub fn is_conflict(&self) -> bool {
+        matches!(self.inner, CreateDatapackErrorInner::Conflict(_))
+    }
+
+    /// The [DatapackConflict] if this error was caused by one.
+    pub fn conflict(&self) -> Option<&DatapackConflict> {
+        match &self.inner {
+            CreateDatapackErrorInner::Conflict(conflict) => Some(conflict),
+            _ => None,
+        }
+    }
+}
+impl From<IoErrorAtPath> for CreateDatapackError {
+    fn from(value: IoErrorAtPath) -> CreateDatapackError {
+        CreateDatapackError::new(CreateDatapackErrorInner::Io(value))
+    }
+}
+impl Display for CreateDatapackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            CreateDatapackErrorInner::Io(error) => error.fmt(f),
+            CreateDatapackErrorInner::Conflict(conflict) => conflict.fmt(f),
+        }
+    }
+}
+impl std::error::Error for CreateDatapackError {}
+impl From<CreateDatapackError> for std::io::Error {
+    fn from(value: CreateDatapackError) -> std::io::Error {
+        match value.inner {
+            CreateDatapackErrorInner::Io(error) => std::io::Error::from(error),
+            CreateDatapackErrorInner::Conflict(conflict) => {
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, conflict.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+    use std::fs::write;
+
+    fn create_datapack(description: &str) -> (TempDir, Vec<(PathBuf, String)>) {
+        let datapack_dir = TempDir::new("conflict");
+        let pack_mcmeta = format!(
+            r#"{{"pack":{{"pack_format":6,"description":"{}"}}}}"#,
+            description
+        );
+        write(datapack_dir.join("pack.mcmeta"), pack_mcmeta).unwrap();
+        write(datapack_dir.join("same.mcfunction"), "say same").unwrap();
+        write(datapack_dir.join("changed.mcfunction"), "say old").unwrap();
+        let files = vec![
+            (datapack_dir.join("pack.mcmeta"), pack_description("mine")),
+            (datapack_dir.join("same.mcfunction"), "say same".to_string()),
+            (
+                datapack_dir.join("changed.mcfunction"),
+                "say new".to_string(),
+            ),
+            (datapack_dir.join("new.mcfunction"), "say new".to_string()),
+        ];
+        (datapack_dir, files)
+    }
+
+    #[test]
+    fn test_find_reports_changed_files_of_other_program() {
+        // given:
+        let (datapack_dir, files) = create_datapack("generated minect datapack 0.1.0 for other");
+
+        // when:
+        let actual = DatapackConflict::find(&LocalFileBackend, &datapack_dir, "mine", &files);
+
+        // then:
+        let actual = actual.unwrap();
+        assert_eq!(actual.existing_identifier, "other");
+        assert_eq!(actual.existing_version, "0.1.0");
+        assert_eq!(actual.changed_files, [PathBuf::from("changed.mcfunction")]);
+    }
+
+    #[test]
+    fn test_find_ignores_older_version_of_same_program() {
+        // given:
+        let (datapack_dir, files) = create_datapack("generated minect datapack 0.1.0 for mine");
+
+        // when:
+        let actual = DatapackConflict::find(&LocalFileBackend, &datapack_dir, "mine", &files);
+
+        // then:
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn test_find_ignores_datapack_without_identifier() {
+        // given:
+        let (datapack_dir, files) = create_datapack("generated minect datapack 0.1.0");
+
+        // when:
+        let actual = DatapackConflict::find(&LocalFileBackend, &datapack_dir, "mine", &files);
+
+        // then:
+        assert!(actual.is_none());
+    }
+}
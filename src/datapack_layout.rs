@@ -0,0 +1,168 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{FileBackend, MinecraftVersion};
+use std::path::{Path, PathBuf};
+
+/// The directory layout of the Minect datapack. Minecraft 1.21 renamed the directories of functions,
+/// tags and other resources to their singular form, for example `functions` to `function` and
+/// `tags/blocks` to `tags/block`. The bundled templates use the plural layout and are translated
+/// when they are written.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum DatapackLayout {
+    Plural,
+    Singular,
+}
+
+/// The directories in `data/<namespace>` and `data/<namespace>/tags` that Minecraft 1.21 renamed.
+const RENAMED_DIRECTORIES: [(&str, &str); 12] = [
+    ("advancements", "advancement"),
+    ("blocks", "block"),
+    ("entity_types", "entity_type"),
+    ("fluids", "fluid"),
+    ("functions", "function"),
+    ("game_events", "game_event"),
+    ("item_modifiers", "item_modifier"),
+    ("items", "item"),
+    ("loot_tables", "loot_table"),
+    ("predicates", "predicate"),
+    ("recipes", "recipe"),
+    ("structures", "structure"),
+];
+
+impl DatapackLayout {
+    pub(crate) fn for_version(version: MinecraftVersion) -> DatapackLayout {
+        if version >= MinecraftVersion::new(1, 21, 0) {
+            DatapackLayout::Singular
+        } else {
+            DatapackLayout::Plural
+        }
+    }
+
+    /// Detects the layout of the existing datapack in `datapack_dir`. If there is no datapack, the
+    /// plural layout is assumed.
+    pub(crate) fn detect(backend: &dyn FileBackend, datapack_dir: &Path) -> DatapackLayout {
+        if backend.is_dir(&datapack_dir.join("data/minect_internal/function")) {
+            DatapackLayout::Singular
+        } else {
+            DatapackLayout::Plural
+        }
+    }
+
+    /// Translates a `relative_path` in the plural layout of the templates to this layout.
+    pub(crate) fn path(self, relative_path: &str) -> String {
+        if self == DatapackLayout::Plural {
+            return relative_path.to_string();
+        }
+        let mut components = relative_path.split('/').collect::<Vec<_>>();
+        // data/<namespace>/functions/... or data/<namespace>/tags/functions/...
+        let index = if components.get(2) == Some(&"tags") {
+            3
+        } else {
+            2
+        };
+        if components.first() == Some(&"data") {
+            let renamed = components.get(index).and_then(|directory| {
+                RENAMED_DIRECTORIES
+                    .iter()
+                    .find(|(plural, _)| plural == directory)
+            });
+            if let Some((_, singular)) = renamed {
+                components[index] = singular;
+            }
+        }
+        components.join("/")
+    }
+
+    pub(crate) fn join(self, datapack_dir: &Path, relative_path: &str) -> PathBuf {
+        datapack_dir.join(self.path(relative_path))
+    }
+}
+
+/// The `pack_format` of datapacks for the given Minecraft `version`. Without a version the format of
+/// Minecraft 1.16.2 is used, which later versions still load.
+pub(crate) fn pack_format(version: Option<MinecraftVersion>) -> u32 {
+    const PACK_FORMATS: [((u16, u16, u16), u32); 15] = [
+        ((1, 21, 4), 61),
+        ((1, 21, 2), 57),
+        ((1, 21, 0), 48),
+        ((1, 20, 5), 41),
+        ((1, 20, 3), 26),
+        ((1, 20, 2), 18),
+        ((1, 20, 0), 15),
+        ((1, 19, 4), 12),
+        ((1, 19, 0), 10),
+        ((1, 18, 2), 9),
+        ((1, 18, 0), 8),
+        ((1, 17, 0), 7),
+        ((1, 16, 2), 6),
+        ((1, 15, 0), 5),
+        ((1, 13, 0), 4),
+    ];
+    let version = match version {
+        Some(version) => version,
+        None => return 6,
+    };
+    PACK_FORMATS
+        .iter()
+        .find(|((major, minor, patch), _)| version >= MinecraftVersion::new(*major, *minor, *patch))
+        .map(|(_, pack_format)| *pack_format)
+        .unwrap_or(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_singular() {
+        // given:
+        let layout = DatapackLayout::Singular;
+
+        // when:
+        let function = layout.path("data/minect/functions/connect/functions.mcfunction");
+        let tag = layout.path("data/minect_internal/tags/functions/batch.json");
+        let block_tag = layout.path("data/minect_internal/tags/blocks/command_blocks.json");
+        let other = layout.path("pack.mcmeta");
+
+        // then:
+        assert_eq!(
+            function,
+            "data/minect/function/connect/functions.mcfunction"
+        );
+        assert_eq!(tag, "data/minect_internal/tags/function/batch.json");
+        assert_eq!(
+            block_tag,
+            "data/minect_internal/tags/block/command_blocks.json"
+        );
+        assert_eq!(other, "pack.mcmeta");
+    }
+
+    #[test]
+    fn test_pack_format() {
+        // when:
+        let actual = [
+            pack_format(None),
+            pack_format(Some(MinecraftVersion::new(1, 20, 4))),
+            pack_format(Some(MinecraftVersion::new(1, 21, 1))),
+        ];
+
+        // then:
+        assert_eq!(actual, [6, 26, 48]);
+    }
+}
@@ -0,0 +1,211 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    create_structure_file,
+    file_backend::FileBackend,
+    io::{io_error, IoErrorAtPath},
+    retry::{is_after, UnacknowledgedBatches},
+    structure::nbt::Structure,
+    structure_id::{format_structure_id, structure_file},
+    warnings::{MinectWarning, Warnings},
+};
+use flate2::Compression;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+/// A batch that was executed with
+/// [execute_commands_with_deadline](crate::MinecraftConnection::execute_commands_with_deadline),
+/// but was not loaded by Minecraft before the deadline, so its commands are never executed.
+///
+/// The event is sent once Minecraft acknowledged that it loaded the replacement of the batch.
+///
+/// These events can be received with
+/// [MinecraftConnection::add_batch_expired_listener](crate::MinecraftConnection::add_batch_expired_listener).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchExpired {
+    /// The structure id of the expired batch, as returned by
+    /// [execute_commands_with_deadline](crate::MinecraftConnection::execute_commands_with_deadline).
+    pub id: u64,
+    _private: (),
+}
+
+/// Keeps track of batches with a deadline that were not yet loaded by Minecraft.
+///
+/// Whether a batch was loaded is only known from its acknowledgement in the log. Minecraft may have
+/// loaded a batch whose acknowledgement was not read yet, so an expired batch is only reported once
+/// its replacement acknowledges that it was loaded instead of the batch.
+pub(crate) struct ExpiringBatches {
+    compression: Compression,
+    batches: Mutex<BTreeMap<u64, ExpiringBatch>>,
+    listeners: RwLock<Vec<UnboundedSender<BatchExpired>>>,
+}
+
+struct ExpiringBatch {
+    deadline: Instant,
+    /// A structure without user commands that replaces the batch when it expires. The batch can't
+    /// simply be removed, because the connection building waits for a structure with its id. The
+    /// replacement acknowledges itself under the name [EXPIRED](crate::names::EXPIRED).
+    replacement: Structure,
+}
+
+impl ExpiringBatches {
+    pub(crate) fn new(compression: Compression) -> ExpiringBatches {
+        ExpiringBatches {
+            compression,
+            batches: Mutex::new(BTreeMap::new()),
+            listeners: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn add_listener(&self) -> impl Stream<Item = BatchExpired> {
+        let (sender, receiver) = unbounded_channel();
+        self.listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    pub(crate) fn insert(&self, id: u64, deadline: Instant, replacement: Structure) {
+        let batch = ExpiringBatch {
+            deadline,
+            replacement,
+        };
+        self.batches.lock().unwrap().insert(id, batch);
+    }
+
+    /// Forgets the batch with the given `id` and all batches before it, because structures are
+    /// always loaded in order.
+    pub(crate) fn acknowledge(&self, id: u64) {
+        self.batches
+            .lock()
+            .unwrap()
+            .retain(|other_id, _| is_after(*other_id, id));
+    }
+
+    /// Called when the replacement of the batch with the given `id` was loaded, so the batch itself
+    /// is never executed.
+    pub(crate) fn replaced(&self, id: u64) {
+        let event = BatchExpired { id, _private: () };
+        self.listeners
+            .write()
+            .unwrap()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+
+    /// Replaces the structure files of all batches whose deadline passed with their replacements.
+    ///
+    /// This happens regardless of whether the structure file still exists, because Minect only
+    /// removes it after reading the acknowledgement. If the batch was already loaded, its
+    /// acknowledgement removes the replacement again and no [BatchExpired] event is sent.
+    pub(crate) fn expire(
+        &self,
+        backend: &dyn FileBackend,
+        structures_dir: &Path,
+        unacknowledged: Option<&UnacknowledgedBatches>,
+        warnings: &Warnings,
+    ) {
+        let now = Instant::now();
+        let mut batches = self.batches.lock().unwrap();
+        let expired = batches
+            .iter()
+            .filter(|(_, batch)| batch.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in expired {
+            let batch = batches.remove(&id).unwrap(); // Unwrap is safe because we just found the id
+            if let Some(unacknowledged) = unacknowledged {
+                unacknowledged.take(id);
+            }
+            let result = rewrite_structure_file(
+                backend,
+                structures_dir,
+                id,
+                &batch.replacement,
+                self.compression,
+            );
+            if let Err(error) = result {
+                warnings.report(MinectWarning::io_failed(error));
+            }
+        }
+    }
+}
+
+fn rewrite_structure_file(
+    backend: &dyn FileBackend,
+    structures_dir: &Path,
+    id: u64,
+    structure: &Structure,
+    compression: Compression,
+) -> Result<(), IoErrorAtPath> {
+    // Use a tmp file that is distinct from the one used by execute_commands to avoid racing it.
+    let tmp_path = structures_dir.join(format!("{}.tmp", format_structure_id(id)));
+    create_structure_file(backend, &tmp_path, structure, compression)?;
+    let path = structure_file(structures_dir, id);
+    backend
+        .rename(&tmp_path, &path)
+        .map_err(io_error("Failed to rename file", tmp_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        structure::StructureBuilder, structure_id::structure_file, utils::TempDir, LocalFileBackend,
+    };
+    use futures::executor::block_on;
+    use std::{fs::write, time::Duration};
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_expire() {
+        // given:
+        let structures_dir = TempDir::new("deadline");
+        write(structure_file(&structures_dir, 1), "original").unwrap();
+        write(structure_file(&structures_dir, 2), "original").unwrap();
+        let expiring_batches = ExpiringBatches::new(Compression::none());
+        let events = expiring_batches.add_listener();
+        let replacement = StructureBuilder::with_capacity(0).build();
+        expiring_batches.insert(1, Instant::now(), replacement.clone());
+        expiring_batches.insert(2, Instant::now(), replacement.clone());
+        expiring_batches.insert(3, Instant::now() + Duration::from_secs(60), replacement);
+
+        // when:
+        let warnings = Warnings::default();
+        expiring_batches.expire(&LocalFileBackend, &structures_dir, None, &warnings);
+        // Batch 1 was loaded before it was replaced, but it is acknowledged afterwards
+        expiring_batches.acknowledge(1);
+        // The replacement of batch 2 is loaded instead of the batch
+        expiring_batches.acknowledge(2);
+        expiring_batches.replaced(2);
+        drop(expiring_batches);
+
+        // then:
+        for id in [1, 2] {
+            let contents = std::fs::read(structure_file(&structures_dir, id)).unwrap();
+            assert_ne!(contents, b"original");
+        }
+        assert!(!structure_file(&structures_dir, 3).exists());
+        let actual = block_on(events.map(|event| event.id).collect::<Vec<_>>());
+        assert_eq!(actual, [2]);
+    }
+}
@@ -0,0 +1,214 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for command line tools whose users don't know where their worlds are saved.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! if let Some(world_dir) = minect::discovery::pick_world()? {
+//!     let connection = minect::MinecraftConnection::builder("example", world_dir).build();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    env::default_minecraft_dir,
+    io::{io_error, IoErrorAtPath},
+};
+use std::{
+    fs::read_dir,
+    io::{self, stdin, stdout, BufRead, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// A world in a `saves` directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SavedWorld {
+    /// The name of the world directory.
+    pub name: String,
+    /// The world directory.
+    pub dir: PathBuf,
+    /// The last modification of the `level.dat` file, which Minecraft writes when saving the world.
+    pub last_played: Option<SystemTime>,
+    _private: (),
+}
+
+/// Lists the worlds in `saves_dir`, most recently played first. Directories without a `level.dat`
+/// file are ignored.
+///
+/// # Errors
+///
+/// This function will return an error if `saves_dir` can't be read.
+pub fn list_worlds(saves_dir: impl AsRef<Path>) -> Result<Vec<SavedWorld>, IoErrorAtPath> {
+    let saves_dir = saves_dir.as_ref();
+    let entries = read_dir(saves_dir).map_err(io_error("Failed to read directory", saves_dir))?;
+    let mut worlds = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let level_dat = dir.join("level.dat");
+            if !level_dat.is_file() {
+                return None;
+            }
+            let last_played = level_dat
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            Some(SavedWorld {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                dir,
+                last_played,
+                _private: (),
+            })
+        })
+        .collect::<Vec<_>>();
+    worlds.sort_by(|a, b| b.last_played.cmp(&a.last_played).then(a.name.cmp(&b.name)));
+    Ok(worlds)
+}
+
+/// Lists the worlds in the `saves` directory of the
+/// [default_minecraft_dir](crate::default_minecraft_dir) on the terminal and lets the user choose
+/// one by entering its number. Returns the directory of the chosen world or [None] if the user
+/// entered nothing.
+///
+/// # Errors
+///
+/// This function will return an error if the default `.minecraft` directory can't be determined,
+/// the `saves` directory can't be read or an error occurs while reading from the terminal.
+pub fn pick_world() -> io::Result<Option<PathBuf>> {
+    let minecraft_dir = default_minecraft_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Failed to find the .minecraft directory",
+        )
+    })?;
+    pick_world_in(minecraft_dir.join("saves"))
+}
+
+/// Like [pick_world], but lists the worlds in `saves_dir`.
+///
+/// # Errors
+///
+/// This function will return an error if `saves_dir` can't be read or an error occurs while reading
+/// from the terminal.
+pub fn pick_world_in(saves_dir: impl AsRef<Path>) -> io::Result<Option<PathBuf>> {
+    let worlds = list_worlds(saves_dir)?;
+    choose(&worlds, stdin().lock(), stdout().lock(), SystemTime::now())
+}
+
+fn choose(
+    worlds: &[SavedWorld],
+    mut input: impl BufRead,
+    mut output: impl Write,
+    now: SystemTime,
+) -> io::Result<Option<PathBuf>> {
+    if worlds.is_empty() {
+        writeln!(output, "No worlds found")?;
+        return Ok(None);
+    }
+    for (index, world) in worlds.iter().enumerate() {
+        let last_played = world
+            .last_played
+            .and_then(|last_played| now.duration_since(last_played).ok())
+            .map(format_age)
+            .unwrap_or_else(|| "unknown".to_string());
+        writeln!(
+            output,
+            "{:>3}) {} (last played: {})",
+            index + 1,
+            world.name,
+            last_played
+        )?;
+    }
+    loop {
+        write!(output, "Choose a world [1-{}]: ", worlds.len())?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(None);
+        }
+        match line.trim().parse::<usize>() {
+            Ok(number) if (1..=worlds.len()).contains(&number) => {
+                return Ok(Some(worlds[number - 1].dir.clone()));
+            }
+            _ => writeln!(output, "Invalid choice: {}", line.trim())?,
+        }
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (value, unit) = if minutes < 60 {
+        (minutes, "minute")
+    } else if minutes < 60 * 24 {
+        (minutes / 60, "hour")
+    } else {
+        (minutes / (60 * 24), "day")
+    };
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{} {}{} ago", value, unit, plural)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(name: &str, last_played: Option<SystemTime>) -> SavedWorld {
+        SavedWorld {
+            name: name.to_string(),
+            dir: PathBuf::from("saves").join(name),
+            last_played,
+            _private: (),
+        }
+    }
+
+    #[test]
+    fn test_choose() {
+        // given:
+        let now = SystemTime::now();
+        let worlds = [
+            world("New World", Some(now - Duration::from_secs(2 * 60 * 60))),
+            world("Old World", None),
+        ];
+        let mut output = Vec::new();
+
+        // when:
+        let actual = choose(&worlds, "x\n2\n".as_bytes(), &mut output, now).unwrap();
+
+        // then:
+        assert_eq!(actual, Some(PathBuf::from("saves/Old World")));
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("1) New World (last played: 2 hours ago)"));
+        assert!(output.contains("2) Old World (last played: unknown)"));
+        assert!(output.contains("Invalid choice: x"));
+    }
+
+    #[test]
+    fn test_choose_without_input() {
+        // given:
+        let worlds = [world("New World", None)];
+
+        // when:
+        let actual = choose(&worlds, "".as_bytes(), Vec::new(), SystemTime::now()).unwrap();
+
+        // then:
+        assert_eq!(actual, None);
+    }
+}
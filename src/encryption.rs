@@ -0,0 +1,93 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Encryption of spooled batches, see
+//! [encrypt_spool](crate::MinecraftConnectionBuilder::encrypt_spool).
+//!
+//! An encrypted batch file starts with a magic line, followed by a random nonce and the
+//! ChaCha20-Poly1305 ciphertext of the plaintext batch.
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use std::fmt::{self, Debug};
+
+const MAGIC: &[u8] = b"minect-encrypted-v1\n";
+const NONCE_LEN: usize = 12;
+
+/// The key of an encrypted spool. It is never printed.
+#[derive(Clone)]
+pub(crate) struct SpoolKey(Key);
+
+impl Debug for SpoolKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SpoolKey(..)")
+    }
+}
+
+impl SpoolKey {
+    pub(crate) fn new(key: [u8; 32]) -> SpoolKey {
+        SpoolKey(key.into())
+    }
+
+    pub(crate) fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .expect("Encrypting into a Vec can't fail");
+        [MAGIC, nonce.as_slice(), &ciphertext].concat()
+    }
+
+    /// Returns [None] if `content` is not encrypted with this key or was modified.
+    pub(crate) fn decrypt(&self, content: &[u8]) -> Option<Vec<u8>> {
+        let content = content.strip_prefix(MAGIC)?;
+        if content.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = content.split_at(NONCE_LEN);
+        let cipher = ChaCha20Poly1305::new(&self.0);
+        cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+    }
+}
+
+pub(crate) fn is_encrypted(content: &[u8]) -> bool {
+    content.starts_with(MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        // given:
+        let key = SpoolKey::new([7; 32]);
+
+        // when:
+        let encrypted = key.encrypt(b"FunctionFile\n[null,\"say secret\"]");
+
+        // then:
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.windows(6).any(|window| window == b"secret"));
+        let decrypted = key.decrypt(&encrypted).unwrap();
+        assert_eq!(decrypted, b"FunctionFile\n[null,\"say secret\"]");
+        assert_eq!(SpoolKey::new([8; 32]).decrypt(&encrypted), None);
+    }
+}
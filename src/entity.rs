@@ -0,0 +1,86 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{command::DataGetEntityOutput, snbt::Snbt};
+
+/// Describes an entity in Minecraft, for example the connection entity of a
+/// [MinecraftConnection](crate::MinecraftConnection) that is obtained with
+/// [MinecraftConnection::connection_entity](crate::MinecraftConnection::connection_entity).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityInfo {
+    /// The UUID of the entity in the hyphenated hexadecimal format, which can be used as a target
+    /// selector in commands (for example `kill 0000002a-0000-0001-ffff-fffe00000003`).
+    pub uuid: String,
+    /// The position of the entity.
+    pub pos: (f64, f64, f64),
+    _private: (),
+}
+
+impl EntityInfo {
+    /// Parses the output of a `data get entity` command that queries all data of an entity.
+    pub(crate) fn parse(output: &str) -> Option<EntityInfo> {
+        let output = output.parse::<DataGetEntityOutput>().ok()?;
+        let data = output.data.parse::<Snbt>().ok()?;
+        let uuid = match data.get("UUID")? {
+            Snbt::IntArray(values) => format_uuid(values)?,
+            _ => return None,
+        };
+        let pos = match data.get("Pos")?.as_list()? {
+            [x, y, z] => (x.as_f64()?, y.as_f64()?, z.as_f64()?),
+            _ => return None,
+        };
+        Some(EntityInfo {
+            uuid,
+            pos,
+            _private: (),
+        })
+    }
+}
+
+fn format_uuid(values: &[i32]) -> Option<String> {
+    let [a, b, c, d] = <[i32; 4]>::try_from(values).ok()?.map(|value| value as u32);
+    Some(format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:04x}{:08x}",
+        a,
+        b >> 16,
+        b & 0xffff,
+        c >> 16,
+        c & 0xffff,
+        d
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        // given:
+        let output = "Marker has the following entity data: \
+            {Tags: [\"minect_connection\"], UUID: [I; 42, 1, -2, 3], Pos: [8.5d, 1.0d, -7.5d]}";
+
+        // when:
+        let actual = EntityInfo::parse(output);
+
+        // then:
+        let actual = actual.unwrap();
+        assert_eq!(actual.uuid, "0000002a-0000-0001-ffff-fffe00000003");
+        assert_eq!(actual.pos, (8.5, 1.0, -7.5));
+    }
+}
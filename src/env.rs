@@ -0,0 +1,173 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::MinecraftConnectionBuilder;
+use std::{
+    env::var_os,
+    ffi::OsString,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// The environment variable containing the world directory or the name of a world in the default
+/// saves directory.
+const WORLD_VARIABLE: &str = "MINECT_WORLD";
+
+/// The environment variable containing the path to the log file.
+const LOG_VARIABLE: &str = "MINECT_LOG";
+
+/// The error returned from [MinecraftConnectionBuilder::from_env].
+#[derive(Debug)]
+pub struct FromEnvError {
+    inner: FromEnvErrorInner,
+}
+#[derive(Debug)]
+enum FromEnvErrorInner {
+    MissingWorld,
+    MissingMinecraftDir(OsString),
+}
+impl Display for FromEnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            FromEnvErrorInner::MissingWorld => {
+                write!(f, "The environment variable {} is not set", WORLD_VARIABLE)
+            }
+            FromEnvErrorInner::MissingMinecraftDir(world) => write!(
+                f,
+                "Failed to find the .minecraft directory for the world {:?}",
+                world
+            ),
+        }
+    }
+}
+impl std::error::Error for FromEnvError {}
+
+impl MinecraftConnectionBuilder {
+    /// Creates a builder for the world selected by environment variables. This reduces boilerplate
+    /// in small utilities and CI scripts.
+    ///
+    /// * `MINECT_WORLD` is required and contains either the path to the world directory or the name
+    ///   of a world in the `saves` directory of the [default_minecraft_dir]. A plain name is only
+    ///   resolved in the `saves` directory if no directory with this name exists in the current
+    ///   working directory.
+    /// * `MINECT_LOG` optionally contains the path to the log file (see
+    ///   [log_file](Self::log_file)).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `identifier` contains an invalid character (see
+    /// [MinecraftConnection::builder](crate::MinecraftConnection::builder)).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `MINECT_WORLD` is not set or if it contains the name of
+    /// a world, but the default `.minecraft` directory can't be determined.
+    pub fn from_env(
+        identifier: impl Into<String>,
+    ) -> Result<MinecraftConnectionBuilder, FromEnvError> {
+        let world = var_os(WORLD_VARIABLE).ok_or(FromEnvError {
+            inner: FromEnvErrorInner::MissingWorld,
+        })?;
+        let world_dir = resolve_world_dir(world, default_minecraft_dir())?;
+        let builder = MinecraftConnectionBuilder::new(identifier, world_dir);
+        Ok(match var_os(LOG_VARIABLE) {
+            Some(log_file) => builder.log_file(log_file),
+            None => builder,
+        })
+    }
+}
+
+fn resolve_world_dir(
+    world: OsString,
+    minecraft_dir: Option<PathBuf>,
+) -> Result<PathBuf, FromEnvError> {
+    let path = Path::new(&world);
+    if path.components().count() > 1 || path.is_absolute() || path.is_dir() {
+        return Ok(PathBuf::from(world));
+    }
+    match minecraft_dir {
+        Some(minecraft_dir) => Ok(minecraft_dir.join("saves").join(world)),
+        None => Err(FromEnvError {
+            inner: FromEnvErrorInner::MissingMinecraftDir(world),
+        }),
+    }
+}
+
+/// Returns the default `.minecraft` directory of the Minecraft launcher on this platform, if the
+/// required environment variables are set:
+/// * Windows: `%APPDATA%\.minecraft`
+/// * macOS: `~/Library/Application Support/minecraft`
+/// * Other: `~/.minecraft`
+pub fn default_minecraft_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        var_os("APPDATA").map(|app_data| PathBuf::from(app_data).join(".minecraft"))
+    } else if cfg!(target_os = "macos") {
+        var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+                .join("minecraft")
+        })
+    } else {
+        var_os("HOME").map(|home| PathBuf::from(home).join(".minecraft"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_world_dir_with_name() {
+        // when:
+        let actual = resolve_world_dir("New World".into(), Some(PathBuf::from("/mc"))).unwrap();
+
+        // then:
+        assert_eq!(actual, PathBuf::from("/mc/saves/New World"));
+    }
+
+    #[test]
+    fn test_resolve_world_dir_with_path() {
+        // when:
+        let actual = resolve_world_dir("server/world".into(), None).unwrap();
+
+        // then:
+        assert_eq!(actual, PathBuf::from("server/world"));
+    }
+
+    #[test]
+    fn test_resolve_world_dir_with_existing_relative_dir() {
+        // given:
+        let dir = "src"; // Tests run in the crate root
+
+        // when:
+        let actual = resolve_world_dir(dir.into(), Some(PathBuf::from("/mc"))).unwrap();
+
+        // then:
+        assert_eq!(actual, PathBuf::from(dir));
+    }
+
+    #[test]
+    fn test_resolve_world_dir_without_minecraft_dir() {
+        // when:
+        let actual = resolve_world_dir("New World".into(), None);
+
+        // then:
+        assert!(actual.is_err());
+    }
+}
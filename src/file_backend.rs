@@ -0,0 +1,195 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! The [FileBackend] used to write the datapack and structure files. The default
+//! [LocalFileBackend] uses the local file system. With the `sftp` feature the files can also be
+//! written to a remote server via [SftpFileBackend], for example when the world directory of the
+//! server is not mounted locally.
+
+#[cfg(test)]
+mod memory;
+#[cfg(feature = "sftp")]
+mod sftp;
+
+#[cfg(test)]
+pub(crate) use self::memory::MemoryFileBackend;
+#[cfg(feature = "sftp")]
+pub use self::sftp::SftpFileBackend;
+
+use fs3::FileExt;
+use std::{
+    fmt::Debug,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// The file system operations that Minect needs to write the datapack, function files and structure
+/// files. Every file that a [MinecraftConnection](crate::MinecraftConnection) writes or removes in
+/// the world directory goes through its backend. Only the log file is read from the local file
+/// system.
+pub trait FileBackend: Debug + Send + Sync {
+    /// Reads the entire contents of the file at `path`.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Creates or truncates the file at `path` and writes `contents` to it. The parent directory
+    /// must exist.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Renames the file at `from` to `to`, replacing `to` if it exists. This should be atomic,
+    /// because Minecraft may read `to` at any time.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Removes the file at `path`.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes the empty directory at `path`.
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes the directory at `path` with all of its contents.
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns the paths of all entries of the directory at `path`.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Creates the directory at `path` and all of its missing parents.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Returns `true` if `path` is an existing file.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Returns `true` if `path` is an existing directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Sets the permission bits of the file or directory at `path`.
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()>;
+
+    /// Changes the owner and group of the file or directory at `path`.
+    fn set_owner(&self, path: &Path, owner: Option<u32>, group: Option<u32>) -> io::Result<()> {
+        let _ = (path, owner, group);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "changing the owner is not supported",
+        ))
+    }
+
+    /// Opens the file at `path`, creating it if it does not exist, and locks it for exclusive
+    /// access by this process until the returned [LockedFile] is dropped. Backends that can't lock
+    /// files should document that only a single process may use a connection.
+    fn lock<'a>(&'a self, path: &Path) -> io::Result<Box<dyn LockedFile + 'a>>;
+}
+
+/// A file returned from [FileBackend::lock].
+pub trait LockedFile {
+    /// Reads the entire contents of the file.
+    fn read_contents(&mut self) -> io::Result<String>;
+
+    /// Replaces the contents of the file with `contents`.
+    fn write_contents(&mut self, contents: &[u8]) -> io::Result<()>;
+}
+
+/// A [FileBackend] that uses the local file system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LocalFileBackend;
+
+impl FileBackend for LocalFileBackend {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    #[cfg(unix)]
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+
+    #[cfg(not(unix))]
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_readonly(mode & 0o200 == 0);
+        fs::set_permissions(path, permissions)
+    }
+
+    #[cfg(unix)]
+    fn set_owner(&self, path: &Path, owner: Option<u32>, group: Option<u32>) -> io::Result<()> {
+        std::os::unix::fs::chown(path, owner, group)
+    }
+
+    fn lock<'a>(&'a self, path: &Path) -> io::Result<Box<dyn LockedFile + 'a>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+        file.lock_exclusive()?;
+        Ok(Box::new(file))
+    }
+}
+
+impl LockedFile for File {
+    fn read_contents(&mut self) -> io::Result<String> {
+        let mut content = String::new();
+        self.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    fn write_contents(&mut self, contents: &[u8]) -> io::Result<()> {
+        self.set_len(0)?;
+        self.seek(SeekFrom::Start(0))?;
+        self.write_all(contents)
+    }
+}
@@ -0,0 +1,156 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::{FileBackend, LockedFile};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A [FileBackend] that keeps all files in memory. Tests use it to check that files are written
+/// through the backend instead of the local file system.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryFileBackend {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    dirs: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl MemoryFileBackend {
+    pub(crate) fn read_string(&self, path: impl AsRef<Path>) -> Option<String> {
+        let files = self.files.lock().unwrap();
+        let contents = files.get(path.as_ref())?;
+        Some(String::from_utf8(contents.clone()).unwrap())
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, path.display().to_string())
+}
+
+impl FileBackend for MemoryFileBackend {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let files = self.files.lock().unwrap();
+        files.get(path).cloned().ok_or_else(|| not_found(path))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            if !self.is_dir(parent) {
+                return Err(not_found(parent));
+            }
+        }
+        let mut files = self.files.lock().unwrap();
+        files.insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        let contents = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), contents);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        files.remove(path).map(drop).ok_or_else(|| not_found(path))
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        if !self.read_dir(path)?.is_empty() {
+            return Err(io::Error::other("directory not empty"));
+        }
+        self.dirs.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        if !self.is_dir(path) {
+            return Err(not_found(path));
+        }
+        self.files
+            .lock()
+            .unwrap()
+            .retain(|file, _| !file.starts_with(path));
+        self.dirs
+            .lock()
+            .unwrap()
+            .retain(|dir| !dir.starts_with(path));
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        if !self.is_dir(path) {
+            return Err(not_found(path));
+        }
+        let files = self.files.lock().unwrap();
+        let dirs = self.dirs.lock().unwrap();
+        Ok(files
+            .keys()
+            .chain(dirs.iter())
+            .filter(|entry| entry.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut dirs = self.dirs.lock().unwrap();
+        dirs.extend(path.ancestors().map(Path::to_path_buf));
+        Ok(())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.dirs.lock().unwrap().contains(path)
+    }
+
+    fn set_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn lock<'a>(&'a self, path: &Path) -> io::Result<Box<dyn LockedFile + 'a>> {
+        if !self.is_file(path) {
+            self.write(path, &[])?;
+        }
+        Ok(Box::new(MemoryLockedFile {
+            backend: self,
+            path: path.to_path_buf(),
+        }))
+    }
+}
+
+struct MemoryLockedFile<'a> {
+    backend: &'a MemoryFileBackend,
+    path: PathBuf,
+}
+
+impl LockedFile for MemoryLockedFile<'_> {
+    fn read_contents(&mut self) -> io::Result<String> {
+        let contents = self.backend.read(&self.path)?;
+        String::from_utf8(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write_contents(&mut self, contents: &[u8]) -> io::Result<()> {
+        self.backend.write(&self.path, contents)
+    }
+}
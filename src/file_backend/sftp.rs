@@ -0,0 +1,267 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::{FileBackend, LockedFile};
+use crate::utils::shell_quote;
+use ssh2::{FileStat, OpenFlags, OpenType, RenameFlags, Session, Sftp};
+use std::{
+    fmt::{self, Debug},
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// How long to wait before trying again to acquire a lock that is held by another process.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+/// Lock files older than this were left behind by a process that crashed while holding the lock.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// A [FileBackend] that accesses the file system of a remote server via SFTP.
+///
+/// SFTP does not support locking files, so [lock](FileBackend::lock) exclusively creates a lock file
+/// next to the locked file (`<file>.lock`) and removes it again when the lock is released. A lock
+/// file that is older than a minute is considered stale and removed. Renaming a file over an
+/// existing file executes `mv -f` via SSH if the server does not support atomic renames via SFTP,
+/// so the user must be allowed to execute commands.
+pub struct SftpFileBackend {
+    session: Session,
+    sftp: Sftp,
+}
+
+impl SftpFileBackend {
+    /// Connects to the SSH server at `addr` and authenticates as `username` using the keys of the
+    /// running SSH agent.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the server can't be reached, authentication fails or
+    /// the server doesn't support SFTP.
+    pub fn connect(addr: impl ToSocketAddrs, username: &str) -> io::Result<SftpFileBackend> {
+        let mut session = Session::new()?;
+        session.set_tcp_stream(TcpStream::connect(addr)?);
+        session.handshake()?;
+        session.userauth_agent(username)?;
+        SftpFileBackend::new(session)
+    }
+
+    /// Creates a backend for an already authenticated SSH `session`. This allows using any
+    /// authentication method supported by [ssh2].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the server doesn't support SFTP.
+    pub fn new(session: Session) -> io::Result<SftpFileBackend> {
+        let sftp = session.sftp()?;
+        Ok(SftpFileBackend { session, sftp })
+    }
+
+    /// The SSH session used by this backend.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    fn stat(&self, path: &Path) -> Option<FileStat> {
+        self.sftp.stat(path).ok()
+    }
+}
+
+impl Debug for SftpFileBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SftpFileBackend").finish_non_exhaustive()
+    }
+}
+
+impl FileBackend for SftpFileBackend {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        self.sftp.open(path)?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.sftp.create(path)?.write_all(contents)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let flags = RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE;
+        match self.sftp.rename(from, to, Some(flags)) {
+            Ok(()) => Ok(()),
+            // SFTP version 3, which is used by most servers, fails if the target exists. Removing
+            // the target first would not be atomic, so the file is renamed by the server itself.
+            Err(_) if self.is_file(to) => self.exec(&format!(
+                "mv -f -- {} {}",
+                shell_quote(&from.to_string_lossy()),
+                shell_quote(&to.to_string_lossy())
+            )),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        Ok(self.sftp.unlink(path)?)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        Ok(self.sftp.rmdir(path)?)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        for (entry, stat) in self.sftp.readdir(path)? {
+            if stat.is_dir() {
+                self.remove_dir_all(&entry)?;
+            } else {
+                self.sftp.unlink(&entry)?;
+            }
+        }
+        Ok(self.sftp.rmdir(path)?)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let entries = self.sftp.readdir(path)?;
+        Ok(entries.into_iter().map(|(entry, _)| entry).collect())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let missing = path
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !self.is_dir(dir))
+            .collect::<Vec<_>>();
+        for dir in missing.into_iter().rev() {
+            self.sftp.mkdir(dir, 0o755)?;
+        }
+        Ok(())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.stat(path).is_some_and(|stat| stat.is_file())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.stat(path).is_some_and(|stat| stat.is_dir())
+    }
+
+    fn set_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        let stat = FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode),
+            atime: None,
+            mtime: None,
+        };
+        Ok(self.sftp.setstat(path, stat)?)
+    }
+
+    fn set_owner(&self, path: &Path, owner: Option<u32>, group: Option<u32>) -> io::Result<()> {
+        let stat = self.sftp.stat(path)?;
+        let stat = FileStat {
+            size: None,
+            uid: owner.or(stat.uid),
+            gid: group.or(stat.gid),
+            perm: None,
+            atime: None,
+            mtime: None,
+        };
+        Ok(self.sftp.setstat(path, stat)?)
+    }
+
+    fn lock<'a>(&'a self, path: &Path) -> io::Result<Box<dyn LockedFile + 'a>> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let lock_path = PathBuf::from(lock_path);
+        let flags = OpenFlags::WRITE | OpenFlags::EXCLUSIVE;
+        loop {
+            match self
+                .sftp
+                .open_mode(&lock_path, flags, 0o644, OpenType::File)
+            {
+                Ok(_) => break,
+                Err(e) => match self.stat(&lock_path) {
+                    Some(stat) if is_stale(&stat) => {
+                        let _ = self.sftp.unlink(&lock_path);
+                    }
+                    Some(_) => sleep(LOCK_RETRY_INTERVAL),
+                    None => return Err(e.into()),
+                },
+            }
+        }
+        Ok(Box::new(LockedSftpFile {
+            backend: self,
+            path: path.to_path_buf(),
+            lock_path,
+        }))
+    }
+}
+
+impl SftpFileBackend {
+    /// Executes `command` on the server and fails if it exits with a non-zero status.
+    fn exec(&self, command: &str) -> io::Result<()> {
+        let mut channel = self.session.channel_session()?;
+        channel.exec(command)?;
+        let mut stderr = String::new();
+        channel.stderr().read_to_string(&mut stderr)?;
+        channel.wait_close()?;
+        match channel.exit_status()? {
+            0 => Ok(()),
+            status => Err(io::Error::other(format!(
+                "{} exited with status {}: {}",
+                command,
+                status,
+                stderr.trim()
+            ))),
+        }
+    }
+}
+
+fn is_stale(stat: &FileStat) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    stat.mtime
+        .is_some_and(|mtime| now.saturating_sub(mtime) > STALE_LOCK_AGE.as_secs())
+}
+
+struct LockedSftpFile<'a> {
+    backend: &'a SftpFileBackend,
+    path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl Drop for LockedSftpFile<'_> {
+    fn drop(&mut self) {
+        let _ = self.backend.sftp.unlink(&self.lock_path);
+    }
+}
+
+impl LockedFile for LockedSftpFile<'_> {
+    fn read_contents(&mut self) -> io::Result<String> {
+        match self.backend.read(&self.path) {
+            Ok(contents) => String::from_utf8(contents)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_contents(&mut self, contents: &[u8]) -> io::Result<()> {
+        self.backend.write(&self.path, contents)
+    }
+}
@@ -0,0 +1,246 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Writes batches of commands to function files for
+//! [ExecutionStrategy::FunctionFile](crate::ExecutionStrategy::FunctionFile).
+//!
+//! The commands of a batch are written to `run.mcfunction` and a `guard.mcfunction` is added to the
+//! function tag `#minect_internal:batch`, which is executed after every `reload`. The structure of
+//! the batch tags the connection entity and executes `reload`, so the guard only executes the batch
+//! once, even if a player reloads the datapacks before the batch was removed. The batch ends with a
+//! logged block that is used to remove the function files again.
+
+use crate::{
+    command::{named_logged_block_commands, summon_named_entity_command, SummonNamedEntityOutput},
+    connect::{add_to_function_tag, remove_from_function_tag},
+    datapack_layout::DatapackLayout,
+    file_backend::FileBackend,
+    io::{io_error, write_with_permissions, FilePermissions, IoErrorAtPath},
+    log::LogEvent,
+    names, Command,
+};
+use std::path::{Path, PathBuf};
+
+const BATCH_EXECUTED_OUTPUT_PREFIX: &str = "minect_batch_";
+pub(crate) const BATCH_TAG: &str = "data/minect_internal/tags/functions/batch.json";
+
+/// Returns the lines of the function file that executes the `commands` of the batch with the given
+/// structure id.
+pub(crate) fn batch_function_lines(
+    identifier: &str,
+    structure_id: u64,
+    commands: Vec<Command>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for command in commands {
+        if let Some(name) = command.get_name() {
+            lines.extend(named_logged_block_commands(name, command.get_command()));
+        } else {
+            lines.push(command.get_command().to_string());
+        }
+    }
+    lines.extend(named_logged_block_commands(
+        names::BATCH,
+        summon_named_entity_command(&format!(
+            "{}{}+{}",
+            BATCH_EXECUTED_OUTPUT_PREFIX, identifier, structure_id
+        )),
+    ));
+    lines
+}
+
+/// Writes the function files for the batch with the given structure id and returns the commands
+/// that have to be executed by the structure instead of the function `lines`.
+pub(crate) fn write_batch_function(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+    structure_id: u64,
+    lines: &[String],
+    permissions: &FilePermissions,
+) -> Result<Vec<Command>, IoErrorAtPath> {
+    let layout = DatapackLayout::detect(backend, datapack_dir);
+    let batch_dir = get_batch_dir(layout, datapack_dir, identifier, structure_id);
+    write_with_permissions(
+        backend,
+        batch_dir.join("run.mcfunction"),
+        lines.join("\n"),
+        permissions,
+    )?;
+    let guard = include_datapack_template!(
+        "data/minect_internal/functions/connection/-connection_id-/batch/-structure_id-/guard.mcfunction"
+    );
+    write_with_permissions(
+        backend,
+        batch_dir.join("guard.mcfunction"),
+        expand_template(guard, identifier, structure_id),
+        permissions,
+    )?;
+    add_to_function_tag(
+        backend,
+        layout.join(datapack_dir, BATCH_TAG),
+        &function_tag_template(identifier, structure_id),
+    )?;
+
+    Ok(Vec::from_iter([
+        Command::new(format!(
+            "tag @e[type=area_effect_cloud,tag=minect_connection+{}] add minect_batch+{}",
+            identifier, structure_id
+        )),
+        Command::new("reload"),
+    ]))
+}
+
+/// Removes the function files of the batch with the given structure id.
+pub(crate) fn remove_batch_function(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+    structure_id: u64,
+) -> Result<(), IoErrorAtPath> {
+    let layout = DatapackLayout::detect(backend, datapack_dir);
+    remove_from_function_tag(
+        backend,
+        layout.join(datapack_dir, BATCH_TAG),
+        &function_tag_template(identifier, structure_id),
+    )?;
+    let batch_dir = get_batch_dir(layout, datapack_dir, identifier, structure_id);
+    backend
+        .remove_dir_all(&batch_dir)
+        .map_err(io_error("Failed to remove directory", batch_dir))
+}
+
+/// Returns the structure id of the batch if the event signals that the batch of the connection with
+/// the given identifier was executed.
+pub(crate) fn parse_batch_executed_output(event: &LogEvent, identifier: &str) -> Option<u64> {
+    if event.executor != names::BATCH {
+        return None;
+    }
+    let output = event.output.parse::<SummonNamedEntityOutput>().ok()?;
+    let batch = output.name.strip_prefix(BATCH_EXECUTED_OUTPUT_PREFIX)?;
+    let (batch_identifier, id) = batch.rsplit_once('+')?;
+    if batch_identifier != identifier {
+        return None;
+    }
+    id.parse().ok()
+}
+
+fn get_batch_dir(
+    layout: DatapackLayout,
+    datapack_dir: &Path,
+    identifier: &str,
+    structure_id: u64,
+) -> PathBuf {
+    layout
+        .join(datapack_dir, "data/minect_internal/functions/connection")
+        .join(identifier)
+        .join("batch")
+        .join(structure_id.to_string())
+}
+
+fn function_tag_template(identifier: &str, structure_id: u64) -> String {
+    expand_template(
+        r#"{"values":["minect_internal:connection/-connection_id-/batch/-structure_id-/guard"]}"#,
+        identifier,
+        structure_id,
+    )
+}
+
+fn expand_template(template: &str, identifier: &str, structure_id: u64) -> String {
+    template
+        .replace("-connection_id-", identifier)
+        .replace("-structure_id-", &structure_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_backend::MemoryFileBackend;
+
+    const DATAPACK_DIR: &str = "/remote/world/datapacks/minect";
+
+    fn write_example_batch(backend: &MemoryFileBackend) {
+        let lines = batch_function_lines("example", 7, vec![Command::new("say hi")]);
+        let permissions = FilePermissions::default();
+        write_batch_function(
+            backend,
+            DATAPACK_DIR.as_ref(),
+            "example",
+            7,
+            &lines,
+            &permissions,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_write_batch_function_uses_backend() {
+        // given:
+        let backend = MemoryFileBackend::default();
+        let datapack_dir = Path::new(DATAPACK_DIR);
+
+        // when:
+        write_example_batch(&backend);
+
+        // then:
+        let batch_dir =
+            datapack_dir.join("data/minect_internal/functions/connection/example/batch/7");
+        let run = backend
+            .read_string(batch_dir.join("run.mcfunction"))
+            .unwrap();
+        assert!(run.starts_with("say hi\n"));
+        assert!(backend.is_file(&batch_dir.join("guard.mcfunction")));
+        let tag = backend.read_string(datapack_dir.join(BATCH_TAG)).unwrap();
+        assert!(tag.contains("minect_internal:connection/example/batch/7/guard"));
+        assert!(!datapack_dir.exists());
+    }
+
+    #[test]
+    fn test_remove_batch_function_uses_backend() {
+        // given:
+        let backend = MemoryFileBackend::default();
+        let datapack_dir = Path::new(DATAPACK_DIR);
+        write_example_batch(&backend);
+
+        // when:
+        remove_batch_function(&backend, datapack_dir, "example", 7).unwrap();
+
+        // then:
+        let batch_dir =
+            datapack_dir.join("data/minect_internal/functions/connection/example/batch/7");
+        assert!(!backend.is_dir(&batch_dir));
+        let tag = backend.read_string(datapack_dir.join(BATCH_TAG)).unwrap();
+        assert!(!tag.contains("minect_internal:connection/example/batch/7/guard"));
+    }
+
+    #[test]
+    fn test_parse_batch_executed_output() {
+        // given:
+        let string =
+            "[21:05:40] [Server thread/INFO]: [minect_batch: Summoned new minect_batch_my+id+42]";
+        let event = string.parse::<LogEvent>().unwrap();
+
+        // when:
+        let actual = parse_batch_executed_output(&event, "my+id");
+        let other = parse_batch_executed_output(&event, "id");
+
+        // then:
+        assert_eq!(actual, Some(42));
+        assert_eq!(other, None);
+    }
+}
@@ -16,6 +16,11 @@
 // You should have received a copy of the GNU General Public License along with Minect.
 // If not, see <http://www.gnu.org/licenses/>.
 
+//! Axes and directions in three dimensions, as used by Minect to place command blocks in
+//! structures. Positions are represented by [BlockPos].
+//!
+//! Minecraft's axes are: X grows towards the east, Y grows upwards and Z grows towards the south.
+
 #![allow(dead_code)]
 
 use std::{
@@ -23,14 +28,17 @@ use std::{
     convert::TryFrom,
     fmt::{self, Display},
     ops::{Add, AddAssign, Index, IndexMut, Neg, Not, Rem, Sub},
+    str::FromStr,
 };
 
+use crate::BlockPos;
 use num_traits::Signed;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) struct Coordinate3<T>(pub(crate) T, pub(crate) T, pub(crate) T);
 
 impl<T> Coordinate3<T> {
+    /// Applies `f` to each component.
     pub(crate) fn map<F, R>(self, f: F) -> Coordinate3<R>
     where
         F: Fn(T) -> R,
@@ -38,6 +46,7 @@ impl<T> Coordinate3<T> {
         Coordinate3(f(self.0), f(self.1), f(self.2))
     }
 
+    /// Combines the components of `self` and `other` on the same axis with `f`.
     pub(crate) fn zip<F, U, R>(self, other: Coordinate3<U>, f: F) -> Coordinate3<R>
     where
         F: Fn(T, U) -> R,
@@ -59,6 +68,8 @@ impl<T: Ord> Coordinate3<T> {
 }
 
 impl<T: Clone + Neg<Output = T>> Coordinate3<T> {
+    /// The component on the [axis](Direction3::axis) of `direction`, inverted if the direction is
+    /// negative.
     pub(crate) fn get_in_direction(&self, direction: Direction3) -> T {
         let raw = self[direction.axis()].clone();
         if direction.is_negative() {
@@ -135,15 +146,33 @@ impl<T> From<Coordinate3<T>> for Vec<T> {
     }
 }
 
+impl<T> From<(T, T, T)> for Coordinate3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Coordinate3(x, y, z)
+    }
+}
+
+impl<T> From<Coordinate3<T>> for (T, T, T) {
+    fn from(c: Coordinate3<T>) -> Self {
+        (c.0, c.1, c.2)
+    }
+}
+
+/// One of the three axes.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub(crate) enum Axis3 {
+pub enum Axis3 {
+    /// The east-west axis.
     X,
+    /// The vertical axis.
     Y,
+    /// The south-north axis.
     Z,
 }
 
+/// One of the six directions along an [Axis3]. The names match the block states of Minecraft, for
+/// example the `facing` of a command block.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-pub(crate) enum Direction3 {
+pub enum Direction3 {
     /// +X
     East,
     /// -X
@@ -159,7 +188,18 @@ pub(crate) enum Direction3 {
 }
 
 impl Direction3 {
-    pub(crate) fn axis(&self) -> Axis3 {
+    /// All six directions.
+    pub const ALL: [Direction3; 6] = [
+        Direction3::East,
+        Direction3::West,
+        Direction3::Up,
+        Direction3::Down,
+        Direction3::South,
+        Direction3::North,
+    ];
+
+    /// The [Axis3] this direction is on.
+    pub fn axis(&self) -> Axis3 {
         match self {
             Direction3::East => Axis3::X,
             Direction3::West => Axis3::X,
@@ -170,7 +210,8 @@ impl Direction3 {
         }
     }
 
-    pub(crate) fn is_positive(&self) -> bool {
+    /// Whether this direction points towards growing coordinates.
+    pub fn is_positive(&self) -> bool {
         match self {
             Direction3::East => true,
             Direction3::West => false,
@@ -181,10 +222,12 @@ impl Direction3 {
         }
     }
 
-    pub(crate) fn is_negative(&self) -> bool {
+    /// Whether this direction points towards shrinking coordinates.
+    pub fn is_negative(&self) -> bool {
         !self.is_positive()
     }
 
+    /// `1` for a [positive](Self::is_positive) direction and `-1` otherwise.
     pub(crate) fn signum(&self) -> i8 {
         if self.is_positive() {
             1
@@ -193,7 +236,8 @@ impl Direction3 {
         }
     }
 
-    pub(crate) fn as_str(&self) -> &'static str {
+    /// The lower case name of this direction as used by Minecraft, for example `"east"`.
+    pub fn as_str(&self) -> &'static str {
         match self {
             Direction3::East => "east",
             Direction3::West => "west",
@@ -204,6 +248,12 @@ impl Direction3 {
         }
     }
 
+    /// The unit vector of this direction, for example `(1, 0, 0)` for [Direction3::East].
+    pub fn unit_vector(&self) -> BlockPos {
+        self.as_coordinate(1, 0).into()
+    }
+
+    /// The unit vector of this direction, built from the given values for `one` and `zero`.
     pub(crate) fn as_coordinate<N>(&self, one: N, zero: N) -> Coordinate3<N>
     where
         N: Clone + Neg<Output = N>,
@@ -225,6 +275,18 @@ impl Display for Direction3 {
     }
 }
 
+impl FromStr for Direction3 {
+    type Err = ();
+
+    /// Parses the lower case name of a direction, for example `"east"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Direction3::ALL
+            .into_iter()
+            .find(|direction| direction.as_str() == s)
+            .ok_or(())
+    }
+}
+
 impl Neg for Direction3 {
     type Output = Direction3;
 
@@ -240,6 +302,16 @@ impl Neg for Direction3 {
     }
 }
 
+impl TryFrom<BlockPos> for Direction3 {
+    type Error = ();
+
+    /// Finds the [Direction3] whose [unit vector](Direction3::unit_vector) points the same way as
+    /// `value`. Fails if `value` is not on exactly one axis.
+    fn try_from(value: BlockPos) -> Result<Self, Self::Error> {
+        Direction3::try_from(Coordinate3::from(value))
+    }
+}
+
 impl<S: Signed> TryFrom<Coordinate3<S>> for Direction3 {
     type Error = ();
 
@@ -279,7 +351,7 @@ impl<S: Signed> TryFrom<Coordinate3<S>> for Direction3 {
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[cfg_attr(test, derive(strum::EnumIter))]
-/// An [Orientation3] represents one of the 48 ways you can allign a structure to a grid in three
+/// An [Orientation3] represents one of the 48 ways you can align a structure to a grid in three
 /// dimensions via rotation and mirroring. It consists of a
 /// [primary](Orientation3::direction1()), a [secondary](Orientation3::direction2())
 /// and a [tertiary](Orientation3::direction3()) [direction](Direction3) which are all
@@ -305,7 +377,7 @@ impl<S: Signed> TryFrom<Coordinate3<S>> for Direction3 {
 /// | **x** | 0 |  0 | -1 |
 /// | **y** | 0 | -1 |  0 |
 /// | **z** | 1 |  0 |  0 |
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub(crate) enum Orientation3 {
     XYZ,
     XYz,
@@ -366,30 +438,26 @@ impl Orientation3 {
     /// For example orienting the coordinate (x: 1, y: 2, z: 3) to [Orientation3::YzX] results in
     /// (y: 1, z: -2, x: 3) or short (3, 1, -2).
     ///
-    /// Some orientations are their own reverse, bot others are not. You can undo by orienting to
+    /// Some orientations are their own reverse, but others are not. You can undo by orienting to
     /// the [inverse](Orientation3::inverse) orientation.
-    // TODO consume to avoid clone
-    pub(crate) fn orient_coordinate<T: Clone + Neg<Output = T>>(
+    pub(crate) fn orient_coordinate<T: Neg<Output = T>>(
         &self,
         coordinate: Coordinate3<T>,
     ) -> Coordinate3<T> {
-        let mut result = coordinate.clone();
-        let Coordinate3(mut t1, mut t2, mut t3) = coordinate;
-        if self.direction1().is_negative() {
-            t1 = -t1;
-        }
-        if self.direction2().is_negative() {
-            t2 = -t2;
+        let Coordinate3(t1, t2, t3) = coordinate;
+        let mut result = Coordinate3(None, None, None);
+        for (direction, t) in [
+            (self.direction1(), t1),
+            (self.direction2(), t2),
+            (self.direction3(), t3),
+        ] {
+            result[direction.axis()] = Some(if direction.is_negative() { -t } else { t });
         }
-        if self.direction3().is_negative() {
-            t3 = -t3;
-        }
-        result[self.direction1().axis()] = t1;
-        result[self.direction2().axis()] = t2;
-        result[self.direction3().axis()] = t3;
-        result
+        result.map(|t| t.expect("Orientation3 has a direction on every axis"))
     }
 
+    /// Orient a [Direction3] according to this [Orientation3], like
+    /// [orient_coordinate](Self::orient_coordinate) does for the unit vector of the direction.
     pub(crate) fn orient_direction(&self, direction: Direction3) -> Direction3 {
         let target_direction = match direction.axis() {
             Axis3::X => self.direction1(),
@@ -631,9 +699,31 @@ impl Not for Orientation3 {
 
 #[cfg(test)]
 mod tests {
-    use crate::geometry3::{Coordinate3, Orientation3};
+    use crate::{
+        geometry::{Coordinate3, Direction3, Orientation3},
+        BlockPos,
+    };
+    use std::convert::TryFrom;
     use strum::IntoEnumIterator;
 
+    #[test]
+    fn test_unit_vector() {
+        for direction in Direction3::ALL {
+            // when:
+            let unit_vector = direction.unit_vector();
+
+            // then:
+            assert_eq!(Direction3::try_from(unit_vector), Ok(direction));
+            assert_eq!((-direction).unit_vector(), -unit_vector);
+        }
+    }
+
+    #[test]
+    fn test_try_from_block_pos_rejects_diagonals() {
+        assert_eq!(Direction3::try_from(BlockPos::new(1, 0, -1)), Err(()));
+        assert_eq!(Direction3::try_from(BlockPos::new(0, 0, 0)), Err(()));
+    }
+
     #[test]
     fn test_inverse() {
         // given:
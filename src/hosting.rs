@@ -0,0 +1,185 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{file_backend::FileBackend, log::LogFormat};
+use std::path::{Path, PathBuf};
+
+/// How the files of a Minecraft installation are arranged around the world directory. This
+/// determines where the log file is located and which world directory the Minect datapack has to
+/// be installed in. The layout is also validated by
+/// [connect](crate::MinecraftConnection::connect), so a misconfigured path is reported instead of
+/// waiting forever.
+///
+/// Set it with [hosting_layout](crate::MinecraftConnectionBuilder::hosting_layout).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HostingLayout {
+    /// A single player world in `.minecraft/saves`. The log file is `.minecraft/logs/latest.log`.
+    #[default]
+    Singleplayer,
+    /// A vanilla server. The world directory is in the server directory, which contains
+    /// `server.properties` and `logs/latest.log`.
+    Server,
+    /// A Spigot, Paper or other Bukkit based server. This is like [Server](Self::Server), but the
+    /// server directory also contains a `plugins` directory and the log is parsed with
+    /// [LogFormat::Paper].
+    ///
+    /// Bukkit stores the nether and the end in separate world directories like `world_nether`, but
+    /// loads datapacks only from the main world. So a `world_dir` of a dimension is mapped to the
+    /// main world.
+    Bukkit,
+    /// A hosted server where only the world directory is accessible, for example via FTP, while the
+    /// log file is available somewhere else, for example in a synchronized directory or via a
+    /// [remote_log_source](crate::MinecraftConnectionBuilder::remote_log_source).
+    Hosted {
+        /// The path of the log file.
+        log_file: PathBuf,
+    },
+}
+
+const DIMENSION_SUFFIXES: [&str; 2] = ["_nether", "_the_end"];
+
+impl HostingLayout {
+    /// The world directory that contains the datapacks for the given `world_dir`.
+    pub fn world_dir(&self, world_dir: &Path) -> PathBuf {
+        if *self == HostingLayout::Bukkit {
+            if let Some(name) = world_dir.file_name().and_then(|name| name.to_str()) {
+                for suffix in DIMENSION_SUFFIXES {
+                    if let Some(main_world) = name.strip_suffix(suffix) {
+                        return world_dir.with_file_name(main_world);
+                    }
+                }
+            }
+        }
+        world_dir.to_path_buf()
+    }
+
+    /// The directory that contains the datapacks of the world in `world_dir`.
+    pub fn datapacks_dir(&self, world_dir: &Path) -> PathBuf {
+        self.world_dir(world_dir).join("datapacks")
+    }
+
+    /// The log file of the Minecraft instance that runs the world in `world_dir`. Returns [None] if
+    /// `world_dir` has too few parent directories for this layout.
+    pub fn log_file(&self, world_dir: &Path) -> Option<PathBuf> {
+        match self {
+            HostingLayout::Singleplayer => {
+                let minecraft_dir = world_dir.parent()?.parent()?;
+                Some(minecraft_dir.join("logs").join("latest.log"))
+            }
+            HostingLayout::Server | HostingLayout::Bukkit => {
+                let server_dir = self.world_dir(world_dir).parent()?.to_path_buf();
+                Some(server_dir.join("logs").join("latest.log"))
+            }
+            HostingLayout::Hosted { log_file } => Some(log_file.clone()),
+        }
+    }
+
+    /// The [LogFormat] of the log file.
+    pub fn log_format(&self) -> LogFormat {
+        match self {
+            HostingLayout::Bukkit => LogFormat::Paper,
+            _ => LogFormat::Vanilla,
+        }
+    }
+
+    /// Checks that the directories around the existing `world_dir` match this layout. Returns a
+    /// message describing the mismatch otherwise.
+    pub(crate) fn validate(
+        &self,
+        world_dir: &Path,
+        backend: &dyn FileBackend,
+    ) -> Result<(), String> {
+        let parent = world_dir.parent().unwrap_or(world_dir);
+        let expect = |condition: bool, expected: &str| {
+            if condition {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Expected {} for {:?}, but world_dir was: {}",
+                    expected,
+                    self,
+                    world_dir.display()
+                ))
+            }
+        };
+        match self {
+            HostingLayout::Singleplayer => expect(
+                parent.file_name().is_some_and(|name| name == "saves"),
+                "the world directory to be in .minecraft/saves",
+            ),
+            HostingLayout::Server => expect(
+                backend.is_file(&parent.join("server.properties")),
+                "server.properties next to the world directory",
+            ),
+            HostingLayout::Bukkit => {
+                expect(
+                    backend.is_file(&parent.join("server.properties")),
+                    "server.properties next to the world directory",
+                )?;
+                expect(
+                    backend.is_dir(&parent.join("plugins")),
+                    "a plugins directory next to the world directory",
+                )
+            }
+            HostingLayout::Hosted { .. } => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+
+    #[test]
+    fn test_bukkit_maps_dimensions_to_main_world() {
+        // given:
+        let world_dir = Path::new("server").join("world_nether");
+
+        // when:
+        let datapacks_dir = HostingLayout::Bukkit.datapacks_dir(&world_dir);
+        let log_file = HostingLayout::Bukkit.log_file(&world_dir);
+
+        // then:
+        let server_dir = Path::new("server");
+        assert_eq!(datapacks_dir, server_dir.join("world").join("datapacks"));
+        assert_eq!(log_file, Some(server_dir.join("logs").join("latest.log")));
+    }
+
+    #[test]
+    fn test_validate_server() {
+        // given:
+        let server_dir = TempDir::new("hosting");
+        let world_dir = server_dir.join("world");
+        std::fs::create_dir_all(&world_dir).unwrap();
+
+        // when:
+        let missing = HostingLayout::Server.validate(&world_dir, &LocalFileBackend);
+        std::fs::write(server_dir.join("server.properties"), "").unwrap();
+        let server = HostingLayout::Server.validate(&world_dir, &LocalFileBackend);
+        let bukkit = HostingLayout::Bukkit.validate(&world_dir, &LocalFileBackend);
+        let singleplayer = HostingLayout::Singleplayer.validate(&world_dir, &LocalFileBackend);
+
+        // then:
+        assert!(missing.is_err());
+        assert_eq!(server, Ok(()));
+        assert!(bukkit.is_err());
+        assert!(singleplayer.is_err());
+    }
+}
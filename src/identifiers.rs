@@ -0,0 +1,96 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    datapack_layout::DatapackLayout, is_allowed_in_identifier, LocalFileBackend, NAMESPACE,
+};
+use std::{collections::BTreeSet, fs::read_dir, path::Path};
+
+/// Returns the identifiers of all connections that have artifacts in the world in `world_dir`,
+/// sorted alphabetically.
+///
+/// This scans the structure files in `generated/minect/structures` and the connection functions in
+/// the Minect datapack, so it finds connections that executed commands as well as connections that
+/// were only installed. The urgent lanes of connections (see
+/// [MinecraftConnection::execute_urgent](crate::MinecraftConnection::execute_urgent)) are not
+/// listed separately.
+///
+/// Directories that can't be read are skipped.
+pub fn list_identifiers(world_dir: impl AsRef<Path>) -> Vec<String> {
+    let world_dir = world_dir.as_ref();
+    let structures_dir = world_dir
+        .join("generated")
+        .join(NAMESPACE)
+        .join("structures");
+    let datapack_dir = world_dir.join("datapacks").join(NAMESPACE);
+    let functions_dir = DatapackLayout::detect(&LocalFileBackend, &datapack_dir)
+        .join(&datapack_dir, "data/minect_internal/functions/connection");
+
+    let identifiers = [structures_dir, functions_dir]
+        .iter()
+        .flat_map(|dir| list_dir_names(dir))
+        .filter(|name| !name.is_empty() && name.chars().all(is_allowed_in_identifier))
+        .collect::<BTreeSet<_>>();
+    identifiers
+        .iter()
+        .filter(|identifier| {
+            !identifier
+                .strip_suffix("_urgent")
+                .is_some_and(|main| identifiers.contains(main))
+        })
+        .cloned()
+        .collect()
+}
+
+fn list_dir_names(dir: &Path) -> Vec<String> {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+    use std::fs::create_dir_all;
+
+    #[test]
+    fn test_list_identifiers() {
+        // given:
+        let world_dir = TempDir::new("list");
+        let structures_dir = world_dir.join("generated/minect/structures");
+        let functions_dir =
+            world_dir.join("datapacks/minect/data/minect_internal/functions/connection");
+        create_dir_all(structures_dir.join("b")).unwrap();
+        create_dir_all(structures_dir.join("b_urgent")).unwrap();
+        create_dir_all(functions_dir.join("a")).unwrap();
+        create_dir_all(functions_dir.join("b")).unwrap();
+
+        // when:
+        let actual = list_identifiers(&world_dir);
+
+        // then:
+        assert_eq!(actual, ["a", "b"]);
+    }
+}
@@ -0,0 +1,401 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Localization of the interactive installer that is created by
+//! [MinecraftConnection::connect](crate::MinecraftConnection::connect).
+//!
+//! The language of the installer can be selected with
+//! [installer_language](crate::MinecraftConnectionBuilder::installer_language). Apart from the
+//! bundled [Lang]uages, custom [Translations] can be supplied:
+//!
+//! ```
+//! use minect::installer::{InstallerMessage, Lang, Translations};
+//!
+//! let translations = Translations::new(Lang::English)
+//!     .with(InstallerMessage::Confirm, "[Yes, install it here]")
+//!     .with(InstallerMessage::Cancel, "[No, thanks]");
+//! ```
+
+use crate::json::escape_json;
+use std::collections::HashMap;
+
+/// A language for which [Translations] are bundled with Minect.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lang {
+    /// English (default).
+    #[default]
+    English,
+    /// German.
+    German,
+}
+
+impl Lang {
+    /// Returns the bundled translation of `message` in this language.
+    pub fn translate(self, message: InstallerMessage) -> &'static str {
+        match self {
+            Lang::English => english(message),
+            Lang::German => german(message),
+        }
+    }
+}
+
+/// A message shown in chat by the interactive installer.
+///
+/// Messages may contain the placeholder `-connection_id-`, which is replaced with the connection
+/// identifier, and `-installer_title-`, which is replaced with the
+/// [installer_title](crate::MinecraftConnectionBuilder::installer_title) or
+/// [ExternalApplication](InstallerMessage::ExternalApplication). [PromptInstallChunk](InstallerMessage::PromptInstallChunk) may additionally contain
+/// the placeholders `-chunk_x-`, `-chunk_z-`, `-block_x-`, `-block_z-`, `-block_x_end-` and
+/// `-block_z_end-`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InstallerMessage {
+    /// The prefix of all messages, `[Info]` in English.
+    Info,
+    /// Refers to the application if no
+    /// [installer_title](crate::MinecraftConnectionBuilder::installer_title) is set.
+    ExternalApplication,
+    /// Asks the player to choose a chunk for the connection building.
+    Prompt,
+    /// Asks the player to confirm the chunk set with
+    /// [install_at](crate::MinecraftConnectionBuilder::install_at).
+    PromptInstallChunk,
+    /// Explains that the chosen chunk will be force loaded.
+    ForceLoadNotice,
+    /// Button to choose a chunk.
+    ChooseChunk,
+    /// Button to choose a different chunk.
+    ChooseDifferentChunk,
+    /// Button to confirm the chunk.
+    Confirm,
+    /// Button to cancel the installation.
+    Cancel,
+    /// Hover text of buttons that execute a command.
+    ClickToExecute,
+    /// Hover text of buttons that suggest a command.
+    ClickForSuggestion,
+    /// Hover text of the button that removes a connection.
+    ClickToRemove,
+    /// Shown when the connection was added.
+    Added,
+    /// Shown when the installation was cancelled.
+    Cancelled,
+    /// Shown when the installation was aborted, because the player confirmed it in a different
+    /// world than the one the connection was created for.
+    WrongWorld,
+}
+
+const MESSAGES: [InstallerMessage; 15] = [
+    InstallerMessage::Info,
+    InstallerMessage::ExternalApplication,
+    InstallerMessage::Prompt,
+    InstallerMessage::PromptInstallChunk,
+    InstallerMessage::ForceLoadNotice,
+    InstallerMessage::ChooseChunk,
+    InstallerMessage::ChooseDifferentChunk,
+    InstallerMessage::Confirm,
+    InstallerMessage::Cancel,
+    InstallerMessage::ClickToExecute,
+    InstallerMessage::ClickForSuggestion,
+    InstallerMessage::ClickToRemove,
+    InstallerMessage::Added,
+    InstallerMessage::Cancelled,
+    InstallerMessage::WrongWorld,
+];
+
+impl InstallerMessage {
+    fn placeholder(self) -> &'static str {
+        match self {
+            InstallerMessage::Info => "-message_info-",
+            InstallerMessage::ExternalApplication => "-message_external_application-",
+            InstallerMessage::Prompt => "-message_prompt-",
+            InstallerMessage::PromptInstallChunk => "-message_prompt_install_chunk-",
+            InstallerMessage::ForceLoadNotice => "-message_force_load_notice-",
+            InstallerMessage::ChooseChunk => "-message_choose_chunk-",
+            InstallerMessage::ChooseDifferentChunk => "-message_choose_different_chunk-",
+            InstallerMessage::Confirm => "-message_confirm-",
+            InstallerMessage::Cancel => "-message_cancel-",
+            InstallerMessage::ClickToExecute => "-message_click_to_execute-",
+            InstallerMessage::ClickForSuggestion => "-message_click_for_suggestion-",
+            InstallerMessage::ClickToRemove => "-message_click_to_remove-",
+            InstallerMessage::Added => "-message_added-",
+            InstallerMessage::Cancelled => "-message_cancelled-",
+            InstallerMessage::WrongWorld => "-message_wrong_world-",
+        }
+    }
+}
+
+/// The messages of the interactive installer. Messages that are not set explicitly are taken from
+/// the bundled translations of a [Lang].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Translations {
+    lang: Lang,
+    messages: HashMap<InstallerMessage, String>,
+}
+
+impl Translations {
+    /// Creates [Translations] that use the bundled translations of `lang`.
+    pub fn new(lang: Lang) -> Translations {
+        Translations {
+            lang,
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Overrides the translation of `message`.
+    pub fn with(mut self, message: InstallerMessage, text: impl Into<String>) -> Translations {
+        self.messages.insert(message, text.into());
+        self
+    }
+
+    /// Returns the translation of `message`.
+    pub fn get(&self, message: InstallerMessage) -> &str {
+        self.messages
+            .get(&message)
+            .map(String::as_str)
+            .unwrap_or_else(|| self.lang.translate(message))
+    }
+
+    /// Replaces all message placeholders in `template` with the JSON escaped translations.
+    pub(crate) fn expand(&self, template: &str) -> String {
+        let mut result = template.to_string();
+        for message in MESSAGES {
+            let placeholder = message.placeholder();
+            if result.contains(placeholder) {
+                result = result.replace(placeholder, &escape_text(self.get(message)));
+            }
+        }
+        result
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    escape_json(text).replace('\n', "\\n")
+}
+
+/// The texts of the interactive installer that can be configured via the
+/// [MinecraftConnectionBuilder](crate::MinecraftConnectionBuilder).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct InstallerTexts {
+    pub(crate) translations: Translations,
+    pub(crate) title: Option<String>,
+    pub(crate) description: Option<String>,
+}
+
+impl InstallerTexts {
+    /// Replaces all message placeholders as well as `-installer_title-` and
+    /// `-installer_description-` in `template`.
+    pub(crate) fn expand(&self, template: &str) -> String {
+        let title = self
+            .title
+            .as_deref()
+            .unwrap_or_else(|| self.translations.get(InstallerMessage::ExternalApplication));
+        let description = match &self.description {
+            Some(description) => format!(" {}", description),
+            None => String::new(),
+        };
+        self.translations
+            .expand(template)
+            .replace("-installer_title-", &escape_text(title))
+            .replace("-installer_description-", &escape_text(&description))
+    }
+
+    /// The chat message that starts the interactive installer as plain text, followed by the
+    /// button that the player has to click to continue.
+    pub(crate) fn plain_prompt(
+        &self,
+        identifier: &str,
+        install_at: Option<(i32, i32)>,
+    ) -> (String, String) {
+        let (message, button) = match install_at {
+            Some(_) => (
+                InstallerMessage::PromptInstallChunk,
+                InstallerMessage::Confirm,
+            ),
+            None => (InstallerMessage::Prompt, InstallerMessage::ChooseChunk),
+        };
+        let title = self
+            .title
+            .as_deref()
+            .unwrap_or_else(|| self.translations.get(InstallerMessage::ExternalApplication));
+        let mut prompt = expand_chunk(
+            self.translations.get(message),
+            install_at.unwrap_or_default(),
+        )
+        .replace("-installer_title-", title)
+        .replace("-connection_id-", identifier);
+        if let Some(description) = &self.description {
+            prompt.push(' ');
+            prompt.push_str(description);
+        }
+        (prompt, self.translations.get(button).to_string())
+    }
+}
+
+/// Replaces the placeholders of the chunk at `chunk_x` and `chunk_z` in `template`.
+pub(crate) fn expand_chunk(template: &str, (chunk_x, chunk_z): (i32, i32)) -> String {
+    let (block_x, block_z) = (chunk_x * 16, chunk_z * 16);
+    template
+        .replace("-chunk_x-", &chunk_x.to_string())
+        .replace("-chunk_z-", &chunk_z.to_string())
+        .replace("-block_x_end-", &(block_x + 15).to_string())
+        .replace("-block_z_end-", &(block_z + 15).to_string())
+        .replace("-block_x-", &block_x.to_string())
+        .replace("-block_z-", &block_z.to_string())
+}
+
+impl From<Lang> for Translations {
+    fn from(lang: Lang) -> Translations {
+        Translations::new(lang)
+    }
+}
+
+fn english(message: InstallerMessage) -> &'static str {
+    match message {
+        InstallerMessage::Info => "[Info]",
+        InstallerMessage::ExternalApplication => "An external application",
+        InstallerMessage::Prompt => {
+            "-installer_title- wants to establish a connection named '-connection_id-'. \
+            You can click on the colored text below to choose a chunk in which to generate the \
+            connection structure. The chunk may be cleared by the connection, so make sure it does \
+            not contain anything important."
+        }
+        InstallerMessage::PromptInstallChunk => {
+            "-installer_title- wants to establish a connection named '-connection_id-' in \
+            the chunk at -chunk_x- -chunk_z- (blocks -block_x- -block_z- to -block_x_end- \
+            -block_z_end-). The chunk may be cleared by the connection and will be force loaded to \
+            keep the connection active when no player is around, so make sure it does not contain \
+            anything important."
+        }
+        InstallerMessage::ForceLoadNotice => {
+            "This chunk will be force loaded to keep the connection active when no player is \
+            around."
+        }
+        InstallerMessage::ChooseChunk => "[Choose a chunk]",
+        InstallerMessage::ChooseDifferentChunk => "[Choose different chunk]",
+        InstallerMessage::Confirm => "[Confirm]",
+        InstallerMessage::Cancel => "[Cancel]",
+        InstallerMessage::ClickToExecute => "Click to execute",
+        InstallerMessage::ClickForSuggestion => "Click for suggestion",
+        InstallerMessage::ClickToRemove => "Click to remove",
+        InstallerMessage::Added => "Added connection -connection_id-",
+        InstallerMessage::Cancelled => "Cancelled adding connection -connection_id-",
+        InstallerMessage::WrongWorld => {
+            "Did not add connection -connection_id-, because -installer_title- wants to connect \
+            to a different world"
+        }
+    }
+}
+
+fn german(message: InstallerMessage) -> &'static str {
+    match message {
+        InstallerMessage::Info => "[Info]",
+        InstallerMessage::ExternalApplication => "Eine externe Anwendung",
+        InstallerMessage::Prompt => {
+            "-installer_title- möchte eine Verbindung namens '-connection_id-' herstellen. Du \
+            kannst auf den farbigen Text unten klicken, um einen Chunk auszuwählen, in dem das \
+            Verbindungsbauwerk erzeugt wird. Der Chunk kann von der Verbindung geleert werden, \
+            stelle also sicher, dass er nichts Wichtiges enthält."
+        }
+        InstallerMessage::PromptInstallChunk => {
+            "-installer_title- möchte eine Verbindung namens '-connection_id-' im Chunk \
+            -chunk_x- -chunk_z- (Blöcke -block_x- -block_z- bis -block_x_end- -block_z_end-) \
+            herstellen. Der Chunk kann von der Verbindung geleert werden und wird dauerhaft \
+            geladen, um die Verbindung aktiv zu halten, wenn kein Spieler in der Nähe ist. Stelle \
+            also sicher, dass er nichts Wichtiges enthält."
+        }
+        InstallerMessage::ForceLoadNotice => {
+            "Dieser Chunk wird dauerhaft geladen, um die Verbindung aktiv zu halten, wenn kein \
+            Spieler in der Nähe ist."
+        }
+        InstallerMessage::ChooseChunk => "[Chunk auswählen]",
+        InstallerMessage::ChooseDifferentChunk => "[Anderen Chunk auswählen]",
+        InstallerMessage::Confirm => "[Bestätigen]",
+        InstallerMessage::Cancel => "[Abbrechen]",
+        InstallerMessage::ClickToExecute => "Klicken zum Ausführen",
+        InstallerMessage::ClickForSuggestion => "Klicken für Vorschlag",
+        InstallerMessage::ClickToRemove => "Klicken zum Entfernen",
+        InstallerMessage::Added => "Verbindung -connection_id- hinzugefügt",
+        InstallerMessage::Cancelled => "Hinzufügen der Verbindung -connection_id- abgebrochen",
+        InstallerMessage::WrongWorld => {
+            "Verbindung -connection_id- nicht hinzugefügt, weil -installer_title- sich mit einer \
+            anderen Welt verbinden möchte"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        // given:
+        let translations =
+            Translations::new(Lang::German).with(InstallerMessage::Cancel, "\"Nein\"\ndanke");
+        let template = r#"{"text":"-message_confirm-"},{"text":"-message_cancel-"}"#;
+
+        // when:
+        let actual = translations.expand(template);
+
+        // then:
+        assert_eq!(
+            actual,
+            r#"{"text":"[Bestätigen]"},{"text":"\"Nein\"\ndanke"}"#
+        );
+    }
+
+    #[test]
+    fn test_expand_title_and_description() {
+        // given:
+        let texts = InstallerTexts {
+            title: Some("MyCompiler".to_string()),
+            description: Some("It needs a connection to run your code.".to_string()),
+            ..InstallerTexts::default()
+        };
+        let template = r#"{"text":" -message_prompt-"},{"text":"-installer_description-"}"#;
+
+        // when:
+        let actual = texts.expand(template);
+
+        // then:
+        assert!(actual.starts_with(
+            r#"{"text":" MyCompiler wants to establish a connection named '-connection_id-'."#
+        ));
+        assert!(actual.ends_with(r#"{"text":" It needs a connection to run your code."}"#));
+    }
+
+    #[test]
+    fn test_plain_prompt() {
+        // given:
+        let texts = InstallerTexts {
+            title: Some("MyCompiler".to_string()),
+            ..InstallerTexts::default()
+        };
+
+        // when:
+        let (prompt, button) = texts.plain_prompt("example", Some((1, -2)));
+
+        // then:
+        assert!(prompt.starts_with(
+            "MyCompiler wants to establish a connection named 'example' in the chunk at 1 -2 \
+            (blocks 16 -32 to 31 -17)."
+        ));
+        assert_eq!(button, "[Confirm]");
+    }
+}
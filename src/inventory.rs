@@ -0,0 +1,285 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Inspecting and manipulating the inventories of players.
+//!
+//! The inventory of a player can be read with
+//! [MinecraftConnection::get_inventory](crate::MinecraftConnection::get_inventory) or by parsing
+//! the output of an [inventory_command] into an [InventoryOutput]. Items can be given, cleared and
+//! replaced with [give_command], [clear_command] and [replace_item_command].
+
+use crate::{
+    command::{data_get_entity_command, player_selector, DataGetEntityOutput},
+    snbt::Snbt,
+};
+use std::{
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+/// A stack of items in an inventory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemStack {
+    /// The item id, for example `minecraft:stone`.
+    pub id: String,
+    /// The number of items in this stack.
+    pub count: i32,
+    /// The inventory slot of this stack, if it was read from an inventory.
+    pub slot: Option<i8>,
+    /// Additional item data that is appended to the [id](Self::id) when the item is used in a
+    /// command.
+    ///
+    /// Before Minecraft 1.20.5 this is an SNBT compound like `{Damage:5}`. Since Minecraft 1.20.5
+    /// this is a list of data components like `[minecraft:damage=5]`.
+    pub nbt: Option<String>,
+    _private: (),
+}
+
+impl ItemStack {
+    /// Creates an [ItemStack] without [slot](Self::slot) and [nbt](Self::nbt).
+    pub fn new(id: impl Into<String>, count: i32) -> ItemStack {
+        ItemStack {
+            id: id.into(),
+            count,
+            slot: None,
+            nbt: None,
+            _private: (),
+        }
+    }
+
+    /// Sets the [nbt](Self::nbt) of this [ItemStack].
+    pub fn with_nbt(mut self, nbt: impl Into<String>) -> ItemStack {
+        self.nbt = Some(nbt.into());
+        self
+    }
+
+    fn from_snbt(snbt: &Snbt) -> Option<ItemStack> {
+        let id = snbt.get("id")?.as_str()?;
+        let count = snbt.get("Count").or_else(|| snbt.get("count"));
+        // Since Minecraft 1.20.5 a count of 1 is omitted
+        let count = count.map_or(Some(1), Snbt::as_i64)?.try_into().ok()?;
+        let slot = snbt.get("Slot").and_then(Snbt::as_i64);
+        let nbt = if let Some(tag) = snbt.get("tag") {
+            Some(tag.to_string())
+        } else if let Some(Snbt::Compound(components)) = snbt.get("components") {
+            Some(format_components(components))
+        } else {
+            None
+        };
+        Some(ItemStack {
+            id: id.to_string(),
+            count,
+            slot: slot.and_then(|slot| slot.try_into().ok()),
+            nbt,
+            _private: (),
+        })
+    }
+}
+
+fn format_components(components: &[(String, Snbt)]) -> String {
+    let mut string = "[".to_string();
+    for (index, (key, value)) in components.iter().enumerate() {
+        if index != 0 {
+            string.push(',');
+        }
+        // Writing to a String can't fail
+        let _ = write!(string, "{}={}", key, value);
+    }
+    string.push(']');
+    string
+}
+
+/// Formats the [ItemStack] as an item argument for commands like `give` (without the count).
+impl Display for ItemStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id)?;
+        if let Some(nbt) = &self.nbt {
+            f.write_str(nbt)?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates a Minecraft command that queries the inventory of the online player with the given
+/// name.
+///
+/// The resulting [LogEvent::output](crate::log::LogEvent::output) can be parsed into an
+/// [InventoryOutput].
+pub fn inventory_command(player: &str) -> String {
+    data_get_entity_command(player_selector(player), "Inventory")
+}
+
+/// The output of an [inventory_command]. This can be parsed from
+/// [LogEvent::output](crate::log::LogEvent::output).
+///
+/// The output has the following format:
+/// ```none
+/// <player> has the following entity data: [<item>, ...]
+/// ```
+///
+/// For example:
+/// ```none
+/// Herobrine has the following entity data: [{Slot: 0b, id: "minecraft:stone", Count: 64b}]
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct InventoryOutput {
+    /// The name of the player.
+    pub player: String,
+    /// The items in the inventory of the player.
+    pub items: Vec<ItemStack>,
+    _private: (),
+}
+impl FromStr for InventoryOutput {
+    type Err = ();
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        let output = output.parse::<DataGetEntityOutput>()?;
+        let snbt = output.data.parse::<Snbt>()?;
+        let items = snbt.as_list().ok_or(())?;
+        let items = items
+            .iter()
+            .map(|item| ItemStack::from_snbt(item).ok_or(()))
+            .collect::<Result<_, _>>()?;
+        Ok(InventoryOutput {
+            player: output.entity,
+            items,
+            _private: (),
+        })
+    }
+}
+
+/// Generates a Minecraft command that gives the `item` to `player`.
+///
+/// `player` can be any selector or name.
+pub fn give_command(player: impl Display, item: &ItemStack) -> String {
+    format!("give {} {} {}", player, item, item.count)
+}
+
+/// Generates a Minecraft command that clears items from the inventory of `player`.
+///
+/// `player` can be any selector or name. If `item` is `None` all items are cleared. If `max_count`
+/// is `None` all matching items are cleared.
+pub fn clear_command(player: impl Display, item: Option<&str>, max_count: Option<i32>) -> String {
+    let mut command = format!("clear {}", player);
+    if let Some(item) = item {
+        command.push(' ');
+        command.push_str(item);
+        if let Some(max_count) = max_count {
+            command.push_str(&format!(" {}", max_count));
+        }
+    } else if let Some(max_count) = max_count {
+        command.push_str(&format!(" * {}", max_count));
+    }
+    command
+}
+
+/// Generates a Minecraft command that replaces the item in `slot` of `entity` with `item`.
+///
+/// `entity` can be any selector or name. `slot` is a slot name like `hotbar.0`, `armor.head` or
+/// `weapon.offhand`.
+///
+/// This command requires Minecraft 1.17 or later.
+pub fn replace_item_command(entity: impl Display, slot: impl Display, item: &ItemStack) -> String {
+    format!(
+        "item replace entity {} {} with {} {}",
+        entity, slot, item, item.count
+    )
+}
+
+#[cfg(feature = "connection")]
+impl crate::MinecraftConnection {
+    /// Reads the inventory of the online player with the given name.
+    ///
+    /// Like [connect](Self::connect) this function blocks indefinately if the player is not online,
+    /// so it should be called with [tokio::time::timeout] or some other means of cancellation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](crate::MinecraftConnectionBuilder::command_policy) denies the query (can be
+    /// checked with [ExecuteCommandsError::is_denied](crate::ExecuteCommandsError::is_denied)) or if
+    /// an [io::Error](std::io::Error) occurs.
+    pub async fn get_inventory(
+        &mut self,
+        player: &str,
+    ) -> Result<Vec<ItemStack>, crate::ExecuteCommandsError> {
+        let command = inventory_command(player);
+        self.check_policy(&[crate::Command::new(&command)])?;
+        self.query(command, |event| {
+            let output = event.output.parse::<InventoryOutput>().ok()?;
+            Some(output.items)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inventory_output_from_str() {
+        // given:
+        let output = r#"Herobrine has the following entity data: [{Slot: 0b, id: "minecraft:stone", Count: 64b}, {Slot: 1b, id: "minecraft:diamond_sword", Count: 1b, tag: {Damage: 5}}]"#;
+
+        // when:
+        let actual = output.parse::<InventoryOutput>().unwrap();
+
+        // then:
+        assert_eq!(actual.player, "Herobrine");
+        let actual = actual.items;
+        assert_eq!(actual.len(), 2);
+        assert_eq!(give_command("@p", &actual[0]), "give @p minecraft:stone 64");
+        assert_eq!(actual[1].slot, Some(1));
+        assert_eq!(
+            give_command("@p", &actual[1]),
+            "give @p minecraft:diamond_sword{Damage:5} 1"
+        );
+    }
+
+    #[test]
+    fn test_inventory_output_from_str_with_components() {
+        // given:
+        let output = r#"Herobrine has the following entity data: [{Slot: 0b, id: "minecraft:diamond_sword", count: 1, components: {"minecraft:damage": 5}}]"#;
+
+        // when:
+        let actual = output.parse::<InventoryOutput>().unwrap().items;
+
+        // then:
+        assert_eq!(
+            give_command("@p", &actual[0]),
+            "give @p minecraft:diamond_sword[minecraft:damage=5] 1"
+        );
+    }
+
+    #[cfg(feature = "connection")]
+    #[tokio::test]
+    async fn test_get_inventory_checks_command_policy() {
+        // given:
+        let mut connection = crate::MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(crate::file_backend::MemoryFileBackend::default())
+            .command_policy(|_| crate::PolicyDecision::Deny)
+            .build();
+
+        // when:
+        let actual = connection.get_inventory("Herobrine").await;
+
+        // then:
+        assert!(actual.unwrap_err().is_denied());
+    }
+}
@@ -18,11 +18,13 @@
 
 use std::{
     fmt::Display,
-    fs::{self, File},
-    io,
+    fs, io,
     path::{Path, PathBuf},
 };
 
+use crate::file_backend::FileBackend;
+use log::debug;
+
 #[derive(Debug)]
 pub struct IoErrorAtPath {
     pub message: String,
@@ -37,7 +39,18 @@ impl Display for IoErrorAtPath {
             self.message,
             self.path.display(),
             self.cause
-        )
+        )?;
+        if self.cause.kind() == io::ErrorKind::PermissionDenied {
+            if let Some(description) = describe_permissions(&self.path) {
+                write!(
+                    f,
+                    " ({}; the directories used by Minect must be writable by this process and \
+                    readable by Minecraft)",
+                    description
+                )?;
+            }
+        }
+        Ok(())
     }
 }
 impl IoErrorAtPath {
@@ -73,11 +86,10 @@ impl From<IoErrorAtPath> for io::Error {
     }
 }
 
-pub(crate) fn create(path: impl AsRef<Path>) -> Result<File, IoErrorAtPath> {
-    File::create(&path).map_err(io_error("Failed to create file", path.as_ref()))
-}
-
-pub(crate) fn write(path: impl AsRef<Path>, contents: &str) -> Result<(), IoErrorAtPath> {
+pub(crate) fn write(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+) -> Result<(), IoErrorAtPath> {
     if let Some(parent) = path.as_ref().parent() {
         create_dir_all(parent)?;
     }
@@ -89,22 +101,162 @@ pub(crate) fn create_dir_all(path: impl AsRef<Path>) -> Result<(), IoErrorAtPath
     Ok(())
 }
 
-pub(crate) fn remove_dir(path: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
-    fs::remove_dir(&path).map_err(io_error("Failed to remove directory", path.as_ref()))?;
+pub(crate) fn remove_file(path: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
+    fs::remove_file(&path).map_err(io_error("Failed to remove file", path.as_ref()))?;
     Ok(())
 }
 
-pub(crate) fn remove_dir_all(path: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
-    fs::remove_dir_all(&path).map_err(io_error("Failed to remove directory", path.as_ref()))?;
+pub(crate) fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
+    fs::rename(&from, &to).map_err(io_error("Failed to rename file", from.as_ref()))?;
     Ok(())
 }
 
-pub(crate) fn remove_file(path: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
-    fs::remove_file(&path).map_err(io_error("Failed to remove file", path.as_ref()))?;
-    Ok(())
+/// Unix permission bits and ownership that are applied to files and directories after creating
+/// them. On other platforms only the read-only flag is derived from the mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FilePermissions {
+    pub(crate) file_mode: Option<u32>,
+    pub(crate) dir_mode: Option<u32>,
+    pub(crate) owner: Option<u32>,
+    pub(crate) group: Option<u32>,
 }
 
-pub(crate) fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), IoErrorAtPath> {
-    fs::rename(&from, &to).map_err(io_error("Failed to rename file", from.as_ref()))?;
+impl FilePermissions {
+    pub(crate) fn apply_to_file(
+        &self,
+        backend: &dyn FileBackend,
+        path: impl AsRef<Path>,
+    ) -> Result<(), IoErrorAtPath> {
+        self.apply(backend, path.as_ref(), self.file_mode)
+    }
+
+    pub(crate) fn apply_to_dir(
+        &self,
+        backend: &dyn FileBackend,
+        path: impl AsRef<Path>,
+    ) -> Result<(), IoErrorAtPath> {
+        self.apply(backend, path.as_ref(), self.dir_mode)
+    }
+
+    fn apply(
+        &self,
+        backend: &dyn FileBackend,
+        path: &Path,
+        mode: Option<u32>,
+    ) -> Result<(), IoErrorAtPath> {
+        if let Some(mode) = mode {
+            backend
+                .set_mode(path, mode)
+                .map_err(io_error("Failed to set permissions of", path))?;
+        }
+        if self.owner.is_some() || self.group.is_some() {
+            // Changing the owner usually requires elevated privileges, so the owner is only a hint.
+            if let Err(e) = backend.set_owner(path, self.owner, self.group) {
+                debug!("Failed to change owner of {}: {}", path.display(), e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Like [write], but uses the `backend` and applies `permissions` to the file and all directories
+/// that had to be created.
+pub(crate) fn write_with_permissions(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+    permissions: &FilePermissions,
+) -> Result<(), IoErrorAtPath> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        create_dir_all_with_permissions(backend, parent, permissions)?;
+    }
+    backend
+        .write(path, contents.as_ref())
+        .map_err(io_error("Failed to create file", path))?;
+    permissions.apply_to_file(backend, path)
+}
+
+/// Like [create_dir_all], but uses the `backend` and applies `permissions` to all directories that
+/// had to be created.
+pub(crate) fn create_dir_all_with_permissions(
+    backend: &dyn FileBackend,
+    path: impl AsRef<Path>,
+    permissions: &FilePermissions,
+) -> Result<(), IoErrorAtPath> {
+    let path = path.as_ref();
+    let missing = path
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !backend.is_dir(dir))
+        .collect::<Vec<_>>();
+    backend
+        .create_dir_all(path)
+        .map_err(io_error("Failed to create directory", path))?;
+    for dir in missing.into_iter().rev() {
+        permissions.apply_to_dir(backend, dir)?;
+    }
     Ok(())
 }
+
+/// Describes the owner and permissions of `path` or its closest existing ancestor to explain a
+/// [PermissionDenied](io::ErrorKind::PermissionDenied) error.
+fn describe_permissions(path: &Path) -> Option<String> {
+    let (existing, metadata) = path
+        .ancestors()
+        .find_map(|dir| fs::metadata(dir).ok().map(|metadata| (dir, metadata)))?;
+    #[cfg(unix)]
+    let description = {
+        use std::os::unix::fs::MetadataExt;
+        format!(
+            "{} is owned by {}:{} with mode {:o}",
+            existing.display(),
+            metadata.uid(),
+            metadata.gid(),
+            metadata.mode() & 0o7777
+        )
+    };
+    #[cfg(not(unix))]
+    let description = format!(
+        "{} is {}",
+        existing.display(),
+        if metadata.permissions().readonly() {
+            "read-only"
+        } else {
+            "writable"
+        }
+    );
+    Some(description)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+    use std::os::unix::fs::PermissionsExt;
+
+    fn mode(path: impl AsRef<Path>) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o7777
+    }
+
+    #[test]
+    fn test_write_with_permissions() {
+        // given:
+        let temp_dir = TempDir::new("io");
+        let dir = temp_dir.join("dir");
+        let file = dir.join("a/b/file.txt");
+        let permissions = FilePermissions {
+            file_mode: Some(0o640),
+            dir_mode: Some(0o750),
+            ..FilePermissions::default()
+        };
+
+        // when:
+        write_with_permissions(&LocalFileBackend, &file, "content", &permissions).unwrap();
+
+        // then:
+        assert_eq!(mode(&file), 0o640);
+        assert_eq!(mode(dir.join("a/b")), 0o750);
+        assert_eq!(mode(dir.join("a")), 0o750);
+        assert_eq!(mode(&dir), 0o750);
+    }
+}
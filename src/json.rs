@@ -16,10 +16,17 @@
 // You should have received a copy of the GNU General Public License along with Minect.
 // If not, see <http://www.gnu.org/licenses/>.
 
-pub(crate) fn create_json_text_component(text: &str) -> String {
+//! Helpers for generating JSON text components and escaping strings in JSON or SNBT.
+
+/// Creates a JSON text component that displays the given `text` literally.
+///
+/// For example `Herobrine` results in `{"text":"Herobrine"}`.
+pub fn create_json_text_component(text: &str) -> String {
     format!(r#"{{"text":"{}"}}"#, escape_json(text))
 }
 
-pub(crate) fn escape_json(json: &str) -> String {
+/// Escapes backslashes and double quotes, so `json` can be embedded in a double quoted JSON or SNBT
+/// string.
+pub fn escape_json(json: &str) -> String {
     json.replace("\\", "\\\\").replace('"', "\\\"")
 }
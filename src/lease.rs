@@ -0,0 +1,221 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Ownership of a connection identifier by a single process, see
+//! [lease](crate::MinecraftConnectionBuilder::lease).
+//!
+//! The owner of an identifier is recorded in a lease file in the structures directory of the
+//! connection, which expires unless it is renewed. The lease file is accessed through the
+//! [FileBackend] of the connection and is checked before anything else is written to the world.
+//! Because clocks of different machines may differ, an expired lease is only taken over after it
+//! was expired for another lease duration.
+
+use crate::{
+    file_backend::{FileBackend, LockedFile},
+    io::{io_error, IoErrorAtPath},
+    utils::io_invalid_data,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// What [connect](crate::MinecraftConnection::connect) does if another process holds the
+/// [lease](crate::MinecraftConnectionBuilder::lease) of the identifier.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LeaseConflict {
+    /// [connect](crate::MinecraftConnection::connect) fails (can be checked with
+    /// [ConnectError::is_lease_held](crate::ConnectError::is_lease_held)).
+    #[default]
+    Fail,
+    /// [connect](crate::MinecraftConnection::connect) waits until the lease expires and then takes
+    /// it over.
+    Wait,
+    /// [connect](crate::MinecraftConnection::connect) succeeds without the lease and without writing
+    /// anything to the world. The connection can observe the log, but executing commands fails (can
+    /// be checked with
+    /// [ExecuteCommandsError::is_read_only](crate::ExecuteCommandsError::is_read_only)).
+    ReadOnly,
+}
+
+/// The lease of a connection identifier that is held by another process.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LeaseHeld {
+    /// The connection identifier.
+    pub identifier: String,
+    /// The random id of the session holding the lease.
+    pub session: i32,
+    /// The id of the process holding the lease.
+    pub pid: u32,
+    /// The time until the lease can be taken over. This includes a grace period of one lease
+    /// duration after the lease expired, which tolerates differing clocks of different machines.
+    pub expires_in: Duration,
+    _private: (),
+}
+
+impl Display for LeaseHeld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The lease of identifier {} is held by session {} of process {}",
+            self.identifier, self.session, self.pid
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct LeaseRecord {
+    session: i32,
+    pid: u32,
+    /// Milliseconds since the unix epoch.
+    expires: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Lease {
+    path: PathBuf,
+    duration: Duration,
+}
+
+impl Lease {
+    pub(crate) fn new(structures_dir: &Path, duration: Duration) -> Lease {
+        Lease {
+            path: structures_dir.join("lease.json"),
+            duration,
+        }
+    }
+
+    pub(crate) fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Acquires the lease for `session` or renews it if `session` already holds it. The lease of
+    /// another session is taken over once it was expired for another lease duration.
+    pub(crate) fn acquire(
+        &self,
+        backend: &dyn FileBackend,
+        identifier: &str,
+        session: i32,
+    ) -> Result<Result<(), LeaseHeld>, IoErrorAtPath> {
+        let mut file = self.lock(backend)?; // Automatically unlocked by dropping file at the end of this function.
+        let now = millis_since_epoch(SystemTime::now());
+        if let Some(record) = self.read(&mut *file)? {
+            let takeover = record.expires + self.duration.as_millis() as u64;
+            if record.session != session && takeover > now {
+                return Ok(Err(LeaseHeld {
+                    identifier: identifier.to_string(),
+                    session: record.session,
+                    pid: record.pid,
+                    expires_in: Duration::from_millis(takeover - now),
+                    _private: (),
+                }));
+            }
+        }
+        let record = LeaseRecord {
+            session,
+            pid: std::process::id(),
+            expires: now + self.duration.as_millis() as u64,
+        };
+        let contents = serde_json::to_string(&record).unwrap();
+        file.write_contents(contents.as_bytes())
+            .map_err(io_error("Failed to write file", &self.path))?;
+        Ok(Ok(()))
+    }
+
+    /// Releases the lease if it is held by `session`.
+    pub(crate) fn release(
+        &self,
+        backend: &dyn FileBackend,
+        session: i32,
+    ) -> Result<(), IoErrorAtPath> {
+        let mut file = self.lock(backend)?; // Automatically unlocked by dropping file at the end of this function.
+        match self.read(&mut *file)? {
+            Some(record) if record.session == session => file
+                .write_contents(b"")
+                .map_err(io_error("Failed to write file", &self.path)),
+            _ => Ok(()),
+        }
+    }
+
+    fn lock<'b>(
+        &self,
+        backend: &'b dyn FileBackend,
+    ) -> Result<Box<dyn LockedFile + 'b>, IoErrorAtPath> {
+        backend
+            .lock(&self.path)
+            .map_err(io_error("Failed to lock file", &self.path))
+    }
+
+    /// Reads the lease record from the locked lease file. An empty file means that no session holds
+    /// the lease.
+    fn read(&self, file: &mut dyn LockedFile) -> Result<Option<LeaseRecord>, IoErrorAtPath> {
+        let content = file
+            .read_contents()
+            .map_err(io_error("Failed to read file", &self.path))?;
+        if content.trim().is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(io_invalid_data)
+            .map_err(io_error("Failed to parse lease file", &self.path))
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+
+    #[test]
+    fn test_acquire() {
+        // given:
+        let dir = TempDir::new("lease");
+        let backend = LocalFileBackend;
+        let lease = Lease::new(&dir, Duration::from_secs(60));
+        let expired = Lease::new(&dir, Duration::ZERO);
+
+        // when:
+        let first = lease.acquire(&backend, "example", 1).unwrap();
+        let renewed = lease.acquire(&backend, "example", 1).unwrap();
+        let held = lease.acquire(&backend, "example", 2).unwrap();
+        expired.acquire(&backend, "example", 1).unwrap().unwrap();
+        let grace = lease.acquire(&backend, "example", 2).unwrap();
+        let stolen = expired.acquire(&backend, "example", 2).unwrap();
+        lease.release(&backend, 2).unwrap();
+        let released = lease.acquire(&backend, "example", 3).unwrap();
+
+        // then:
+        assert_eq!(first, Ok(()));
+        assert_eq!(renewed, Ok(()));
+        assert_eq!(held.unwrap_err().session, 1);
+        let grace = grace.unwrap_err();
+        assert!(grace.expires_in > Duration::ZERO && grace.expires_in <= Duration::from_secs(60));
+        assert_eq!(stolen, Ok(()));
+        assert_eq!(released, Ok(()));
+    }
+}
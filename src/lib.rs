@@ -37,8 +37,9 @@
 //! let world_dir = "C:/Users/Herobrine/AppData/Roaming/.minecraft/saves/New World";
 //! let mut connection = MinecraftConnection::builder(identifier, world_dir).build();
 //!
-//! println!("If you are connecting for the first time please execute /reload in Minecraft.");
-//! connection.connect().await?;
+//! connection
+//!     .connect_or_instruct(|instructions| println!("{}", instructions))
+//!     .await?;
 //!
 //! let events = connection.add_listener();
 //!
@@ -58,60 +59,340 @@
 //! # Ok::<(), std::io::Error>(())
 //! # };
 //! ```
+//!
+//! ## Features
+//!
+//! * `connection` (enabled by default): Everything needed to connect to Minecraft, including
+//!   [MinecraftConnection] and [LogObserver](log::LogObserver). When this feature is disabled via
+//!   `default-features = false`, Minect only provides the [command] generators, parsing of
+//!   [LogEvent](log::LogEvent)s and the [json] helpers without any heavy dependencies. This is
+//!   useful for generating commands and parsing their output in environments that don't need a
+//!   connection.
 
+#[cfg(feature = "connection")]
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "connection")]
+mod building;
+#[cfg(feature = "connection")]
+mod bulk;
 pub mod command;
+#[cfg(feature = "connection")]
+mod command_result;
+#[cfg(feature = "connection")]
+mod config;
+#[cfg(feature = "connection")]
 mod connect;
-mod geometry3;
+#[cfg(feature = "connection")]
+mod custom_structure;
+#[cfg(feature = "connection")]
+mod datapack_conflict;
+#[cfg(feature = "connection")]
+mod datapack_layout;
+#[cfg(feature = "connection")]
+mod deadline;
+#[cfg(feature = "connection")]
+pub mod discovery;
+#[cfg(feature = "encryption")]
+mod encryption;
+#[cfg(feature = "connection")]
+mod entity;
+#[cfg(feature = "connection")]
+mod env;
+#[cfg(feature = "connection")]
+mod file_backend;
+#[cfg(feature = "connection")]
+mod function_file;
+#[cfg(feature = "connection")]
+pub mod geometry;
+#[cfg(feature = "connection")]
+mod hosting;
+#[cfg(feature = "connection")]
+mod identifiers;
+#[cfg(feature = "connection")]
+pub mod installer;
+pub mod inventory;
+#[cfg(feature = "connection")]
 mod io;
-mod json;
+pub mod json;
+#[cfg(feature = "connection")]
+mod lease;
+#[cfg(feature = "connection")]
+mod loaded;
 pub mod log;
+#[cfg(feature = "connection")]
+mod log_volume;
+pub mod names;
+#[cfg(feature = "connection")]
+mod objectives;
+#[cfg(feature = "connection")]
 mod on_drop;
+#[cfg(feature = "connection")]
 mod placement;
+pub mod policy;
+mod position;
+#[cfg(feature = "connection")]
+mod purge;
+#[cfg(feature = "connection")]
+mod read_only;
+mod redaction;
+#[cfg(feature = "connection")]
+mod retry;
+#[cfg(feature = "connection")]
+mod session;
+mod sidebar;
+#[cfg(feature = "signing")]
+pub mod signing;
+#[cfg(feature = "connection")]
+mod skipped;
+mod snbt;
+#[cfg(feature = "connection")]
+mod spool;
+#[cfg(feature = "connection")]
+pub mod stream;
+#[cfg(feature = "connection")]
 mod structure;
+#[cfg(feature = "connection")]
+mod structure_id;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "connection")]
+mod uninstall;
+#[cfg(feature = "connection")]
+mod update_delay;
+#[cfg(feature = "connection")]
 mod utils;
+mod version;
+#[cfg(feature = "connection")]
+mod warnings;
+#[cfg(feature = "connection")]
+mod world;
 
-pub use crate::connect::ConnectError;
+#[cfg(feature = "connection")]
+pub use crate::building::BuildingInfo;
+#[cfg(feature = "connection")]
+pub use crate::bulk::{BulkJob, BulkProgress};
+#[cfg(feature = "connection")]
+pub use crate::command_result::{CommandResult, CommandResultEvent};
+#[cfg(feature = "connection")]
+pub use crate::config::ConnectionConfig;
+#[cfg(feature = "connection")]
+pub use crate::connect::{ConnectError, ConnectInstructions, ConnectProgress};
+#[cfg(feature = "connection")]
+pub use crate::custom_structure::{CustomStructure, InvalidCustomStructure};
+#[cfg(feature = "connection")]
+pub use crate::datapack_conflict::{CreateDatapackError, DatapackConflict};
+#[cfg(feature = "connection")]
+pub use crate::deadline::BatchExpired;
+#[cfg(feature = "connection")]
+pub use crate::entity::EntityInfo;
+#[cfg(feature = "connection")]
+pub use crate::env::{default_minecraft_dir, FromEnvError};
+#[cfg(feature = "sftp")]
+pub use crate::file_backend::SftpFileBackend;
+#[cfg(feature = "connection")]
+pub use crate::file_backend::{FileBackend, LocalFileBackend, LockedFile};
+#[cfg(feature = "connection")]
+pub use crate::hosting::HostingLayout;
+#[cfg(feature = "connection")]
+pub use crate::identifiers::list_identifiers;
+#[cfg(feature = "connection")]
+pub use crate::lease::{LeaseConflict, LeaseHeld};
+#[cfg(feature = "connection")]
+pub use crate::loaded::{BatchLoaded, LoadedSource};
+#[cfg(feature = "connection")]
+pub use crate::log_volume::{LogVolume, LogVolumeAction};
+#[cfg(feature = "connection")]
+pub use crate::placement::PlacementError;
+pub use crate::position::{BlockPos, ChunkPos, Region};
+#[cfg(feature = "connection")]
+pub use crate::purge::{purge_identifier, purge_identifier_with_backend};
+#[cfg(feature = "connection")]
+pub use crate::read_only::MinecraftObserver;
+pub use crate::redaction::{clear_command_redactor, set_command_redactor};
+#[cfg(feature = "connection")]
+pub use crate::session::IdentifierInUse;
+pub use crate::sidebar::Sidebar;
+#[cfg(feature = "connection")]
+pub use crate::skipped::BatchSkipped;
+#[cfg(feature = "connection")]
+pub use crate::uninstall::UninstallReport;
+#[cfg(feature = "connection")]
+pub use crate::update_delay::{UpdateDelayChanged, UpdateDelayReason};
+pub use crate::version::MinecraftVersion;
+#[cfg(feature = "connection")]
+pub use crate::warnings::MinectWarning;
+#[cfg(feature = "connection")]
+pub use crate::world::WrongWorld;
 
+use crate::redaction::redact;
+#[cfg(feature = "signing")]
+use crate::signing::BatchSigner;
+#[cfg(feature = "connection")]
 use crate::{
+    bulk::split_into_chunks,
     command::{
-        enable_logging_command, reset_logging_command, summon_named_entity_command,
-        SummonNamedEntityOutput,
+        data_get_entity_command, datapack_enable_command, datapack_list_command,
+        enable_logging_command, escape_selector_argument, execute_as_player_command,
+        place_template_command, player_executed, player_selector, query_scoreboard_command,
+        reset_logging_command, store_result_in_score, summon_named_entity_command,
+        teleport_command, time_query_command, DataGetEntityOutput, DatapackList,
+        DatapackListOutput, QueryScoreboardOutput, StructureMirror, StructureRotation,
+        SummonNamedEntityOutput, TeleportOutput, TimeQuery,
     },
-    connect::connect,
+    command_result::{add_result_commands, COMMANDS_PER_RESULT},
+    connect::{connect, connect_or_instruct},
+    datapack_conflict::{pack_description, CreateDatapackErrorInner},
+    datapack_layout::{pack_format, DatapackLayout},
+    deadline::ExpiringBatches,
+    function_file::{
+        batch_function_lines, parse_batch_executed_output, remove_batch_function,
+        write_batch_function, BATCH_TAG,
+    },
+    geometry::Coordinate3,
+    installer::{InstallerTexts, Translations},
     io::{
-        create, create_dir_all, io_error, remove_dir_all, remove_file, rename, write, IoErrorAtPath,
+        create_dir_all_with_permissions, io_error, write_with_permissions, FilePermissions,
+        IoErrorAtPath,
+    },
+    json::create_json_text_component,
+    lease::Lease,
+    loaded::{watch_structures_dir, LoadedBatches},
+    log::{
+        FunctionLoadError, InterceptorAction, LogEvent, LogFormat, LogObserver, ObserverError,
+        RemoteLogSource, DEFAULT_POLL_INTERVAL,
     },
-    log::{LogEvent, LogObserver},
-    placement::generate_structure,
+    log_volume::LogVolumeMeter,
+    objectives::{
+        objective_name, read_objectives, track_objective, untrack_objectives, REMOVE_OBJECTIVES_TAG,
+    },
+    placement::{
+        generate_structure, protection_commands, summon_connection_entity_from_rail_command,
+        validate_building_y, validate_chains, CommandBlockArea, CONNECTION_ENTITY_Y,
+        DEFAULT_BUILDING_Y,
+    },
+    policy::PolicyDecision,
+    purge::PURGE_TAG,
+    retry::UnacknowledgedBatches,
+    session::{session_commands, SESSION_COMMANDS_LEN},
+    skipped::SkippedBatches,
+    snbt::Snbt,
+    spool::{count_pending_structures, Spool},
     structure::nbt::Structure,
+    structure_id::{first_id_of_run, next_run, parse_structure_file, run_of, structure_file},
+    uninstall::UNINSTALL_FUNCTION,
+    update_delay::UpdateDelayController,
     utils::io_invalid_data,
+    warnings::Warnings,
 };
-use ::log::error;
+#[cfg(feature = "connection")]
+use ::log::{debug, warn};
+#[cfg(feature = "connection")]
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+#[cfg(feature = "connection")]
 use fs3::FileExt;
+#[cfg(feature = "connection")]
 use indexmap::IndexSet;
-use json::create_json_text_component;
+#[cfg(feature = "connection")]
+use notify::RecommendedWatcher;
+#[cfg(feature = "connection")]
 use std::{
     fmt::Display,
     fs::{File, OpenOptions},
-    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    io::{ErrorKind, Read},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+#[cfg(feature = "connection")]
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    time::{interval, timeout, MissedTickBehavior},
 };
-use tokio_stream::Stream;
+#[cfg(feature = "connection")]
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream, StreamExt};
 
 /// A builder to create a [MinecraftConnection] is obtained via [MinecraftConnection::builder].
 ///
 /// The builder pattern is used to add new parameters without breaking backwards compatibility.
+#[cfg(feature = "connection")]
+#[derive(Clone)]
 pub struct MinecraftConnectionBuilder {
     identifier: String,
     world_dir: PathBuf,
     log_file: Option<PathBuf>,
+    log_format: Option<LogFormat>,
+    hosting_layout: Option<HostingLayout>,
+    remote_log_source: Option<Arc<dyn RemoteLogSource>>,
     enable_logging_automatically: bool,
+    log_volume_threshold: Option<u64>,
+    log_volume_action: LogVolumeAction,
+    retry_unacknowledged_after: Option<Duration>,
+    regenerate_skipped_batches: bool,
+    query_gametick: bool,
+    command_block_offset: Option<Coordinate3<i32>>,
+    command_block_area_size: Option<Coordinate3<i32>>,
+    minecraft_version: Option<MinecraftVersion>,
+    install_at: Option<(i32, i32)>,
+    installer_texts: InstallerTexts,
+    execution_strategy: ExecutionStrategy,
+    detect_command_results: bool,
+    protect_building: bool,
+    building_y: i32,
+    hide_building: bool,
+    force_overwrite: bool,
+    validate_placement: bool,
+    namespace_ids_per_run: bool,
+    share_identifier: bool,
+    lease: Option<Duration>,
+    lease_conflict: LeaseConflict,
+    #[cfg(feature = "signing")]
+    signing_key: Option<Vec<u8>>,
+    file_permissions: FilePermissions,
+    file_backend: Arc<dyn FileBackend>,
+    compression_level: u32,
+    update_delay: Option<u32>,
+    adaptive_update_delay: Option<RangeInclusive<u32>>,
+    adaptive_update_delay_idle_after: Duration,
+    log_poll_interval: Duration,
+    acknowledge_every: u64,
+    spool_dir: Option<PathBuf>,
+    #[cfg(feature = "encryption")]
+    spool_key: Option<[u8; 32]>,
+    max_pending_structures: usize,
+    merge_spooled_batches: bool,
+    command_policy: Option<CommandPolicy>,
 }
 
+#[cfg(feature = "connection")]
 impl MinecraftConnectionBuilder {
+    /// Creates the builder of the [urgent lane](MinecraftConnection::execute_urgent). It shares the
+    /// building, log and file configuration, but processes every batch with command blocks as soon
+    /// as it is loaded.
+    fn urgent_lane(&self) -> MinecraftConnectionBuilder {
+        MinecraftConnectionBuilder {
+            identifier: format!("{}_urgent", self.identifier),
+            retry_unacknowledged_after: None,
+            regenerate_skipped_batches: false,
+            query_gametick: false,
+            install_at: None,
+            execution_strategy: ExecutionStrategy::CommandBlocks,
+            share_identifier: true,
+            lease: None,
+            update_delay: None,
+            adaptive_update_delay: None,
+            acknowledge_every: 1,
+            spool_dir: None,
+            merge_spooled_batches: false,
+            ..self.clone()
+        }
+    }
+
     fn new(
         identifier: impl Into<String>,
         world_dir: impl Into<PathBuf>,
@@ -122,7 +403,47 @@ impl MinecraftConnectionBuilder {
             identifier,
             world_dir: world_dir.into(),
             log_file: None,
+            log_format: None,
+            hosting_layout: None,
+            remote_log_source: None,
             enable_logging_automatically: true,
+            log_volume_threshold: None,
+            log_volume_action: LogVolumeAction::default(),
+            retry_unacknowledged_after: None,
+            regenerate_skipped_batches: false,
+            query_gametick: false,
+            command_block_offset: None,
+            command_block_area_size: None,
+            minecraft_version: None,
+            install_at: None,
+            installer_texts: InstallerTexts::default(),
+            execution_strategy: ExecutionStrategy::default(),
+            detect_command_results: false,
+            protect_building: false,
+            building_y: DEFAULT_BUILDING_Y,
+            hide_building: false,
+            force_overwrite: false,
+            validate_placement: cfg!(debug_assertions),
+            namespace_ids_per_run: false,
+            share_identifier: false,
+            lease: None,
+            lease_conflict: LeaseConflict::default(),
+            #[cfg(feature = "signing")]
+            signing_key: None,
+            file_permissions: FilePermissions::default(),
+            file_backend: Arc::new(LocalFileBackend),
+            compression_level: Compression::default().level(),
+            update_delay: None,
+            adaptive_update_delay: None,
+            adaptive_update_delay_idle_after: Duration::from_secs(5),
+            log_poll_interval: DEFAULT_POLL_INTERVAL,
+            acknowledge_every: 1,
+            spool_dir: None,
+            #[cfg(feature = "encryption")]
+            spool_key: None,
+            max_pending_structures: 4,
+            merge_spooled_batches: false,
+            command_policy: None,
         }
     }
 
@@ -135,13 +456,52 @@ impl MinecraftConnectionBuilder {
     ///
     /// For servers it is at `logs/latest.log` in the server directory.
     ///
-    /// Defaults to `../../logs/latest.log` relative to `world_dir`, which is the correct value for
-    /// single player, but usually not for servers.
+    /// Defaults to the log file of the [hosting_layout](Self::hosting_layout). Without a hosting
+    /// layout this is `../../logs/latest.log` relative to `world_dir`, which is the correct value
+    /// for single player, but usually not for servers.
     pub fn log_file(mut self, log_file: impl Into<PathBuf>) -> MinecraftConnectionBuilder {
         self.log_file = Some(log_file.into());
         self
     }
 
+    /// The [LogFormat] of the [log_file](Self::log_file). Set this to [LogFormat::Paper] when
+    /// connecting to a Paper server.
+    ///
+    /// Default: The log format of the [hosting_layout](Self::hosting_layout), which is
+    /// [LogFormat::Vanilla] without a hosting layout.
+    pub fn log_format(mut self, log_format: impl Into<LogFormat>) -> MinecraftConnectionBuilder {
+        self.log_format = Some(log_format.into());
+        self
+    }
+
+    /// The [HostingLayout] of the Minecraft installation that runs the world. It determines the
+    /// defaults of [log_file](Self::log_file) and [log_format](Self::log_format) and the world
+    /// directory in which the Minect datapack is installed. When connecting, the directories around
+    /// the world directory are checked to match the layout.
+    ///
+    /// Default: `None`, which uses the paths of [HostingLayout::Singleplayer] without checking them.
+    pub fn hosting_layout(
+        mut self,
+        hosting_layout: impl Into<HostingLayout>,
+    ) -> MinecraftConnectionBuilder {
+        self.hosting_layout = Some(hosting_layout.into());
+        self
+    }
+
+    /// Reads the log file from the given [RemoteLogSource] instead of the
+    /// [log_file](Self::log_file), for example via an `SshLogSource`. Together with a remote
+    /// [file_backend](Self::file_backend) this allows connecting to a server that is not accessible
+    /// via the local file system.
+    ///
+    /// Default: `None`.
+    pub fn remote_log_source(
+        mut self,
+        remote_log_source: impl RemoteLogSource + 'static,
+    ) -> MinecraftConnectionBuilder {
+        self.remote_log_source = Some(Arc::new(remote_log_source));
+        self
+    }
+
     /// Whether logging is automatically enabled for all commands passed to
     /// [MinecraftConnection::execute_commands]. This works by prepending an
     /// [enable_logging_command] and appending a [reset_logging_command] to the list of commands.
@@ -158,297 +518,2419 @@ impl MinecraftConnectionBuilder {
         self
     }
 
-    /// Creates a [MinecraftConnection] with the configured parameters.
-    ///
-    /// # Panics
+    /// The number of bytes of log output a single batch may produce before the
+    /// [log_volume_action](Self::log_volume_action) is taken. Large logged batches can quickly
+    /// grow Minecraft's log file, because every command writes at least one line. The measured
+    /// volume is available through [MinecraftConnection::log_volume] regardless of this setting.
     ///
-    /// Panics if no [log_file](Self::log_file()) was specified and the
-    /// [world_dir](MinecraftConnection::builder) has less than 2 path compontents. In this case the
-    /// default value of `../../logs/latest.log` can not be resolved.
-    pub fn build(self) -> MinecraftConnection {
-        let world_dir = self.world_dir;
-        let log_file = self
-            .log_file
-            .unwrap_or_else(|| log_file_from_world_dir(&world_dir));
-        MinecraftConnection::new(
-            self.identifier,
-            world_dir,
-            log_file,
-            self.enable_logging_automatically,
-        )
+    /// Default: `None`.
+    pub fn log_volume_threshold(
+        mut self,
+        log_volume_threshold: impl Into<Option<u64>>,
+    ) -> MinecraftConnectionBuilder {
+        self.log_volume_threshold = log_volume_threshold.into();
+        self
     }
-}
 
-fn validate_identifier(identifier: &str) {
-    let invalid_chars = identifier
-        .chars()
-        .filter(|c| !is_allowed_in_identifier(*c))
-        .collect::<IndexSet<_>>();
-    if !invalid_chars.is_empty() {
-        panic!(
-            "Invalid characters in MinecraftConnection.identifier: '{}'",
-            invalid_chars
-                .iter()
-                .fold(String::new(), |joined, c| joined + &c.to_string())
-        );
+    /// What happens when a batch exceeds the [log_volume_threshold](Self::log_volume_threshold).
+    ///
+    /// Default: [LogVolumeAction::Warn].
+    pub fn log_volume_action(
+        mut self,
+        log_volume_action: impl Into<LogVolumeAction>,
+    ) -> MinecraftConnectionBuilder {
+        self.log_volume_action = log_volume_action.into();
+        self
     }
-}
-fn is_allowed_in_identifier(c: char) -> bool {
-    return c >= '0' && c <= '9'
-        || c >= 'A' && c <= 'Z'
-        || c >= 'a' && c <= 'z'
-        || c == '+'
-        || c == '-'
-        || c == '.'
-        || c == '_';
-}
-
-fn log_file_from_world_dir(world_dir: &PathBuf) -> PathBuf {
-    let panic_invalid_dir = || {
-        panic!(
-            "Expected world_dir to be in .minecraft/saves, but was: {}",
-            world_dir.display()
-        )
-    };
-    let minecraft_dir = world_dir
-        .parent()
-        .unwrap_or_else(panic_invalid_dir)
-        .parent()
-        .unwrap_or_else(panic_invalid_dir);
-    minecraft_dir.join("logs/latest.log")
-}
-
-macro_rules! extract_datapack_file {
-    ($output_path:expr, $relative_path:expr) => {{
-        let path = $output_path.join($relative_path);
-        let contents = include_datapack_template!($relative_path);
-        write(&path, &contents)
-    }};
-}
-
-/// A connection to Minecraft that can [execute commands](MinecraftConnection::execute_commands) in
-/// Minecraft and [listen for command output](MinecraftConnection::add_listener).
-///
-/// If you need to listen for command output, but don't need to execute commands, consider using
-/// [LogObserver] directly.
-///
-/// The connection requires a building in Minecraft which continuously loads structure files that
-/// contain the commands passed to [execute_commands](MinecraftConnection::execute_commands). To
-/// create such a connection building you can call [connect](MinecraftConnection::connect).
-///
-/// Minect supports operating multiple connection buildings in parallel, each with a unique
-/// identifier. A single connection building can be shared between any number of Rust programs, but
-/// it has a limited update frequency. For optimal performance every Rust program can use a
-/// different connection identifier.
-///
-/// The update frequency can be configured globally for all connections in a Minecraft world by
-/// changing the score of `update_delay` for the objective `minect_config`.
-pub struct MinecraftConnection {
-    identifier: String,
-    structures_dir: PathBuf,
-    datapack_dir: PathBuf,
-    log_file: PathBuf,
-    log_observer: Option<LogObserver>,
-    loaded_listener_initialized: bool,
-    enable_logging_automatically: bool,
-    _private: (),
-}
 
-const NAMESPACE: &str = "minect";
-
-impl MinecraftConnection {
-    /// Creates a [MinecraftConnectionBuilder].
+    /// If set, the commands of a batch that Minecraft did not acknowledge within the given duration
+    /// are executed again in a new batch with a new structure id, because Minecraft caches
+    /// structures by id and never loads a structure again that it considered missing or invalid.
+    /// This can recover batches that were lost, for instance because Minecraft crashed while a
+    /// structure file was written. The new batch is written before the next batch passed to
+    /// [MinecraftConnection::execute_commands].
     ///
-    /// `identifier` is a string that uniquely identifies a connection building in Minecraft.
-    /// Because it is used with Minecraft's `tag` command, it may only contain the following
-    /// Characters: `0-9`, `A-Z`, `a-z`, `+`, `-`, `.` & `_`.
-    ///
-    /// `world_dir` is the directory containing the Minecraft world to connect to.
-    /// For single player this is typically a directory within the saves directory:
-    /// * Windows: `C:\Users\Herobrine\AppData\Roaming\.minecraft\saves\`
-    /// * GNU/Linux: `~/.minecraft/saves/`
-    /// * Mac: `~/Library/Application Support/minecraft/saves/`
-    ///
-    /// For servers it is specified in `server.properties`.
+    /// Only the oldest unacknowledged batch is retried, once it waited for the given duration since
+    /// the previous batch was acknowledged. Later batches are just queued behind it, so they are not
+    /// retried.
     ///
-    /// # Panics
+    /// Note that a missing acknowledgement does not necessarily mean that the commands were not
+    /// executed. If for example the log file could not be read in time, the commands of a batch may
+    /// be executed twice. So when enabling this option, all commands passed to
+    /// [MinecraftConnection::execute_commands] should be idempotent, meaning executing them twice
+    /// has the same effect as executing them once. For example prefer
+    /// `scoreboard players set ...` over `scoreboard players add ...`.
     ///
-    /// Panics if `identifier` contains an invalid character.
-    pub fn builder(
-        identifier: impl Into<String>,
-        world_dir: impl Into<PathBuf>,
+    /// Default: `None`.
+    pub fn retry_unacknowledged_after(
+        mut self,
+        retry_unacknowledged_after: impl Into<Option<Duration>>,
     ) -> MinecraftConnectionBuilder {
-        MinecraftConnectionBuilder::new(identifier, world_dir)
+        self.retry_unacknowledged_after = retry_unacknowledged_after.into();
+        self
     }
 
-    fn new(
-        identifier: String,
-        world_dir: PathBuf,
-        log_file: PathBuf,
-        enable_logging_automatically: bool,
-    ) -> MinecraftConnection {
-        MinecraftConnection {
-            structures_dir: world_dir
-                .join("generated")
-                .join(NAMESPACE)
-                .join("structures")
-                .join(&identifier),
-            datapack_dir: world_dir.join("datapacks").join(NAMESPACE),
-            identifier,
-            log_file,
-            log_observer: None,
-            loaded_listener_initialized: false,
-            enable_logging_automatically,
-            _private: (),
-        }
+    /// Whether the commands of batches that Minecraft skipped are executed again in a new batch.
+    /// Skipped batches are detected when a later batch is acknowledged (see [BatchSkipped]). The
+    /// commands are executed again before the next batch passed to
+    /// [MinecraftConnection::execute_commands].
+    ///
+    /// This requires [retry_unacknowledged_after](Self::retry_unacknowledged_after), because only
+    /// then the commands of a batch are kept until it is acknowledged.
+    ///
+    /// Default: `false`.
+    pub fn regenerate_skipped_batches(
+        mut self,
+        regenerate_skipped_batches: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.regenerate_skipped_batches = regenerate_skipped_batches.into();
+        self
     }
 
-    /// The connection identifier uniquely identifies a connection building in Minecraft.
-    pub fn get_identifier(&self) -> &str {
-        &self.identifier
+    /// Whether the game time is queried at the start of every batch of commands passed to
+    /// [MinecraftConnection::execute_commands]. If enabled, the [LogObserver] annotates all
+    /// following [LogEvent]s with the queried [gametick](LogEvent::gametick). This allows ordering
+    /// events by game time, because the timestamps in the log file only have a resolution of one
+    /// second.
+    ///
+    /// Default: `false`.
+    pub fn query_gametick(mut self, query_gametick: impl Into<bool>) -> MinecraftConnectionBuilder {
+        self.query_gametick = query_gametick.into();
+        self
     }
 
-    /// The root directory of the datapack used to operate the connection in Minecraft.
-    pub fn get_datapack_dir(&self) -> &Path {
-        &self.datapack_dir
+    /// The [ExecutionStrategy] used by [MinecraftConnection::execute_commands]. Use
+    /// [ExecutionStrategy::Automatic] to execute batches that don't fit into the command block area
+    /// with a function file.
+    ///
+    /// Default: [ExecutionStrategy::CommandBlocks].
+    pub fn execution_strategy(
+        mut self,
+        execution_strategy: impl Into<ExecutionStrategy>,
+    ) -> MinecraftConnectionBuilder {
+        self.execution_strategy = execution_strategy.into();
+        self
     }
 
-    /// This function can be used to set up the connection building in Minecraft, which is required
-    /// for [execute_commands](Self::execute_commands).
+    /// Whether a [CommandResultEvent] is logged for every command passed to
+    /// [MinecraftConnection::execute_commands]. The events can be received with
+    /// [MinecraftConnection::add_command_result_listener]. This works by wrapping every command in
+    /// an `execute store success` command and querying the stored score afterwards, so it reduces
+    /// the [max_commands_per_batch](MinecraftConnection::max_commands_per_batch).
     ///
-    /// If the connection can be established, this function simply returns. Note that this still
-    /// requires a running Minecraft instance. Otherwise this function blocks until a connection
-    /// building is created.
-    /// This function also creates an interactive installer that a player can start by executing
-    /// `/reload` in Minecraft.
+    /// This setting has no effect for [ExecutionStrategy::FunctionFile].
     ///
-    /// Because this function blocks indefinately if the connection can't be established, it should
-    /// be called with [tokio::time::timeout] or some other means of cancellation, such as
-    /// [futures::future::select].
+    /// Default: `false`.
+    pub fn detect_command_results(
+        mut self,
+        detect_command_results: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.detect_command_results = detect_command_results.into();
+        self
+    }
+
+    /// Whether the connection building is protected against players that accidentally break it.
+    /// If enabled, every batch of commands restores the stone around the activator rail and fills
+    /// the air around the connection building with barrier blocks, which can't be broken in survival
+    /// mode. The barrier blocks are removed together with the connection building when
+    /// disconnecting.
     ///
-    /// # Errors
+    /// Default: `false`.
+    pub fn protect_building(
+        mut self,
+        protect_building: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.protect_building = protect_building.into();
+        self
+    }
+
+    /// The Y coordinate at which the connection building is installed by
+    /// [MinecraftConnection::connect]. Command blocks are placed above the connection building, so
+    /// a lower Y coordinate allows more commands per batch. To hide the connection building in
+    /// survival worlds it can be placed at bedrock level (`Y=-63` since Minecraft 1.18) or above the
+    /// area in which players build. Changing this only affects new installations.
     ///
-    /// This function will return an error if the player cancels the installation in the interactive
-    /// installer (can be checked with [ConnectError::is_cancelled]) or if an
-    /// [io::Error](std::io::Error) occurs.
-    pub async fn connect(&mut self) -> Result<(), ConnectError> {
-        connect(self).await
+    /// Default: `1`.
+    pub fn building_y(mut self, building_y: impl Into<i32>) -> MinecraftConnectionBuilder {
+        self.building_y = building_y.into();
+        self
     }
 
-    /// Creates the [Minect datapack](Self::get_datapack_dir()).
-    pub fn create_datapack(&self) -> Result<(), IoErrorAtPath> {
-        macro_rules! extract {
-            ($relative_path:expr) => {
-                extract_datapack_file!(self.datapack_dir, $relative_path)
-            };
-        }
+    /// Whether the connection building is made as invisible as possible. If enabled, the blocks
+    /// protecting the activator rail are barrier blocks instead of stone. Blocks that are required
+    /// for the connection to work, like structure blocks and command blocks, remain visible, so
+    /// this is best combined with [building_y](Self::building_y).
+    ///
+    /// Default: `false`.
+    pub fn hide_building(mut self, hide_building: impl Into<bool>) -> MinecraftConnectionBuilder {
+        self.hide_building = hide_building.into();
+        self
+    }
 
-        extract!("data/minecraft/tags/functions/load.json")?;
-        extract!("data/minecraft/tags/functions/tick.json")?;
-        extract!("data/minect_internal/functions/clean_up.mcfunction")?;
-        extract!("data/minect_internal/functions/connect/align_to_chunk.mcfunction")?;
-        extract!("data/minect_internal/functions/connect/remove_connector.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/clean_up.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/initialize.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/move_and_place_ahead.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/move.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/place_ahead.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/place.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/try_place_facing_east.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/try_place_facing_north.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/try_place_facing_south.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/try_place_facing_west.mcfunction")?;
-        extract!("data/minect_internal/functions/cursor/try_place_facing_z.mcfunction")?;
-        extract!("data/minect_internal/functions/enable_logging_initially.mcfunction")?;
-        extract!("data/minect_internal/functions/load.mcfunction")?;
-        extract!("data/minect_internal/functions/pulse_redstone.mcfunction")?;
-        extract!("data/minect_internal/functions/reload.mcfunction")?;
-        extract!("data/minect_internal/functions/reset_logging_finally.mcfunction")?;
-        extract!("data/minect_internal/functions/tick.mcfunction")?;
-        extract!("data/minect_internal/functions/update.mcfunction")?;
-        extract!("data/minect_internal/functions/v1_uninstall.mcfunction")?;
-        extract!("data/minect_internal/functions/v2_migrate.mcfunction")?;
-        extract!("data/minect_internal/functions/v2_uninstall.mcfunction")?;
-        extract!("data/minect_internal/functions/v3_install.mcfunction")?;
-        extract!("data/minect_internal/functions/v3_uninstall.mcfunction")?;
-        extract!("data/minect_internal/tags/blocks/command_blocks.json")?;
-        extract!("data/minect/functions/connect/choose_chunk.mcfunction")?;
-        extract!("data/minect/functions/disconnect_self.mcfunction")?;
-        extract!("data/minect/functions/disconnect.mcfunction")?;
-        extract!("data/minect/functions/enable_logging.mcfunction")?;
-        extract!("data/minect/functions/prepare_logged_block.mcfunction")?;
-        extract!("data/minect/functions/reset_logging.mcfunction")?;
-        extract!("data/minect/functions/uninstall_completely.mcfunction")?;
-        extract!("data/minect/functions/uninstall.mcfunction")?;
-        extract!("pack.mcmeta")?;
-        Ok(())
+    /// Whether [MinecraftConnection::create_datapack] overwrites an existing Minect datapack that was
+    /// created by a connection with another identifier and differs from the datapack it would
+    /// create. This happens when multiple programs that bundle different versions of Minect use the
+    /// same world. If disabled, such a datapack is left untouched and a [DatapackConflict] is
+    /// returned instead. A datapack created by an older version of this program is always
+    /// overwritten.
+    ///
+    /// Default: `false`.
+    pub fn force_overwrite(
+        mut self,
+        force_overwrite: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.force_overwrite = force_overwrite.into();
+        self
     }
 
-    /// Removes the [Minect datapack](Self::get_datapack_dir()).
-    pub fn remove_datapack(&self) -> Result<(), IoErrorAtPath> {
-        remove_dir_all(&self.datapack_dir)
+    /// Whether the command block chains of every generated structure are checked before the
+    /// structure file is written. A chain command block that is not faced by the command block
+    /// before it would silently break the batch, so instead a [PlacementError] is returned (can be
+    /// checked with [ExecuteCommandsError::is_placement_error]).
+    ///
+    /// Default: `true` in debug builds and `false` in release builds.
+    pub fn validate_placement(
+        mut self,
+        validate_placement: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.validate_placement = validate_placement.into();
+        self
     }
 
-    /// Executes the given `commands` in Minecraft.
+    /// Whether the structure ids of every run of the program get their own namespace. The first
+    /// batch of a run then loads a structure named `run-<n>-0`, where `n` is one greater than the
+    /// run of the previous batch, and all following batches of the run count up from there.
     ///
-    /// # Errors
+    /// This keeps the cleanup of old structure files from reaching into files of another run, when
+    /// runs are started and stopped quickly. Files left behind by a crashed run are easy to
+    /// identify and are removed once a structure of a later run is loaded.
     ///
-    /// This function will return an error if an [io::Error](std::io::Error) occurs.
-    pub fn execute_commands(
-        &mut self,
-        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
-    ) -> Result<(), ExecuteCommandsError> {
-        if !self.datapack_dir.is_dir() {
-            self.create_datapack()?;
-        }
-        if !self.loaded_listener_initialized {
-            self.init_loaded_listener();
-        }
-        create_dir_all(&self.structures_dir)?;
-
-        let id_path = self.structures_dir.join("id.txt");
-        let mut id_file = lock_file(&id_path)?; // Automatically unlocked by dropping id_file at the end of this function.
-
-        let id = read_incremented_id(&mut id_file, &id_path)?;
-        let next_id = id.wrapping_add(1);
-
-        let (commands, commands_len) = add_implicit_commands(
-            commands,
-            &self.identifier,
-            id,
-            self.enable_logging_automatically,
-        );
-        let structure = generate_structure(&self.identifier, next_id, commands, commands_len);
-
-        // To create the structure file as atomically as possible we first write to a temporary file
-        // and then rename it, which is an atomic operation on most operating systems. If Minecraft
-        // would attempt to load a half written file, it would likely cache the file as invalid
-        // (depending on what bytes it sees). Locking the file also causes Minecraft to cache it as
-        // invalid.
-        let tmp_path = self.get_structure_file("tmp");
-        create_structure_file(&tmp_path, structure)?;
-        rename(tmp_path, self.get_structure_file(id))?;
+    /// Default: `false`.
+    pub fn namespace_ids_per_run(
+        mut self,
+        namespace_ids_per_run: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.namespace_ids_per_run = namespace_ids_per_run.into();
+        self
+    }
 
-        // We do this at the end to not increment the id on a failure, which would break the connection.
-        write_id(&mut id_file, id_path, id)?;
+    /// Whether multiple processes may intentionally use the same
+    /// [identifier](MinecraftConnection::builder) at the same time. Otherwise
+    /// [MinecraftConnection::connect] fails if another process executed a batch with this
+    /// identifier within the last minute of game time (can be checked with
+    /// [ConnectError::is_identifier_in_use]). Such processes share the connection building and their
+    /// acknowledgements interleave.
+    ///
+    /// Default: `false`.
+    pub fn share_identifier(
+        mut self,
+        share_identifier: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.share_identifier = share_identifier.into();
+        self
+    }
 
-        Ok(())
+    /// Enables a lease on the identifier that expires after the given duration. When
+    /// [MinecraftConnection::connect] succeeds, the connection owns the identifier until it is
+    /// [released](MinecraftConnection::release_lease) or expires. The lease is renewed by executing
+    /// commands, so an idle owner should call [MinecraftConnection::heartbeat] at least every half
+    /// lease. The lease is acquired before [MinecraftConnection::connect] writes anything to the
+    /// world. An expired lease is only taken over after it was expired for another lease duration,
+    /// which tolerates clocks of different machines differing by up to one lease duration.
+    ///
+    /// What [MinecraftConnection::connect] does if another process owns the identifier is
+    /// configured with [lease_conflict](Self::lease_conflict). With a lease the identifier check of
+    /// [share_identifier](Self::share_identifier) is not performed.
+    ///
+    /// Default: `None`.
+    pub fn lease(mut self, lease: impl Into<Option<Duration>>) -> MinecraftConnectionBuilder {
+        self.lease = lease.into();
+        self
     }
 
-    fn get_structure_file(&self, id: impl Display) -> PathBuf {
-        self.structures_dir.join(format!("{}.nbt", id))
+    /// What [MinecraftConnection::connect] does if another process holds the [lease](Self::lease).
+    ///
+    /// Default: [LeaseConflict::Fail].
+    pub fn lease_conflict(
+        mut self,
+        lease_conflict: impl Into<LeaseConflict>,
+    ) -> MinecraftConnectionBuilder {
+        self.lease_conflict = lease_conflict.into();
+        self
     }
 
-    /// Returns a [Stream] of all [LogEvent]s. To remove the listener simply drop the stream.
+    /// Signs every batch with an HMAC-SHA256 using the given `key`. The MAC covers the connection
+    /// identifier, the id of the batch and all blocks of its structure file. It is stored by a
+    /// marker in the storage `minect:integrity`, so moderators can see in Minecraft which batch was
+    /// executed last. Players with the tag `minect_moderator` are alerted if a batch is executed
+    /// that was not announced by the previous batch, for instance because an old structure file was
+    /// replayed. Structure files and function files can be checked for tampering with
+    /// [signing::verify_structure_file] and [signing::verify_function_file] using the same `key`.
+    /// The marker occupies two command blocks of every batch and requires the function written by
+    /// [MinecraftConnection::create_datapack].
     ///
-    /// Internally the stream is backed by an unbound channel. This means it should be polled
-    /// regularly to avoid memory leaks.
-    pub fn add_listener(&mut self) -> impl Stream<Item = LogEvent> {
-        self.get_log_observer().add_listener()
+    /// Default: `None`.
+    #[cfg(feature = "signing")]
+    pub fn sign_batches(mut self, key: impl Into<Option<Vec<u8>>>) -> MinecraftConnectionBuilder {
+        self.signing_key = key.into();
+        self
     }
 
-    /// Returns a [Stream] of [LogEvent]s with [executor](LogEvent::executor) equal to the given
-    /// `name`. To remove the listener simply drop the stream.
+    /// The permission bits (for example `0o664`) that are set on the files created by this
+    /// connection, such as the datapack and structure files. This is useful when Minecraft runs as
+    /// a different user than this program. If `None` the permissions are determined by the
+    /// operating system (usually via the umask). On platforms other than unix only the owner write
+    /// bit is considered, to mark files as read-only.
+    ///
+    /// Default: `None`.
+    pub fn file_mode(mut self, file_mode: impl Into<Option<u32>>) -> MinecraftConnectionBuilder {
+        self.file_permissions.file_mode = file_mode.into();
+        self
+    }
+
+    /// The permission bits (for example `0o2775`) that are set on the directories created by this
+    /// connection. See [file_mode](Self::file_mode).
+    ///
+    /// Default: `None`.
+    pub fn dir_mode(mut self, dir_mode: impl Into<Option<u32>>) -> MinecraftConnectionBuilder {
+        self.file_permissions.dir_mode = dir_mode.into();
+        self
+    }
+
+    /// The id of the user that should own the files and directories created by this connection.
+    /// This is only a hint: changing the owner usually requires elevated privileges, so if it fails
+    /// the files are left with their default owner. Only supported on unix.
+    ///
+    /// Default: `None`.
+    pub fn file_owner(mut self, uid: impl Into<Option<u32>>) -> MinecraftConnectionBuilder {
+        self.file_permissions.owner = uid.into();
+        self
+    }
+
+    /// The id of the group that should own the files and directories created by this connection.
+    /// Like [file_owner](Self::file_owner) this is only a hint. Only supported on unix.
+    ///
+    /// Default: `None`.
+    pub fn file_group(mut self, gid: impl Into<Option<u32>>) -> MinecraftConnectionBuilder {
+        self.file_permissions.group = gid.into();
+        self
+    }
+
+    /// The [FileBackend] that is used to write the datapack, function files and structure files.
+    /// This allows connecting to a server whose world directory is not mounted locally, for example
+    /// via `SftpFileBackend` (requires the `sftp` feature). All paths, such as the
+    /// [world_dir](MinecraftConnection::builder), are then interpreted by the backend. Only the log
+    /// file is still read from the local file system.
+    ///
+    /// Default: [LocalFileBackend].
+    pub fn file_backend(
+        mut self,
+        file_backend: impl FileBackend + 'static,
+    ) -> MinecraftConnectionBuilder {
+        self.file_backend = Arc::new(file_backend);
+        self
+    }
+
+    /// The offset of the area in which command blocks are placed, relative to the lower north west
+    /// corner of the connection building. The area must stay within the chunk of the connection
+    /// building and must not overlap the building itself at `(0, 0..=5, 0)`. Command blocks are
+    /// also not allowed in the area `(1..=15, 0..=253, 0..=7)` when using
+    /// [logged_block_commands](command::logged_block_commands).
+    ///
+    /// Default: `(0, 0, 8)`.
+    pub fn command_block_offset(
+        mut self,
+        command_block_offset: impl Into<(i32, i32, i32)>,
+    ) -> MinecraftConnectionBuilder {
+        let (x, y, z) = command_block_offset.into();
+        self.command_block_offset = Some(Coordinate3(x, y, z));
+        self
+    }
+
+    /// The size of the area in which command blocks are placed. This limits the number of commands
+    /// that can be executed in a single call to [MinecraftConnection::execute_commands].
+    ///
+    /// Default: The area extends from the [command_block_offset](Self::command_block_offset) to the
+    /// end of the chunk and up to the build height of the
+    /// [minecraft_version](Self::minecraft_version). For the default offset this is `(16, 255, 8)`
+    /// before Minecraft 1.18 and `(16, 319, 8)` since Minecraft 1.18.
+    pub fn command_block_area_size(
+        mut self,
+        command_block_area_size: impl Into<(i32, i32, i32)>,
+    ) -> MinecraftConnectionBuilder {
+        let (x, y, z) = command_block_area_size.into();
+        self.command_block_area_size = Some(Coordinate3(x, y, z));
+        self
+    }
+
+    /// The version of Minecraft that is connected to. This is used to place command blocks up to
+    /// the build height limit of the version. Command blocks are placed above the connection
+    /// building (see [building_y](Self::building_y)), so the area below it can not be used. The
+    /// version also determines the directory layout and `pack_format` of the
+    /// [datapack](MinecraftConnection::create_datapack).
+    ///
+    /// Default: `None`, which assumes the limits of Minecraft before 1.18.
+    pub fn minecraft_version(
+        mut self,
+        minecraft_version: impl Into<MinecraftVersion>,
+    ) -> MinecraftConnectionBuilder {
+        self.minecraft_version = Some(minecraft_version.into());
+        self
+    }
+
+    /// The chunk in which the connection building is installed by [MinecraftConnection::connect].
+    /// If set, the interactive installer only asks the player to confirm this chunk instead of
+    /// letting them choose one. The chunk coordinates are the block coordinates divided by 16, so
+    /// for example the chunk `(-1, 2)` contains the blocks from `(-16, 32)` to `(-1, 47)`.
+    ///
+    /// Default: `None`.
+    pub fn install_at(mut self, chunk_x: i32, chunk_z: i32) -> MinecraftConnectionBuilder {
+        self.install_at = Some((chunk_x, chunk_z));
+        self
+    }
+
+    /// The language of the chat messages shown by the interactive installer. Either one of the
+    /// bundled [Lang](installer::Lang)uages or custom [Translations].
+    ///
+    /// Default: [Lang::English](installer::Lang::English).
+    pub fn installer_language(
+        mut self,
+        installer_language: impl Into<Translations>,
+    ) -> MinecraftConnectionBuilder {
+        self.installer_texts.translations = installer_language.into();
+        self
+    }
+
+    /// The name of the application that is shown to the player by the interactive installer, for
+    /// example `"MyCompiler"`.
+    ///
+    /// If not set, the application is referred to as
+    /// [ExternalApplication](installer::InstallerMessage::ExternalApplication).
+    pub fn installer_title(
+        mut self,
+        installer_title: impl Into<String>,
+    ) -> MinecraftConnectionBuilder {
+        self.installer_texts.title = Some(installer_title.into());
+        self
+    }
+
+    /// A short explanation that is shown to the player by the interactive installer, for example
+    /// why the application needs a connection to Minecraft.
+    ///
+    /// Default: `None`.
+    pub fn installer_description(
+        mut self,
+        installer_description: impl Into<String>,
+    ) -> MinecraftConnectionBuilder {
+        self.installer_texts.description = Some(installer_description.into());
+        self
+    }
+
+    /// The gzip compression level of structure files from `0` (no compression) to `9` (best
+    /// compression). For a local world a low level like `1` saves CPU time, while a higher level
+    /// reduces the amount of data that has to be written, for instance to a network file system.
+    ///
+    /// Default: `6`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `compression_level` is greater than `9`.
+    pub fn compression_level(
+        mut self,
+        compression_level: impl Into<u32>,
+    ) -> MinecraftConnectionBuilder {
+        let compression_level = compression_level.into();
+        assert!(
+            compression_level <= 9,
+            "Invalid compression level: {}",
+            compression_level
+        );
+        self.compression_level = compression_level;
+        self
+    }
+
+    /// If set, every batch sets the score of `update_delay` for the objective `minect_config` to
+    /// this value. This is the number of game ticks between two updates of the connection building,
+    /// so `1` minimizes the time until a structure file is loaded. Note that the score applies to all
+    /// connections in the Minecraft world.
+    ///
+    /// Default: `None`, which keeps the configuration of the world (`1` after the installation).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `update_delay` is `0`.
+    pub fn update_delay(
+        mut self,
+        update_delay: impl Into<Option<u32>>,
+    ) -> MinecraftConnectionBuilder {
+        let update_delay = update_delay.into();
+        assert!(update_delay != Some(0), "Invalid update delay: 0");
+        self.update_delay = update_delay;
+        self
+    }
+
+    /// If set, the connection building of this connection is updated with its own delay in game
+    /// ticks, which is adjusted within these bounds to the load of the connection. While batches
+    /// are waiting to be loaded or take long to be loaded, the delay is lowered to the minimum for
+    /// low latency. After no batch was executed for
+    /// [adaptive_update_delay_idle_after](Self::adaptive_update_delay_idle_after), Minecraft raises
+    /// the delay to the maximum to reduce the overhead of an idle connection. Changes can be
+    /// received with [MinecraftConnection::add_update_delay_listener].
+    ///
+    /// Unlike a fixed [update_delay](Self::update_delay), which this overrides, the delay does not
+    /// affect other connections in the Minecraft world. The connection building keeps its own delay
+    /// until it is disconnected. Every batch ends with four commands that update the delay.
+    ///
+    /// Default: `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounds are empty or include `0`.
+    pub fn adaptive_update_delay(
+        mut self,
+        bounds: impl Into<Option<RangeInclusive<u32>>>,
+    ) -> MinecraftConnectionBuilder {
+        let bounds = bounds.into();
+        if let Some(bounds) = &bounds {
+            assert!(
+                !bounds.is_empty() && *bounds.start() > 0,
+                "Invalid update delay bounds: {:?}",
+                bounds
+            );
+        }
+        self.adaptive_update_delay = bounds;
+        self
+    }
+
+    /// How long no batch has to be executed before the
+    /// [adaptive_update_delay](Self::adaptive_update_delay) is raised to its maximum.
+    ///
+    /// Default: 5 seconds.
+    pub fn adaptive_update_delay_idle_after(
+        mut self,
+        idle_after: impl Into<Duration>,
+    ) -> MinecraftConnectionBuilder {
+        self.adaptive_update_delay_idle_after = idle_after.into();
+        self
+    }
+
+    /// How often Minecraft's log file is checked for new lines if the operating system does not
+    /// report a change. A shorter interval reduces the time until the output of commands is
+    /// received, but costs CPU time.
+    ///
+    /// Default: 50 milliseconds (one game tick).
+    pub fn log_poll_interval(
+        mut self,
+        log_poll_interval: impl Into<Duration>,
+    ) -> MinecraftConnectionBuilder {
+        self.log_poll_interval = log_poll_interval.into();
+        self
+    }
+
+    /// Only every `acknowledge_every`th batch is acknowledged by the connection building, which
+    /// saves a command and a line in the log file for all other batches. Structure files of batches
+    /// that are not acknowledged are removed once a later batch is acknowledged. To avoid waiting
+    /// for them, the [spool_dir](Self::spool_dir) holds back batches only if more than
+    /// `acknowledge_every` structure files are pending.
+    ///
+    /// So that no structure files remain after the last batch, the batch that empties the
+    /// [spool_dir](Self::spool_dir), the last chunk of a
+    /// [bulk job](MinecraftConnection::start_bulk_job) and a
+    /// [heartbeat](MinecraftConnection::heartbeat) are always acknowledged. Without a spool, call
+    /// [heartbeat](MinecraftConnection::heartbeat) after the last batch to remove the structure
+    /// files of the batches before it.
+    ///
+    /// Batches with a [deadline](MinecraftConnection::execute_commands_with_deadline) are always
+    /// acknowledged. If [retry_unacknowledged_after](Self::retry_unacknowledged_after) is set, all
+    /// batches are acknowledged and this setting has no effect.
+    ///
+    /// Default: `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `acknowledge_every` is `0`.
+    pub fn acknowledge_every(
+        mut self,
+        acknowledge_every: impl Into<u64>,
+    ) -> MinecraftConnectionBuilder {
+        let acknowledge_every = acknowledge_every.into();
+        assert!(acknowledge_every > 0, "Invalid acknowledgement interval: 0");
+        self.acknowledge_every = acknowledge_every;
+        self
+    }
+
+    /// Tunes the connection to minimize the round-trip time of commands, for interactive
+    /// applications like live music sequencers. This is a shortcut for:
+    /// * an [update_delay](Self::update_delay) of `1`, so the connection building looks for
+    ///   structure files every game tick,
+    /// * a [compression_level](Self::compression_level) of `0`. Minecraft only accepts gzipped
+    ///   structure files, so they are still written in the gzip format, but without compressing
+    ///   the data,
+    /// * a [log_poll_interval](Self::log_poll_interval) of 5 milliseconds.
+    ///
+    /// Structure files only contain the command blocks needed for the commands of a batch, so
+    /// executing fewer commands per call to [MinecraftConnection::execute_commands] also results in
+    /// smaller structures.
+    ///
+    /// Each of these settings can be overridden by calling the respective method afterwards.
+    pub fn low_latency(self) -> MinecraftConnectionBuilder {
+        self.update_delay(1)
+            .compression_level(0u32)
+            .log_poll_interval(Duration::from_millis(5))
+    }
+
+    /// If set, [MinecraftConnection::execute_commands] does not write structure files directly, but
+    /// appends the commands to a queue in this directory. Batches are only written as structure files
+    /// while less than [max_pending_structures](Self::max_pending_structures) structure files are
+    /// waiting to be loaded by Minecraft. The remaining batches are written by
+    /// [MinecraftConnection::process_spool] and by later calls to
+    /// [execute_commands](MinecraftConnection::execute_commands).
+    ///
+    /// This allows a program to produce batches faster than Minecraft can execute them without
+    /// flooding the structures directory. Because the queue is stored on disk, batches that were not
+    /// written before the program exits are written by the next program using the same directory.
+    /// If a program exits right after writing a structure file, the batch may be executed twice.
+    ///
+    /// Commands executed internally, for instance by [eval_int](MinecraftConnection::eval_int), are
+    /// not queued and may therefore be executed before queued batches.
+    ///
+    /// Default: `None`.
+    pub fn spool_dir(mut self, spool_dir: impl Into<PathBuf>) -> MinecraftConnectionBuilder {
+        self.spool_dir = Some(spool_dir.into());
+        self
+    }
+
+    /// Encrypts batches in the [spool_dir](Self::spool_dir) with ChaCha20-Poly1305 using the given
+    /// 256 bit `key`, so queued commands are not stored in plaintext in the world directory. Batches
+    /// are decrypted when they are written as structure files, which Minecraft can only read in
+    /// plaintext.
+    ///
+    /// Batches that were spooled without a key can still be read. Spooled batches that can't be
+    /// decrypted with the key cause an error when they are flushed.
+    ///
+    /// Default: `None`.
+    #[cfg(feature = "encryption")]
+    pub fn encrypt_spool(mut self, key: impl Into<Option<[u8; 32]>>) -> MinecraftConnectionBuilder {
+        self.spool_key = key.into();
+        self
+    }
+
+    /// The maximum number of structure files that are waiting to be loaded by Minecraft before
+    /// batches in the [spool_dir](Self::spool_dir) are held back.
+    ///
+    /// Default: `4`.
+    pub fn max_pending_structures(
+        mut self,
+        max_pending_structures: impl Into<usize>,
+    ) -> MinecraftConnectionBuilder {
+        self.max_pending_structures = max_pending_structures.into();
+        self
+    }
+
+    /// Whether consecutive batches in the [spool_dir](Self::spool_dir) that use the same
+    /// [ExecutionStrategy] are written as a single structure file. Batches are only merged if the
+    /// result can still be executed, so for [ExecutionStrategy::FunctionFile] any number of batches
+    /// is merged, while for other strategies the merged batch must not exceed
+    /// [max_commands_per_batch](MinecraftConnection::max_commands_per_batch).
+    ///
+    /// Default: `false`.
+    pub fn merge_spooled_batches(
+        mut self,
+        merge_spooled_batches: impl Into<bool>,
+    ) -> MinecraftConnectionBuilder {
+        self.merge_spooled_batches = merge_spooled_batches.into();
+        self
+    }
+
+    /// A policy that decides for every [Command] passed to [MinecraftConnection::execute_commands]
+    /// whether it may be executed. If the policy denies any command of a batch, the whole batch is
+    /// rejected with an error (can be checked with [ExecuteCommandsError::is_denied]).
+    ///
+    /// This is useful to restrict what user provided scripts can do in Minecraft. The policy also
+    /// applies to the commands that are generated by helpers like
+    /// [teleport](MinecraftConnection::teleport) and [eval_int](MinecraftConnection::eval_int), but
+    /// not to the commands Minect executes internally.
+    ///
+    /// Default: `None`, meaning all commands are allowed.
+    pub fn command_policy(
+        mut self,
+        command_policy: impl Fn(&Command) -> PolicyDecision + Send + Sync + 'static,
+    ) -> MinecraftConnectionBuilder {
+        self.command_policy = Some(Arc::new(command_policy));
+        self
+    }
+
+    /// Tunes the connection for throughput, for jobs like world generation that execute a lot of
+    /// commands and care about the total time rather than the latency of individual commands. This
+    /// is a shortcut for:
+    /// * the [execution_strategy](Self::execution_strategy) [ExecutionStrategy::Automatic], which
+    ///   executes batches that don't fit into the command block area with a single structure file.
+    ///   Smaller batches are still placed in command blocks, because a function file requires
+    ///   Minecraft to reload all datapacks,
+    /// * [acknowledge_every](Self::acknowledge_every) 8th batch,
+    /// * [merge_spooled_batches](Self::merge_spooled_batches), which combines queued batches into
+    ///   as few structure files as possible if a [spool_dir](Self::spool_dir) is configured.
+    ///
+    /// Each of these settings can be overridden by calling the respective method afterwards.
+    pub fn bulk_mode(self) -> MinecraftConnectionBuilder {
+        self.execution_strategy(ExecutionStrategy::Automatic)
+            .acknowledge_every(8u64)
+            .merge_spooled_batches(true)
+    }
+
+    /// Creates a [MinecraftConnection] with the configured parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no [log_file](Self::log_file()) was specified and the
+    /// [world_dir](MinecraftConnection::builder) has too few path compontents to resolve the log
+    /// file of the [hosting_layout](Self::hosting_layout), for example less than 2 for the default
+    /// value of `../../logs/latest.log`.
+    ///
+    /// Panics if the [command_block_offset](Self::command_block_offset) and
+    /// [command_block_area_size](Self::command_block_area_size) describe an invalid area.
+    pub fn build(self) -> MinecraftConnection {
+        MinecraftConnection::new(self)
+    }
+}
+
+#[cfg(feature = "connection")]
+fn validate_identifier(identifier: &str) {
+    let invalid_chars = identifier
+        .chars()
+        .filter(|c| !is_allowed_in_identifier(*c))
+        .collect::<IndexSet<_>>();
+    if !invalid_chars.is_empty() {
+        panic!(
+            "Invalid characters in MinecraftConnection.identifier: '{}'",
+            invalid_chars
+                .iter()
+                .fold(String::new(), |joined, c| joined + &c.to_string())
+        );
+    }
+}
+#[cfg(feature = "connection")]
+fn is_allowed_in_identifier(c: char) -> bool {
+    return c >= '0' && c <= '9'
+        || c >= 'A' && c <= 'Z'
+        || c >= 'a' && c <= 'z'
+        || c == '+'
+        || c == '-'
+        || c == '.'
+        || c == '_';
+}
+
+#[cfg(feature = "connection")]
+fn log_file_from_world_dir(layout: &HostingLayout, world_dir: &Path) -> PathBuf {
+    layout.log_file(world_dir).unwrap_or_else(|| {
+        panic!(
+            "Expected world_dir to match {:?}, but was: {}",
+            layout,
+            world_dir.display()
+        )
+    })
+}
+
+#[cfg(feature = "connection")]
+macro_rules! extract_datapack_file {
+    ($output_path:expr, $layout:expr, $relative_path:expr) => {{
+        let path = $layout.join(&$output_path, $relative_path);
+        let contents = include_datapack_template!($relative_path);
+        (path, contents.to_string())
+    }};
+}
+
+/// A connection to Minecraft that can [execute commands](MinecraftConnection::execute_commands) in
+/// Minecraft and [listen for command output](MinecraftConnection::add_listener).
+///
+/// If you need to listen for command output, but don't need to execute commands, consider using
+/// [LogObserver] directly.
+///
+/// The connection requires a building in Minecraft which continuously loads structure files that
+/// contain the commands passed to [execute_commands](MinecraftConnection::execute_commands). To
+/// create such a connection building you can call [connect](MinecraftConnection::connect).
+///
+/// Minect supports operating multiple connection buildings in parallel, each with a unique
+/// identifier. A single connection building can be shared between any number of Rust programs, but
+/// it has a limited update frequency. For optimal performance every Rust program can use a
+/// different connection identifier.
+///
+/// The update frequency can be configured globally for all connections in a Minecraft world by
+/// changing the score of `update_delay` for the objective `minect_config`.
+#[cfg(feature = "connection")]
+pub struct MinecraftConnection {
+    identifier: String,
+    structures_dir: PathBuf,
+    datapack_dir: PathBuf,
+    world_dir: PathBuf,
+    log_file: PathBuf,
+    log_format: LogFormat,
+    hosting_layout: Option<HostingLayout>,
+    remote_log_source: Option<Arc<dyn RemoteLogSource>>,
+    log_observer: Option<LogObserver>,
+    loaded_listener_initialized: bool,
+    enable_logging_automatically: bool,
+    log_volume: Arc<LogVolumeMeter>,
+    query_gametick: bool,
+    minecraft_version: Option<MinecraftVersion>,
+    command_block_area: CommandBlockArea,
+    install_at: Option<(i32, i32)>,
+    installer_texts: InstallerTexts,
+    execution_strategy: ExecutionStrategy,
+    detect_command_results: bool,
+    protect_building: bool,
+    building_y: i32,
+    hide_building: bool,
+    force_overwrite: bool,
+    validate_placement: bool,
+    namespace_ids_per_run: bool,
+    /// The run that namespaces the structure ids of this connection, determined by the first batch
+    /// (see [namespace_ids_per_run](MinecraftConnectionBuilder::namespace_ids_per_run)).
+    run: Option<u32>,
+    share_identifier: bool,
+    /// The random id that is recorded by every batch once [connect](Self::connect) succeeded.
+    session: Option<i32>,
+    #[cfg(feature = "signing")]
+    batch_signer: Option<Arc<BatchSigner>>,
+    lease: Option<Lease>,
+    lease_conflict: LeaseConflict,
+    /// When this connection last acquired or renewed the [lease](Self::lease).
+    lease_renewed: Option<Instant>,
+    /// Set when another process holds the [lease](Self::lease).
+    read_only: bool,
+    file_permissions: FilePermissions,
+    file_backend: Arc<dyn FileBackend>,
+    unacknowledged_batches: Option<Arc<UnacknowledgedBatches>>,
+    skipped_batches: Arc<SkippedBatches>,
+    expiring_batches: Arc<ExpiringBatches>,
+    loaded_batches: Arc<LoadedBatches>,
+    structures_watcher: Option<RecommendedWatcher>,
+    warnings: Arc<Warnings>,
+    connect_progress_listeners: RwLock<Vec<UnboundedSender<ConnectProgress>>>,
+    urgent_lane: Option<Box<MinecraftConnection>>,
+    /// Builds the [urgent lane](Self::execute_urgent) when it is first used.
+    urgent_lane_builder: Option<Box<MinecraftConnectionBuilder>>,
+    compression: Compression,
+    update_delay: Option<u32>,
+    update_delay_controller: Option<Arc<UpdateDelayController>>,
+    log_poll_interval: Duration,
+    acknowledge_every: u64,
+    spool: Option<Spool>,
+    merge_spooled_batches: bool,
+    command_policy: Option<CommandPolicy>,
+    paused: bool,
+    _private: (),
+}
+
+#[cfg(feature = "connection")]
+const NAMESPACE: &str = "minect";
+/// The namespace of the structures loaded by [MinecraftConnection::load_structure], so they are
+/// never mistaken for batches.
+#[cfg(feature = "connection")]
+const CUSTOM_STRUCTURES_NAMESPACE: &str = "minect_custom";
+
+#[cfg(feature = "connection")]
+impl MinecraftConnection {
+    /// Creates a [MinecraftConnectionBuilder].
+    ///
+    /// `identifier` is a string that uniquely identifies a connection building in Minecraft.
+    /// Because it is used with Minecraft's `tag` command, it may only contain the following
+    /// Characters: `0-9`, `A-Z`, `a-z`, `+`, `-`, `.` & `_`.
+    ///
+    /// `world_dir` is the directory containing the Minecraft world to connect to.
+    /// For single player this is typically a directory within the saves directory:
+    /// * Windows: `C:\Users\Herobrine\AppData\Roaming\.minecraft\saves\`
+    /// * GNU/Linux: `~/.minecraft/saves/`
+    /// * Mac: `~/Library/Application Support/minecraft/saves/`
+    ///
+    /// For servers it is specified in `server.properties`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `identifier` contains an invalid character.
+    pub fn builder(
+        identifier: impl Into<String>,
+        world_dir: impl Into<PathBuf>,
+    ) -> MinecraftConnectionBuilder {
+        MinecraftConnectionBuilder::new(identifier, world_dir)
+    }
+
+    fn new(builder: MinecraftConnectionBuilder) -> MinecraftConnection {
+        let urgent_lane_builder = Box::new(builder.urgent_lane());
+        if let Err(message) = validate_building_y(builder.minecraft_version, builder.building_y) {
+            panic!("Invalid building Y coordinate: {}", message);
+        }
+        let command_block_area = CommandBlockArea::new(
+            builder.minecraft_version,
+            builder.building_y,
+            builder.command_block_offset,
+            builder.command_block_area_size,
+        );
+        if let Err(message) =
+            command_block_area.validate(builder.minecraft_version, builder.building_y)
+        {
+            panic!("Invalid command block area: {}", message);
+        }
+        let layout = builder.hosting_layout.clone().unwrap_or_default();
+        let world_dir = layout.world_dir(&builder.world_dir);
+        let log_file = builder
+            .log_file
+            .unwrap_or_else(|| log_file_from_world_dir(&layout, &builder.world_dir));
+        let compression = Compression::new(builder.compression_level);
+        // Retrying requires an acknowledgement for every batch
+        let acknowledge_every = match builder.retry_unacknowledged_after {
+            Some(_) => 1,
+            None => builder.acknowledge_every,
+        };
+        let structures_dir = world_dir
+            .join("generated")
+            .join(NAMESPACE)
+            .join("structures")
+            .join(&builder.identifier);
+        let lease = builder
+            .lease
+            .map(|duration| Lease::new(&structures_dir, duration));
+        let update_delay_controller = builder.adaptive_update_delay.clone().map(|bounds| {
+            Arc::new(UpdateDelayController::new(
+                &builder.identifier,
+                bounds,
+                builder.adaptive_update_delay_idle_after,
+                acknowledge_every,
+            ))
+        });
+        MinecraftConnection {
+            structures_dir,
+            datapack_dir: layout.datapacks_dir(&world_dir).join(NAMESPACE),
+            identifier: builder.identifier,
+            log_file,
+            world_dir,
+            log_format: builder.log_format.unwrap_or_else(|| layout.log_format()),
+            hosting_layout: builder.hosting_layout,
+            remote_log_source: builder.remote_log_source,
+            log_observer: None,
+            loaded_listener_initialized: false,
+            enable_logging_automatically: builder.enable_logging_automatically,
+            log_volume: Arc::new(LogVolumeMeter::new(
+                builder.log_volume_threshold,
+                builder.log_volume_action,
+            )),
+            query_gametick: builder.query_gametick,
+            minecraft_version: builder.minecraft_version,
+            command_block_area,
+            install_at: builder.install_at,
+            installer_texts: builder.installer_texts,
+            execution_strategy: builder.execution_strategy,
+            detect_command_results: builder.detect_command_results,
+            protect_building: builder.protect_building,
+            building_y: builder.building_y,
+            hide_building: builder.hide_building,
+            force_overwrite: builder.force_overwrite,
+            validate_placement: builder.validate_placement,
+            namespace_ids_per_run: builder.namespace_ids_per_run,
+            run: None,
+            share_identifier: builder.share_identifier,
+            session: None,
+            #[cfg(feature = "signing")]
+            batch_signer: builder
+                .signing_key
+                .map(|key| Arc::new(BatchSigner::new(key))),
+            lease,
+            lease_conflict: builder.lease_conflict,
+            lease_renewed: None,
+            read_only: false,
+            file_permissions: builder.file_permissions,
+            file_backend: builder.file_backend,
+            unacknowledged_batches: builder
+                .retry_unacknowledged_after
+                .map(|retry_after| Arc::new(UnacknowledgedBatches::new(retry_after))),
+            skipped_batches: Arc::new(SkippedBatches::new(
+                builder.regenerate_skipped_batches,
+                acknowledge_every,
+            )),
+            expiring_batches: Arc::new(ExpiringBatches::new(compression)),
+            loaded_batches: Arc::new(LoadedBatches::new()),
+            structures_watcher: None,
+            warnings: Arc::new(Warnings::default()),
+            connect_progress_listeners: RwLock::new(Vec::new()),
+            urgent_lane: None,
+            urgent_lane_builder: Some(urgent_lane_builder),
+            compression,
+            update_delay: builder
+                .update_delay
+                .filter(|_| builder.adaptive_update_delay.is_none()),
+            update_delay_controller,
+            log_poll_interval: builder.log_poll_interval,
+            acknowledge_every,
+            spool: builder.spool_dir.map(|dir| {
+                let spool = Spool::new(
+                    dir,
+                    builder
+                        .max_pending_structures
+                        .max(acknowledge_every as usize),
+                );
+                #[cfg(feature = "encryption")]
+                let spool = spool.encrypted(builder.spool_key.map(encryption::SpoolKey::new));
+                spool
+            }),
+            merge_spooled_batches: builder.merge_spooled_batches,
+            command_policy: builder.command_policy,
+            paused: false,
+            _private: (),
+        }
+    }
+
+    /// Turns this connection into a [MinecraftObserver], which can only listen to the log file and
+    /// has no methods that write to the world directory.
+    pub fn observer_only(self) -> MinecraftObserver {
+        MinecraftObserver::new(self)
+    }
+
+    /// The connection identifier uniquely identifies a connection building in Minecraft.
+    pub fn get_identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The root directory of the datapack used to operate the connection in Minecraft.
+    pub fn get_datapack_dir(&self) -> &Path {
+        &self.datapack_dir
+    }
+
+    /// The volume of log output produced by the batches of this connection. Log output is only
+    /// measured while the connection observes the log file, which starts with the first batch or
+    /// listener.
+    pub fn log_volume(&self) -> LogVolume {
+        self.log_volume.volume()
+    }
+
+    /// This function can be used to set up the connection building in Minecraft, which is required
+    /// for [execute_commands](Self::execute_commands).
+    ///
+    /// If the connection can be established, this function simply returns. Note that this still
+    /// requires a running Minecraft instance. Otherwise this function blocks until a connection
+    /// building is created.
+    /// This function also creates an interactive installer that a player can start by executing
+    /// `/reload` in Minecraft. The chunk for the connection building can be preselected with
+    /// [install_at](MinecraftConnectionBuilder::install_at).
+    ///
+    /// If the world directory or the log file does not exist yet, for example because the world is
+    /// still being generated, this function waits for them. Missing directories inside the world,
+    /// such as `datapacks`, are created. The progress can be observed with
+    /// [add_connect_progress_listener](Self::add_connect_progress_listener).
+    ///
+    /// Because this function blocks indefinately if the connection can't be established, it should
+    /// be called with [tokio::time::timeout] or some other means of cancellation, such as
+    /// [futures::future::select].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the player cancels the installation in the interactive
+    /// installer (can be checked with [ConnectError::is_cancelled]), if the player confirms the
+    /// installer in a world with a different seed than the world directory (can be checked with
+    /// [ConnectError::is_wrong_world]), if another process actively uses the same identifier (can
+    /// be checked with [ConnectError::is_identifier_in_use], see
+    /// [share_identifier](MinecraftConnectionBuilder::share_identifier)), if another process takes
+    /// over the [lease](MinecraftConnectionBuilder::lease) while connecting (can be checked with
+    /// [ConnectError::is_read_only]) or if an [io::Error](std::io::Error) occurs.
+    pub async fn connect(&mut self) -> Result<(), ConnectError> {
+        connect(self).await
+    }
+
+    /// Like [connect](Self::connect), but if the connection can't be established right away, the
+    /// `printer` is called once with [ConnectInstructions] that tell the player how to install the
+    /// connection building. They are generated from the configuration of this connection, so they
+    /// name the world, the reload command of the
+    /// [hosting_layout](MinecraftConnectionBuilder::hosting_layout) and the message of the
+    /// interactive installer.
+    ///
+    /// ```no_run
+    /// # use minect::*;
+    /// # async fn doc(mut connection: MinecraftConnection) -> Result<(), ConnectError> {
+    /// connection
+    ///     .connect_or_instruct(|instructions| println!("{}", instructions))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [connect](Self::connect).
+    pub async fn connect_or_instruct(
+        &mut self,
+        printer: impl FnOnce(&ConnectInstructions),
+    ) -> Result<(), ConnectError> {
+        connect_or_instruct(self, printer).await
+    }
+
+    /// Returns a [Stream] of [ConnectProgress] events, which report what
+    /// [connect](Self::connect) is waiting for. For instance a freshly created world may not exist
+    /// yet, in which case [connect](Self::connect) waits for it with an increasing delay.
+    pub fn add_connect_progress_listener(&self) -> impl Stream<Item = ConnectProgress> {
+        let (sender, receiver) = unbounded_channel();
+        self.connect_progress_listeners
+            .write()
+            .unwrap()
+            .push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    pub(crate) fn report_connect_progress(&self, progress: ConnectProgress) {
+        let mut listeners = self.connect_progress_listeners.write().unwrap();
+        listeners.retain(|listener| listener.send(progress.clone()).is_ok());
+    }
+
+    /// Creates the [Minect datapack](Self::get_datapack_dir()).
+    ///
+    /// The directory layout and `pack_format` of the datapack are chosen according to the
+    /// [minecraft_version](MinecraftConnectionBuilder::minecraft_version), so the game loads the
+    /// datapack. Without a version the layout of an existing datapack is kept.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an existing datapack differs from the datapack that
+    /// would be created, unless [force_overwrite](MinecraftConnectionBuilder::force_overwrite) is
+    /// enabled (can be checked with [CreateDatapackError::is_conflict]), or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn create_datapack(&self) -> Result<(), CreateDatapackError> {
+        let layout = match self.minecraft_version {
+            Some(version) => DatapackLayout::for_version(version),
+            None => DatapackLayout::detect(&*self.file_backend, &self.datapack_dir),
+        };
+        let mut files = Vec::new();
+        macro_rules! extract {
+            ($relative_path:expr) => {
+                files.push(extract_datapack_file!(
+                    self.datapack_dir,
+                    layout,
+                    $relative_path
+                ))
+            };
+        }
+
+        extract!("data/minecraft/tags/functions/load.json");
+        extract!("data/minecraft/tags/functions/tick.json");
+        extract!("data/minect_internal/functions/adaptive_pulse.mcfunction");
+        extract!("data/minect_internal/functions/adaptive_update.mcfunction");
+        extract!("data/minect_internal/functions/clean_up.mcfunction");
+        extract!("data/minect_internal/functions/connect/align_to_chunk.mcfunction");
+        extract!("data/minect_internal/functions/connect/remove_connector.mcfunction");
+        extract!("data/minect_internal/functions/cursor/clean_up.mcfunction");
+        extract!("data/minect_internal/functions/cursor/initialize.mcfunction");
+        extract!("data/minect_internal/functions/cursor/move_and_place_ahead.mcfunction");
+        extract!("data/minect_internal/functions/cursor/move.mcfunction");
+        extract!("data/minect_internal/functions/cursor/place_ahead.mcfunction");
+        extract!("data/minect_internal/functions/cursor/place.mcfunction");
+        extract!("data/minect_internal/functions/cursor/try_place_facing_east.mcfunction");
+        extract!("data/minect_internal/functions/cursor/try_place_facing_north.mcfunction");
+        extract!("data/minect_internal/functions/cursor/try_place_facing_south.mcfunction");
+        extract!("data/minect_internal/functions/cursor/try_place_facing_west.mcfunction");
+        extract!("data/minect_internal/functions/cursor/try_place_facing_z.mcfunction");
+        extract!("data/minect_internal/functions/enable_logging_initially.mcfunction");
+        extract!("data/minect_internal/functions/load.mcfunction");
+        extract!("data/minect_internal/functions/pulse_redstone.mcfunction");
+        extract!("data/minect_internal/functions/reload.mcfunction");
+        extract!("data/minect_internal/functions/reset_logging_finally.mcfunction");
+        extract!("data/minect_internal/functions/tick.mcfunction");
+        extract!("data/minect_internal/functions/update.mcfunction");
+        extract!("data/minect_internal/functions/v1_uninstall.mcfunction");
+        extract!("data/minect_internal/functions/v2_migrate.mcfunction");
+        extract!("data/minect_internal/functions/v2_uninstall.mcfunction");
+        extract!("data/minect_internal/functions/v3_install.mcfunction");
+        extract!("data/minect_internal/functions/v3_uninstall.mcfunction");
+        extract!("data/minect_internal/tags/blocks/command_blocks.json");
+        // The function tag contains pending batches, so it must not be overwritten
+        let backend = &*self.file_backend;
+        if !backend.is_file(&layout.join(&self.datapack_dir, BATCH_TAG)) {
+            extract!("data/minect_internal/tags/functions/batch.json");
+        }
+        // The function tag contains purged identifiers, so it must not be overwritten
+        if !backend.is_file(&layout.join(&self.datapack_dir, PURGE_TAG)) {
+            extract!("data/minect_internal/tags/functions/purge.json");
+        }
+        // The function tag contains the objectives of all connections, so it must not be overwritten
+        if !backend.is_file(&layout.join(&self.datapack_dir, REMOVE_OBJECTIVES_TAG)) {
+            extract!("data/minect_internal/tags/functions/remove_objectives.json");
+        }
+        extract!("data/minect/functions/connect/choose_chunk.mcfunction");
+        extract!("data/minect/functions/disconnect_self.mcfunction");
+        extract!("data/minect/functions/disconnect.mcfunction");
+        extract!("data/minect/functions/enable_logging.mcfunction");
+        extract!("data/minect/functions/prepare_logged_block.mcfunction");
+        extract!("data/minect/functions/reset_logging.mcfunction");
+        extract!("data/minect/functions/reset_silence.mcfunction");
+        extract!("data/minect/functions/silence.mcfunction");
+        extract!("data/minect/functions/uninstall_completely.mcfunction");
+        extract!("data/minect/functions/uninstall.mcfunction");
+        let pack_mcmeta = include_datapack_template!("pack.mcmeta")
+            .replace(
+                "-pack_format-",
+                &pack_format(self.minecraft_version).to_string(),
+            )
+            .replace("-description-", &pack_description(&self.identifier));
+        files.push((self.datapack_dir.join("pack.mcmeta"), pack_mcmeta));
+        #[cfg(feature = "signing")]
+        if self.batch_signer.is_some() {
+            let path = format!(
+                "data/minect_internal/functions/connection/{}/verify_integrity.mcfunction",
+                self.identifier
+            );
+            let contents = include_datapack_template!(
+                "data/minect_internal/functions/connection/-connection_id-/verify_integrity.mcfunction"
+            );
+            files.push((
+                layout.join(&self.datapack_dir, &path),
+                contents.replace("-connection_id-", &self.identifier),
+            ));
+        }
+
+        if !self.force_overwrite {
+            let datapack_dir = &self.datapack_dir;
+            let conflict = DatapackConflict::find(backend, datapack_dir, &self.identifier, &files);
+            if let Some(conflict) = conflict {
+                let inner = CreateDatapackErrorInner::Conflict(conflict);
+                return Err(CreateDatapackError::new(inner));
+            }
+        }
+        for (path, contents) in files {
+            write_with_permissions(backend, &path, &contents, &self.file_permissions)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the [Minect datapack](Self::get_datapack_dir()).
+    pub fn remove_datapack(&self) -> Result<(), IoErrorAtPath> {
+        self.file_backend
+            .remove_dir_all(&self.datapack_dir)
+            .map_err(io_error("Failed to remove directory", &self.datapack_dir))
+    }
+
+    /// Uninstalls Minect completely, like a player executing `function minect:uninstall_completely`,
+    /// and waits until Minecraft confirms that the uninstall function was executed. This removes
+    /// all connections and objectives of Minect from Minecraft and disables the Minect datapack.
+    /// Afterwards the [Minect datapack](Self::get_datapack_dir) and the structure files of this
+    /// connection are removed from the world directory. Structure files of other connections are
+    /// left alone. The returned [UninstallReport] lists anything that could not be removed.
+    ///
+    /// This consumes the connection, because no commands can be executed after the uninstall.
+    /// Minecraft keeps the removed datapack available until `reload` is executed or Minecraft is
+    /// restarted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if Minecraft does not confirm the uninstall within
+    /// `max_wait`, for instance because it is not running (can be checked with
+    /// [ExecuteCommandsError::is_timed_out]). In this case nothing is removed from the world
+    /// directory. It will also return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn uninstall_and_verify(
+        mut self,
+        max_wait: Duration,
+    ) -> Result<UninstallReport, ExecuteCommandsError> {
+        // The output of the command block is logged after the function was executed
+        let name = next_query_name();
+        let mut events = self.add_named_listener(name.clone());
+        self.write_batch(
+            [Command::named(name, UNINSTALL_FUNCTION)],
+            ExecutionStrategy::CommandBlocks,
+        )?;
+        match timeout(max_wait, events.next()).await {
+            Ok(event) => {
+                event.expect("LogObserver panicked");
+                Ok(self.remove_installation())
+            }
+            Err(_) => Err(ExecuteCommandsError::new(
+                ExecuteCommandsErrorInner::TimedOut,
+            )),
+        }
+    }
+
+    fn remove_installation(&self) -> UninstallReport {
+        UninstallReport::remove(
+            &*self.file_backend,
+            [self.datapack_dir.clone(), self.structures_dir.clone()],
+        )
+    }
+
+    /// The maximum number of commands that can be passed to a single call of
+    /// [execute_commands](Self::execute_commands). This is the number of command blocks that fit
+    /// into the [command block area](MinecraftConnectionBuilder::command_block_area_size) minus the
+    /// number of commands that are added implicitly, for example to
+    /// [enable logging](MinecraftConnectionBuilder::enable_logging_automatically).
+    pub fn max_commands_per_batch(&self) -> usize {
+        let (implicit_commands, _) = add_implicit_commands(
+            Vec::new(),
+            &self.identifier,
+            Some(0),
+            self.enable_logging_automatically,
+            self.query_gametick,
+            self.protect_building.then_some(self.hide_building),
+            &self.command_block_area,
+        );
+        let update_delay_len = match &self.update_delay_controller {
+            Some(controller) => controller.commands_len(),
+            None => usize::from(self.update_delay.is_some()),
+        };
+        let session_len = if self.session.is_some() {
+            SESSION_COMMANDS_LEN
+        } else {
+            0
+        };
+        #[cfg(feature = "signing")]
+        let marker_len = if self.batch_signer.is_some() {
+            signing::MARKER_LEN
+        } else {
+            0
+        };
+        #[cfg(not(feature = "signing"))]
+        let marker_len = 0;
+        let implicit_len = implicit_commands.count() + update_delay_len + session_len + marker_len;
+        let max_len = self.command_block_area.max_len();
+        let max_commands = max_len.saturating_sub(implicit_len);
+        if self.detect_command_results {
+            max_commands / COMMANDS_PER_RESULT
+        } else {
+            max_commands
+        }
+    }
+
+    /// Executes the given `commands` in Minecraft using the configured
+    /// [execution_strategy](MinecraftConnectionBuilder::execution_strategy).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn execute_commands(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+    ) -> Result<(), ExecuteCommandsError> {
+        let commands = self.check_commands(commands.into_iter(), self.execution_strategy)?;
+        self.execute_or_spool(commands, self.execution_strategy)
+    }
+
+    /// Executes the given `commands` like [execute_commands](Self::execute_commands) and returns a
+    /// [Stream] of the [LogEvent]s that are logged while the batch is executed. The stream ends
+    /// after the last of the `commands` was executed.
+    ///
+    /// The batch is delimited by a marker command before and after the `commands`, so
+    /// [LogEvent]s of other batches or of players are not part of the stream, unless they are
+    /// logged in between. This avoids mixing up the output of concurrent batches, which can easily
+    /// happen with [add_listener](Self::add_listener).
+    ///
+    /// Because the end marker must never be ignored, a batch that does not fit into the command
+    /// block area together with the markers is executed with [ExecutionStrategy::FunctionFile].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]), if a
+    /// [conditional](Command::conditional) batch does not fit into the command block area together
+    /// with the markers (can be checked with [ExecuteCommandsError::is_too_many_commands]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn execute_and_stream(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+    ) -> Result<impl Stream<Item = LogEvent>, ExecuteCommandsError> {
+        let commands = self.check_commands(commands.into_iter(), self.execution_strategy)?;
+        let start = next_query_name();
+        let end = next_query_name();
+        let marker = |name: &String| Command::named(name, summon_named_entity_command(name));
+        let mut marked_commands = Vec::with_capacity(commands.len() + 2);
+        marked_commands.push(marker(&start));
+        marked_commands.extend(commands);
+        marked_commands.push(marker(&end));
+        // If the end marker was ignored, the stream would never end
+        let max = self.max_commands_per_batch();
+        let strategy = match self.resolve_strategy(&marked_commands, self.execution_strategy) {
+            ExecutionStrategy::CommandBlocks if marked_commands.len() > max => {
+                if marked_commands.iter().any(Command::is_conditional) {
+                    let commands = marked_commands.len();
+                    let inner = ExecuteCommandsErrorInner::TooManyCommands { commands, max };
+                    return Err(ExecuteCommandsError::new(inner));
+                }
+                ExecutionStrategy::FunctionFile
+            }
+            strategy => strategy,
+        };
+        let events = self.add_listener();
+        self.execute_or_spool(marked_commands, strategy)?;
+        Ok(events
+            .skip_while(move |event| event.executor != start)
+            .skip(1)
+            .take_while(move |event| event.executor != end))
+    }
+
+    /// The name of the scoreboard objective `name` in the sandbox of this connection:
+    /// `minect_<identifier>_<name>`. Using this name avoids collisions with the objectives of other
+    /// programs that use the same world.
+    pub fn objective_name(&self, name: &str) -> String {
+        objective_name(&self.identifier, name)
+    }
+
+    /// Creates the scoreboard objective `name` in the sandbox of this connection with the given
+    /// `criterion` (for example `dummy`) and returns its full [objective_name](Self::objective_name).
+    ///
+    /// The objective is tracked in the Minect datapack, so it is removed by
+    /// [remove_objectives](Self::remove_objectives) and when Minect is uninstalled with
+    /// `function minect:uninstall_completely`. Creating an objective that already exists is
+    /// harmless.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) denies the command or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn add_objective(
+        &mut self,
+        name: &str,
+        criterion: &str,
+    ) -> Result<String, ExecuteCommandsError> {
+        let objective = self.objective_name(name);
+        track_objective(
+            &*self.file_backend,
+            &self.datapack_dir,
+            &self.identifier,
+            &objective,
+            &self.file_permissions,
+        )?;
+        self.execute_commands([Command::new(format!(
+            "scoreboard objectives add {} {}",
+            objective, criterion
+        ))])?;
+        Ok(objective)
+    }
+
+    /// Creates a dummy scoreboard objective with [add_objective](Self::add_objective), sets its
+    /// display name to `title` and shows it in the sidebar. The returned [Sidebar] has no rows yet,
+    /// they are added with [update_sidebar](Self::update_sidebar).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) denies the commands or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn show_sidebar(
+        &mut self,
+        name: &str,
+        title: &str,
+    ) -> Result<Sidebar, ExecuteCommandsError> {
+        let objective = self.add_objective(name, "dummy")?;
+        let sidebar = Sidebar::new(objective);
+        let [_, display_name, set_display] = sidebar.show_commands(title);
+        self.execute_commands([Command::new(display_name), Command::new(set_display)])?;
+        Ok(sidebar)
+    }
+
+    /// Replaces the rows of the `sidebar` with `rows`, only executing commands for rows that changed.
+    /// If no row changed, no commands are executed at all. This makes it cheap to call once per
+    /// refresh of a dashboard.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) denies the commands or if an
+    /// [io::Error](std::io::Error) occurs. In this case `sidebar` is left unchanged.
+    pub fn update_sidebar(
+        &mut self,
+        sidebar: &mut Sidebar,
+        rows: impl IntoIterator<Item = (impl Into<String>, i32)>,
+    ) -> Result<(), ExecuteCommandsError> {
+        let mut updated = sidebar.clone();
+        let commands = updated.update(rows);
+        if !commands.is_empty() {
+            self.execute_commands(commands.into_iter().map(Command::new))?;
+        }
+        *sidebar = updated;
+        Ok(())
+    }
+
+    /// The full names of all scoreboard objectives that were created with
+    /// [add_objective](Self::add_objective) and not yet removed.
+    pub fn get_objectives(&self) -> Vec<String> {
+        read_objectives(&*self.file_backend, &self.datapack_dir, &self.identifier)
+    }
+
+    /// Removes all scoreboard objectives that were created with [add_objective](Self::add_objective)
+    /// from Minecraft and stops tracking them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) denies the commands or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn remove_objectives(&mut self) -> Result<(), ExecuteCommandsError> {
+        let objectives = self.get_objectives();
+        if !objectives.is_empty() {
+            self.execute_commands(objectives.iter().map(|objective| {
+                Command::new(format!("scoreboard objectives remove {}", objective))
+            }))?;
+        }
+        untrack_objectives(&*self.file_backend, &self.datapack_dir, &self.identifier)?;
+        Ok(())
+    }
+
+    /// Like [execute_commands](Self::execute_commands), but allows reserved names.
+    pub(crate) fn execute_internal_commands(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+    ) -> Result<(), ExecuteCommandsError> {
+        self.write_batch(commands, self.execution_strategy)?;
+        Ok(())
+    }
+
+    /// Executes the given `commands` in Minecraft using the given [ExecutionStrategy].
+    ///
+    /// With [ExecutionStrategy::CommandBlocks] at most
+    /// [max_commands_per_batch](Self::max_commands_per_batch) commands can be executed at once. Any
+    /// additional commands are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn execute_commands_with_strategy(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+        strategy: ExecutionStrategy,
+    ) -> Result<(), ExecuteCommandsError> {
+        let commands = self.check_commands(commands.into_iter(), strategy)?;
+        self.execute_or_spool(commands, strategy)
+    }
+
+    /// Executes the given `commands` like [execute_commands](Self::execute_commands), unless
+    /// Minecraft does not load the batch before the `deadline`. In that case the batch is replaced by
+    /// a batch without commands. Once Minecraft loaded the replacement, a [BatchExpired] event is
+    /// sent to all [batch expired listeners](Self::add_batch_expired_listener). If Minecraft loaded
+    /// the batch just before it was replaced, no event is sent. This is useful for real-time
+    /// control, where late commands are worse than dropped ones.
+    ///
+    /// Returns the structure id of the batch, which identifies it in [BatchExpired] events.
+    ///
+    /// Deadlines are only checked while this connection observes the log file, which starts with
+    /// the first executed batch. The batch is written immediately, even if a
+    /// [spool_dir](MinecraftConnectionBuilder::spool_dir) is configured, because a spooled batch
+    /// would likely miss its deadline anyway.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn execute_commands_with_deadline(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+        deadline: Instant,
+    ) -> Result<u64, ExecuteCommandsError> {
+        let commands = self.check_commands(commands.into_iter(), self.execution_strategy)?;
+        self.write_pending_batches()?;
+        let strategy = Some(self.execution_strategy);
+        self.write_batch_with_strategy(commands, strategy, Some(deadline), false)
+    }
+
+    /// Executes a large number of `commands` in chunks of at most `chunk_size` commands and returns
+    /// a [BulkJob] to track the progress. Each chunk is executed like
+    /// [execute_commands](Self::execute_commands) and is completed once Minecraft acknowledges
+    /// it was loaded, which makes it possible to render a progress bar for long running imports.
+    ///
+    /// If the chunks are placed in command blocks, `chunk_size` is limited to
+    /// [max_commands_per_batch](Self::max_commands_per_batch), so no command is ignored.
+    ///
+    /// All chunks are written immediately, even if a
+    /// [spool_dir](MinecraftConnectionBuilder::spool_dir) is configured, because the progress is
+    /// tracked via the structure ids of the chunks. The last chunk is always acknowledged, even if
+    /// [acknowledge_every](MinecraftConnectionBuilder::acknowledge_every) is set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]) or if an
+    /// [io::Error](std::io::Error) occurs. In the latter case some chunks may already be executed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is `0`.
+    pub fn start_bulk_job(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+        chunk_size: usize,
+    ) -> Result<BulkJob, ExecuteCommandsError> {
+        assert!(chunk_size > 0, "Invalid chunk size: 0");
+        let commands = self.check_commands(commands.into_iter(), self.execution_strategy)?;
+        self.write_pending_batches()?;
+        let chunk_size = match self.execution_strategy {
+            ExecutionStrategy::Automatic if !commands.iter().any(Command::is_conditional) => {
+                chunk_size
+            }
+            ExecutionStrategy::Automatic | ExecutionStrategy::CommandBlocks => {
+                chunk_size.min(self.max_commands_per_batch().max(1))
+            }
+            ExecutionStrategy::FunctionFile | ExecutionStrategy::Minecart => chunk_size,
+        };
+        let job = BulkJob::new(commands.len());
+        self.loaded_batches.add_observer(job.observer());
+        let chunks = split_into_chunks(commands, chunk_size);
+        let last = chunks.len().saturating_sub(1);
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let len = chunk.len();
+            let strategy = Some(self.execution_strategy);
+            let id = self.write_batch_with_strategy(chunk, strategy, None, index == last)?;
+            job.add_chunk(id, len);
+        }
+        Ok(job)
+    }
+
+    /// Executes the given `chains` of commands in Minecraft as a single batch, placing each chain as
+    /// a separate chain of command blocks that is started by its own impulse command block.
+    ///
+    /// The chains are executed one after another in the same gametick, but independently of each
+    /// other. This is useful if a batch contains slow commands like large `fill` sequences and
+    /// unrelated commands like queries, which should not be delayed by them.
+    ///
+    /// Commands are always executed with [ExecutionStrategy::CommandBlocks], so at most
+    /// [max_commands_per_batch](Self::max_commands_per_batch) commands can be executed at once.
+    /// Because every chain and the final clean up of the batch start on a new position of the
+    /// command block area, this can be up to one layer less.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a [Command] has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]), if the chains don't fit
+    /// into the [command block area](MinecraftConnectionBuilder::command_block_area_size) (can be
+    /// checked with [ExecuteCommandsError::placement_error]) or if an [io::Error](std::io::Error)
+    /// occurs.
+    pub fn execute_chains(
+        &mut self,
+        chains: impl IntoIterator<Item = impl IntoIterator<Item = Command>>,
+    ) -> Result<(), ExecuteCommandsError> {
+        let mut commands = Vec::new();
+        for chain in chains {
+            let start = commands.len();
+            commands.extend(chain);
+            if start > 0 {
+                if let Some(first) = commands.get_mut(start) {
+                    first.chain = ChainPlacement::New;
+                }
+            }
+        }
+        let commands =
+            self.check_commands(commands.into_iter(), ExecutionStrategy::CommandBlocks)?;
+        self.execute_or_spool(commands, ExecutionStrategy::CommandBlocks)
+    }
+
+    fn execute_or_spool(
+        &mut self,
+        commands: Vec<Command>,
+        strategy: ExecutionStrategy,
+    ) -> Result<(), ExecuteCommandsError> {
+        self.check_lease()?;
+        self.write_pending_batches()?;
+        if let Some(spool) = &self.spool {
+            // Fail before the batch is spooled, because it would fail again every time it is written
+            self.validate_batch(&commands, strategy)?;
+            spool.append(strategy, &commands)?;
+            self.drain_spool()?;
+        } else {
+            self.write_batch(commands, strategy)?;
+        }
+        Ok(())
+    }
+
+    /// Writes batches from the [spool_dir](MinecraftConnectionBuilder::spool_dir) as structure files
+    /// until all batches are written. This waits for Minecraft to load structure files whenever
+    /// [max_pending_structures](MinecraftConnectionBuilder::max_pending_structures) is reached.
+    ///
+    /// If no [spool_dir](MinecraftConnectionBuilder::spool_dir) is configured, this returns
+    /// immediately.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn process_spool(&mut self) -> Result<(), ExecuteCommandsError> {
+        if self.spool.is_some() && !self.loaded_listener_initialized {
+            // Structure files of a previous program have to be removed once they are loaded
+            self.init_loaded_listener();
+        }
+        loop {
+            let mut events = self.add_named_listener(names::LOADED);
+            if self.drain_spool()? {
+                return Ok(());
+            }
+            // Structure files are removed shortly after they are acknowledged, so we check again
+            // regularly instead of relying on the next acknowledgement.
+            let _ = timeout(Duration::from_secs(1), events.next()).await;
+        }
+    }
+
+    /// Writes spooled batches as structure files until the spool is empty or
+    /// [max_pending_structures](MinecraftConnectionBuilder::max_pending_structures) is reached.
+    /// Returns `true` if the spool is empty.
+    fn drain_spool(&mut self) -> Result<bool, ExecuteCommandsError> {
+        let spool = match &self.spool {
+            Some(spool) => spool.clone(),
+            None => return Ok(true),
+        };
+        let _lock = spool.lock()?; // Automatically unlocked at the end of this function.
+        let pending = count_pending_structures(&*self.file_backend, &self.structures_dir);
+        let capacity = spool.max_pending_structures().saturating_sub(pending);
+        let mut batches = spool.batches()?.into_iter().peekable();
+        let mut next = None;
+        for _ in 0..capacity {
+            let (mut ids, strategy, mut commands) = match next.take() {
+                Some(batch) => batch,
+                None => match batches.next() {
+                    Some(id) => {
+                        let (strategy, commands) = spool.read(id)?;
+                        (vec![id], strategy, commands)
+                    }
+                    None => break,
+                },
+            };
+            if self.merge_spooled_batches {
+                for id in batches.by_ref() {
+                    let (next_strategy, next_commands) = spool.read(id)?;
+                    let mut merged = commands.clone();
+                    merged.extend(next_commands.iter().cloned());
+                    if next_strategy == strategy && self.can_merge(strategy, &merged) {
+                        ids.push(id);
+                        commands = merged;
+                    } else {
+                        next = Some((vec![id], next_strategy, next_commands));
+                        break;
+                    }
+                }
+            }
+            // Acknowledge the batch that empties the spool, so no structure file remains
+            let last = next.is_none() && batches.peek().is_none();
+            match self.write_batch_with_strategy(commands, Some(strategy), None, last) {
+                Ok(_) => {
+                    for id in ids {
+                        spool.remove(id)?;
+                    }
+                }
+                // Merged batches are validated, so only a single batch can be invalid
+                Err(error) if error.is_invalid_batch() => {
+                    for id in ids {
+                        let path = spool.quarantine(id)?;
+                        let message = error.to_string();
+                        let warning = MinectWarning::SpooledBatchQuarantined { path, message };
+                        self.warnings.report(warning);
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(next.is_none() && batches.next().is_none())
+    }
+
+    /// Whether spooled batches with the given `strategy` and the `merged` commands can be written as
+    /// a single structure file.
+    fn can_merge(&self, strategy: ExecutionStrategy, merged: &[Command]) -> bool {
+        (strategy == ExecutionStrategy::FunctionFile
+            || merged.len() <= self.max_commands_per_batch())
+            && self.validate_batch(merged, strategy).is_ok()
+    }
+
+    /// Executes a single `command` in Minecraft without waiting for previously executed commands.
+    ///
+    /// This uses a second connection building with the identifier `<identifier>_urgent`, which has
+    /// to be set up with [connect_urgent](Self::connect_urgent). Because the building processes its
+    /// own structure files, the `command` is executed as soon as Minecraft loads it, even if many
+    /// batches of [execute_commands](Self::execute_commands) are still pending. Consequently the `command` may
+    /// execute before commands that were passed to [execute_commands](Self::execute_commands)
+    /// earlier.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `command` has a name that is reserved for internal
+    /// use (can be checked with [ExecuteCommandsError::is_reserved_name]), if the urgent lane is not
+    /// connected (can be checked with [ExecuteCommandsError::is_urgent_lane_not_connected]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub fn execute_urgent(&mut self, command: Command) -> Result<(), ExecuteCommandsError> {
+        let command =
+            self.check_commands(std::iter::once(command), ExecutionStrategy::CommandBlocks)?;
+        self.connected_urgent_lane()?
+            .write_batch(command, ExecutionStrategy::CommandBlocks)?;
+        Ok(())
+    }
+
+    /// Sets up the connection building used by [execute_urgent](Self::execute_urgent) like
+    /// [connect](Self::connect).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the player cancels the installation in the interactive
+    /// installer (can be checked with [ConnectError::is_cancelled]) or if an
+    /// [io::Error](std::io::Error) occurs.
+    pub async fn connect_urgent(&mut self) -> Result<(), ConnectError> {
+        connect(self.get_urgent_lane()).await
+    }
+
+    /// Pauses the connection building, so it no longer loads structure files and does not cost any
+    /// performance during heavy gameplay moments. Batches that are executed while the connection is
+    /// paused are executed after it is [resumed](Self::resume).
+    ///
+    /// Because the paused building can't load the batch that resumes it, both functions use the
+    /// [urgent lane](Self::execute_urgent), which has to be set up with
+    /// [connect_urgent](Self::connect_urgent).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if another process holds the
+    /// [lease](MinecraftConnectionBuilder::lease) (can be checked with
+    /// [ExecuteCommandsError::is_read_only]), if the urgent lane is not connected (can be checked
+    /// with [ExecuteCommandsError::is_urgent_lane_not_connected]) or if an
+    /// [io::Error](std::io::Error) occurs. In this case the [status](Self::status) does not change.
+    pub fn pause(&mut self) -> Result<(), ExecuteCommandsError> {
+        self.set_paused(true)
+    }
+
+    /// Resumes the connection building after it was [paused](Self::pause).
+    ///
+    /// # Errors
+    ///
+    /// This function will return the same errors as [pause](Self::pause).
+    pub fn resume(&mut self) -> Result<(), ExecuteCommandsError> {
+        self.set_paused(false)
+    }
+
+    /// Executes an empty batch. This renews the [lease](MinecraftConnectionBuilder::lease) and the
+    /// in-game heartbeat that shows other processes that this connection is still active.
+    ///
+    /// The batch is always acknowledged, so the structure files of previous batches that were not
+    /// acknowledged (see [acknowledge_every](MinecraftConnectionBuilder::acknowledge_every)) are
+    /// removed once Minecraft loaded it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if another process holds the lease (can be checked with
+    /// [ExecuteCommandsError::is_read_only]) or if an [io::Error](std::io::Error) occurs.
+    pub fn heartbeat(&mut self) -> Result<(), ExecuteCommandsError> {
+        let strategy = Some(self.execution_strategy);
+        self.write_batch_with_strategy(Vec::new(), strategy, None, true)?;
+        Ok(())
+    }
+
+    /// Whether this connection holds the [lease](MinecraftConnectionBuilder::lease) of its
+    /// identifier. This is `false` until [connect](Self::connect) succeeded.
+    pub fn holds_lease(&self) -> bool {
+        self.lease_renewed.is_some()
+    }
+
+    /// Releases the [lease](MinecraftConnectionBuilder::lease) of the identifier, so other processes
+    /// can take it over immediately. Afterwards this connection is read-only until the next
+    /// [connect](Self::connect).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub fn release_lease(&mut self) -> Result<(), IoErrorAtPath> {
+        if let (Some(lease), Some(session), Some(_)) =
+            (&self.lease, self.session, self.lease_renewed.take())
+        {
+            self.read_only = true;
+            lease.release(&*self.file_backend, session)?;
+        }
+        Ok(())
+    }
+
+    /// Fails if this connection is read-only and renews the
+    /// [lease](MinecraftConnectionBuilder::lease) once half of it elapsed. If another process took
+    /// over the lease in the meantime, this connection becomes read-only.
+    fn check_lease(&mut self) -> Result<(), ExecuteCommandsError> {
+        if self.read_only {
+            return Err(ExecuteCommandsError::new(
+                ExecuteCommandsErrorInner::ReadOnly,
+            ));
+        }
+        if let (Some(lease), Some(session), Some(renewed)) =
+            (&self.lease, self.session, self.lease_renewed)
+        {
+            if renewed.elapsed() >= lease.duration() / 2 {
+                let backend = &*self.file_backend;
+                if lease.acquire(backend, &self.identifier, session)?.is_err() {
+                    self.lease_renewed = None;
+                    self.read_only = true;
+                    return Err(ExecuteCommandsError::new(
+                        ExecuteCommandsErrorInner::ReadOnly,
+                    ));
+                }
+                self.lease_renewed = Some(Instant::now());
+            }
+        }
+        Ok(())
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ExecuteCommandsError> {
+        self.check_lease()?;
+        let command = Command::new(format!(
+            "tag @e[type=area_effect_cloud,tag=minect_connection+{}] {} {}",
+            self.identifier,
+            if paused { "add" } else { "remove" },
+            PAUSED_TAG
+        ));
+        self.connected_urgent_lane()?
+            .write_batch([command], ExecutionStrategy::CommandBlocks)?;
+        self.paused = paused;
+        Ok(())
+    }
+
+    /// Whether this connection [paused](Self::pause) the connection building.
+    ///
+    /// The status is not shared with other programs, so it only reflects the calls to
+    /// [pause](Self::pause) and [resume](Self::resume) of this connection.
+    pub fn status(&self) -> ConnectionStatus {
+        if self.paused {
+            ConnectionStatus::Paused
+        } else {
+            ConnectionStatus::Running
+        }
+    }
+
+    /// Returns the urgent lane if [connect_urgent](Self::connect_urgent) succeeded, because
+    /// otherwise no connection building loads its structure files.
+    fn connected_urgent_lane(&mut self) -> Result<&mut MinecraftConnection, ExecuteCommandsError> {
+        match self.urgent_lane.as_deref_mut() {
+            Some(lane) if lane.session.is_some() => Ok(lane),
+            _ => Err(ExecuteCommandsError::new(
+                ExecuteCommandsErrorInner::UrgentLaneNotConnected,
+            )),
+        }
+    }
+
+    fn get_urgent_lane(&mut self) -> &mut MinecraftConnection {
+        if self.urgent_lane.is_none() {
+            // Unwrap is safe because the builder is only taken here
+            let builder = self.urgent_lane_builder.take().unwrap();
+            let mut lane = MinecraftConnection::new(*builder);
+            lane.urgent_lane_builder = None;
+            lane.warnings = self.warnings.clone();
+            self.urgent_lane = Some(Box::new(lane));
+        }
+        self.urgent_lane.as_mut().unwrap() // Unwrap is safe because we just assigned the value
+    }
+
+    /// Writes a structure file containing the given `commands` and returns its id.
+    fn write_batch(
+        &mut self,
+        commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+        strategy: ExecutionStrategy,
+    ) -> Result<u64, ExecuteCommandsError> {
+        let commands = commands.into_iter().collect::<Vec<_>>();
+        self.write_batch_with_strategy(commands, Some(strategy), None, false)
+    }
+
+    /// Writes the batches whose commands have to be executed again, because Minecraft skipped them
+    /// or did not acknowledge them in time.
+    fn write_pending_batches(&mut self) -> Result<(), ExecuteCommandsError> {
+        let retried = self
+            .unacknowledged_batches
+            .as_ref()
+            .map(|unacknowledged_batches| unacknowledged_batches.take_pending())
+            .unwrap_or_default();
+        for commands in self
+            .skipped_batches
+            .take_pending()
+            .into_iter()
+            .chain(retried)
+        {
+            self.write_prepared_batch(commands)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a structure file containing `commands` that were already prepared by a previous
+    /// [write_batch](Self::write_batch), for example to execute a skipped batch again.
+    fn write_prepared_batch(
+        &mut self,
+        commands: Vec<Command>,
+    ) -> Result<u64, ExecuteCommandsError> {
+        self.write_batch_with_strategy(commands, None, None, false)
+    }
+
+    fn write_batch_with_strategy(
+        &mut self,
+        mut commands: Vec<Command>,
+        strategy: Option<ExecutionStrategy>,
+        deadline: Option<Instant>,
+        acknowledge: bool,
+    ) -> Result<u64, ExecuteCommandsError> {
+        let strategy = strategy.map(|strategy| self.resolve_strategy(&commands, strategy));
+        if let Some(strategy) = strategy {
+            self.check_conditionals(&commands, strategy)?;
+        }
+        self.check_lease()?;
+        let backend = self.file_backend.clone();
+        if !backend.is_dir(&self.datapack_dir) {
+            self.create_datapack()?;
+        }
+        if !self.loaded_listener_initialized {
+            self.init_loaded_listener();
+        }
+        let backend = &*backend;
+        create_dir_all_with_permissions(backend, &self.structures_dir, &self.file_permissions)?;
+
+        let id_path = self.structures_dir.join("id.txt");
+        let id_file_exists = backend.is_file(&id_path);
+        let mut id_file = backend // Automatically unlocked by dropping id_file at the end of this function.
+            .lock(&id_path)
+            .map_err(io_error("Failed to lock file", &id_path))?;
+
+        let id = read_incremented_id(&mut *id_file, &id_path)?;
+        if !id_file_exists {
+            self.file_permissions.apply_to_file(backend, &id_path)?;
+        }
+        if self.namespace_ids_per_run && self.run.is_none() {
+            self.run = Some(next_run(id));
+        }
+
+        if let Some(strategy) = strategy {
+            commands = self.prepare_batch(commands, strategy, id)?;
+        }
+        let copies = self
+            .unacknowledged_batches
+            .as_ref()
+            .map(|_| commands.to_vec());
+        if let Some(deadline) = deadline {
+            let expired = Command::named(
+                names::EXPIRED,
+                summon_named_entity_command(&format!(
+                    "{}{}+{}",
+                    STRUCTURE_EXPIRED_OUTPUT_PREFIX, self.identifier, id
+                )),
+            );
+            let replacement =
+                self.generate_batch_structure(vec![expired], id, false, &self.warnings)?;
+            self.expiring_batches.insert(id, deadline, replacement);
+        }
+        if let Some(controller) = &self.update_delay_controller {
+            commands.extend(controller.written(id, Instant::now()));
+        }
+        let acknowledged = acknowledge || deadline.is_some() || id % self.acknowledge_every == 0;
+        let structure =
+            self.generate_batch_structure(commands, id, acknowledged, &self.warnings)?;
+
+        // To create the structure file as atomically as possible we first write to a temporary file
+        // and then rename it, which is an atomic operation on most operating systems. If Minecraft
+        // would attempt to load a half written file, it would likely cache the file as invalid
+        // (depending on what bytes it sees). Locking the file also causes Minecraft to cache it as
+        // invalid.
+        let tmp_path = self.get_structure_file("tmp");
+        create_structure_file(backend, &tmp_path, &structure, self.compression)?;
+        if let (Some(unacknowledged_batches), Some(copies)) = (&self.unacknowledged_batches, copies)
+        {
+            unacknowledged_batches.insert(id, copies);
+        }
+        self.file_permissions.apply_to_file(backend, &tmp_path)?;
+        backend
+            .rename(&tmp_path, &structure_file(&self.structures_dir, id))
+            .map_err(io_error("Failed to rename file", tmp_path))?;
+
+        // We do this at the end to not increment the id on a failure, which would break the connection.
+        // The id file holds the id before the next one, which differs at the start of a run.
+        let next_id = self.next_structure_id(id);
+        write_id(&mut *id_file, id_path, next_id.wrapping_sub(1))?;
+
+        Ok(id)
+    }
+
+    fn generate_batch_structure(
+        &self,
+        commands: Vec<Command>,
+        id: u64,
+        acknowledged: bool,
+        warnings: &Warnings,
+    ) -> Result<Structure, PlacementError> {
+        let update_delay = self.update_delay.map(update_delay_command);
+        let session = self
+            .session
+            .map(|session| session_commands(&self.identifier, session));
+        // The marker comes first, so it is never ignored because a batch contains too many commands
+        #[cfg(feature = "signing")]
+        let marker = self
+            .batch_signer
+            .as_ref()
+            .map(|_| BatchSigner::placeholder(&self.identifier, id, self.next_structure_id(id)));
+        #[cfg(not(feature = "signing"))]
+        let marker = None::<[Command; 0]>;
+        let commands = marker
+            .into_iter()
+            .flatten()
+            .chain(update_delay)
+            .chain(session.into_iter().flatten())
+            .chain(commands)
+            .collect();
+        let (commands, commands_len) = add_implicit_commands(
+            commands,
+            &self.identifier,
+            acknowledged.then_some(id),
+            self.enable_logging_automatically,
+            self.query_gametick,
+            self.protect_building.then_some(self.hide_building),
+            &self.command_block_area,
+        );
+        #[allow(unused_mut)]
+        let mut structure = generate_structure(
+            &self.identifier,
+            self.next_structure_id(id),
+            commands,
+            commands_len,
+            &self.command_block_area,
+            warnings,
+        )?;
+        if self.validate_placement {
+            validate_chains(&structure)?;
+        }
+        #[cfg(feature = "signing")]
+        if let Some(signer) = &self.batch_signer {
+            signer.sign(&self.identifier, id, &mut structure);
+        }
+        Ok(structure)
+    }
+
+    /// Applies the `strategy` to the `commands` of the batch with the given structure `id`.
+    /// Returns the [ExecutionStrategy] that is used to execute `commands` with `strategy`, which is
+    /// only different for [ExecutionStrategy::Automatic].
+    fn resolve_strategy(
+        &self,
+        commands: &[Command],
+        strategy: ExecutionStrategy,
+    ) -> ExecutionStrategy {
+        match strategy {
+            ExecutionStrategy::Automatic
+                if commands.len() > self.max_commands_per_batch()
+                    && !commands.iter().any(Command::is_conditional) =>
+            {
+                ExecutionStrategy::FunctionFile
+            }
+            ExecutionStrategy::Automatic => ExecutionStrategy::CommandBlocks,
+            strategy => strategy,
+        }
+    }
+
+    /// Fails if one of the `commands` is [conditional](Command::conditional), but would not be
+    /// executed conditionally with `strategy`. Only command blocks support conditions and
+    /// [detect_command_results](MinecraftConnectionBuilder::detect_command_results) would place its
+    /// commands between a conditional command and the command it depends on.
+    fn check_conditionals(
+        &self,
+        commands: &[Command],
+        strategy: ExecutionStrategy,
+    ) -> Result<(), ExecuteCommandsError> {
+        let supported =
+            strategy == ExecutionStrategy::CommandBlocks && !self.detect_command_results;
+        match commands.iter().find(|command| command.is_conditional()) {
+            Some(command) if !supported => {
+                let inner = ExecuteCommandsErrorInner::UnsupportedConditional(command.clone());
+                Err(ExecuteCommandsError::new(inner))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if the batch with the given `commands` can never be written with `strategy`. This
+    /// generates the structure without writing it and without reporting [MinectWarning]s.
+    fn validate_batch(
+        &self,
+        commands: &[Command],
+        strategy: ExecutionStrategy,
+    ) -> Result<(), ExecuteCommandsError> {
+        let strategy = self.resolve_strategy(commands, strategy);
+        self.check_conditionals(commands, strategy)?;
+        // Preparing a function file batch writes the function, but its structure can not fail
+        if strategy != ExecutionStrategy::FunctionFile {
+            let commands = self.prepare_batch(commands.to_vec(), strategy, 0)?;
+            self.generate_batch_structure(commands, 0, true, &Warnings::default())?;
+        }
+        Ok(())
+    }
+
+    fn prepare_batch(
+        &self,
+        commands: Vec<Command>,
+        strategy: ExecutionStrategy,
+        id: u64,
+    ) -> Result<Vec<Command>, ExecuteCommandsError> {
+        Ok(if strategy == ExecutionStrategy::FunctionFile {
+            #[allow(unused_mut)]
+            let mut lines = batch_function_lines(&self.identifier, id, commands);
+            #[cfg(feature = "signing")]
+            if let Some(signer) = &self.batch_signer {
+                lines.insert(0, signer.sign_function(&self.identifier, id, &lines));
+            }
+            write_batch_function(
+                &*self.file_backend,
+                &self.datapack_dir,
+                &self.identifier,
+                id,
+                &lines,
+                &self.file_permissions,
+            )?
+        } else {
+            let commands = if self.detect_command_results {
+                add_result_commands(commands, id)
+            } else {
+                commands
+            };
+            if strategy == ExecutionStrategy::Minecart {
+                self.place_in_minecarts(commands)
+            } else {
+                commands
+            }
+        })
+    }
+
+    /// Marks the `commands` to be executed by command block minecarts. Because the command blocks
+    /// of the batch reset logging before the minecarts are executed, logging is enabled again by the
+    /// first minecart.
+    fn place_in_minecarts(&self, commands: Vec<Command>) -> Vec<Command> {
+        let mut carts = Vec::with_capacity(commands.len() + 2);
+        if self.enable_logging_automatically {
+            carts.push(Command::new(enable_logging_command()));
+        }
+        carts.extend(commands);
+        if self.enable_logging_automatically {
+            carts.push(Command::new(reset_logging_command()));
+        }
+        for cart in &mut carts {
+            cart.chain = ChainPlacement::Minecart;
+        }
+        carts
+    }
+
+    /// Returns a [Stream] of [MinectWarning]s, which are sent whenever Minect works around a problem,
+    /// for example when a batch contains too many commands. This allows applications to react to
+    /// these problems programmatically. The warnings are also logged with the `log` crate. To remove
+    /// the listener simply drop the stream.
+    ///
+    /// The warnings of the [urgent lane](Self::execute_urgent) are sent to the same listeners.
+    pub fn warnings(&self) -> impl Stream<Item = MinectWarning> {
+        self.warnings.add_listener()
+    }
+
+    /// Returns a [Stream] of [BatchExpired] events, which are sent when a batch executed with
+    /// [execute_commands_with_deadline](Self::execute_commands_with_deadline) misses its deadline.
+    /// To remove the listener simply drop the stream.
+    pub fn add_batch_expired_listener(&self) -> impl Stream<Item = BatchExpired> {
+        self.expiring_batches.add_listener()
+    }
+
+    /// Returns a [Stream] of [UpdateDelayChanged] events, which are sent when the
+    /// [adaptive_update_delay](MinecraftConnectionBuilder::adaptive_update_delay) controller changes
+    /// the update delay of this connection. If no controller is configured, the stream ends
+    /// immediately. To remove the listener simply drop the stream.
+    pub fn add_update_delay_listener(&self) -> impl Stream<Item = UpdateDelayChanged> {
+        match &self.update_delay_controller {
+            Some(controller) => controller.add_listener(),
+            None => UnboundedReceiverStream::new(unbounded_channel().1),
+        }
+    }
+
+    /// Returns a [Stream] of [BatchLoaded] events, which are sent when Minecraft loaded a batch of
+    /// this connection. To remove the listener simply drop the stream.
+    ///
+    /// Usually batches are reported with [LoadedSource::Log] once the connection building
+    /// acknowledges them in Minecraft's log file. On servers where this acknowledgement is delayed,
+    /// the structures directory is watched as well: a structure file that is removed before its
+    /// acknowledgement is reported with [LoadedSource::FileSystem]. In that case the same batch is
+    /// reported a second time with [LoadedSource::Log] once the acknowledgement arrives.
+    pub fn add_batch_loaded_listener(&mut self) -> impl Stream<Item = BatchLoaded> {
+        if self.structures_watcher.is_none() {
+            let loaded_batches = self.loaded_batches.clone();
+            match watch_structures_dir(&self.structures_dir, loaded_batches) {
+                Ok(watcher) => self.structures_watcher = Some(watcher),
+                Err(error) => debug!("Not watching structures directory: {}", error),
+            }
+        }
+        self.loaded_batches.add_listener()
+    }
+
+    /// Returns a [Stream] of [BatchSkipped] events, which are sent when Minecraft acknowledges a
+    /// batch while an earlier batch of this connection was never acknowledged. Without this, a
+    /// skipped batch would go unnoticed.
+    ///
+    /// Skipped batches are only detected while this connection observes the log file, which starts
+    /// with the first executed batch.
+    pub fn add_batch_skipped_listener(&self) -> impl Stream<Item = BatchSkipped> {
+        self.skipped_batches.add_listener()
+    }
+
+    /// Restores the connection after Minecraft was restarted, for example after a crash.
+    ///
+    /// When Minecraft crashes, the world is rolled back to the last save. In this case the
+    /// connection building may wait for a structure file that was already deleted, so no more
+    /// commands are executed. This function removes all stale structure files and writes a new
+    /// structure for the most recent ids, so the connection building continues with whichever id it
+    /// waits for. Afterwards it checks that the connection entity exists and summons it again if it
+    /// is missing.
+    ///
+    /// If [retry_unacknowledged_after](MinecraftConnectionBuilder::retry_unacknowledged_after) is
+    /// set, all batches that were not acknowledged are executed again afterwards. Otherwise they are
+    /// lost.
+    ///
+    /// This function blocks until Minecraft executed the new structure, so it should be called with
+    /// [tokio::time::timeout] or some other means of cancellation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn resync(&mut self) -> Result<(), ExecuteCommandsError> {
+        const RESYNC_WINDOW: u64 = 64;
+
+        let unacknowledged = self
+            .unacknowledged_batches
+            .as_ref()
+            .map(|unacknowledged_batches| unacknowledged_batches.take_all())
+            .unwrap_or_default();
+        self.remove_structure_files()?;
+
+        let name = next_query_name();
+        let mut events = Box::pin(self.add_named_listener(name.clone()));
+        let id = self.write_batch(
+            [Command::named(&name, summon_named_entity_command(&name))],
+            ExecutionStrategy::CommandBlocks,
+        )?;
+
+        // Write the same structure for previous ids, because the world may have been rolled back.
+        let structure_path = structure_file(&self.structures_dir, id);
+        let tmp_path = self.get_structure_file("resync");
+        let backend = self.file_backend.clone();
+        // If the structure was already loaded and removed, no copies are needed
+        if let Ok(contents) = backend.read(&structure_path) {
+            for previous in 1..=RESYNC_WINDOW {
+                if !backend.is_file(&structure_path) {
+                    break;
+                }
+                backend
+                    .write(&tmp_path, &contents)
+                    .map_err(io_error("Failed to create file", &tmp_path))?;
+                backend
+                    .rename(
+                        &tmp_path,
+                        &structure_file(&self.structures_dir, id.wrapping_sub(previous)),
+                    )
+                    .map_err(io_error("Failed to rename file", &tmp_path))?;
+            }
+        }
+
+        events.next().await.expect("LogObserver panicked");
+        for previous in 1..=RESYNC_WINDOW {
+            let _ = backend.remove_file(&structure_file(
+                &self.structures_dir,
+                id.wrapping_sub(previous),
+            ));
+        }
+
+        let count_command = format!(
+            "execute if entity @e[type=area_effect_cloud,tag=minect_connection+{}]",
+            self.identifier
+        );
+        if self.eval_internal_int(count_command).await? == 0 {
+            // Minecarts on the activator rail are the only commands with a known position relative
+            // to the connection building
+            self.write_batch(
+                [Command::new(summon_connection_entity_from_rail_command(
+                    &self.identifier,
+                ))],
+                ExecutionStrategy::Minecart,
+            )?;
+        }
+
+        for commands in unacknowledged {
+            self.execute_internal_commands(commands)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the id of the structure that is loaded by the structure with the given `id`.
+    fn next_structure_id(&self, id: u64) -> u64 {
+        match self.run {
+            // The first batch of a run still has an id of the previous run, because Minecraft
+            // already waits for it
+            Some(run) if run_of(id) != run => first_id_of_run(run),
+            _ => id.wrapping_add(1),
+        }
+    }
+
+    fn remove_structure_files(&self) -> Result<(), IoErrorAtPath> {
+        let backend = &*self.file_backend;
+        let entries = match backend.read_dir(&self.structures_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(io_error("Failed to read directory", &self.structures_dir)(
+                    e,
+                ))
+            }
+        };
+        for path in entries {
+            let extension = path.extension().and_then(|extension| extension.to_str());
+            if matches!(extension, Some("nbt") | Some("tmp")) {
+                backend
+                    .remove_file(&path)
+                    .map_err(io_error("Failed to remove file", &path))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get_structure_file(&self, id: impl Display) -> PathBuf {
+        self.structures_dir.join(format!("{}.nbt", id))
+    }
+
+    /// Executes the given `commands` in Minecraft as and at the online player with the given name
+    /// (see [execute_as_player_command]). If the player is not online, no command is executed.
+    ///
+    /// Returns a [Stream] of [LogEvent]s that were produced as the player (see [player_executed]),
+    /// which contains the output of the `commands` even if the player's team decorates its display
+    /// name with a prefix or suffix. Note that this stream also contains the output of commands that
+    /// are executed by the player directly. To remove the listener simply drop the stream.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub fn run_as(
+        &mut self,
+        player: &str,
+        commands: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<impl Stream<Item = LogEvent>, ExecuteCommandsError> {
+        let name = player.to_string();
+        let events = self
+            .add_listener()
+            .filter(move |event| player_executed(event, &name));
+        let commands = commands
+            .into_iter()
+            .map(|command| Command::new(execute_as_player_command(player, command)))
+            .collect::<Vec<_>>();
+        self.execute_commands(commands)?;
+        Ok(events)
+    }
+
+    /// Adds an `interceptor` that can modify or drop [LogEvent]s before they are sent to listeners
+    /// (see [LogObserver::add_interceptor]).
+    pub fn add_interceptor(
+        &mut self,
+        interceptor: impl Fn(&mut LogEvent) -> InterceptorAction + Send + Sync + 'static,
+    ) {
+        self.get_log_observer().add_interceptor(interceptor)
+    }
+
+    /// Returns a [Stream] of errors that occur while reading the log file (see
+    /// [LogObserver::add_error_listener]).
+    pub fn add_error_listener(&mut self) -> impl Stream<Item = ObserverError> {
+        self.get_log_observer().add_error_listener()
+    }
+
+    /// Returns a [Stream] of all [LogEvent]s. To remove the listener simply drop the stream.
+    ///
+    /// Internally the stream is backed by an unbound channel. This means it should be polled
+    /// regularly to avoid memory leaks.
+    pub fn add_listener(&mut self) -> impl Stream<Item = LogEvent> {
+        self.get_log_observer().add_listener()
+    }
+
+    /// Returns a [Stream] of [LogEvent]s with [executor](LogEvent::executor) equal to the given
+    /// `name`. To remove the listener simply drop the stream.
     ///
     /// This can be more memory efficient than [add_listener](Self::add_listener), because only a
     /// small subset of [LogEvent]s has to be buffered if not that many commands are executed with
@@ -460,22 +2942,536 @@ impl MinecraftConnection {
         self.get_log_observer().add_named_listener(name)
     }
 
+    /// Like [add_named_listener](Self::add_named_listener), but for the given `name` in the given
+    /// `namespace` (see [names::namespaced]).
+    pub fn namespaced_listener(
+        &mut self,
+        namespace: impl AsRef<str>,
+        name: impl AsRef<str>,
+    ) -> impl Stream<Item = LogEvent> {
+        self.add_named_listener(names::namespaced(namespace, name))
+    }
+
+    /// Returns a [Stream] of [CommandResultEvent]s, which are only logged if
+    /// [detect_command_results](MinecraftConnectionBuilder::detect_command_results) is enabled. To
+    /// remove the listener simply drop the stream.
+    pub fn add_command_result_listener(&mut self) -> impl Stream<Item = CommandResultEvent> {
+        self.add_listener()
+            .filter_map(|event| CommandResultEvent::from_log_event(&event))
+    }
+
+    /// Queries the position of the online player with the given name.
+    ///
+    /// Like [connect](Self::connect) this function blocks indefinately if the player is not online,
+    /// so it should be called with [tokio::time::timeout] or some other means of cancellation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn get_position(
+        &mut self,
+        player: &str,
+    ) -> Result<(f64, f64, f64), ExecuteCommandsError> {
+        let command = data_get_entity_command(player_selector(player), "Pos");
+        self.check_policy(&[Command::new(&command)])?;
+        self.query(command, |event| parse_position(&event.output))
+            .await
+    }
+
+    /// Returns a [BuildingInfo] that describes where the connection building is located in the world
+    /// and what it consists of.
+    ///
+    /// The location is queried from the connection entity, so like [connect](Self::connect) this
+    /// function blocks indefinately if the connection is not installed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn building_info(&mut self) -> Result<BuildingInfo, ExecuteCommandsError> {
+        let selector = format!(
+            "@e[type=area_effect_cloud,tag=minect_connection+{},limit=1]",
+            self.identifier
+        );
+        let command = data_get_entity_command(selector, "Pos");
+        let (x, y, z) = self
+            .query(command, |event| parse_position(&event.output))
+            .await?;
+        let entity = BlockPos::new(x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let origin = entity.offset(0, -CONNECTION_ENTITY_Y, 0);
+        Ok(BuildingInfo::new(
+            &self.identifier,
+            origin,
+            &self.command_block_area,
+            self.hide_building,
+        ))
+    }
+
+    /// Returns an [EntityInfo] with the UUID and position of the connection entity. This is useful to
+    /// execute commands relative to the connection building, for example to summon helper entities
+    /// near it.
+    ///
+    /// Like [building_info](Self::building_info) this function blocks indefinately if the
+    /// connection is not installed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn connection_entity(&mut self) -> Result<EntityInfo, ExecuteCommandsError> {
+        let command = format!(
+            "data get entity @e[type=area_effect_cloud,tag=minect_connection+{},limit=1]",
+            self.identifier
+        );
+        self.query(command, |event| EntityInfo::parse(&event.output))
+            .await
+    }
+
+    /// Returns a [Stream] that repeatedly queries the position of the online player with the given
+    /// name (see [get_position](Self::get_position)).
+    ///
+    /// A new query is started every `every_ticks` game ticks (assuming 20 ticks per second), but
+    /// never before the previous query was answered. So if the player goes offline, the stream
+    /// stops yielding items until the player is online again.
+    ///
+    /// The stream ends after the first error.
+    pub fn stream_positions<'l>(
+        &'l mut self,
+        player: &'l str,
+        every_ticks: u32,
+    ) -> impl Stream<Item = Result<(f64, f64, f64), ExecuteCommandsError>> + 'l {
+        let period = Duration::from_millis(50) * every_ticks.max(1);
+        let mut interval = interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        futures::stream::unfold(Some((self, interval)), move |state| async move {
+            let (connection, mut interval) = state?;
+            interval.tick().await;
+            match connection.get_position(player).await {
+                Ok(position) => Some((Ok(position), Some((connection, interval)))),
+                Err(error) => Some((Err(error), None)),
+            }
+        })
+    }
+
+    /// Teleports `entity` to `destination` (see [teleport_command]) and waits until Minecraft
+    /// executed the command.
+    ///
+    /// Returns `true` if at least one entity was teleported and `false` if the command failed, for
+    /// instance because no entity was found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn teleport(
+        &mut self,
+        entity: impl Display,
+        destination: impl Display,
+    ) -> Result<bool, ExecuteCommandsError> {
+        let command = teleport_command(entity, destination);
+        self.check_policy(&[Command::new(&command)])?;
+        let name = next_query_name();
+        let events = self.add_named_listener(name.clone());
+        // A failing command is not logged, so we use a marker to know when the teleport was executed.
+        let marker = format!("{}_done", name);
+        self.execute_internal_commands([
+            Command::named(&name, command),
+            Command::named(&name, summon_named_entity_command(&marker)),
+        ])?;
+        let mut events = Box::pin(events);
+        let mut teleported = false;
+        while let Some(event) = events.next().await {
+            if event.output.parse::<TeleportOutput>().is_ok() {
+                teleported = true;
+            } else if let Ok(output) = event.output.parse::<SummonNamedEntityOutput>() {
+                if output.name == marker {
+                    return Ok(teleported);
+                }
+            }
+        }
+        panic!("LogObserver panicked")
+    }
+
+    /// Executes the given `command` and returns its result (see [store_result_in_score]). If the
+    /// `command` fails, `0` is returned.
+    ///
+    /// For example the result of `time query daytime` is the time of day and the result of
+    /// `execute if entity @e[type=pig]` is the number of pigs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn eval_int(
+        &mut self,
+        command: impl AsRef<str>,
+    ) -> Result<i32, ExecuteCommandsError> {
+        self.check_policy(&[Command::new(command.as_ref())])?;
+        self.eval_internal_int(command).await
+    }
+
+    /// Like [eval_int](Self::eval_int), but without checking the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy), because the `command` is
+    /// generated by Minect.
+    async fn eval_internal_int(
+        &mut self,
+        command: impl AsRef<str>,
+    ) -> Result<i32, ExecuteCommandsError> {
+        const HOLDER: &str = "eval_result";
+        const OBJECTIVE: &str = "minect_global";
+        let name = next_query_name();
+        let events = self.add_named_listener(name.clone());
+        // Both commands are executed in the same batch, so no other command can change the score in
+        // between.
+        self.execute_internal_commands([
+            Command::new(store_result_in_score(command, HOLDER, OBJECTIVE)),
+            Command::named(name, query_scoreboard_command(HOLDER, OBJECTIVE)),
+        ])?;
+        let output = events
+            .filter_map(|event| event.output.parse::<QueryScoreboardOutput>().ok())
+            .next()
+            .await;
+        Ok(output.expect("LogObserver panicked").score)
+    }
+
+    /// Waits until no entity with the given `name` exists anymore, for example a marker that was
+    /// summoned with [summon_marker_command](command::summon_marker_command) as a condition flag and
+    /// is removed by a datapack. Because entities killed by a datapack don't leave a trace in the
+    /// log, the entity is checked with [eval_int](Self::eval_int) every `every_ticks` game ticks
+    /// (assuming 20 ticks per second).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn await_entity_removed(
+        &mut self,
+        name: &str,
+        every_ticks: u32,
+    ) -> Result<(), ExecuteCommandsError> {
+        let command = format!(
+            "execute if entity @e[name={}]",
+            escape_selector_argument(name)
+        );
+        let period = Duration::from_millis(50) * every_ticks.max(1);
+        let mut interval = interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if self.eval_int(&command).await? == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Makes sure the [Minect datapack](Self::get_datapack_dir) is enabled in Minecraft, creating
+    /// it if necessary. This lists the enabled datapacks and enables the Minect datapack if it is
+    /// only available.
+    ///
+    /// Executing commands requires an active Minect datapack, so if Minecraft does not respond
+    /// within 10 seconds, the datapack was probably never loaded. In this case
+    /// [DatapackStatus::ReloadRequired] is returned and the application should ask the player to
+    /// execute `/reload`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the datapack has to be created, but conflicts with an
+    /// existing datapack (can be checked with [ExecuteCommandsError::is_datapack_conflict]), or if
+    /// an [io::Error](std::io::Error) occurs.
+    pub async fn ensure_datapack_enabled(
+        &mut self,
+    ) -> Result<DatapackStatus, ExecuteCommandsError> {
+        const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+        let datapack = format!("file/{}", NAMESPACE);
+        if !self.file_backend.is_dir(&self.datapack_dir) {
+            self.create_datapack()?;
+        }
+
+        let enabled = self.is_datapack_enabled(&datapack);
+        match timeout(RESPONSE_TIMEOUT, enabled).await {
+            Ok(enabled) => {
+                if enabled? {
+                    return Ok(DatapackStatus::Enabled);
+                }
+            }
+            Err(_) => {
+                warn!(
+                    "Minecraft did not respond, the Minect datapack has to be loaded with /reload"
+                );
+                return Ok(DatapackStatus::ReloadRequired);
+            }
+        }
+
+        self.execute_internal_commands([Command::new(datapack_enable_command(&datapack))])?;
+        let enabled = self.is_datapack_enabled(&datapack);
+        if let Ok(true) = timeout(RESPONSE_TIMEOUT, enabled)
+            .await
+            .unwrap_or(Ok(false))
+        {
+            Ok(DatapackStatus::EnabledNow)
+        } else {
+            warn!("Failed to enable the Minect datapack, it has to be loaded with /reload");
+            Ok(DatapackStatus::ReloadRequired)
+        }
+    }
+
+    /// Places the `structure` in the world with its origin at `pos`, mirrored and rotated around the
+    /// origin by Minecraft (see [place_template_command](command::place_template_command)). Waits
+    /// until Minecraft placed the structure.
+    ///
+    /// The structure is written as a structure file in the namespace `minect_custom`, so it is not
+    /// mistaken for a batch of this connection, and loaded with the `place template` command, which
+    /// requires Minecraft 1.19 or newer. The file is removed afterwards.
+    ///
+    /// Like [connect](Self::connect) this blocks indefinately if Minecraft does not execute the
+    /// command, for instance because it is not running.
+    ///
+    /// The `place template` command is checked by the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) like any other command, but the
+    /// policy can't inspect the blocks and entities of the `structure`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy) denies the `place template`
+    /// command (can be checked with [ExecuteCommandsError::is_denied]), if an
+    /// [io::Error](std::io::Error) occurs or if Minecraft did not place the structure (see
+    /// [ExecuteCommandsError::is_structure_not_loaded]), for example because `pos` is outside of
+    /// the world.
+    pub async fn load_structure(
+        &mut self,
+        structure: &CustomStructure,
+        pos: impl Into<BlockPos>,
+        rotation: StructureRotation,
+        mirror: StructureMirror,
+    ) -> Result<(), ExecuteCommandsError> {
+        let structure = structure.to_structure();
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        // Minecraft caches templates by name, so every structure needs a new name
+        let name = format!("custom_{}", nanos);
+        let template = format!(
+            "{}:{}/{}",
+            CUSTOM_STRUCTURES_NAMESPACE, self.identifier, name
+        );
+        let command = place_template_command(template, pos.into(), rotation, mirror);
+        // Checked before the structure file is written, which the policy can't inspect
+        self.check_policy(&[Command::new(&command)])?;
+        let backend = self.file_backend.clone();
+        let backend = &*backend;
+        let dir = self
+            .world_dir
+            .join("generated")
+            .join(CUSTOM_STRUCTURES_NAMESPACE)
+            .join("structures")
+            .join(&self.identifier);
+        create_dir_all_with_permissions(backend, &dir, &self.file_permissions)?;
+        let path = dir.join(format!("{}.nbt", name));
+        let tmp_path = dir.join(format!("{}.tmp", name));
+        create_structure_file(backend, &tmp_path, &structure, self.compression)?;
+        self.file_permissions.apply_to_file(backend, &tmp_path)?;
+        backend
+            .rename(&tmp_path, &path)
+            .map_err(io_error("Failed to rename file", tmp_path))?;
+
+        let query_name = next_query_name();
+        let mut events = self.add_named_listener(query_name.clone());
+        let written = self.write_batch(
+            [Command::named(query_name, command)],
+            ExecutionStrategy::CommandBlocks,
+        );
+        let output = match written {
+            Ok(_) => Ok(events.next().await.expect("LogObserver panicked").output),
+            Err(error) => Err(error),
+        };
+        backend
+            .remove_file(&path)
+            .map_err(io_error("Failed to remove file", path))?;
+        let output = output?;
+        if output.starts_with("Loaded template") {
+            Ok(())
+        } else {
+            let inner = ExecuteCommandsErrorInner::StructureNotLoaded(output);
+            Err(ExecuteCommandsError::new(inner))
+        }
+    }
+
+    /// Executes `reload` in Minecraft and waits until the datapacks are reloaded. Returns the
+    /// [FunctionLoadError]s that Minecraft logged during the reload, so an empty [Vec] means all
+    /// functions were loaded successfully.
+    ///
+    /// This works like [ExecutionStrategy::FunctionFile]: A function is added to the Minect datapack
+    /// that confirms the reload once Minecraft loaded it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an [io::Error](std::io::Error) occurs.
+    pub async fn reload(&mut self) -> Result<Vec<FunctionLoadError>, ExecuteCommandsError> {
+        let mut function_errors = self.get_log_observer().add_function_error_listener();
+        let name = next_query_name();
+        let mut events = self.add_named_listener(name.clone());
+        self.write_batch(
+            [Command::named(
+                name,
+                summon_named_entity_command(RELOADED_OUTPUT),
+            )],
+            ExecutionStrategy::FunctionFile,
+        )?;
+        events.next().await.expect("LogObserver panicked");
+
+        // Errors are logged while loading, so they were observed before the confirmation
+        let mut errors = Vec::new();
+        while let Ok(Some(error)) = timeout(Duration::ZERO, function_errors.next()).await {
+            errors.push(error);
+        }
+        Ok(errors)
+    }
+
+    async fn is_datapack_enabled(&mut self, datapack: &str) -> Result<bool, ExecuteCommandsError> {
+        let command = datapack_list_command(DatapackList::Enabled);
+        self.query(command, |event| {
+            let output = event.output.parse::<DatapackListOutput>().ok()?;
+            Some(output.datapacks.iter().any(|it| it == datapack))
+        })
+        .await
+    }
+
+    /// Executes the given `command` under a unique name and waits for the first [LogEvent] of it
+    /// that can be parsed with `parse`.
+    ///
+    /// Like [connect](Self::connect) this blocks indefinately if the command does not produce a
+    /// matching [LogEvent], for instance because it failed.
+    async fn query<T>(
+        &mut self,
+        command: impl Into<String>,
+        parse: impl Fn(LogEvent) -> Option<T>,
+    ) -> Result<T, ExecuteCommandsError> {
+        let name = next_query_name();
+        let events = self.add_named_listener(name.clone());
+        self.execute_internal_commands([Command::named(name, command)])?;
+        let output = events.filter_map(parse).next().await;
+        Ok(output.expect("LogObserver panicked"))
+    }
+
+    /// Checks the names of the `commands` and the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy).
+    /// Checks the `commands` before they are written or spooled, so a batch that can never be
+    /// executed with `strategy` is rejected immediately instead of failing every time it is written.
+    fn check_commands(
+        &self,
+        commands: impl Iterator<Item = Command>,
+        strategy: ExecutionStrategy,
+    ) -> Result<Vec<Command>, ExecuteCommandsError> {
+        let commands = check_names(commands)?;
+        let commands = resolve_at_connection(commands, &self.identifier);
+        self.check_policy(&commands)?;
+        let strategy = self.resolve_strategy(&commands, strategy);
+        self.check_conditionals(&commands, strategy)?;
+        Ok(apply_logging_overrides(
+            commands,
+            self.enable_logging_automatically,
+        ))
+    }
+
+    fn check_policy(&self, commands: &[Command]) -> Result<(), ExecuteCommandsError> {
+        if let Some(command_policy) = &self.command_policy {
+            let denied = commands
+                .iter()
+                .filter(|command| command_policy(command) == PolicyDecision::Deny)
+                .cloned()
+                .collect::<Vec<_>>();
+            if !denied.is_empty() {
+                let inner = ExecuteCommandsErrorInner::Denied(denied);
+                return Err(ExecuteCommandsError::new(inner));
+            }
+        }
+        Ok(())
+    }
+
     fn init_loaded_listener(&mut self) {
-        let structures_dir = self.structures_dir.clone();
-        let listener = LoadedListener { structures_dir };
+        let listener = self.loaded_listener();
         self.get_log_observer().add_loaded_listener(listener);
         self.loaded_listener_initialized = true;
     }
 
+    fn loaded_listener(&self) -> LoadedListener {
+        LoadedListener {
+            identifier: self.identifier.clone(),
+            structures_dir: self.structures_dir.clone(),
+            datapack_dir: self.datapack_dir.clone(),
+            file_backend: self.file_backend.clone(),
+            unacknowledged_batches: self.unacknowledged_batches.clone(),
+            skipped_batches: self.skipped_batches.clone(),
+            expiring_batches: self.expiring_batches.clone(),
+            loaded_batches: self.loaded_batches.clone(),
+            update_delay_controller: self.update_delay_controller.clone(),
+            warnings: self.warnings.clone(),
+            collected_run: AtomicU32::new(0),
+        }
+    }
+
     fn get_log_observer(&mut self) -> &mut LogObserver {
         if self.log_observer.is_none() {
             // Start LogObserver only when needed
-            self.log_observer = Some(LogObserver::new(&self.log_file));
+            let log_observer = match &self.remote_log_source {
+                Some(source) => LogObserver::start(
+                    source.path().to_path_buf(),
+                    self.log_format,
+                    Some(source.clone()),
+                    self.log_poll_interval,
+                ),
+                None => LogObserver::start(
+                    self.log_file.clone(),
+                    self.log_format,
+                    None,
+                    self.log_poll_interval,
+                ),
+            };
+            let log_volume = self.log_volume.clone();
+            let warnings = self.warnings.clone();
+            log_observer.add_interceptor(move |event| log_volume.measure(event, &warnings));
+            self.log_observer = Some(log_observer);
         }
         self.log_observer.as_mut().unwrap() // Unwrap is safe because we just assigned the value
     }
 }
 
+#[cfg(feature = "connection")]
+fn parse_position(output: &str) -> Option<(f64, f64, f64)> {
+    let output = output.parse::<DataGetEntityOutput>().ok()?;
+    let pos = output.data.parse::<Snbt>().ok()?;
+    match pos.as_list()? {
+        [x, y, z] => Some((x.as_f64()?, y.as_f64()?, z.as_f64()?)),
+        _ => None,
+    }
+}
+
+/// Returns the `commands` if none of them has a name that starts with [names::RESERVED_PREFIX].
+///
+/// Such names would collide with the listeners Minect uses internally, for example to acknowledge
+/// loaded structures, so the output of user commands could be mistaken for an acknowledgement.
+#[cfg(feature = "connection")]
+fn check_names(
+    commands: impl Iterator<Item = Command>,
+) -> Result<Vec<Command>, ExecuteCommandsError> {
+    let commands = commands.collect::<Vec<_>>();
+    let reserved_name = commands
+        .iter()
+        .filter_map(Command::get_name)
+        .find(|name| name.starts_with(names::RESERVED_PREFIX));
+    if let Some(name) = reserved_name {
+        let inner = ExecuteCommandsErrorInner::ReservedName(name.to_string());
+        return Err(ExecuteCommandsError::new(inner));
+    }
+    Ok(commands)
+}
+
+/// Generates a name for a query that is unique across processes sharing a connection.
+#[cfg(feature = "connection")]
+fn next_query_name() -> String {
+    static NEXT_QUERY_ID: AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{}{}_{}", names::QUERY_PREFIX, std::process::id(), id)
+}
+
+#[cfg(feature = "connection")]
 fn lock_file(path: impl AsRef<Path>) -> Result<File, IoErrorAtPath> {
     let file = OpenOptions::new()
         .create(true)
@@ -488,9 +3484,13 @@ fn lock_file(path: impl AsRef<Path>) -> Result<File, IoErrorAtPath> {
     Ok(file)
 }
 
-fn read_incremented_id(file: &mut File, path: impl AsRef<Path>) -> Result<u64, IoErrorAtPath> {
-    let mut content = String::new();
-    file.read_to_string(&mut content)
+#[cfg(feature = "connection")]
+fn read_incremented_id(
+    file: &mut dyn LockedFile,
+    path: impl AsRef<Path>,
+) -> Result<u64, IoErrorAtPath> {
+    let content = file
+        .read_contents()
         .map_err(io_error("Failed to read file", path.as_ref()))?;
     let id = if content.is_empty() {
         0
@@ -507,70 +3507,376 @@ fn read_incremented_id(file: &mut File, path: impl AsRef<Path>) -> Result<u64, I
     Ok(id)
 }
 
-fn write_id(file: &mut File, path: impl AsRef<Path>, id: u64) -> Result<(), IoErrorAtPath> {
-    file.set_len(0)
-        .map_err(io_error("Failed to truncate file", path.as_ref()))?;
-    file.seek(SeekFrom::Start(0))
-        .map_err(io_error("Failed to seek beginning of file", path.as_ref()))?;
-    file.write_all(id.to_string().as_bytes())
-        .map_err(io_error("Failed to write to file", path.as_ref()))?;
-    Ok(())
+#[cfg(feature = "connection")]
+fn write_id(
+    file: &mut dyn LockedFile,
+    path: impl AsRef<Path>,
+    id: u64,
+) -> Result<(), IoErrorAtPath> {
+    file.write_contents(id.to_string().as_bytes())
+        .map_err(io_error("Failed to write to file", path.as_ref()))
 }
 
+#[cfg(feature = "connection")]
 fn create_structure_file(
+    backend: &dyn FileBackend,
     path: impl AsRef<Path>,
-    structure: Structure,
+    structure: &Structure,
+    compression: Compression,
 ) -> Result<(), IoErrorAtPath> {
-    let file = create(path)?;
-    let mut writer = BufWriter::new(file);
-    nbt::to_gzip_writer(&mut writer, &structure, None).unwrap();
-    Ok(())
+    let mut encoder = GzEncoder::new(Vec::new(), compression);
+    nbt::to_writer(&mut encoder, structure, None).unwrap();
+    let contents = encoder.finish().unwrap(); // Writing to a Vec can't fail
+    backend
+        .write(path.as_ref(), &contents)
+        .map_err(io_error("Failed to create file", path.as_ref()))
+}
+
+/// The status of the Minect datapack returned from [MinecraftConnection::ensure_datapack_enabled].
+#[cfg(feature = "connection")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DatapackStatus {
+    /// The datapack was already enabled.
+    Enabled,
+    /// The datapack was available, but disabled and has now been enabled.
+    EnabledNow,
+    /// Minecraft did not confirm that the datapack is enabled. The player has to execute `/reload`
+    /// to load it.
+    ReloadRequired,
+}
+
+/// The status of the connection building returned from [MinecraftConnection::status].
+#[cfg(feature = "connection")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConnectionStatus {
+    /// The connection building loads structure files.
+    Running,
+    /// The connection building was [paused](MinecraftConnection::pause) and does not load structure
+    /// files until it is [resumed](MinecraftConnection::resume).
+    Paused,
 }
 
 /// The error returned from [MinecraftConnection::execute_commands].
+#[cfg(feature = "connection")]
 #[derive(Debug)]
 pub struct ExecuteCommandsError {
     inner: ExecuteCommandsErrorInner,
 }
+#[cfg(feature = "connection")]
 #[derive(Debug)]
 enum ExecuteCommandsErrorInner {
     Io(IoErrorAtPath),
+    DatapackConflict(DatapackConflict),
+    ReservedName(String),
+    Denied(Vec<Command>),
+    ReadOnly,
+    Placement(PlacementError),
+    UnsupportedConditional(Command),
+    TooManyCommands { commands: usize, max: usize },
+    StructureNotLoaded(String),
+    UrgentLaneNotConnected,
+    TimedOut,
     // TODO: Add error for executing too many commands instead of ignoring them
 }
+#[cfg(feature = "connection")]
 impl ExecuteCommandsError {
     fn new(inner: ExecuteCommandsErrorInner) -> ExecuteCommandsError {
         ExecuteCommandsError { inner }
     }
+
+    /// Returns `true` if the commands were not executed, because the Minect datapack had to be
+    /// created, but an existing datapack differs from it (see [DatapackConflict]).
+    pub fn is_datapack_conflict(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::DatapackConflict(_))
+    }
+
+    /// Returns `true` if a [Command] was not executed, because its name starts with `minect_`. These
+    /// names are reserved for internal use by Minect.
+    pub fn is_reserved_name(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::ReservedName(_))
+    }
+
+    /// Returns `true` if the [command_policy](MinecraftConnectionBuilder::command_policy) denied at
+    /// least one command.
+    pub fn is_denied(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::Denied(_))
+    }
+
+    /// The commands that were denied by the
+    /// [command_policy](MinecraftConnectionBuilder::command_policy). This is empty if the error has
+    /// a different cause.
+    pub fn denied_commands(&self) -> &[Command] {
+        match &self.inner {
+            ExecuteCommandsErrorInner::Denied(commands) => commands,
+            _ => &[],
+        }
+    }
+
+    /// Returns `true` if the commands were not executed, because another process holds the
+    /// [lease](MinecraftConnectionBuilder::lease) of the identifier.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::ReadOnly)
+    }
+
+    /// Returns `true` if the commands were not executed, because they can't be placed as command
+    /// blocks, for example because the chains of
+    /// [execute_chains](MinecraftConnection::execute_chains) don't fit into the command block area,
+    /// or because the generated structure contains a broken command block chain (see
+    /// [validate_placement](MinecraftConnectionBuilder::validate_placement)).
+    pub fn is_placement_error(&self) -> bool {
+        self.placement_error().is_some()
+    }
+
+    /// Returns the command block that can't be placed, if this is why the commands were not
+    /// executed.
+    pub fn placement_error(&self) -> Option<&PlacementError> {
+        match &self.inner {
+            ExecuteCommandsErrorInner::Placement(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the commands were not executed, because a
+    /// [conditional](Command::conditional) command would not have been executed conditionally. This
+    /// is the case for every [ExecutionStrategy] other than [ExecutionStrategy::CommandBlocks] and
+    /// if [detect_command_results](MinecraftConnectionBuilder::detect_command_results) is enabled.
+    pub fn is_unsupported_conditional(&self) -> bool {
+        matches!(
+            self.inner,
+            ExecuteCommandsErrorInner::UnsupportedConditional(_)
+        )
+    }
+
+    /// Returns `true` if the commands were not executed, because they don't fit into the command
+    /// block area, for example in [execute_and_stream](MinecraftConnection::execute_and_stream)
+    /// where no command may be ignored.
+    pub fn is_too_many_commands(&self) -> bool {
+        matches!(
+            self.inner,
+            ExecuteCommandsErrorInner::TooManyCommands { .. }
+        )
+    }
+
+    /// Returns `true` if Minecraft did not place the structure passed to
+    /// [load_structure](MinecraftConnection::load_structure).
+    pub fn is_structure_not_loaded(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::StructureNotLoaded(_))
+    }
+
+    /// Returns `true` if the command was not executed, because the
+    /// [urgent lane](MinecraftConnection::execute_urgent) was not set up with
+    /// [connect_urgent](MinecraftConnection::connect_urgent).
+    pub fn is_urgent_lane_not_connected(&self) -> bool {
+        matches!(
+            self.inner,
+            ExecuteCommandsErrorInner::UrgentLaneNotConnected
+        )
+    }
+
+    /// Returns `true` if Minecraft did not confirm the execution in time, for example in
+    /// [uninstall_and_verify](MinecraftConnection::uninstall_and_verify).
+    pub fn is_timed_out(&self) -> bool {
+        matches!(self.inner, ExecuteCommandsErrorInner::TimedOut)
+    }
+
+    /// Whether the batch itself can never be written, regardless of the state of the connection.
+    fn is_invalid_batch(&self) -> bool {
+        self.is_placement_error() || self.is_unsupported_conditional()
+    }
+}
+#[cfg(feature = "connection")]
+impl From<PlacementError> for ExecuteCommandsError {
+    fn from(value: PlacementError) -> ExecuteCommandsError {
+        ExecuteCommandsError::new(ExecuteCommandsErrorInner::Placement(value))
+    }
 }
+#[cfg(feature = "connection")]
 impl From<IoErrorAtPath> for ExecuteCommandsError {
     fn from(value: IoErrorAtPath) -> ExecuteCommandsError {
         ExecuteCommandsError::new(ExecuteCommandsErrorInner::Io(value))
     }
 }
+#[cfg(feature = "connection")]
+impl From<CreateDatapackError> for ExecuteCommandsError {
+    fn from(value: CreateDatapackError) -> ExecuteCommandsError {
+        let inner = match value.into_inner() {
+            CreateDatapackErrorInner::Io(error) => ExecuteCommandsErrorInner::Io(error),
+            CreateDatapackErrorInner::Conflict(conflict) => {
+                ExecuteCommandsErrorInner::DatapackConflict(conflict)
+            }
+        };
+        ExecuteCommandsError::new(inner)
+    }
+}
+#[cfg(feature = "connection")]
 impl Display for ExecuteCommandsError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.inner {
             ExecuteCommandsErrorInner::Io(error) => error.fmt(f),
+            ExecuteCommandsErrorInner::DatapackConflict(conflict) => conflict.fmt(f),
+            ExecuteCommandsErrorInner::ReservedName(name) => write!(
+                f,
+                "The command name '{}' is reserved, because it starts with '{}'",
+                name,
+                names::RESERVED_PREFIX
+            ),
+            ExecuteCommandsErrorInner::Denied(commands) => {
+                write!(f, "Commands denied by the command policy:")?;
+                for command in commands {
+                    write!(f, "\n{}", command)?;
+                }
+                Ok(())
+            }
+            ExecuteCommandsErrorInner::Placement(error) => error.fmt(f),
+            ExecuteCommandsErrorInner::UnsupportedConditional(command) => write!(
+                f,
+                "The conditional command can only be executed by command blocks without \
+                 detecting command results: {}",
+                command
+            ),
+            ExecuteCommandsErrorInner::TooManyCommands { commands, max } => write!(
+                f,
+                "Attempted to execute {} commands, but only {} fit into the command block area",
+                commands, max
+            ),
+            ExecuteCommandsErrorInner::StructureNotLoaded(output) => {
+                write!(f, "Minecraft did not load the structure: {}", output)
+            }
+            ExecuteCommandsErrorInner::ReadOnly => write!(
+                f,
+                "The connection is read-only, because another process holds the lease"
+            ),
+            ExecuteCommandsErrorInner::UrgentLaneNotConnected => write!(
+                f,
+                "The urgent lane is not connected, because connect_urgent did not succeed"
+            ),
+            ExecuteCommandsErrorInner::TimedOut => {
+                write!(f, "Minecraft did not confirm the execution in time")
+            }
         }
     }
 }
+#[cfg(feature = "connection")]
 impl std::error::Error for ExecuteCommandsError {}
+#[cfg(feature = "connection")]
 impl From<ExecuteCommandsError> for std::io::Error {
     fn from(value: ExecuteCommandsError) -> std::io::Error {
         match value.inner {
             ExecuteCommandsErrorInner::Io(error) => std::io::Error::from(error),
+            inner @ ExecuteCommandsErrorInner::DatapackConflict(_) => std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                ExecuteCommandsError::new(inner),
+            ),
+            inner @ (ExecuteCommandsErrorInner::Placement(_)
+            | ExecuteCommandsErrorInner::StructureNotLoaded(_)) => std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                ExecuteCommandsError::new(inner),
+            ),
+            inner @ (ExecuteCommandsErrorInner::ReservedName(_)
+            | ExecuteCommandsErrorInner::UnsupportedConditional(_)
+            | ExecuteCommandsErrorInner::TooManyCommands { .. }) => std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                ExecuteCommandsError::new(inner),
+            ),
+            inner
+            @ (ExecuteCommandsErrorInner::Denied(_) | ExecuteCommandsErrorInner::ReadOnly) => {
+                std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    ExecuteCommandsError::new(inner),
+                )
+            }
+            inner @ ExecuteCommandsErrorInner::UrgentLaneNotConnected => std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                ExecuteCommandsError::new(inner),
+            ),
+            inner @ ExecuteCommandsErrorInner::TimedOut => std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                ExecuteCommandsError::new(inner),
+            ),
         }
     }
 }
 
+#[cfg(feature = "connection")]
+type CommandPolicy = Arc<dyn Fn(&Command) -> PolicyDecision + Send + Sync>;
+
+/// How the commands of a batch are executed in Minecraft, see
+/// [MinecraftConnection::execute_commands_with_strategy].
+#[cfg(feature = "connection")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExecutionStrategy {
+    /// Uses [CommandBlocks](Self::CommandBlocks) if the commands fit into a single structure (see
+    /// [max_commands_per_batch](MinecraftConnection::max_commands_per_batch)) and
+    /// [FunctionFile](Self::FunctionFile) otherwise. A batch then behaves differently depending
+    /// on its size, for example relative coordinates and the gametick of the output, so this has to
+    /// be chosen explicitly.
+    Automatic,
+    /// Every command is placed in its own command block. All commands are executed in the same
+    /// gametick and their output is logged, if [logging is
+    /// enabled](MinecraftConnectionBuilder::enable_logging_automatically). The number of commands is
+    /// limited by [max_commands_per_batch](MinecraftConnection::max_commands_per_batch).
+    #[default]
+    CommandBlocks,
+    /// The commands are written to an `mcfunction` file in the Minect datapack, which is executed
+    /// after a `reload`. This supports an unlimited number of commands and results in much smaller
+    /// structure files, but reloading all datapacks can take a while. The function is executed at
+    /// the position of the connection entity, so relative coordinates differ from
+    /// [CommandBlocks](Self::CommandBlocks).
+    ///
+    /// Commands executed by a function do not log their output. To still receive [LogEvent]s, named
+    /// [Command]s are executed via [logged_block_commands](command::logged_block_commands). These
+    /// may execute in the gametick after the function.
+    ///
+    /// This strategy requires the datapack created by [MinecraftConnection::connect] or
+    /// [MinecraftConnection::create_datapack] of this version of Minect.
+    FunctionFile,
+    /// Every command is executed by a command block minecart that is spawned on the activator rail
+    /// of the connection building, instead of being placed in a chain of command blocks. Only the
+    /// few commands that Minect adds implicitly are still placed in command blocks. On some servers
+    /// this is less likely to be flagged by anti-cheat plugins that scan for command blocks.
+    ///
+    /// The minecarts are executed in order after the command blocks of the batch and are removed
+    /// after they executed. The commands are executed at the position of the activator rail, so
+    /// relative coordinates differ from [CommandBlocks](Self::CommandBlocks). Their output is
+    /// logged, if [logging is enabled](MinecraftConnectionBuilder::enable_logging_automatically).
+    /// The number of commands is not limited by the
+    /// [command_block_area_size](MinecraftConnectionBuilder::command_block_area_size).
+    ///
+    /// This strategy requires the datapack created by [MinecraftConnection::connect] or
+    /// [MinecraftConnection::create_datapack] of this version of Minect.
+    Minecart,
+}
+
 /// A [Command] can be passed to [MinecraftConnection::execute_commands] and contains a Minecraft
 /// command to execute and optionally a custom name.
 ///
 /// The custom name can be useful in conjunction with [MinecraftConnection::add_named_listener] to
 /// easily and performantly filter for the correct [LogEvent].
+///
+/// The [Display](std::fmt::Display) and [Debug](std::fmt::Debug) representations of a [Command]
+/// apply the redactor set with [set_command_redactor], so sensitive values are not leaked into
+/// logs.
+///
+/// With the feature `serde` a [Command] can be serialized and deserialized, for example to persist
+/// and replay batches.
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Command {
+    #[cfg_attr(feature = "serde", serde(default))]
     name: Option<String>,
     command: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    logging: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[cfg_attr(not(feature = "connection"), allow(dead_code))]
+    at_connection: bool,
+    /// Only set internally, so it is not part of the serialized form.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(not(feature = "connection"), allow(dead_code))]
+    chain: ChainPlacement,
+    #[cfg_attr(feature = "serde", serde(default))]
+    conditional: bool,
 }
 impl Command {
     /// Creates a [Command] without custom name. These commands are typically executed under the
@@ -579,73 +3885,394 @@ impl Command {
         Command {
             name: None,
             command: command.into(),
+            logging: None,
+            at_connection: false,
+            chain: ChainPlacement::Continue,
+            conditional: false,
+        }
+    }
+
+    /// Creates a [Command] that is executed at the position of the connection entity. When the
+    /// command is executed by a [MinecraftConnection] it is wrapped in
+    /// `execute at @e[tag=minect_connection+<identifier>] run ...` with the identifier of that
+    /// connection.
+    ///
+    /// This allows relative coordinates like `~ ~ ~` to refer to the connection building, regardless
+    /// of where it was installed.
+    pub fn at_connection(command: impl Into<String>) -> Command {
+        Command {
+            at_connection: true,
+            ..Command::new(command)
         }
     }
 
     /// Creates a [Command] with the given custom `name`.
+    ///
+    /// Names starting with `minect_` are reserved for internal use by Minect.
     pub fn named(name: impl Into<String>, command: impl Into<String>) -> Command {
         Command {
             name: Some(name.into()),
             command: command.into(),
+            logging: None,
+            at_connection: false,
+            chain: ChainPlacement::Continue,
+            conditional: false,
         }
     }
 
+    /// Overrides whether the output of this [Command] is logged, regardless of
+    /// [enable_logging_automatically](MinecraftConnectionBuilder::enable_logging_automatically).
+    ///
+    /// This can be used to exclude noisy commands from logging in an otherwise logged batch or to
+    /// log specific commands in an otherwise unlogged batch. Consecutive commands with an override
+    /// are wrapped in a single pair of [reset_logging_command] and [enable_logging_command].
+    pub fn with_logging(mut self, logging: bool) -> Command {
+        self.logging = Some(logging);
+        self
+    }
+
+    /// Makes this [Command] conditional, so it is only executed if the previous [Command]
+    /// succeeded. Consecutive conditional commands are placed in a straight line together with the
+    /// command they depend on, so the condition never checks a command block around a corner.
+    ///
+    /// Conditions are only supported by [ExecutionStrategy::CommandBlocks], so
+    /// [ExecutionStrategy::Automatic] never uses a function file for a batch with a conditional
+    /// command. Conditions are not supported together with
+    /// [detect_command_results](MinecraftConnectionBuilder::detect_command_results). Batches with
+    /// unsupported conditions are rejected (see [ExecuteCommandsError::is_unsupported_conditional]).
+    /// So are batches whose conditional commands can't be placed in a straight line (see
+    /// [ExecuteCommandsError::is_placement_error]).
+    pub fn conditional(mut self) -> Command {
+        self.conditional = true;
+        self
+    }
+
+    /// Whether this [Command] is [conditional](Self::conditional).
+    pub fn is_conditional(&self) -> bool {
+        self.conditional
+    }
+
     /// The optional custom name.
     pub fn get_name(&self) -> Option<&str> {
         self.name.as_ref().map(|it| it.as_str())
     }
 
-    /// The Minecraft command.
+    /// The Minecraft command. For a command created with [at_connection](Self::at_connection) this
+    /// is the command before it is wrapped.
     pub fn get_command(&self) -> &str {
         &self.command
     }
 
+    #[cfg(feature = "connection")]
     fn get_name_as_json(&self) -> Option<String> {
         self.get_name().map(create_json_text_component)
     }
 }
 
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let command = redact(&self.command);
+        match &self.name {
+            Some(name) => write!(f, "[{}] {}", name, command),
+            None => write!(f, "{}", command),
+        }
+    }
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.name)
+            .field("command", &redact(&self.command))
+            .field("logging", &self.logging)
+            .field("at_connection", &self.at_connection)
+            .field("chain", &self.chain)
+            .field("conditional", &self.conditional)
+            .finish()
+    }
+}
+
+/// Where a [Command] is placed in the command block area relative to the previous [Command].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(not(feature = "connection"), allow(dead_code))]
+enum ChainPlacement {
+    /// The command continues the chain of the previous command.
+    #[default]
+    Continue,
+    /// The command starts a new chain, which is executed independently of the previous chain.
+    New,
+    /// The command starts a new chain on a higher layer than all previous commands, so it is
+    /// executed after all previous chains.
+    NewAbove,
+    /// The command is executed by a command block minecart on the activator rail (see
+    /// [ExecutionStrategy::Minecart]).
+    Minecart,
+}
+
+#[cfg(feature = "connection")]
 struct LoadedListener {
+    identifier: String,
     structures_dir: PathBuf,
+    datapack_dir: PathBuf,
+    file_backend: Arc<dyn FileBackend>,
+    unacknowledged_batches: Option<Arc<UnacknowledgedBatches>>,
+    skipped_batches: Arc<SkippedBatches>,
+    expiring_batches: Arc<ExpiringBatches>,
+    loaded_batches: Arc<LoadedBatches>,
+    update_delay_controller: Option<Arc<UpdateDelayController>>,
+    warnings: Arc<Warnings>,
+    /// Structure files of runs before this one were already removed.
+    collected_run: AtomicU32,
 }
+#[cfg(feature = "connection")]
 impl LoadedListener {
     fn on_event(&self, event: LogEvent) {
-        if let Some(id) = parse_loaded_output(&event) {
-            let structure_file = self.get_structure_file(id);
-            if let Err(error) = remove_file(&structure_file) {
-                error!("{}", error);
+        if let Some((identifier, id)) = parse_loaded_output(&event) {
+            if identifier == self.identifier {
+                self.on_acknowledged(id, true);
+            }
+        } else if let Some((identifier, id)) = parse_expired_output(&event) {
+            if identifier == self.identifier {
+                self.on_acknowledged(id, false);
             }
-            // Remove all previous structure files in case they are still there
-            // (for instance because a structure was loaded while no connection was active)
-            let mut i = 1;
-            while let Ok(()) = remove_file(self.get_structure_file(id.wrapping_sub(i))) {
-                i += 1;
+        } else if let Some(id) = parse_legacy_loaded_output(&event) {
+            self.on_legacy_loaded(id);
+        } else if let Some(id) = parse_batch_executed_output(&event, &self.identifier) {
+            let result = remove_batch_function(
+                &*self.file_backend,
+                &self.datapack_dir,
+                &self.identifier,
+                id,
+            );
+            if let Err(error) = result {
+                self.warnings.report(MinectWarning::io_failed(error));
             }
         }
     }
 
-    fn get_structure_file(&self, id: impl Display) -> PathBuf {
-        self.structures_dir.join(format!("{}.nbt", id))
+    /// Called when the structure with the given `id` was loaded. If `loaded` is `false`, the
+    /// replacement of an expired batch was loaded instead of the batch.
+    fn on_acknowledged(&self, id: u64, loaded: bool) {
+        let unacknowledged_batches = self.unacknowledged_batches.as_deref();
+        self.skipped_batches
+            .acknowledge(id, unacknowledged_batches, &self.warnings);
+        if let Some(unacknowledged_batches) = unacknowledged_batches {
+            unacknowledged_batches.acknowledge(id);
+        }
+        self.expiring_batches.acknowledge(id);
+        if loaded {
+            self.loaded_batches.logged(id);
+        } else {
+            self.loaded_batches.replaced(id);
+            self.expiring_batches.replaced(id);
+        }
+        if let Some(controller) = &self.update_delay_controller {
+            controller.loaded(id, Instant::now());
+        }
+        let structure_file = self.get_structure_file(id);
+        if let Err(error) = self.file_backend.remove_file(&structure_file) {
+            let error = IoErrorAtPath::new("Failed to remove file", structure_file, error);
+            self.warnings.report(MinectWarning::io_failed(error));
+        }
+        // Remove all previous structure files in case they are still there
+        // (for instance because a structure was loaded while no connection was active)
+        let mut i = 1;
+        while let Ok(()) = self
+            .file_backend
+            .remove_file(&self.get_structure_file(id.wrapping_sub(i)))
+        {
+            i += 1;
+        }
+        // Once a structure of a run is loaded, no structure of an earlier run can be loaded
+        let run = run_of(id);
+        if run > self.collected_run.fetch_max(run, Ordering::Relaxed) {
+            self.remove_structure_files_before_run(run);
+        }
+    }
+
+    /// Called when a structure was loaded that was written before the identifier was added to the
+    /// loaded output. Such an output can't be attributed to a connection, so it is neither used to
+    /// acknowledge batches nor to detect skipped batches. The structure file with the `id` is only
+    /// removed if it was written in the old format as well, so it is not left behind forever.
+    fn on_legacy_loaded(&self, id: u64) {
+        let structure_file = self.get_structure_file(id);
+        let is_legacy = match self.file_backend.read(&structure_file) {
+            Ok(contents) => is_legacy_structure(&contents, &self.identifier),
+            Err(_) => false,
+        };
+        if is_legacy {
+            if let Err(error) = self.file_backend.remove_file(&structure_file) {
+                let error = IoErrorAtPath::new("Failed to remove file", structure_file, error);
+                self.warnings.report(MinectWarning::io_failed(error));
+            }
+        }
+    }
+
+    /// Called regularly by the [LogObserver], even if no [LogEvent]s occur.
+    fn on_poll(&self) {
+        if let Some(controller) = &self.update_delay_controller {
+            controller.poll(Instant::now());
+        }
+        self.expiring_batches.expire(
+            &*self.file_backend,
+            &self.structures_dir,
+            self.unacknowledged_batches.as_deref(),
+            &self.warnings,
+        );
+        if let Some(unacknowledged_batches) = &self.unacknowledged_batches {
+            unacknowledged_batches.retry_expired(&self.warnings);
+        }
+    }
+
+    /// Removes structure files left behind by runs that were stopped or crashed before Minecraft
+    /// loaded them (see [namespace_ids_per_run](MinecraftConnectionBuilder::namespace_ids_per_run)).
+    fn remove_structure_files_before_run(&self, run: u32) {
+        let entries = match self.file_backend.read_dir(&self.structures_dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let paths = entries
+            .into_iter()
+            .filter(|path| parse_structure_file(path).is_some_and(|id| run_of(id) < run));
+        for path in paths {
+            if let Err(error) = self.file_backend.remove_file(&path) {
+                let error = IoErrorAtPath::new("Failed to remove file", path, error);
+                self.warnings.report(MinectWarning::io_failed(error));
+            }
+        }
+    }
+
+    fn get_structure_file(&self, id: u64) -> PathBuf {
+        structure_file(&self.structures_dir, id)
     }
 }
 
-const LOADED_LISTENER_NAME: &str = "minect_loaded";
+/// Connection entities with this tag are skipped by `minect_internal:update`.
+#[cfg(feature = "connection")]
+const PAUSED_TAG: &str = "minect_paused";
+
+#[cfg(feature = "connection")]
 const STRUCTURE_LOADED_OUTPUT_PREFIX: &str = "minect_loaded_";
+#[cfg(feature = "connection")]
+const STRUCTURE_EXPIRED_OUTPUT_PREFIX: &str = "minect_expired_";
+
+#[cfg(feature = "connection")]
+const RELOADED_OUTPUT: &str = "minect_reloaded";
+
+#[cfg(feature = "connection")]
+fn parse_loaded_output(event: &LogEvent) -> Option<(String, u64)> {
+    parse_structure_output(event, names::LOADED, STRUCTURE_LOADED_OUTPUT_PREFIX)
+}
 
-fn parse_loaded_output(event: &LogEvent) -> Option<u64> {
-    if event.executor != LOADED_LISTENER_NAME {
+/// Parses the loaded output of structures that were written before the identifier was added to it:
+/// `minect_loaded_<id>` instead of `minect_loaded_<identifier>+<id>`.
+#[cfg(feature = "connection")]
+fn parse_legacy_loaded_output(event: &LogEvent) -> Option<u64> {
+    if event.executor != names::LOADED {
         return None;
     }
     let output = event.output.parse::<SummonNamedEntityOutput>().ok()?;
-    let id = &output.name.strip_prefix(STRUCTURE_LOADED_OUTPUT_PREFIX)?;
+    let id = output.name.strip_prefix(STRUCTURE_LOADED_OUTPUT_PREFIX)?;
     id.parse().ok()
 }
 
+/// Whether the gzipped structure file `contents` lack the loaded output of the connection with the
+/// given `identifier`, because they were written in the format of
+/// [parse_legacy_loaded_output].
+#[cfg(feature = "connection")]
+fn is_legacy_structure(contents: &[u8], identifier: &str) -> bool {
+    let mut decoded = Vec::new();
+    if GzDecoder::new(contents).read_to_end(&mut decoded).is_err() {
+        return false;
+    }
+    let marker = format!("{}{}+", STRUCTURE_LOADED_OUTPUT_PREFIX, identifier);
+    !decoded
+        .windows(marker.len())
+        .any(|window| window == marker.as_bytes())
+}
+
+#[cfg(feature = "connection")]
+fn parse_expired_output(event: &LogEvent) -> Option<(String, u64)> {
+    parse_structure_output(event, names::EXPIRED, STRUCTURE_EXPIRED_OUTPUT_PREFIX)
+}
+
+#[cfg(feature = "connection")]
+fn parse_structure_output(event: &LogEvent, executor: &str, prefix: &str) -> Option<(String, u64)> {
+    if event.executor != executor {
+        return None;
+    }
+    let output = event.output.parse::<SummonNamedEntityOutput>().ok()?;
+    let name = output.name.strip_prefix(prefix)?;
+    let (identifier, id) = name.rsplit_once('+')?;
+    Some((identifier.to_string(), id.parse().ok()?))
+}
+
+/// Wraps consecutive commands whose [logging](Command::with_logging) differs from the batch in
+/// commands that toggle logging.
+#[cfg(feature = "connection")]
+fn apply_logging_overrides(commands: Vec<Command>, batch_logging: bool) -> Vec<Command> {
+    let (before, after) = if batch_logging {
+        (reset_logging_command(), enable_logging_command())
+    } else {
+        (enable_logging_command(), reset_logging_command())
+    };
+    let mut result = Vec::with_capacity(commands.len());
+    let mut overridden = false;
+    for command in commands {
+        let is_override = command.logging.is_some_and(|it| it != batch_logging);
+        if is_override != overridden {
+            let toggle = if is_override { &before } else { &after };
+            result.push(Command::new(toggle.clone()));
+            overridden = is_override;
+        }
+        result.push(command);
+    }
+    if overridden {
+        result.push(Command::new(after));
+    }
+    result
+}
+
+/// Wraps all `commands` that were created with [Command::at_connection] in an `execute at` for the
+/// connection entity of the connection with the given `identifier`.
+#[cfg(feature = "connection")]
+fn resolve_at_connection(commands: Vec<Command>, identifier: &str) -> Vec<Command> {
+    commands
+        .into_iter()
+        .map(|command| {
+            if command.at_connection {
+                Command {
+                    command: format!(
+                        "execute at @e[type=area_effect_cloud,tag=minect_connection+{},limit=1] run {}",
+                        identifier, command.command
+                    ),
+                    at_connection: false,
+                    ..command
+                }
+            } else {
+                command
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "connection")]
+fn update_delay_command(update_delay: u32) -> Command {
+    Command::new(format!(
+        "scoreboard players set update_delay minect_config {}",
+        update_delay
+    ))
+}
+
+#[cfg(feature = "connection")]
 fn add_implicit_commands(
-    commands: impl IntoIterator<IntoIter = impl ExactSizeIterator<Item = Command>>,
+    commands: Vec<Command>,
     connection_id: &str,
-    structure_id: u64,
+    acknowledged_id: Option<u64>,
     enable_logging_automatically: bool,
+    query_gametick: bool,
+    protect_building: Option<bool>,
+    command_block_area: &CommandBlockArea,
 ) -> (impl Iterator<Item = Command>, usize) {
     let mut first_cmds = Vec::from_iter([
         Command::new(format!(
@@ -653,23 +4280,492 @@ fn add_implicit_commands(
             connection_id
         )),
         Command::new(enable_logging_command()),
-        Command::named(
-            LOADED_LISTENER_NAME,
+    ]);
+    if let Some(structure_id) = acknowledged_id {
+        first_cmds.push(Command::named(
+            names::LOADED,
             summon_named_entity_command(&format!(
-                "{}{}",
-                STRUCTURE_LOADED_OUTPUT_PREFIX, structure_id
+                "{}{}+{}",
+                STRUCTURE_LOADED_OUTPUT_PREFIX, connection_id, structure_id
             )),
-        ),
-    ]);
+        ));
+    }
+    if query_gametick {
+        first_cmds.push(Command::named(
+            names::GAMETICK,
+            time_query_command(TimeQuery::Gametime),
+        ));
+    }
+    if let Some(hide_building) = protect_building {
+        let commands = protection_commands(connection_id, hide_building);
+        first_cmds.extend(commands.into_iter().map(Command::new));
+    }
     let mut last_cmds = Vec::new();
     if !enable_logging_automatically {
         first_cmds.push(Command::new(reset_logging_command()));
         last_cmds.push(Command::new(enable_logging_command()));
     }
-    last_cmds.push(Command::new("function minect_internal:clean_up"));
+    let fill_commands = command_block_area.clean_up_commands(connection_id);
+    if fill_commands.is_empty() {
+        last_cmds.push(Command::new("function minect_internal:clean_up"));
+    } else {
+        // The clean_up function only removes command blocks in the default area
+        last_cmds.push(Command::new(reset_logging_command()));
+        last_cmds.push(Command::new(
+            "tag @e[type=area_effect_cloud,tag=minect_connection,tag=minect_inactive] remove minect_inactive",
+        ));
+        last_cmds.extend(fill_commands.into_iter().map(Command::new));
+    }
+    // The clean up must not remove command blocks of chains that were not yet executed
+    if commands
+        .iter()
+        .any(|command| command.chain != ChainPlacement::Continue)
+    {
+        last_cmds[0].chain = ChainPlacement::NewAbove;
+    }
 
     let commands = commands.into_iter();
     let commands_len = first_cmds.len() + commands.len();
     let commands = first_cmds.into_iter().chain(commands).chain(last_cmds);
     (commands, commands_len)
 }
+
+#[cfg(all(test, feature = "connection"))]
+mod tests {
+    use super::*;
+    use crate::{file_backend::MemoryFileBackend, utils::TempDir};
+    use std::io::Write;
+
+    #[test]
+    fn test_default_execution_strategy() {
+        // when:
+        let builder = MinecraftConnection::builder("test", "minecraft/saves/world");
+
+        // then:
+        assert_eq!(builder.execution_strategy, ExecutionStrategy::CommandBlocks);
+    }
+
+    #[test]
+    fn test_bulk_job_chunks_fit_into_command_blocks() {
+        // given:
+        let dir = TempDir::new("bulk_job_chunks");
+        let mut connection = MinecraftConnection::builder("test", dir.join("saves/world"))
+            .file_backend(MemoryFileBackend::default())
+            .build();
+        let max = connection.max_commands_per_batch();
+        let commands = (0..max + 1).map(|index| Command::new(format!("say {}", index)));
+
+        // when:
+        let job = connection.start_bulk_job(commands, usize::MAX).unwrap();
+
+        // then:
+        let chunks = job.chunk_lens();
+        assert_eq!(chunks, [max, 1]);
+    }
+
+    #[test]
+    fn test_execute_and_stream_full_batch_falls_back_to_function_file() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .execution_strategy(ExecutionStrategy::CommandBlocks)
+            .build();
+        let max = connection.max_commands_per_batch();
+        let commands = (0..max).map(|index| Command::new(format!("say {}", index)));
+
+        // when:
+        let actual = connection.execute_and_stream(commands.collect::<Vec<_>>());
+
+        // then:
+        assert!(actual.is_ok());
+        let tag_path = connection.datapack_dir.join(BATCH_TAG);
+        let tag = connection.file_backend.read(&tag_path).unwrap();
+        let tag = String::from_utf8(tag).unwrap();
+        assert!(tag.contains("minect_internal:connection/test/batch/"));
+    }
+
+    #[test]
+    fn test_execute_and_stream_full_conditional_batch_rejected() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .execution_strategy(ExecutionStrategy::CommandBlocks)
+            .build();
+        let max = connection.max_commands_per_batch();
+        let mut commands = (0..max)
+            .map(|index| Command::new(format!("say {}", index)))
+            .collect::<Vec<_>>();
+        commands[1] = commands[1].clone().conditional();
+
+        // when:
+        let actual = connection.execute_and_stream(commands);
+
+        // then:
+        assert!(actual.err().unwrap().is_too_many_commands());
+    }
+
+    #[test]
+    fn test_drain_spool_merges_batches() {
+        // given:
+        let dir = TempDir::new("drain_spool_merges");
+        let world_dir = dir.join("saves/world");
+        let mut connection = MinecraftConnection::builder("test", &world_dir)
+            .spool_dir(dir.join("spool"))
+            .merge_spooled_batches(true)
+            .build();
+        let spool = connection.spool.clone().unwrap();
+        for index in 0..3 {
+            let commands = [Command::new(format!("say {}", index))];
+            spool
+                .append(ExecutionStrategy::CommandBlocks, &commands)
+                .unwrap();
+        }
+
+        // when:
+        let drained = connection.drain_spool().unwrap();
+
+        // then:
+        assert!(drained);
+        assert!(spool.batches().unwrap().is_empty());
+        let structures_dir = world_dir.join("generated/minect/structures/test");
+        assert_eq!(
+            count_pending_structures(&*connection.file_backend, &structures_dir),
+            1
+        );
+    }
+
+    #[test]
+    fn test_invalid_chains_rejected_before_spooling() {
+        // given:
+        let dir = TempDir::new("invalid_chains_rejected_before_spooling");
+        let mut connection = MinecraftConnection::builder("test", dir.join("saves/world"))
+            .file_backend(MemoryFileBackend::default())
+            .spool_dir(dir.join("spool"))
+            .build();
+        let spool = connection.spool.clone().unwrap();
+        let max = connection.max_commands_per_batch();
+        let chain = || (0..max).map(|index| Command::new(format!("say {}", index)));
+
+        // when:
+        let actual = connection.execute_chains([chain(), chain()]);
+
+        // then:
+        assert!(actual.unwrap_err().is_placement_error());
+        assert!(spool.batches().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_drain_spool_quarantines_invalid_batch() {
+        // given:
+        let dir = TempDir::new("drain_spool_quarantines");
+        let world_dir = dir.join("saves/world");
+        let mut connection = MinecraftConnection::builder("test", &world_dir)
+            .spool_dir(dir.join("spool"))
+            .build();
+        let mut warnings = connection.warnings();
+        let spool = connection.spool.clone().unwrap();
+        let strategy = ExecutionStrategy::FunctionFile;
+        spool.append(strategy, &conditional_batch()).unwrap();
+        spool.append(strategy, &[Command::new("say 3")]).unwrap();
+        let invalid = spool.batches().unwrap()[0];
+
+        // when:
+        let drained = connection.drain_spool().unwrap();
+
+        // then:
+        assert!(drained);
+        assert!(spool.batches().unwrap().is_empty());
+        let structures_dir = world_dir.join("generated/minect/structures/test");
+        assert_eq!(
+            count_pending_structures(&*connection.file_backend, &structures_dir),
+            1
+        );
+        let path = dir.join("spool").join(format!("{}.failed", invalid));
+        assert!(path.is_file());
+        let warning = futures::executor::block_on(warnings.next());
+        assert!(
+            matches!(warning, Some(MinectWarning::SpooledBatchQuarantined { path: actual, .. }) if actual == path)
+        );
+    }
+
+    #[test]
+    fn test_urgent_lane_processes_every_batch_with_command_blocks() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .execution_strategy(ExecutionStrategy::FunctionFile)
+            .acknowledge_every(4u32)
+            .build();
+
+        // when:
+        let lane = connection.get_urgent_lane();
+
+        // then:
+        assert_eq!(lane.identifier, "test_urgent");
+        assert_eq!(lane.execution_strategy, ExecutionStrategy::CommandBlocks);
+        assert_eq!(lane.acknowledge_every, 1);
+        assert!(lane.structures_dir.ends_with("structures/test_urgent"));
+    }
+
+    #[test]
+    fn test_pause_fails_without_urgent_lane() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .build();
+
+        // when:
+        let actual = connection.pause();
+
+        // then:
+        assert!(actual.unwrap_err().is_urgent_lane_not_connected());
+        assert_eq!(connection.status(), ConnectionStatus::Running);
+    }
+
+    #[test]
+    fn test_pause_and_resume_through_urgent_lane() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .build();
+        connection.get_urgent_lane().session = Some(1);
+
+        // when:
+        connection.pause().unwrap();
+
+        // then:
+        assert_eq!(connection.status(), ConnectionStatus::Paused);
+
+        // when:
+        connection.resume().unwrap();
+
+        // then:
+        assert_eq!(connection.status(), ConnectionStatus::Running);
+    }
+
+    #[test]
+    fn test_connect_error_from_read_only() {
+        // given:
+        let error = ExecuteCommandsError::new(ExecuteCommandsErrorInner::ReadOnly);
+
+        // when:
+        let actual = ConnectError::from(error);
+
+        // then:
+        assert!(actual.is_read_only());
+        let io_error = std::io::Error::from(actual);
+        assert_eq!(io_error.kind(), std::io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn test_connect_error_from_denied() {
+        // given:
+        let commands = vec![Command::new("stop")];
+        let error = ExecuteCommandsError::new(ExecuteCommandsErrorInner::Denied(commands.clone()));
+
+        // when:
+        let actual = ConnectError::from(error);
+
+        // then:
+        assert!(actual.is_denied());
+        assert_eq!(actual.denied_commands(), commands);
+    }
+
+    fn loaded_event(output: &str) -> LogEvent {
+        format!(
+            "[21:05:40] [Server thread/INFO]: [{}: Summoned new {}]",
+            names::LOADED,
+            output
+        )
+        .parse()
+        .unwrap()
+    }
+
+    fn gzip(contents: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_parse_legacy_loaded_output() {
+        // when:
+        let legacy = parse_legacy_loaded_output(&loaded_event("minect_loaded_5"));
+        let current = parse_legacy_loaded_output(&loaded_event("minect_loaded_test+5"));
+
+        // then:
+        assert_eq!(legacy, Some(5));
+        assert_eq!(current, None);
+        assert_eq!(parse_loaded_output(&loaded_event("minect_loaded_5")), None);
+    }
+
+    #[test]
+    fn test_legacy_loaded_output_removes_only_legacy_structures() {
+        // given:
+        let connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .build();
+        let backend = &*connection.file_backend;
+        let structures_dir = &connection.structures_dir;
+        backend.create_dir_all(structures_dir).unwrap();
+        let legacy_file = structure_file(structures_dir, 1);
+        let current_file = structure_file(structures_dir, 2);
+        let legacy = gzip("summon area_effect_cloud ~ ~ ~ {CustomName:'minect_loaded_1'}");
+        let current = gzip("summon area_effect_cloud ~ ~ ~ {CustomName:'minect_loaded_test+2'}");
+        backend.write(&legacy_file, &legacy).unwrap();
+        backend.write(&current_file, &current).unwrap();
+        let listener = connection.loaded_listener();
+
+        // when:
+        listener.on_event(loaded_event("minect_loaded_1"));
+        listener.on_event(loaded_event("minect_loaded_2"));
+
+        // then:
+        assert!(!backend.is_file(&legacy_file));
+        assert!(backend.is_file(&current_file));
+    }
+
+    #[test]
+    fn test_command_serialization_omits_chain_placement() {
+        // given:
+        let mut command = Command::named("name", "say hi");
+        command.chain = ChainPlacement::NewAbove;
+
+        // when:
+        let json = serde_json::to_string(&command).unwrap();
+        let actual = serde_json::from_str::<Command>(&json).unwrap();
+
+        // then:
+        assert!(!json.contains("chain"));
+        assert_eq!(actual, Command::named("name", "say hi"));
+    }
+
+    #[tokio::test]
+    async fn test_load_structure_checks_command_policy() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .command_policy(|command| {
+                if command.get_command().starts_with("place template") {
+                    PolicyDecision::Deny
+                } else {
+                    PolicyDecision::Allow
+                }
+            })
+            .build();
+        let structure = CustomStructure::new();
+
+        // when:
+        let actual = connection
+            .load_structure(
+                &structure,
+                (0, 0, 0),
+                StructureRotation::None,
+                StructureMirror::None,
+            )
+            .await;
+
+        // then:
+        assert!(actual.unwrap_err().is_denied());
+        let dir = connection.world_dir.join("generated/minect_custom");
+        assert!(!connection.file_backend.is_dir(&dir));
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_and_verify_times_out_without_minecraft() {
+        // given:
+        let dir = TempDir::new("uninstall_times_out");
+        let connection = MinecraftConnection::builder("test", dir.join("saves/world")).build();
+        let structures_dir = connection.structures_dir.clone();
+        std::fs::create_dir_all(&structures_dir).unwrap();
+
+        // when:
+        let actual = connection
+            .uninstall_and_verify(Duration::from_millis(10))
+            .await;
+
+        // then:
+        let error = actual.unwrap_err();
+        assert!(error.is_timed_out());
+        assert_eq!(
+            std::io::Error::from(error).kind(),
+            std::io::ErrorKind::TimedOut
+        );
+        assert!(structures_dir.is_dir());
+    }
+
+    #[test]
+    fn test_remove_installation_keeps_other_connections() {
+        // given:
+        let connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .build();
+        let backend = &*connection.file_backend;
+        let other_dir = connection.structures_dir.with_file_name("other");
+        backend.create_dir_all(&connection.datapack_dir).unwrap();
+        backend.create_dir_all(&connection.structures_dir).unwrap();
+        backend.create_dir_all(&other_dir).unwrap();
+
+        // when:
+        let report = connection.remove_installation();
+
+        // then:
+        assert!(report.is_complete());
+        assert!(!backend.is_dir(&connection.datapack_dir));
+        assert!(!backend.is_dir(&connection.structures_dir));
+        assert!(backend.is_dir(&other_dir));
+    }
+
+    fn conditional_batch() -> [Command; 2] {
+        [Command::new("say 1"), Command::new("say 2").conditional()]
+    }
+
+    #[test]
+    fn test_conditional_rejected_by_function_file() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .build();
+
+        // when:
+        let strategy = ExecutionStrategy::FunctionFile;
+        let actual = connection.execute_commands_with_strategy(conditional_batch(), strategy);
+
+        // then:
+        assert!(actual.unwrap_err().is_unsupported_conditional());
+    }
+
+    #[test]
+    fn test_conditional_rejected_with_detect_command_results() {
+        // given:
+        let mut connection = MinecraftConnection::builder("test", "minecraft/saves/world")
+            .file_backend(MemoryFileBackend::default())
+            .detect_command_results(true)
+            .build();
+
+        // when:
+        let strategy = ExecutionStrategy::CommandBlocks;
+        let actual = connection.execute_commands_with_strategy(conditional_batch(), strategy);
+
+        // then:
+        assert!(actual.unwrap_err().is_unsupported_conditional());
+    }
+    #[test]
+    fn test_conditional_rejected_before_spooling() {
+        // given:
+        let dir = TempDir::new("conditional_rejected_before_spooling");
+        let mut connection = MinecraftConnection::builder("test", dir.join("saves/world"))
+            .file_backend(MemoryFileBackend::default())
+            .spool_dir(dir.join("spool"))
+            .execution_strategy(ExecutionStrategy::FunctionFile)
+            .build();
+        let spool = connection.spool.clone().unwrap();
+
+        // when:
+        let actual = connection.execute_commands(conditional_batch());
+
+        // then:
+        assert!(actual.unwrap_err().is_unsupported_conditional());
+        assert!(spool.batches().unwrap().is_empty());
+    }
+}
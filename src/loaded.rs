@@ -0,0 +1,227 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{retry::is_after, structure_id::parse_structure_file};
+use log::debug;
+use notify::{
+    event::RemoveKind, recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+/// A batch that Minecraft loaded.
+///
+/// These events can be received with
+/// [MinecraftConnection::add_batch_loaded_listener](crate::MinecraftConnection::add_batch_loaded_listener).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchLoaded {
+    /// The structure id of the loaded batch.
+    pub id: u64,
+    /// How Minect noticed that the batch was loaded.
+    pub source: LoadedSource,
+    _private: (),
+}
+
+impl BatchLoaded {
+    pub(crate) fn new(id: u64, source: LoadedSource) -> BatchLoaded {
+        BatchLoaded {
+            id,
+            source,
+            _private: (),
+        }
+    }
+}
+
+/// How Minect noticed that a batch was loaded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoadedSource {
+    /// The connection building acknowledged the batch in Minecraft's log file. This requires the
+    /// gamerule `logAdminCommands`.
+    Log,
+    /// The structure file of the batch was removed from the structures directory before Minect
+    /// read the acknowledgement from the log file. Minecraft itself never removes structure files,
+    /// so this requires a cleanup outside of Minect, for example a server side script or plugin.
+    /// Only local structures directories are watched.
+    FileSystem,
+}
+
+/// Sends [BatchLoaded] events for batches acknowledged in the log file and for structure files that
+/// are removed before their acknowledgement.
+pub(crate) struct LoadedBatches {
+    last_logged: Mutex<Option<u64>>,
+    last_removed: Mutex<Option<u64>>,
+    listeners: RwLock<Vec<UnboundedSender<BatchLoaded>>>,
+    observers: RwLock<Vec<Observer>>,
+}
+
+/// Called for each [BatchLoaded] event until it returns `false`.
+type Observer = Box<dyn Fn(&BatchLoaded) -> bool + Send + Sync>;
+
+impl LoadedBatches {
+    pub(crate) fn new() -> LoadedBatches {
+        LoadedBatches {
+            last_logged: Mutex::new(None),
+            last_removed: Mutex::new(None),
+            listeners: RwLock::new(Vec::new()),
+            observers: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn add_listener(&self) -> impl Stream<Item = BatchLoaded> {
+        let (sender, receiver) = unbounded_channel();
+        self.listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    pub(crate) fn add_observer(
+        &self,
+        observer: impl Fn(&BatchLoaded) -> bool + Send + Sync + 'static,
+    ) {
+        self.observers.write().unwrap().push(Box::new(observer));
+    }
+
+    /// Called when the connection building acknowledged the batch with the given `id`.
+    ///
+    /// This must be called before Minect removes the structure file, so the removal is not
+    /// reported as [LoadedSource::FileSystem].
+    pub(crate) fn logged(&self, id: u64) {
+        *self.last_logged.lock().unwrap() = Some(id);
+        self.send(id, LoadedSource::Log);
+    }
+
+    /// Called when the replacement of an expired batch with the given `id` was loaded instead of the
+    /// batch. No event is sent, but Minect removing the structure file is not reported either.
+    pub(crate) fn replaced(&self, id: u64) {
+        *self.last_logged.lock().unwrap() = Some(id);
+    }
+
+    /// Called when the structure file of the batch with the given `id` was removed.
+    pub(crate) fn removed(&self, id: u64) {
+        if let Some(last_logged) = *self.last_logged.lock().unwrap() {
+            if !is_after(id, last_logged) {
+                return; // Removed by Minect after the acknowledgement
+            }
+        }
+        let mut last_removed = self.last_removed.lock().unwrap();
+        if let Some(last_removed) = *last_removed {
+            if !is_after(id, last_removed) {
+                return;
+            }
+        }
+        *last_removed = Some(id);
+        self.send(id, LoadedSource::FileSystem);
+    }
+
+    fn send(&self, id: u64, source: LoadedSource) {
+        let event = BatchLoaded::new(id, source);
+        self.observers
+            .write()
+            .unwrap()
+            .retain(|observer| observer(&event));
+        self.listeners
+            .write()
+            .unwrap()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
+/// Watches the `structures_dir` for removed structure files and reports them to `loaded_batches`.
+///
+/// The returned watcher stops watching when it is dropped.
+pub(crate) fn watch_structures_dir(
+    structures_dir: &Path,
+    loaded_batches: Arc<LoadedBatches>,
+) -> notify::Result<RecommendedWatcher> {
+    let mut watcher = recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let event = match event {
+            Ok(event) => event,
+            Err(error) => {
+                debug!("Failed to watch structures directory: {}", error);
+                return;
+            }
+        };
+        if let EventKind::Remove(RemoveKind::File | RemoveKind::Any) = event.kind {
+            for id in event
+                .paths
+                .iter()
+                .filter_map(|path| parse_structure_file(path))
+            {
+                loaded_batches.removed(id);
+            }
+        }
+    })?;
+    watcher.watch(structures_dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_removed_after_logged_is_not_reported() {
+        // given:
+        let loaded_batches = LoadedBatches::new();
+        let events = loaded_batches.add_listener();
+
+        // when:
+        loaded_batches.removed(1);
+        loaded_batches.logged(1);
+        loaded_batches.removed(1);
+        loaded_batches.logged(2);
+        loaded_batches.removed(2);
+        loaded_batches.removed(3);
+        loaded_batches.removed(3);
+
+        // then:
+        drop(loaded_batches);
+        let events = futures::executor::block_on(events.collect::<Vec<_>>());
+        let events = events
+            .into_iter()
+            .map(|event| (event.id, event.source))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                (1, LoadedSource::FileSystem),
+                (1, LoadedSource::Log),
+                (2, LoadedSource::Log),
+                (3, LoadedSource::FileSystem),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_structure_file() {
+        assert_eq!(
+            parse_structure_file(Path::new("structures/42.nbt")),
+            Some(42)
+        );
+        assert_eq!(parse_structure_file(Path::new("structures/42.tmp")), None);
+        assert_eq!(parse_structure_file(Path::new("structures/abc.nbt")), None);
+        assert_eq!(
+            parse_structure_file(Path::new("structures/run-1-2.nbt")),
+            Some((1 << 32) + 2)
+        );
+    }
+}
@@ -18,10 +18,34 @@
 
 //! Observing Minecraft's log file.
 
+mod format;
+#[cfg(feature = "connection")]
+mod function_error;
+#[cfg(feature = "connection")]
+mod history;
+#[cfg(feature = "connection")]
+mod multi_line;
+#[cfg(feature = "connection")]
 mod observer;
-pub use observer::LogObserver;
+mod paper;
+mod parser;
+#[cfg(feature = "connection")]
+mod remote;
 
-use std::{fmt::Display, str::FromStr};
+pub use format::LogFormat;
+#[cfg(feature = "connection")]
+pub use function_error::FunctionLoadError;
+#[cfg(feature = "connection")]
+pub(crate) use observer::DEFAULT_POLL_INTERVAL;
+#[cfg(feature = "connection")]
+pub use observer::{InterceptorAction, LogEventIter, LogObserver, LogObserverSet, ObserverError};
+pub use parser::ParserRegistry;
+#[cfg(feature = "connection")]
+pub use remote::RemoteLogSource;
+#[cfg(feature = "ssh")]
+pub use remote::SshLogSource;
+
+use std::{any::Any, collections::BTreeMap, fmt::Display, path::PathBuf, str::FromStr};
 
 /// A [LogEvent] is created for every command that is successfully executed and logged.
 ///
@@ -33,6 +57,9 @@ use std::{fmt::Display, str::FromStr};
 /// ```none
 /// [13:14:30] [Server thread/INFO]: [executor: output]
 /// ```
+///
+/// The output of some commands spans multiple lines. A [LogObserver] combines these lines into a
+/// single [LogEvent] whose [output](LogEvent::output) contains line breaks.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LogEvent {
     hour: u8,
@@ -42,6 +69,27 @@ pub struct LogEvent {
     pub executor: String,
     /// The output of the command.
     pub output: String,
+    /// The game time in ticks of the most recent batch of commands executed with
+    /// [query_gametick](crate::MinecraftConnectionBuilder::query_gametick) enabled.
+    ///
+    /// This is only set by a [LogObserver] and only after such a batch was executed. Note that
+    /// commands executed outside of a batch (for example by players) may have been executed in a
+    /// later game tick.
+    pub gametick: Option<u64>,
+    /// A number that is incremented for every [LogEvent] read by a [LogObserver]. This can be used
+    /// to restore the order of [LogEvent]s from multiple streams of the same [LogObserver].
+    ///
+    /// [LogEvent]s that are not read by a [LogObserver] have a sequence of `0`.
+    pub sequence: u64,
+    /// Whether this [LogEvent] was read from the history of the log file by
+    /// [LogObserver::read_history] rather than observed while it was written.
+    pub historical: bool,
+    /// The path of the log file this [LogEvent] was read from. This is set by a [LogObserver] and
+    /// can be used to distinguish [LogEvent]s of different servers when using a [LogObserverSet].
+    pub source: Option<PathBuf>,
+    /// Arbitrary key value pairs that can be added by interceptors (see
+    /// [LogObserver::add_interceptor]), for example correlation ids.
+    pub annotations: BTreeMap<String, String>,
     _private: (),
 }
 
@@ -49,28 +97,17 @@ impl FromStr for LogEvent {
     type Err = ();
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        fn from_str_opt(line: &str) -> Option<LogEvent> {
-            let line = line.strip_prefix('[')?;
-            let (hour, line) = read_digits(line, 2)?;
-            let line = line.strip_prefix(':')?;
-            let (minute, line) = read_digits(line, 2)?;
-            let line = line.strip_prefix(':')?;
-            let (second, line) = read_digits(line, 2)?;
-            let line = line.strip_prefix("] [Server thread/INFO]: [")?;
-            let line = line.trim_end();
-            let line = line.strip_suffix(']')?;
-            let (executor, output) = line.split_once(": ")?;
+        LogFormat::Vanilla.parse_event(line).ok_or(())
+    }
+}
 
-            Some(LogEvent {
-                hour,
-                minute,
-                second,
-                executor: executor.to_string(),
-                output: output.to_string(),
-                _private: (),
-            })
-        }
-        from_str_opt(line).ok_or(())
+impl LogEvent {
+    /// Parses the [output](LogEvent::output) of this [LogEvent] with the given [ParserRegistry].
+    ///
+    /// Returns [None] if no registered parser matches the output. Otherwise the result can be
+    /// downcast to the type of the parser that matched.
+    pub fn parse_with(&self, registry: &ParserRegistry) -> Option<Box<dyn Any + Send>> {
+        registry.parse(&self.output)
     }
 }
 
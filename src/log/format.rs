@@ -0,0 +1,111 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::log::{paper, read_digits, LogEvent};
+use std::collections::BTreeMap;
+
+/// The layout of lines in a log file. This depends on the server software that writes the log file.
+///
+/// Regardless of the [LogFormat] that a [LogEvent] was parsed with, it is always
+/// [displayed](std::fmt::Display) in the [Vanilla](LogFormat::Vanilla) format.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogFormat {
+    /// The format of the vanilla client and server:
+    /// ```none
+    /// [13:14:30] [Server thread/INFO]: [executor: output]
+    /// ```
+    #[default]
+    Vanilla,
+    /// **Experimental:** The format of [Paper](https://papermc.io) servers and their forks. In
+    /// addition to the vanilla format this accepts messages logged from other threads, an optional
+    /// logger name and the console layout:
+    /// ```none
+    /// [13:14:30] [Craft Scheduler Thread - 3/INFO]: [executor: output]
+    /// [13:14:30] [Server thread/INFO] [minecraft/MinecraftServer]: [executor: output]
+    /// [13:14:30 INFO]: [executor: output]
+    /// ```
+    Paper,
+}
+
+impl LogFormat {
+    /// Parses a single line of a log file. Returns [None] if the line is not a [LogEvent] in this
+    /// format.
+    pub fn parse_event(self, line: &str) -> Option<LogEvent> {
+        let line = line.strip_prefix('[')?;
+        let (hour, line) = read_digits(line, 2)?;
+        let line = line.strip_prefix(':')?;
+        let (minute, line) = read_digits(line, 2)?;
+        let line = line.strip_prefix(':')?;
+        let (second, line) = read_digits(line, 2)?;
+        let line = self.strip_header(line)?;
+        let line = line.trim_end();
+        let line = line.strip_prefix('[')?.strip_suffix(']')?;
+        let (executor, output) = line.split_once(": ")?;
+
+        Some(LogEvent {
+            hour,
+            minute,
+            second,
+            executor: executor.to_string(),
+            output: output.to_string(),
+            gametick: None,
+            sequence: 0,
+            historical: false,
+            source: None,
+            annotations: BTreeMap::new(),
+            _private: (),
+        })
+    }
+
+    /// Whether the `line` starts like a [LogEvent], but may continue on the following lines.
+    #[cfg_attr(not(feature = "connection"), allow(dead_code))]
+    pub(crate) fn starts_like_log_event(self, line: &str) -> bool {
+        line.get(9..)
+            .and_then(|line| self.strip_header(line))
+            .is_some_and(|line| line.starts_with('['))
+    }
+
+    /// Strips everything between the timestamp and the message.
+    fn strip_header(self, line: &str) -> Option<&str> {
+        match self {
+            LogFormat::Vanilla => line.strip_prefix("] [Server thread/INFO]: "),
+            LogFormat::Paper => paper::strip_header(line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vanilla_rejects_other_threads() {
+        // given:
+        let line =
+            "[21:05:40] [Craft Scheduler Thread - 3/INFO]: [test: Added tag 'success' to test]";
+
+        // when:
+        let vanilla = LogFormat::Vanilla.parse_event(line);
+        let paper = LogFormat::Paper.parse_event(line);
+
+        // then:
+        assert_eq!(vanilla, None);
+        assert_eq!(paper.unwrap().output, "Added tag 'success' to test");
+    }
+}
@@ -0,0 +1,88 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::{self, Display};
+
+/// An error that Minecraft logged, because a function of a datapack could not be loaded, for
+/// example because it contains an invalid command. These errors can be received with
+/// [LogObserver::add_function_error_listener](super::LogObserver::add_function_error_listener).
+///
+/// This is what such an error looks like in Minecraft's log file:
+/// ```none
+/// [13:14:30] [Worker-Main-2/ERROR]: Failed to load function my_namespace:my_function
+/// java.util.concurrent.CompletionException: java.lang.IllegalArgumentException: Whilst parsing command on line 1: ...
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionLoadError {
+    /// The id of the function that could not be loaded, for example `my_namespace:my_function`.
+    pub function: String,
+    /// The line following the error, which usually describes the problem.
+    pub message: Option<String>,
+    _private: (),
+}
+
+impl FunctionLoadError {
+    pub(crate) fn new(function: impl Into<String>) -> FunctionLoadError {
+        FunctionLoadError {
+            function: function.into(),
+            message: None,
+            _private: (),
+        }
+    }
+
+    /// Parses the first line of a [FunctionLoadError].
+    pub(crate) fn parse_line(line: &str) -> Option<FunctionLoadError> {
+        let (header, message) = line.split_once("]: ")?;
+        if !header.contains("/ERROR") && !header.contains(" ERROR") {
+            return None;
+        }
+        let function = message.strip_prefix("Failed to load function ")?;
+        Some(FunctionLoadError::new(function.trim_end()))
+    }
+}
+
+impl Display for FunctionLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to load function {}", self.function)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        // given:
+        let line =
+            "[13:14:30] [Worker-Main-2/ERROR]: Failed to load function my_namespace:my_function";
+
+        // when:
+        let actual = FunctionLoadError::parse_line(line);
+
+        // then:
+        assert_eq!(
+            actual,
+            Some(FunctionLoadError::new("my_namespace:my_function"))
+        );
+    }
+}
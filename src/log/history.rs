@@ -0,0 +1,121 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    io::{io_error, IoErrorAtPath},
+    log::{multi_line::MultiLineParser, observer::ENCODING, LogEvent, LogFormat},
+};
+use flate2::read::GzDecoder;
+use log::error;
+use std::{
+    fs::{read_dir, File},
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Reads the last `archives` compressed log files in the directory of `path` and then `path` itself
+/// up to `end` and sends all [LogEvent]s to `sender` marked as
+/// [historical](LogEvent::historical).
+pub(crate) fn read_history(
+    path: &Path,
+    end: u64,
+    archives: usize,
+    format: LogFormat,
+    sender: UnboundedSender<LogEvent>,
+) {
+    if let Err(error) = try_read_history(path, end, archives, format, &sender) {
+        error!("Failed to read log history: {}", error);
+    }
+}
+
+fn try_read_history(
+    path: &Path,
+    end: u64,
+    archives: usize,
+    format: LogFormat,
+    sender: &UnboundedSender<LogEvent>,
+) -> Result<(), IoErrorAtPath> {
+    for archive in find_archives(path, archives)? {
+        let file = File::open(&archive).map_err(io_error("Failed to open file", &archive))?;
+        let reader = BufReader::new(GzDecoder::new(file));
+        if !send_events(reader, &archive, format, sender) {
+            return Ok(());
+        }
+    }
+    let file = File::open(path).map_err(io_error("Failed to open file", path))?;
+    send_events(BufReader::new(file.take(end)), path, format, sender);
+    Ok(())
+}
+
+/// Finds the last `archives` compressed log files. Minecraft names them `<date>-<n>.log.gz`, so
+/// sorting them by name sorts them chronologically (as long as there are less than 10 per day).
+fn find_archives(path: &Path, archives: usize) -> Result<Vec<PathBuf>, IoErrorAtPath> {
+    if archives == 0 {
+        return Ok(Vec::new());
+    }
+    let dir = path.parent().unwrap_or(path);
+    let mut paths = read_dir(dir)
+        .map_err(io_error("Failed to read directory", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(".log.gz"))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    let skip = paths.len().saturating_sub(archives);
+    Ok(paths.split_off(skip))
+}
+
+/// Returns `false` if the receiver was dropped.
+fn send_events(
+    mut reader: impl BufRead,
+    source: &Path,
+    format: LogFormat,
+    sender: &UnboundedSender<LogEvent>,
+) -> bool {
+    let mut parser = MultiLineParser::new(format);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let events = match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => parser.finish().into_iter().collect(),
+            Ok(_) => {
+                let (line, _) = ENCODING.decode_without_bom_handling(&buffer);
+                parser.push_line(&line)
+            }
+            Err(e) => {
+                error!("Failed to read log history: {}", e);
+                return false;
+            }
+        };
+        let done = buffer.is_empty();
+        for mut event in events {
+            event.historical = true;
+            event.source = Some(source.to_path_buf());
+            if sender.send(event).is_err() {
+                return false;
+            }
+        }
+        if done {
+            return true;
+        }
+    }
+}
@@ -0,0 +1,157 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::log::{LogEvent, LogFormat};
+
+/// The maximum number of continuation lines that are buffered for a single [LogEvent]. This
+/// prevents unbounded memory usage if a line looks like the start of a multi-line [LogEvent], but
+/// never ends.
+const MAX_CONTINUATION_LINES: usize = 1024;
+
+/// Parses [LogEvent]s from lines of a log file, including [LogEvent]s whose output spans multiple
+/// lines.
+///
+/// A line that starts like a [LogEvent], but does not end like one, is buffered. All following lines
+/// that don't start with a timestamp are treated as continuation lines. As soon as the buffered lines
+/// form a [LogEvent] with balanced square brackets, it is returned. If the brackets never become
+/// balanced, the [LogEvent] is returned when the next line with a timestamp is read.
+#[derive(Default)]
+pub(crate) struct MultiLineParser {
+    format: LogFormat,
+    pending: Option<Pending>,
+}
+
+struct Pending {
+    text: String,
+    lines: usize,
+}
+
+impl MultiLineParser {
+    pub(crate) fn new(format: LogFormat) -> MultiLineParser {
+        MultiLineParser {
+            format,
+            pending: None,
+        }
+    }
+
+    /// Processes the next `line` and returns all [LogEvent]s that are complete.
+    pub(crate) fn push_line(&mut self, line: &str) -> Vec<LogEvent> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let mut events = Vec::new();
+        if starts_with_timestamp(line) {
+            if let Some(event) = self.flush() {
+                events.push(event);
+            }
+            if let Some(event) = self.format.parse_event(line) {
+                events.push(event);
+            } else if self.format.starts_like_log_event(line) {
+                self.pending = Some(Pending {
+                    text: line.to_string(),
+                    lines: 0,
+                });
+            }
+        } else if let Some(pending) = &mut self.pending {
+            pending.text.push('\n');
+            pending.text.push_str(line);
+            pending.lines += 1;
+            if has_balanced_brackets(&pending.text) {
+                if let Some(event) = self.format.parse_event(&pending.text) {
+                    self.pending = None;
+                    events.push(event);
+                }
+            } else if pending.lines >= MAX_CONTINUATION_LINES {
+                self.pending = None;
+            }
+        }
+        events
+    }
+
+    /// Returns the buffered [LogEvent], if any. This should be called at the end of a file.
+    pub(crate) fn finish(&mut self) -> Option<LogEvent> {
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Option<LogEvent> {
+        self.format.parse_event(&self.pending.take()?.text)
+    }
+}
+
+fn starts_with_timestamp(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.len() >= 10
+        && bytes[0] == b'['
+        && bytes[1..9].iter().enumerate().all(|(index, b)| {
+            if index % 3 == 2 {
+                *b == b':'
+            } else {
+                b.is_ascii_digit()
+            }
+        })
+        && (bytes[9] == b']' || bytes[9] == b' ')
+}
+
+fn has_balanced_brackets(text: &str) -> bool {
+    let mut depth = 0i32;
+    for c in text.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_line_event() {
+        // given:
+        let mut parser = MultiLineParser::default();
+
+        // when:
+        let first = parser.push_line("[21:05:40] [Server thread/INFO]: [test: first line\n");
+        let second = parser.push_line("second [line]]\n");
+
+        // then:
+        assert_eq!(first, []);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].executor, "test");
+        assert_eq!(second[0].output, "first line\nsecond [line]");
+    }
+
+    #[test]
+    fn test_unbalanced_multi_line_event_is_flushed_by_next_line() {
+        // given:
+        let mut parser = MultiLineParser::default();
+
+        // when:
+        let first = parser.push_line("[21:05:40] [Server thread/INFO]: [test: first [line");
+        let second = parser.push_line("second line]");
+        let third = parser.push_line("[21:05:41] [Server thread/INFO]: [test: next]");
+
+        // then:
+        assert_eq!(first, []);
+        assert_eq!(second, []);
+        assert_eq!(third.len(), 2);
+        assert_eq!(third[0].output, "first [line\nsecond line");
+        assert_eq!(third[1].output, "next");
+    }
+}
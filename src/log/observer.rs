@@ -16,21 +16,33 @@
 // You should have received a copy of the GNU General Public License along with Minect.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{LoadedListener, LogEvent};
+use crate::{
+    command::TimeQueryOutput,
+    log::{
+        history::read_history, multi_line::MultiLineParser, FunctionLoadError, LogFormat,
+        RemoteLogSource,
+    },
+    names, LoadedListener, LogEvent,
+};
 use encoding_rs::Encoding;
-use log::trace;
+use futures::stream::select_all;
+use log::{error, trace};
 use notify::{event::ModifyKind, recommended_watcher, EventKind, RecursiveMode, Watcher};
 use std::{
+    any::Any,
+    cell::{Cell, RefCell},
     collections::HashMap,
+    fmt::{self, Display},
     fs::File,
     io::{BufRead, BufReader, Seek, SeekFrom},
+    panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     sync::{
-        mpsc::{channel, RecvTimeoutError, Sender},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
         Arc, RwLock,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::sync::mpsc::{error::SendError, unbounded_channel, UnboundedSender};
 use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
@@ -51,40 +63,153 @@ use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
 /// is shut down after the [LogObserver] is dropped.
 pub struct LogObserver {
     loaded_listeners: Arc<RwLock<Vec<LoadedListener>>>,
+    interceptors: Arc<RwLock<Vec<Interceptor>>>,
+    error_listeners: Arc<RwLock<Vec<UnboundedSender<ObserverError>>>>,
+    function_error_listeners: Arc<RwLock<Vec<UnboundedSender<FunctionLoadError>>>>,
     listeners: Arc<RwLock<Vec<UnboundedSender<LogEvent>>>>,
     named_listeners: Arc<RwLock<HashMap<String, Vec<UnboundedSender<LogEvent>>>>>,
+    sync_listeners: Arc<RwLock<Vec<Sender<LogEvent>>>>,
+    path: PathBuf,
+    format: LogFormat,
+    history_end: u64,
+    remote: bool,
 }
 
 impl LogObserver {
     pub fn new<P: AsRef<Path>>(path: P) -> LogObserver {
-        let path = path.as_ref().to_path_buf();
+        LogObserver::with_format(path, LogFormat::default())
+    }
+
+    /// Creates a [LogObserver] for a log file that is written in the given [LogFormat]. This is
+    /// required for server software like Paper, whose log format differs from vanilla.
+    pub fn with_format<P: AsRef<Path>>(path: P, format: LogFormat) -> LogObserver {
+        LogObserver::start(
+            path.as_ref().to_path_buf(),
+            format,
+            None,
+            DEFAULT_POLL_INTERVAL,
+        )
+    }
+
+    /// Creates a [LogObserver] for a log file on a remote host that is read from the given
+    /// [RemoteLogSource], for example an `SshLogSource`.
+    ///
+    /// The history of a remote log file is not available, so [read_history](Self::read_history)
+    /// returns an empty [Stream].
+    pub fn remote(source: Arc<dyn RemoteLogSource>, format: LogFormat) -> LogObserver {
+        LogObserver::start(
+            source.path().to_path_buf(),
+            format,
+            Some(source),
+            DEFAULT_POLL_INTERVAL,
+        )
+    }
+
+    /// Starts the background thread, which checks the log file for new lines at least once per
+    /// `poll_interval`.
+    pub(crate) fn start(
+        path: PathBuf,
+        format: LogFormat,
+        remote_source: Option<Arc<dyn RemoteLogSource>>,
+        poll_interval: Duration,
+    ) -> LogObserver {
+        let remote = remote_source.is_some();
         let listeners = Arc::new(RwLock::new(Vec::new()));
         let named_listeners = Arc::new(RwLock::new(HashMap::new()));
         let loaded_listeners = Arc::new(RwLock::new(Vec::new()));
+        let interceptors = Arc::new(RwLock::new(Vec::new()));
+        let error_listeners = Arc::new(RwLock::new(Vec::new()));
+        let function_error_listeners = Arc::new(RwLock::new(Vec::new()));
+        let sync_listeners = Arc::new(RwLock::new(Vec::new()));
 
         let backend = LogObserverBackend {
-            path,
+            path: path.clone(),
+            format,
+            poll_interval,
             loaded_listeners: loaded_listeners.clone(),
+            interceptors: interceptors.clone(),
+            error_listeners: error_listeners.clone(),
+            function_error_listeners: function_error_listeners.clone(),
+            pending_function_error: RefCell::new(None),
             listeners: listeners.clone(),
             named_listeners: named_listeners.clone(),
+            sync_listeners: sync_listeners.clone(),
+            gametick: Cell::new(None),
+            next_sequence: Cell::new(1),
         };
         let (initialized_sender, initialized_receiver) = channel();
-        thread::spawn(|| backend.observe_log(initialized_sender));
+        thread::spawn(|| match remote_source {
+            Some(source) => backend.observe_remote_log(source, initialized_sender),
+            None => backend.observe_log(initialized_sender),
+        });
         // Wait for the background thread to seek the end of the log file. This is important to
         // ensure that no events of commands executed after starting the log observer are lost.
-        let _ = initialized_receiver.recv();
+        let history_end = initialized_receiver.recv().unwrap_or(0);
 
         LogObserver {
             loaded_listeners,
+            interceptors,
+            error_listeners,
+            function_error_listeners,
             listeners,
             named_listeners,
+            sync_listeners,
+            path,
+            format,
+            history_end,
+            remote,
+        }
+    }
+
+    /// Returns a [Stream] of the [LogEvent]s that were written to the log file before this
+    /// [LogObserver] was created. These [LogEvent]s are marked as
+    /// [historical](LogEvent::historical) and have a [sequence](LogEvent::sequence) of `0`.
+    ///
+    /// If `archives` is greater than zero, the given number of most recent compressed log files
+    /// (`*.log.gz` in the directory of the log file) are read first. This is useful to process what
+    /// happened while the program was offline.
+    ///
+    /// The stream ends after all historical [LogEvent]s are read. The history is read in a
+    /// background thread, so together with [add_listener](Self::add_listener) no [LogEvent] is
+    /// missed or duplicated.
+    pub fn read_history(&self, archives: usize) -> impl Stream<Item = LogEvent> {
+        let (sender, receiver) = unbounded_channel();
+        if self.remote {
+            return UnboundedReceiverStream::new(receiver); // The sender is dropped, so the stream ends
         }
+        let path = self.path.clone();
+        let history_end = self.history_end;
+        let format = self.format;
+        thread::spawn(move || read_history(&path, history_end, archives, format, sender));
+        UnboundedReceiverStream::new(receiver)
     }
 
     pub(crate) fn add_loaded_listener(&self, listener: LoadedListener) {
         self.loaded_listeners.write().unwrap().push(listener);
     }
 
+    /// Adds an `interceptor` that is called for every [LogEvent] before it is sent to listeners.
+    /// Interceptors are called in the order they were added.
+    ///
+    /// An interceptor can modify the [LogEvent], for example to redact player names or to add
+    /// [annotations](LogEvent::annotations) like correlation ids. By returning
+    /// [InterceptorAction::Drop] it can prevent the [LogEvent] from being sent to any listener or
+    /// subsequent interceptor. Named listeners are matched against the modified
+    /// [executor](LogEvent::executor).
+    ///
+    /// Interceptors do not affect the [LogEvent]s that are used internally to operate a
+    /// [MinecraftConnection](crate::MinecraftConnection).
+    pub fn add_interceptor(
+        &self,
+        interceptor: impl Fn(&mut LogEvent) -> InterceptorAction + Send + Sync + 'static,
+    ) {
+        self.add_shared_interceptor(Arc::new(interceptor));
+    }
+
+    fn add_shared_interceptor(&self, interceptor: Interceptor) {
+        self.interceptors.write().unwrap().push(interceptor);
+    }
+
     /// Returns a [Stream] of all [LogEvent]s. To remove the listener simply drop the stream.
     ///
     /// Internally the stream is backed by an unbound channel. This means it should be polled
@@ -114,87 +239,393 @@ impl LogObserver {
             .push(sender);
         UnboundedReceiverStream::new(receiver)
     }
+
+    /// Returns a blocking [Iterator] of all [LogEvent]s. This allows consuming [LogEvent]s without
+    /// an async runtime. To remove the listener simply drop the iterator.
+    ///
+    /// [next](Iterator::next) returns [None] if no [LogEvent] is received within `timeout_per_item`
+    /// or if this [LogObserver] was dropped. After a timeout the iterator can still be used to
+    /// continue waiting for [LogEvent]s without missing any.
+    ///
+    /// Internally the iterator is backed by an unbound channel. This means it should be consumed
+    /// regularly to avoid memory leaks.
+    pub fn iter_events(&self, timeout_per_item: Duration) -> LogEventIter {
+        let (sender, receiver) = channel();
+        self.sync_listeners.write().unwrap().push(sender);
+        LogEventIter {
+            receiver,
+            timeout_per_item,
+        }
+    }
+
+    /// Returns a [Stream] of [ObserverError]s. To remove the listener simply drop the stream.
+    ///
+    /// An [ObserverError] is sent whenever a panic occurs while processing a line of the log file,
+    /// for example in an interceptor. The panic is caught, so the [LogObserver] continues to work,
+    /// but the affected [LogEvent] may be lost.
+    pub fn add_error_listener(&self) -> impl Stream<Item = ObserverError> {
+        let (sender, receiver) = unbounded_channel();
+        self.error_listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Returns a [Stream] of [FunctionLoadError]s that Minecraft logs when a datapack function can
+    /// not be loaded, for example after a `reload`. To remove the listener simply drop the stream.
+    pub fn add_function_error_listener(&self) -> impl Stream<Item = FunctionLoadError> {
+        let (sender, receiver) = unbounded_channel();
+        self.function_error_listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// The number of listeners added with [add_listener](Self::add_listener) whose stream was not
+    /// dropped yet.
+    pub fn listener_count(&self) -> usize {
+        let listeners = self.listeners.read().unwrap();
+        listeners
+            .iter()
+            .filter(|sender| !sender.is_closed())
+            .count()
+    }
+
+    /// The number of listeners added with [add_named_listener](Self::add_named_listener) whose
+    /// stream was not dropped yet.
+    pub fn named_listener_count(&self) -> usize {
+        let named_listeners = self.named_listeners.read().unwrap();
+        named_listeners
+            .values()
+            .flatten()
+            .filter(|sender| !sender.is_closed())
+            .count()
+    }
+}
+
+/// A blocking [Iterator] of [LogEvent]s returned by [LogObserver::iter_events].
+pub struct LogEventIter {
+    receiver: Receiver<LogEvent>,
+    timeout_per_item: Duration,
+}
+
+impl Iterator for LogEventIter {
+    type Item = LogEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv_timeout(self.timeout_per_item).ok()
+    }
 }
 
+/// A [LogObserverSet] observes multiple log files at once, for example the log files of multiple
+/// servers behind a proxy. The [LogEvent]s of all log files are merged into unified streams and can
+/// be distinguished by their [source](LogEvent::source).
+///
+/// Note that the [sequence](LogEvent::sequence) of [LogEvent]s is only ordered per log file.
+pub struct LogObserverSet {
+    observers: Vec<LogObserver>,
+}
+
+impl LogObserverSet {
+    /// Creates a [LogObserver] for each of the given `paths`.
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> LogObserverSet {
+        LogObserverSet {
+            observers: paths.into_iter().map(LogObserver::new).collect(),
+        }
+    }
+
+    /// The [LogObserver]s of this set in the order of the paths passed to [new](Self::new).
+    pub fn observers(&self) -> &[LogObserver] {
+        &self.observers
+    }
+
+    /// Adds an `interceptor` to all [LogObserver]s of this set (see
+    /// [LogObserver::add_interceptor]).
+    pub fn add_interceptor(
+        &self,
+        interceptor: impl Fn(&mut LogEvent) -> InterceptorAction + Send + Sync + 'static,
+    ) {
+        let interceptor: Interceptor = Arc::new(interceptor);
+        for observer in &self.observers {
+            observer.add_shared_interceptor(interceptor.clone());
+        }
+    }
+
+    /// Returns a [Stream] of all [LogEvent]s of all log files. To remove the listener simply drop
+    /// the stream.
+    ///
+    /// Internally the stream is backed by unbound channels. This means it should be polled
+    /// regularly to avoid memory leaks.
+    pub fn add_listener(&self) -> impl Stream<Item = LogEvent> {
+        select_all(
+            self.observers
+                .iter()
+                .map(|observer| Box::pin(observer.add_listener())),
+        )
+    }
+
+    /// Returns a [Stream] of [LogEvent]s of all log files with [executor](LogEvent::executor) equal
+    /// to the given `name`. To remove the listener simply drop the stream.
+    ///
+    /// Internally the stream is backed by unbound channels. This means it should be polled
+    /// regularly to avoid memory leaks.
+    pub fn add_named_listener(&self, name: impl Into<String>) -> impl Stream<Item = LogEvent> {
+        let name = name.into();
+        select_all(
+            self.observers
+                .iter()
+                .map(|observer| Box::pin(observer.add_named_listener(name.clone()))),
+        )
+    }
+}
+
+/// Decides what happens with a [LogEvent] after it was passed to an interceptor (see
+/// [LogObserver::add_interceptor]).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InterceptorAction {
+    /// Pass the (possibly modified) [LogEvent] on to the next interceptor and the listeners.
+    Continue,
+    /// Discard the [LogEvent].
+    Drop,
+}
+
+/// An error that occurred in the background thread of a [LogObserver] (see
+/// [LogObserver::add_error_listener]).
+#[derive(Clone, Debug)]
+pub struct ObserverError {
+    message: String,
+}
+
+impl ObserverError {
+    fn from_panic(context: &str, payload: Box<dyn Any + Send>) -> ObserverError {
+        let cause = if let Some(message) = payload.downcast_ref::<&str>() {
+            message
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message
+        } else {
+            "Box<dyn Any>"
+        };
+        ObserverError {
+            message: format!("Panicked while {}: {}", context, cause),
+        }
+    }
+}
+
+impl Display for ObserverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ObserverError {}
+
+/// How often the log file is checked for new lines if no file system event occurs. Once per game
+/// tick by default.
+pub(crate) const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often closed listeners are removed.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+type Interceptor = Arc<dyn Fn(&mut LogEvent) -> InterceptorAction + Send + Sync>;
+
 #[cfg(target_os = "windows")]
-static ENCODING: &'static Encoding = encoding_rs::WINDOWS_1252;
+pub(crate) static ENCODING: &'static Encoding = encoding_rs::WINDOWS_1252;
 #[cfg(not(target_os = "windows"))]
-static ENCODING: &'static Encoding = encoding_rs::UTF_8;
+pub(crate) static ENCODING: &'static Encoding = encoding_rs::UTF_8;
 
 struct LogObserverBackend {
     path: PathBuf,
+    format: LogFormat,
+    poll_interval: Duration,
     loaded_listeners: Arc<RwLock<Vec<LoadedListener>>>,
+    interceptors: Arc<RwLock<Vec<Interceptor>>>,
+    error_listeners: Arc<RwLock<Vec<UnboundedSender<ObserverError>>>>,
+    function_error_listeners: Arc<RwLock<Vec<UnboundedSender<FunctionLoadError>>>>,
+    /// A [FunctionLoadError] whose message may still follow on the next line.
+    pending_function_error: RefCell<Option<FunctionLoadError>>,
     listeners: Arc<RwLock<Vec<UnboundedSender<LogEvent>>>>,
     named_listeners: Arc<RwLock<HashMap<String, Vec<UnboundedSender<LogEvent>>>>>,
+    sync_listeners: Arc<RwLock<Vec<Sender<LogEvent>>>>,
+    gametick: Cell<Option<u64>>,
+    next_sequence: Cell<u64>,
 }
 impl LogObserverBackend {
-    fn observe_log(self, initialized_sender: Sender<()>) {
+    fn observe_log(self, initialized_sender: Sender<u64>) {
         let (event_sender, event_reciever) = channel();
         let mut watcher = recommended_watcher(event_sender).unwrap(); // may panic
         let watch_path = self.path.parent().unwrap_or(&self.path);
         watcher.watch(watch_path, RecursiveMode::Recursive).unwrap(); // may panic
 
         let mut file = File::open(&self.path).unwrap(); // may panic
-        file.seek(SeekFrom::End(0)).unwrap(); // may panic
+        let history_end = file.seek(SeekFrom::End(0)).unwrap(); // may panic
 
-        let _ = initialized_sender.send(());
+        let _ = initialized_sender.send(history_end);
 
         let mut reader = BufReader::new(file);
-        self.continue_to_read_file(&mut reader);
+        let mut parser = MultiLineParser::new(self.format);
+        self.continue_to_read_file(&mut reader, &mut parser);
+
+        let mut last_sweep = Instant::now();
 
         // Watch log file as long as the LogFileObserver is not dropped
         while Arc::strong_count(&self.listeners) > 1 {
             // On Windows we don't get any modify events, so we check for changes at least once per game tick
-            match event_reciever.recv_timeout(Duration::from_millis(50)) {
+            match event_reciever.recv_timeout(self.poll_interval) {
                 Ok(Ok(event)) if event.paths.contains(&self.path) => match event.kind {
-                    EventKind::Create(_) => self.update_reader(&mut reader),
+                    EventKind::Create(_) => self.update_reader(&mut reader, &mut parser),
                     EventKind::Modify(ModifyKind::Data(_)) => {
-                        self.continue_to_read_file(&mut reader)
+                        self.continue_to_read_file(&mut reader, &mut parser)
                     }
                     _ => {}
                 },
-                Err(RecvTimeoutError::Timeout) => self.continue_to_read_file(&mut reader),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.continue_to_read_file(&mut reader, &mut parser)
+                }
                 Err(RecvTimeoutError::Disconnected) => panic!("File watcher thread crashed!"),
                 _ => {}
             }
+            self.poll_loaded_listeners();
+            if last_sweep.elapsed() >= SWEEP_INTERVAL {
+                self.remove_closed_listeners();
+                last_sweep = Instant::now();
+            }
         }
         trace!("Shutting down LogObserverBackend");
     }
 
-    fn update_reader(&self, reader: &mut BufReader<File>) {
-        self.continue_to_read_file(reader);
+    fn observe_remote_log(self, source: Arc<dyn RemoteLogSource>, initialized_sender: Sender<u64>) {
+        let (line_sender, line_receiver) = channel();
+        thread::spawn(move || tail_remote_log(&*source, initialized_sender, line_sender));
+
+        let mut parser = MultiLineParser::new(self.format);
+        let mut last_sweep = Instant::now();
+
+        // Watch log file as long as the LogFileObserver is not dropped
+        while Arc::strong_count(&self.listeners) > 1 {
+            match line_receiver.recv_timeout(self.poll_interval) {
+                Ok(line) if line.is_empty() => {} // Sent to detect a dropped LogObserver
+                Ok(line) => self.process_line(&line, &mut parser),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => panic!("Remote log thread crashed!"),
+            }
+            self.poll_loaded_listeners();
+            if last_sweep.elapsed() >= SWEEP_INTERVAL {
+                self.remove_closed_listeners();
+                last_sweep = Instant::now();
+            }
+        }
+        trace!("Shutting down LogObserverBackend");
+    }
+
+    /// Closed listeners are usually removed when sending the next event fails. But named listeners
+    /// may never receive another event, so we regularly remove them.
+    fn remove_closed_listeners(&self) {
+        self.listeners
+            .write()
+            .unwrap()
+            .retain(|sender| !sender.is_closed());
+        self.named_listeners.write().unwrap().retain(|_, senders| {
+            senders.retain(|sender| !sender.is_closed());
+            !senders.is_empty()
+        });
+    }
+
+    fn update_reader(&self, reader: &mut BufReader<File>, parser: &mut MultiLineParser) {
+        self.continue_to_read_file(reader, parser);
         if let Ok(file) = File::open(&self.path) {
             trace!("Detected file change");
             *reader = BufReader::new(file);
         }
     }
 
-    fn continue_to_read_file(&self, reader: &mut impl BufRead) {
+    fn continue_to_read_file(&self, reader: &mut impl BufRead, parser: &mut MultiLineParser) {
         let mut buffer = Vec::new();
         loop {
             buffer.clear();
             let bytes_read = reader.read_until(b'\n', &mut buffer).unwrap(); // may panic
             if bytes_read != 0 {
-                let (line, _) = ENCODING.decode_without_bom_handling(&buffer);
-                self.process_line(&line);
+                self.process_line(&buffer, parser);
             } else {
                 break;
             }
         }
     }
 
-    fn process_line(&self, line: &str) {
-        if let Some(event) = line.parse::<LogEvent>().ok() {
-            self.send_event_to_loaded_listeners(&event);
+    fn process_line(&self, buffer: &[u8], parser: &mut MultiLineParser) {
+        let (line, _) = ENCODING.decode_without_bom_handling(buffer);
+        self.process_function_error(&line);
+        let events = self.isolate("parsing a line", || parser.push_line(&line));
+        for event in events.into_iter().flatten() {
+            self.process_event(event);
+        }
+    }
+
+    fn process_function_error(&self, line: &str) {
+        if let Some(mut error) = self.pending_function_error.take() {
+            if !line.starts_with('[') {
+                error.message = Some(line.trim_end().to_string());
+            }
+            let mut listeners = self.function_error_listeners.write().unwrap();
+            listeners.retain(|sender| sender.send(error.clone()).is_ok());
+        }
+        if let Some(error) = FunctionLoadError::parse_line(line) {
+            self.pending_function_error.replace(Some(error));
+        }
+    }
+
+    fn process_event(&self, mut event: LogEvent) {
+        if event.executor == names::GAMETICK {
+            if let Ok(output) = event.output.parse::<TimeQueryOutput>() {
+                self.gametick.set(output.time.try_into().ok());
+            }
+        }
+        event.source = Some(self.path.clone());
+        event.gametick = self.gametick.get();
+        event.sequence = self.next_sequence.get();
+        self.next_sequence.set(event.sequence + 1);
+        self.send_event_to_loaded_listeners(&event);
+        if self.intercept(&mut event) == InterceptorAction::Continue {
             self.send_event_to_listeners(&event);
+            self.send_event_to_sync_listeners(&event);
             self.send_event_to_named_listeners(event);
         }
     }
 
+    fn intercept(&self, event: &mut LogEvent) -> InterceptorAction {
+        let interceptors = self.interceptors.read().unwrap();
+        for interceptor in interceptors.iter() {
+            let action = self.isolate("calling an interceptor", || interceptor(event));
+            if action == Some(InterceptorAction::Drop) {
+                return InterceptorAction::Drop;
+            }
+        }
+        InterceptorAction::Continue
+    }
+
     fn send_event_to_loaded_listeners(&self, event: &LogEvent) {
         let loaded_listeners = self.loaded_listeners.read().unwrap();
         for loaded_listener in loaded_listeners.iter() {
-            loaded_listener.on_event(event.clone())
+            self.isolate("processing a loaded event", || {
+                loaded_listener.on_event(event.clone())
+            });
+        }
+    }
+
+    fn poll_loaded_listeners(&self) {
+        let loaded_listeners = self.loaded_listeners.read().unwrap();
+        for loaded_listener in loaded_listeners.iter() {
+            self.isolate("polling a loaded listener", || loaded_listener.on_poll());
+        }
+    }
+
+    /// Calls `f` and catches any panic, so a single bad callback can not stop the background
+    /// thread. Panics are logged and sent to the error listeners.
+    fn isolate<T>(&self, context: &str, f: impl FnOnce() -> T) -> Option<T> {
+        match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                let observer_error = ObserverError::from_panic(context, payload);
+                error!("{}", observer_error);
+                let mut error_listeners = self.error_listeners.write().unwrap();
+                error_listeners.retain(|sender| sender.send(observer_error.clone()).is_ok());
+                None
+            }
         }
     }
 
@@ -209,6 +640,13 @@ impl LogObserverBackend {
         }
     }
 
+    fn send_event_to_sync_listeners(&self, event: &LogEvent) {
+        let mut sync_listeners = self.sync_listeners.write().unwrap();
+        if !sync_listeners.is_empty() {
+            sync_listeners.retain(|sender| sender.send(event.clone()).is_ok());
+        }
+    }
+
     fn send_event_to_named_listeners(&self, event: LogEvent) {
         let indexes_to_delete = {
             let named_listeners = self.named_listeners.read().unwrap();
@@ -231,6 +669,50 @@ impl LogObserverBackend {
     }
 }
 
+/// How long to wait before following a remote log file again after the connection was lost.
+const REMOTE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Reads lines from the `source` and sends them to `line_sender` until the receiver is dropped.
+fn tail_remote_log(
+    source: &dyn RemoteLogSource,
+    initialized_sender: Sender<u64>,
+    line_sender: Sender<Vec<u8>>,
+) {
+    let mut initialized_sender = Some(initialized_sender);
+    loop {
+        let reader = source.tail();
+        if let Some(initialized_sender) = initialized_sender.take() {
+            // The history of a remote log file is not available
+            let _ = initialized_sender.send(0);
+        }
+        match reader {
+            Ok(reader) => {
+                let mut reader = BufReader::new(reader);
+                loop {
+                    let mut buffer = Vec::new();
+                    match reader.read_until(b'\n', &mut buffer) {
+                        Ok(0) => break,
+                        Ok(_) => {
+                            if line_sender.send(buffer).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to read {}: {}", source.path().display(), e);
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(e) => error!("Failed to follow {}: {}", source.path().display(), e),
+        }
+        thread::sleep(REMOTE_RETRY_DELAY);
+        if line_sender.send(Vec::new()).is_err() {
+            return; // The LogObserver was dropped
+        }
+    }
+}
+
 fn send_event_to_listeners<'l>(
     event: &LogEvent,
     listeners: impl IntoIterator<Item = &'l UnboundedSender<LogEvent>>,
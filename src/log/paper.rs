@@ -0,0 +1,90 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Compatibility with the log files of [Paper](https://papermc.io) servers.
+//!
+//! Paper uses the vanilla layout for `latest.log`, but command output is not always logged from
+//! the `Server thread`, for example when a plugin dispatches commands from the scheduler. Depending
+//! on the logging configuration the name of the logger is appended to the thread or the console
+//! layout without a thread is used.
+
+/// Strips the header of a Paper log line that follows the timestamp, for example
+/// `] [Async Chat Thread - #0/INFO]: `.
+pub(super) fn strip_header(line: &str) -> Option<&str> {
+    if let Some(line) = line.strip_prefix(" INFO]: ") {
+        return Some(line);
+    }
+    let line = line.strip_prefix("] [")?;
+    let (thread, line) = line.split_once("/INFO]")?;
+    if thread.contains(['[', ']']) {
+        return None;
+    }
+    let line = match line.strip_prefix(" [") {
+        Some(line) => {
+            let (logger, line) = line.split_once(']')?;
+            if logger.contains('[') {
+                return None;
+            }
+            line
+        }
+        None => line,
+    };
+    line.strip_prefix(": ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::log::LogFormat;
+
+    #[test]
+    fn test_paper_formats() {
+        // given:
+        let lines = [
+            "[21:05:40] [Server thread/INFO]: [test: Added tag 'success' to test]",
+            "[21:05:40] [Async Chat Thread - #0/INFO]: [test: Added tag 'success' to test]",
+            "[21:05:40] [Server thread/INFO] [minecraft/MinecraftServer]: [test: Added tag 'success' to test]",
+            "[21:05:40 INFO]: [test: Added tag 'success' to test]\r\n",
+        ];
+
+        for line in lines {
+            // when:
+            let event = LogFormat::Paper.parse_event(line);
+
+            // then:
+            let event = event.unwrap_or_else(|| panic!("Failed to parse {:?}", line));
+            assert_eq!(event.executor, "test");
+            assert_eq!(event.output, "Added tag 'success' to test");
+            assert_eq!(
+                event.to_string(),
+                "[21:05:40] [Server thread/INFO]: [test: Added tag 'success' to test]"
+            );
+        }
+    }
+
+    #[test]
+    fn test_paper_rejects_other_levels() {
+        // given:
+        let line = "[21:05:40] [Server thread/WARN]: [test: Added tag 'success' to test]";
+
+        // when:
+        let event = LogFormat::Paper.parse_event(line);
+
+        // then:
+        assert_eq!(event, None);
+    }
+}
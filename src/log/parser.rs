@@ -0,0 +1,146 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::command::{
+    AddTagOutput, DataGetEntityOutput, DatapackListOutput, DifficultyQueryOutput,
+    DifficultySetOutput, FillOutput, GameruleOutput, ListPlayersOutput, LocateOutput,
+    QueryScoreboardOutput, SeedOutput, SetblockOutput, SummonNamedEntityOutput, TeleportOutput,
+    TickFreezeOutput, TickStepOutput, TimeQueryOutput, TimeSetOutput, WeatherOutput,
+    WorldborderGetOutput, WorldborderSetOutput,
+};
+use std::{any::Any, str::FromStr};
+
+type Parser = Box<dyn Fn(&str) -> Option<Box<dyn Any + Send>> + Send + Sync>;
+
+/// A collection of parsers for the [output](super::LogEvent::output) of [LogEvent](super::LogEvent)s.
+///
+/// Each parser is registered with a prefix and is only tried for outputs that start with this
+/// prefix. Parsers are tried in the order in which they were registered and the first successful
+/// result is returned by [parse](ParserRegistry::parse) or
+/// [LogEvent::parse_with](super::LogEvent::parse_with). The result can then be downcast to the
+/// concrete output type:
+///
+/// ```
+/// use minect::{command::AddTagOutput, log::ParserRegistry};
+///
+/// let registry = ParserRegistry::with_builtin();
+/// let output = registry.parse("Added tag 'success' to test").unwrap();
+/// let output = output.downcast::<AddTagOutput>().unwrap();
+/// assert_eq!(output.tag, "success");
+/// ```
+#[derive(Default)]
+pub struct ParserRegistry {
+    parsers: Vec<(String, Parser)>,
+}
+
+impl ParserRegistry {
+    /// Creates an empty [ParserRegistry].
+    pub fn new() -> ParserRegistry {
+        ParserRegistry::default()
+    }
+
+    /// Creates a [ParserRegistry] that contains parsers for all output types of the
+    /// [command](crate::command) module.
+    pub fn with_builtin() -> ParserRegistry {
+        let mut registry = ParserRegistry::new();
+        registry.register::<SummonNamedEntityOutput>("Summoned new ");
+        registry.register::<AddTagOutput>("Added tag '");
+        registry.register::<QueryScoreboardOutput>("Added 0 to [");
+        registry.register::<TimeQueryOutput>("The time is ");
+        registry.register::<TimeSetOutput>("Set the time to ");
+        registry.register::<WeatherOutput>("Set the weather to ");
+        registry.register::<WorldborderGetOutput>("The world border is currently ");
+        registry.register::<WorldborderSetOutput>("Set the world border to ");
+        registry.register::<TeleportOutput>("Teleported ");
+        registry.register::<ListPlayersOutput>("There are ");
+        registry.register::<DatapackListOutput>("There are ");
+        registry.register::<SeedOutput>("Seed: ");
+        registry.register::<DifficultyQueryOutput>("The difficulty is ");
+        registry.register::<DifficultySetOutput>("The difficulty has been set to ");
+        registry.register::<GameruleOutput>("Gamerule ");
+        registry.register::<TickFreezeOutput>("The game is ");
+        registry.register::<TickStepOutput>("Stepping ");
+        registry.register::<LocateOutput>("The nearest ");
+        registry.register::<SetblockOutput>("Changed the block at ");
+        registry.register::<FillOutput>("Successfully filled ");
+        // The output of data get entity starts with the name of the entity
+        registry.register::<DataGetEntityOutput>("");
+        registry
+    }
+
+    /// Registers the [FromStr] implementation of `T` for outputs starting with `prefix`.
+    pub fn register<T: FromStr + Send + 'static>(&mut self, prefix: impl Into<String>) {
+        self.register_fn(prefix, |output| output.parse::<T>().ok());
+    }
+
+    /// Registers a parser function for outputs starting with `prefix`. The parser should return
+    /// [None] if it can not parse the output, in which case the next matching parser is tried.
+    pub fn register_fn<T: Send + 'static>(
+        &mut self,
+        prefix: impl Into<String>,
+        parser: impl Fn(&str) -> Option<T> + Send + Sync + 'static,
+    ) {
+        let parser = move |output: &str| parser(output).map(|t| Box::new(t) as Box<dyn Any + Send>);
+        self.parsers.push((prefix.into(), Box::new(parser)));
+    }
+
+    /// Parses the `output` with the first registered parser whose prefix matches and that succeeds.
+    pub fn parse(&self, output: &str) -> Option<Box<dyn Any + Send>> {
+        self.parsers
+            .iter()
+            .filter(|(prefix, _)| output.starts_with(prefix.as_str()))
+            .find_map(|(_, parser)| parser(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct CustomOutput(u32);
+
+    impl FromStr for CustomOutput {
+        type Err = ();
+
+        fn from_str(output: &str) -> Result<Self, Self::Err> {
+            let count = output.strip_prefix("Custom: ").ok_or(())?;
+            count.parse().map(CustomOutput).map_err(|_| ())
+        }
+    }
+
+    #[test]
+    fn test_custom_parser() {
+        // given:
+        let mut registry = ParserRegistry::with_builtin();
+        registry.register::<CustomOutput>("Custom: ");
+
+        // when:
+        let custom = registry.parse("Custom: 42");
+        let tag = registry.parse("Added tag 'success' to test");
+        let unknown = registry.parse("Custom: not a number");
+
+        // then:
+        assert_eq!(
+            *custom.unwrap().downcast::<CustomOutput>().unwrap(),
+            CustomOutput(42)
+        );
+        assert!(tag.unwrap().is::<AddTagOutput>());
+        assert!(unknown.is_none());
+    }
+}
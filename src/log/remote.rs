@@ -0,0 +1,44 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+#[cfg(feature = "ssh")]
+mod ssh;
+
+#[cfg(feature = "ssh")]
+pub use self::ssh::SshLogSource;
+
+use std::{io, io::Read, path::Path};
+
+/// A source for the log file of a Minecraft server that is not accessible via the local file system.
+/// A [LogObserver](super::LogObserver) created with
+/// [LogObserver::remote](super::LogObserver::remote) reads its [LogEvent](super::LogEvent)s from
+/// such a source.
+///
+/// With the `ssh` feature the log file can be read via [SshLogSource].
+pub trait RemoteLogSource: Send + Sync {
+    /// The path of the log file on the remote host. This is used as the
+    /// [source](super::LogEvent::source) of [LogEvent](super::LogEvent)s.
+    fn path(&self) -> &Path;
+
+    /// Starts following the log file and returns a reader of all bytes that are appended to it from
+    /// now on, similar to `tail -F -n 0`. Reading from the returned reader should block until more
+    /// bytes are available and it should continue after the log file is rotated.
+    ///
+    /// When the reader ends or fails, this is called again to resume following the log file.
+    fn tail(&self) -> io::Result<Box<dyn Read + Send>>;
+}
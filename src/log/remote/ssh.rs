@@ -0,0 +1,89 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use super::RemoteLogSource;
+use crate::utils::shell_quote;
+use ssh2::Session;
+use std::{
+    fmt::{self, Debug},
+    io::{self, Read},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+};
+
+/// A [RemoteLogSource] that follows the log file on a remote host by executing `tail` via SSH.
+///
+/// Reading the log file blocks the SSH session, so it should not be shared with other users such as
+/// an `SftpFileBackend`.
+pub struct SshLogSource {
+    session: Session,
+    path: PathBuf,
+}
+
+impl SshLogSource {
+    /// Connects to the SSH server at `addr` and authenticates as `username` using the keys of the
+    /// running SSH agent. `path` is the path of the log file on the remote host, usually
+    /// `logs/latest.log` in the server directory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the server can't be reached or authentication fails.
+    pub fn connect(
+        addr: impl ToSocketAddrs,
+        username: &str,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<SshLogSource> {
+        let mut session = Session::new()?;
+        session.set_tcp_stream(TcpStream::connect(addr)?);
+        session.handshake()?;
+        session.userauth_agent(username)?;
+        Ok(SshLogSource::new(session, path))
+    }
+
+    /// Creates a source for an already authenticated SSH `session`. This allows using any
+    /// authentication method supported by [ssh2].
+    pub fn new(session: Session, path: impl Into<PathBuf>) -> SshLogSource {
+        SshLogSource {
+            session,
+            path: path.into(),
+        }
+    }
+}
+
+impl Debug for SshLogSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SshLogSource")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RemoteLogSource for SshLogSource {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn tail(&self) -> io::Result<Box<dyn Read + Send>> {
+        let mut channel = self.session.channel_session()?;
+        channel.exec(&format!(
+            "tail -F -n 0 {}",
+            shell_quote(&self.path.to_string_lossy())
+        ))?;
+        Ok(Box::new(channel))
+    }
+}
@@ -0,0 +1,152 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    log::InterceptorAction,
+    log::LogEvent,
+    names, parse_loaded_output,
+    warnings::{MinectWarning, Warnings},
+};
+use std::sync::Mutex;
+
+/// Measurements of the log output produced by the batches of a
+/// [MinecraftConnection](crate::MinecraftConnection), obtained via
+/// [MinecraftConnection::log_volume](crate::MinecraftConnection::log_volume).
+///
+/// The volume of a [LogEvent] is the number of bytes of its executor and output. This does not
+/// include the timestamp and thread written by Minecraft, so the actual growth of the log file is
+/// somewhat larger. [LogEvent]s of commands that Minect executes internally are not measured.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogVolume {
+    /// The volume of all [LogEvent]s observed by the connection.
+    pub total_bytes: u64,
+    /// The number of batches that started executing while the connection observed the log file.
+    pub batches: u64,
+    /// The volume of the [LogEvent]s of the batch that executed last.
+    pub last_batch_bytes: u64,
+    /// The largest volume of a single batch.
+    pub max_batch_bytes: u64,
+    /// The number of batches that exceeded the
+    /// [log_volume_threshold](crate::MinecraftConnectionBuilder::log_volume_threshold).
+    pub batches_over_threshold: u64,
+    _private: (),
+}
+
+/// What happens when a batch exceeds the
+/// [log_volume_threshold](crate::MinecraftConnectionBuilder::log_volume_threshold).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogVolumeAction {
+    /// A warning is logged once per batch.
+    #[default]
+    Warn,
+    /// A warning is logged once per batch and all further [LogEvent]s of the batch are dropped
+    /// instead of being sent to listeners. This protects listener streams from being flooded, but
+    /// can not prevent Minecraft from writing the output to the log file. To avoid that, use
+    /// [silent_command](crate::command::silent_command).
+    Throttle,
+}
+
+pub(crate) struct LogVolumeMeter {
+    threshold: Option<u64>,
+    action: LogVolumeAction,
+    volume: Mutex<LogVolume>,
+}
+
+impl LogVolumeMeter {
+    pub(crate) fn new(threshold: Option<u64>, action: LogVolumeAction) -> LogVolumeMeter {
+        LogVolumeMeter {
+            threshold,
+            action,
+            volume: Mutex::new(LogVolume::default()),
+        }
+    }
+
+    pub(crate) fn volume(&self) -> LogVolume {
+        self.volume.lock().unwrap().clone()
+    }
+
+    /// Adds the volume of the `event` to the current batch. Returns [InterceptorAction::Drop] if
+    /// the event should not be sent to listeners.
+    pub(crate) fn measure(&self, event: &LogEvent, warnings: &Warnings) -> InterceptorAction {
+        let mut volume = self.volume.lock().unwrap();
+        if parse_loaded_output(event).is_some() {
+            volume.batches += 1;
+            volume.last_batch_bytes = 0;
+        }
+        if event.executor.starts_with(names::RESERVED_PREFIX) {
+            return InterceptorAction::Continue;
+        }
+        let bytes = (event.executor.len() + event.output.len()) as u64;
+        let previous_batch_bytes = volume.last_batch_bytes;
+        volume.total_bytes += bytes;
+        volume.last_batch_bytes += bytes;
+        volume.max_batch_bytes = volume.max_batch_bytes.max(volume.last_batch_bytes);
+
+        if let Some(threshold) = self.threshold {
+            if volume.last_batch_bytes > threshold {
+                if previous_batch_bytes <= threshold {
+                    volume.batches_over_threshold += 1;
+                    warnings.report(MinectWarning::LogVolumeExceeded { threshold });
+                }
+                if self.action == LogVolumeAction::Throttle {
+                    return InterceptorAction::Drop;
+                }
+            }
+        }
+        InterceptorAction::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(executor: &str, output: &str) -> LogEvent {
+        format!(
+            "[21:05:40] [Server thread/INFO]: [{}: {}]",
+            executor, output
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_measure_throttles_batch_over_threshold() {
+        // given:
+        let meter = LogVolumeMeter::new(Some(10), LogVolumeAction::Throttle);
+        let loaded = event(names::LOADED, "Summoned new minect_loaded_my_id+1");
+
+        let warnings = Warnings::default();
+
+        // when:
+        let loaded_action = meter.measure(&loaded, &warnings);
+        let small_action = meter.measure(&event("@", "a"), &warnings);
+        let large_action = meter.measure(&event("@", "0123456789"), &warnings);
+
+        // then:
+        assert_eq!(loaded_action, InterceptorAction::Continue);
+        assert_eq!(small_action, InterceptorAction::Continue);
+        assert_eq!(large_action, InterceptorAction::Drop);
+        let volume = meter.volume();
+        assert_eq!(volume.batches, 1);
+        assert_eq!(volume.batches_over_threshold, 1);
+        assert_eq!(volume.total_bytes, 13);
+        assert_eq!(volume.max_batch_bytes, 13);
+    }
+}
@@ -0,0 +1,75 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Names of the [executor](crate::log::LogEvent::executor)s that Minect uses internally and helpers
+//! to avoid collisions between names of different libraries.
+//!
+//! All names that Minect uses internally start with [RESERVED_PREFIX]. Commands with such a name are
+//! rejected by [MinecraftConnection::execute_commands](crate::MinecraftConnection::execute_commands),
+//! because their output could be mistaken for the output of internal commands.
+//!
+//! When multiple libraries built on Minect share a connection, each of them should use
+//! [namespaced] names for its commands and listeners:
+//!
+//! ```no_run
+//! use minect::{names::namespaced, Command, MinecraftConnection};
+//!
+//! # let mut connection = MinecraftConnection::builder("", "").build();
+//! let events = connection.namespaced_listener("myapp", "query");
+//! connection.execute_commands([Command::named(
+//!     namespaced("myapp", "query"),
+//!     "say querying",
+//! )])?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+/// The prefix of all names that are reserved for internal use by Minect.
+pub const RESERVED_PREFIX: &str = "minect_";
+
+/// The name under which Minect acknowledges that a structure was loaded.
+pub const LOADED: &str = "minect_loaded";
+
+/// The name under which Minect acknowledges that an expired batch was replaced, see
+/// [execute_commands_with_deadline](crate::MinecraftConnection::execute_commands_with_deadline).
+pub const EXPIRED: &str = "minect_expired";
+
+/// The name used to query the current gametick, see
+/// [query_gametick](crate::MinecraftConnectionBuilder::query_gametick).
+pub const GAMETICK: &str = "minect_gametick";
+
+/// The name used to detect that [connect](crate::MinecraftConnection::connect) succeeded.
+pub const CONNECT: &str = "minect_connect";
+
+/// The name under which Minect acknowledges that a batch of
+/// [ExecutionStrategy::FunctionFile](crate::ExecutionStrategy::FunctionFile) was executed.
+pub const BATCH: &str = "minect_batch";
+
+/// The prefix of the names used by
+/// [detect_command_results](crate::MinecraftConnectionBuilder::detect_command_results).
+pub const COMMAND_RESULT_PREFIX: &str = "minect_result_";
+
+/// The prefix of the names used by queries like [eval_int](crate::MinecraftConnection::eval_int).
+pub const QUERY_PREFIX: &str = "minect_query_";
+
+/// Returns `name` in the given `namespace` (`<namespace>.<name>`).
+///
+/// Using a unique `namespace`, for example the name of an application or library, avoids
+/// collisions with names used by other libraries that share the same connection.
+pub fn namespaced(namespace: impl AsRef<str>, name: impl AsRef<str>) -> String {
+    format!("{}.{}", namespace.as_ref(), name.as_ref())
+}
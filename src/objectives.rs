@@ -0,0 +1,157 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    connect::{add_to_function_tag, remove_from_function_tag},
+    datapack_layout::DatapackLayout,
+    file_backend::FileBackend,
+    io::{io_error, write_with_permissions, FilePermissions, IoErrorAtPath},
+};
+use std::path::{Path, PathBuf};
+
+pub(crate) const REMOVE_OBJECTIVES_TAG: &str =
+    "data/minect_internal/tags/functions/remove_objectives.json";
+
+const REMOVE_OBJECTIVE_PREFIX: &str = "scoreboard objectives remove ";
+
+/// The name of the scoreboard objective `name` in the sandbox of the connection with the given
+/// `identifier`.
+pub(crate) fn objective_name(identifier: &str, name: &str) -> String {
+    format!("minect_{}_{}", identifier, name)
+}
+
+/// Returns the objectives that are tracked for the connection with the given `identifier`.
+pub(crate) fn read_objectives(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+) -> Vec<String> {
+    let path = remove_objectives_function(backend, datapack_dir, identifier);
+    backend
+        .read(&path)
+        .map(|contents| parse_remove_objectives_function(&String::from_utf8_lossy(&contents)))
+        .unwrap_or_default()
+}
+
+/// Tracks the `objective` of the connection with the given `identifier`, so it is removed when
+/// Minect is uninstalled.
+pub(crate) fn track_objective(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+    objective: &str,
+    permissions: &FilePermissions,
+) -> Result<(), IoErrorAtPath> {
+    let mut objectives = read_objectives(backend, datapack_dir, identifier);
+    if objectives.iter().any(|it| it == objective) {
+        return Ok(());
+    }
+    objectives.push(objective.to_string());
+    let path = remove_objectives_function(backend, datapack_dir, identifier);
+    let contents = remove_objectives_function_contents(&objectives);
+    write_with_permissions(backend, path, contents, permissions)?;
+    add_to_function_tag(
+        backend,
+        DatapackLayout::detect(backend, datapack_dir).join(datapack_dir, REMOVE_OBJECTIVES_TAG),
+        &function_tag_template(identifier),
+    )
+}
+
+/// Stops tracking all objectives of the connection with the given `identifier`.
+pub(crate) fn untrack_objectives(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+) -> Result<(), IoErrorAtPath> {
+    let path = remove_objectives_function(backend, datapack_dir, identifier);
+    if !backend.is_file(&path) {
+        return Ok(());
+    }
+    remove_from_function_tag(
+        backend,
+        DatapackLayout::detect(backend, datapack_dir).join(datapack_dir, REMOVE_OBJECTIVES_TAG),
+        &function_tag_template(identifier),
+    )?;
+    backend
+        .remove_file(&path)
+        .map_err(io_error("Failed to remove file", path))
+}
+
+fn remove_objectives_function(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    identifier: &str,
+) -> PathBuf {
+    DatapackLayout::detect(backend, datapack_dir)
+        .join(datapack_dir, "data/minect_internal/functions/connection")
+        .join(identifier)
+        .join("remove_objectives.mcfunction")
+}
+
+fn remove_objectives_function_contents(objectives: &[String]) -> String {
+    objectives
+        .iter()
+        .map(|objective| format!("{}{}\n", REMOVE_OBJECTIVE_PREFIX, objective))
+        .collect()
+}
+
+fn parse_remove_objectives_function(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter_map(|line| line.strip_prefix(REMOVE_OBJECTIVE_PREFIX))
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn function_tag_template(identifier: &str) -> String {
+    format!(
+        r#"{{"values":["minect_internal:connection/{}/remove_objectives"]}}"#,
+        identifier
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_backend::MemoryFileBackend;
+
+    #[test]
+    fn test_track_and_untrack_objectives() {
+        // given:
+        let backend = MemoryFileBackend::default();
+        let datapack_dir = Path::new("/remote/world/datapacks/minect");
+        let objective = objective_name("prog", "health");
+        let permissions = FilePermissions::default();
+
+        // when:
+        track_objective(&backend, datapack_dir, "prog", &objective, &permissions).unwrap();
+        track_objective(&backend, datapack_dir, "prog", &objective, &permissions).unwrap();
+        let tracked = read_objectives(&backend, datapack_dir, "prog");
+        let tag = backend
+            .read_string(datapack_dir.join(REMOVE_OBJECTIVES_TAG))
+            .unwrap();
+        untrack_objectives(&backend, datapack_dir, "prog").unwrap();
+        let untracked = read_objectives(&backend, datapack_dir, "prog");
+
+        // then:
+        assert_eq!(objective, "minect_prog_health");
+        assert_eq!(tracked, vec![objective]);
+        assert!(tag.contains("minect_internal:connection/prog/remove_objectives"));
+        assert!(untracked.is_empty());
+    }
+}
@@ -17,37 +17,59 @@
 // If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-    geometry3::{Coordinate3, Direction3, Orientation3},
+    geometry::{Coordinate3, Direction3, Orientation3},
     json::{create_json_text_component, escape_json},
     structure::{
-        nbt::Structure, new_command_block, new_structure_block, Block, CommandBlockKind,
-        StructureBuilder,
+        nbt::Structure, new_command_block, new_command_block_minecart, new_structure_block, Block,
+        CommandBlockKind, StructureBuilder,
     },
-    Command, NAMESPACE,
+    structure_id::format_structure_id,
+    warnings::Warnings,
+    BlockPos, ChainPlacement, Command, MinecraftVersion, MinectWarning, NAMESPACE,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    iter::FromIterator,
 };
-use log::warn;
-use std::{collections::BTreeMap, iter::FromIterator};
 
 pub(crate) fn generate_structure(
     identifier: &str,
     next_id: u64,
     commands: impl Iterator<Item = Command>,
     commands_len: usize,
-) -> Structure {
-    let mut builder = StructureBuilder::new();
-    for block in generate_basic_structure(identifier, next_id) {
+    area: &CommandBlockArea,
+    warnings: &Warnings,
+) -> Result<Structure, PlacementError> {
+    let (carts, commands): (Vec<_>, Vec<_>) =
+        commands.partition(|command| command.chain == ChainPlacement::Minecart);
+    let commands_len = commands_len.saturating_sub(carts.len());
+    let basic_structure = generate_basic_structure(identifier, next_id);
+    let mut builder = StructureBuilder::with_capacity(basic_structure.len() + commands_len);
+    for block in basic_structure {
         builder.add_block(block);
     }
-    for block in generate_command_blocks(commands, commands_len) {
+    for block in generate_command_blocks(commands.into_iter(), commands_len, area, warnings)? {
         builder.add_block(block);
     }
-    builder.build()
+    // The minecarts are placed on the activator rail
+    let rail = Coordinate3(0.5, CONNECTION_ENTITY_Y as f64, 0.5);
+    for command in carts {
+        let name = command.get_name_as_json();
+        builder.add_entity(new_command_block_minecart(name, command.command, rail));
+    }
+    Ok(builder.build())
 }
 
 fn generate_basic_structure(connection_id: &str, next_structure_id: u64) -> Vec<Block> {
     Vec::from_iter([
         new_structure_block(
-            format!("{}:{}/{}", NAMESPACE, connection_id, next_structure_id),
+            format!(
+                "{}:{}/{}",
+                NAMESPACE,
+                connection_id,
+                format_structure_id(next_structure_id)
+            ),
             "LOAD".to_string(),
             Coordinate3(0, 0, 0),
         ),
@@ -58,7 +80,12 @@ fn generate_basic_structure(connection_id: &str, next_structure_id: u64) -> Vec<
             nbt: None,
         },
         new_structure_block(
-            format!("{}:{}/{}", NAMESPACE, connection_id, next_structure_id),
+            format!(
+                "{}:{}/{}",
+                NAMESPACE,
+                connection_id,
+                format_structure_id(next_structure_id)
+            ),
             "CORNER".to_string(),
             Coordinate3(0, 2, 0),
         ),
@@ -95,6 +122,27 @@ fn generate_basic_structure(connection_id: &str, next_structure_id: u64) -> Vec<
     ])
 }
 
+/// The blocks of the connection building relative to its origin. In addition to the basic structure
+/// this includes the stone that is placed around the activator rail during installation to protect
+/// it from neighbouring redstone.
+pub(crate) fn generate_building(connection_id: &str, hide_building: bool) -> Vec<Block> {
+    let protection = |pos| Block {
+        name: protection_block(hide_building).to_string(),
+        pos,
+        properties: BTreeMap::new(),
+        nbt: None,
+    };
+    let mut blocks = generate_basic_structure(connection_id, 0);
+    blocks.extend([
+        protection(Coordinate3(1, 5, 0)),
+        protection(Coordinate3(-1, 5, 0)),
+        protection(Coordinate3(0, 5, 1)),
+        protection(Coordinate3(0, 5, -1)),
+        protection(Coordinate3(0, 6, 0)),
+    ]);
+    blocks
+}
+
 fn summon_connection_entity_command(connection_id: &str) -> String {
     format!(
         "execute \
@@ -116,68 +164,388 @@ fn summon_connection_entity_command(connection_id: &str) -> String {
     )
 }
 
+/// Summons the connection entity if it is missing. This must be executed by a command block minecart
+/// on the activator rail of the connection building (see [ChainPlacement::Minecart]).
+pub(crate) fn summon_connection_entity_from_rail_command(connection_id: &str) -> String {
+    // The minecart is two blocks above the repeating command block that usually summons the entity
+    format!(
+        "execute positioned ~ ~-2 ~ run {}",
+        summon_connection_entity_command(connection_id)
+    )
+}
+
 const CMD_BLOCK_OFFSET: Coordinate3<i32> = Coordinate3(0, 0, 8);
 /// Minecraft limits the number of blocks that can be targeted by a fill command (which we use to
 /// clean up) to 32768. X is limited to 16 and Z to 8 to stay in the chunk. The Y limit is
 /// therefore calculated as: floor(32768 / 8 / 16) = 256.
 /// Additionally there is a height limit in Minecraft before 1.18 of 256. Because we start at Y=1
 /// (to avoid a hole in the bedrock layer) our height limit is 255.
-/// The size is also hardcoded in the clean_up functions.
+/// This size is hardcoded in the clean_up functions, larger areas are cleaned up with multiple fill
+/// commands.
 const MAX_SIZE: Coordinate3<i32> = Coordinate3(16, 255, 8);
-const MAX_LEN: usize = MAX_SIZE.0 as usize * MAX_SIZE.1 as usize * MAX_SIZE.2 as usize;
+const MAX_FILL_VOLUME: i32 = 32768;
+/// The default Y coordinate at which the connection building is installed. Because structures can't
+/// have negative coordinates, command blocks can't be placed below it, even in Minecraft 1.18 and
+/// later.
+pub(crate) const DEFAULT_BUILDING_Y: i32 = 1;
+/// The connection building occupies the blocks from Y=0 to Y=5 at X=0 and Z=0.
+const BUILDING_HEIGHT: i32 = 6;
+/// The connection entity is summoned in the block above the activator rail. Commands that are
+/// executed at the connection entity use this to find the origin of the connection building.
+pub(crate) const CONNECTION_ENTITY_Y: i32 = 5;
+/// The stone that protects the activator rail reaches up to Y=6.
+const PROTECTED_HEIGHT: i32 = 7;
+
+/// The area in which command blocks are placed, relative to the connection building.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct CommandBlockArea {
+    pub(crate) offset: Coordinate3<i32>,
+    pub(crate) size: Coordinate3<i32>,
+}
+
+impl Default for CommandBlockArea {
+    fn default() -> CommandBlockArea {
+        CommandBlockArea {
+            offset: CMD_BLOCK_OFFSET,
+            size: MAX_SIZE,
+        }
+    }
+}
+
+impl CommandBlockArea {
+    /// Creates an area with the given offset and size. By default the area extends from the offset
+    /// to the end of the chunk and up to the build height of the given version. If the version is
+    /// unknown the build height of Minecraft before 1.18 is assumed.
+    pub(crate) fn new(
+        version: Option<MinecraftVersion>,
+        building_y: i32,
+        offset: Option<Coordinate3<i32>>,
+        size: Option<Coordinate3<i32>>,
+    ) -> CommandBlockArea {
+        let offset = offset.unwrap_or(CMD_BLOCK_OFFSET);
+        let size = size.unwrap_or_else(|| {
+            Coordinate3(
+                16 - offset.0,
+                max_height(version, building_y) - offset.1,
+                16 - offset.2,
+            )
+        });
+        CommandBlockArea { offset, size }
+    }
+
+    /// Checks that the area is inside the chunk of the connection building, below the build height
+    /// limit and does not overlap the building.
+    pub(crate) fn validate(
+        &self,
+        version: Option<MinecraftVersion>,
+        building_y: i32,
+    ) -> Result<(), String> {
+        let CommandBlockArea { offset, size } = *self;
+        if size.0 <= 0 || size.1 <= 0 || size.2 <= 0 {
+            return Err(format!("size must be positive, but was {:?}", size));
+        }
+        if offset.0 < 0 || offset.2 < 0 || offset.0 + size.0 > 16 || offset.2 + size.2 > 16 {
+            return Err(format!(
+                "offset {:?} and size {:?} exceed the chunk of the connection",
+                offset, size
+            ));
+        }
+        let max_height = max_height(version, building_y);
+        if offset.1 < 0 || offset.1 + size.1 > max_height {
+            return Err(format!(
+                "offset {:?} and size {:?} exceed the height limit of {}",
+                offset, size, max_height
+            ));
+        }
+        if offset.0 == 0 && offset.2 == 0 && offset.1 < BUILDING_HEIGHT {
+            return Err(format!(
+                "offset {:?} overlaps the connection building",
+                offset
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn max_len(&self) -> usize {
+        self.size.0 as usize * self.size.1 as usize * self.size.2 as usize
+    }
+
+    /// The clean_up function only cleans up the default area, so any other area needs custom fill
+    /// commands. Because a fill command is limited to 32768 blocks, large areas are split into
+    /// horizontal slices. The slices are filled from the bottom up, so if a fill command removes
+    /// the command blocks of the following fill commands, the slices above can't contain any
+    /// command blocks of this chain.
+    pub(crate) fn clean_up_commands(&self, connection_id: &str) -> Vec<String> {
+        if *self == CommandBlockArea::default() {
+            return Vec::new();
+        }
+        let slice_height = MAX_FILL_VOLUME / (self.size.0 * self.size.2);
+        let min = self.offset;
+        let max = self.offset + self.size - Coordinate3(1, 1, 1);
+        (min.1..=max.1)
+            .step_by(slice_height as usize)
+            .map(|min_y| {
+                let max_y = (min_y + slice_height - 1).min(max.1);
+                format!(
+                    "execute at @e[type=area_effect_cloud,tag=minect_connection+{},sort=nearest,limit=1] \
+                    align xyz positioned ~ ~-5 ~ run \
+                    fill ~{} ~{} ~{} ~{} ~{} ~{} stone replace #minect_internal:command_blocks",
+                    connection_id, min.0, min_y, min.2, max.0, max_y, max.2
+                )
+            })
+            .collect()
+    }
+}
+
+/// Commands that protect the connection building from players. The blocks around the activator rail
+/// are restored if they were broken and all air around the building is filled with barrier blocks,
+/// which can't be broken in survival mode. The remaining blocks of the building are restored anyway
+/// when the next structure is loaded.
+pub(crate) fn protection_commands(connection_id: &str, hide_building: bool) -> Vec<String> {
+    let block = protection_block(hide_building);
+    [
+        format!("setblock ~1 ~5 ~ {} keep", block),
+        format!("setblock ~-1 ~5 ~ {} keep", block),
+        format!("setblock ~ ~5 ~1 {} keep", block),
+        format!("setblock ~ ~5 ~-1 {} keep", block),
+        format!("setblock ~ ~6 ~ {} keep", block),
+        "fill ~-1 ~ ~-1 ~1 ~7 ~1 barrier keep".to_string(),
+    ]
+    .into_iter()
+    .map(|command| {
+        format!(
+            "execute at @e[type=area_effect_cloud,tag=minect_connection+{},sort=nearest,limit=1] \
+            align xyz positioned ~ ~-{} ~ run {}",
+            connection_id, CONNECTION_ENTITY_Y, command
+        )
+    })
+    .collect()
+}
+
+/// Checks that the connection building fits between the build limits of the given version.
+pub(crate) fn validate_building_y(
+    version: Option<MinecraftVersion>,
+    building_y: i32,
+) -> Result<(), String> {
+    let min = version.map_or(0, MinecraftVersion::min_build_height);
+    let max = version.map_or(256, MinecraftVersion::max_build_height) - PROTECTED_HEIGHT;
+    if (min..=max).contains(&building_y) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Y={} is outside of the range from {} to {}",
+            building_y, min, max
+        ))
+    }
+}
+
+/// The maximum height of the command block area above the connection building.
+fn max_height(version: Option<MinecraftVersion>, building_y: i32) -> i32 {
+    version.map_or(256, MinecraftVersion::max_build_height) - building_y
+}
+
+/// The block that protects the activator rail. A barrier is invisible, but like stone it does not
+/// conduct redstone signals from neighbouring blocks.
+pub(crate) fn protection_block(hide_building: bool) -> &'static str {
+    if hide_building {
+        "minecraft:barrier"
+    } else {
+        "minecraft:stone"
+    }
+}
 
 fn generate_command_blocks(
     commands: impl Iterator<Item = Command>,
     commands_len: usize,
-) -> impl Iterator<Item = Block> {
-    if commands_len > MAX_LEN {
-        warn!(
-            "Attempted to execute {} commands. \
-             Only the first {} commands will be executed. \
-             The rest will be ignored.",
-            commands_len, MAX_LEN
-        );
+    area: &CommandBlockArea,
+    warnings: &Warnings,
+) -> Result<Vec<Block>, PlacementError> {
+    let max_len = area.max_len();
+    if commands_len > max_len {
+        warnings.report(MinectWarning::TooManyCommands {
+            commands: commands_len,
+            max: max_len,
+        });
     }
 
     const CURVE_ORIENTATION: Orientation3 = Orientation3::XZY;
-    let max_size = CURVE_ORIENTATION.inverse().orient_coordinate(MAX_SIZE);
-    let curve = CuboidCurve::new(max_size).map(|(coordinate, direction)| {
-        (
-            CURVE_ORIENTATION.orient_coordinate(coordinate),
-            CURVE_ORIENTATION.orient_direction(direction),
-        )
-    });
-
-    commands
-        .zip(curve)
-        .map(|(command, (coordinate, direction))| CommandBlock {
-            command,
-            coordinate,
-            direction,
-        })
-        .map(|cmd_block| {
-            let first = cmd_block.coordinate == Coordinate3(0, 0, 0);
-            let kind = if first {
-                CommandBlockKind::Impulse
-            } else {
-                CommandBlockKind::Chain
-            };
-            new_command_block(
-                kind,
-                cmd_block.command.get_name_as_json(),
-                cmd_block.command.command,
-                false,
-                true,
-                cmd_block.direction,
-                cmd_block.coordinate + CMD_BLOCK_OFFSET,
+    let max_size = CURVE_ORIENTATION.inverse().orient_coordinate(area.size);
+    let offset = area.offset;
+    let curve = CuboidCurve::new(max_size)
+        .map(|(coordinate, direction)| {
+            (
+                CURVE_ORIENTATION.orient_coordinate(coordinate),
+                CURVE_ORIENTATION.orient_direction(direction),
             )
         })
+        .collect::<Vec<_>>();
+
+    let commands = commands.collect::<Vec<_>>();
+    // With several chains the clean up starts a chain of its own, so dropping commands would drop
+    // the clean up and the batch would never be acknowledged
+    let multiple_chains = commands
+        .iter()
+        .any(|command| command.chain != ChainPlacement::Continue);
+    let conditional_runs = count_conditional_runs(&commands);
+    let mut blocks = Vec::with_capacity(commands.len());
+    let mut index = 0;
+    let mut layer = None;
+    let new_command_block = |command: Command, (coordinate, direction)| {
+        let first = coordinate == Coordinate3(0, 0, 0);
+        let kind = if first || command.chain != ChainPlacement::Continue {
+            CommandBlockKind::Impulse
+        } else {
+            CommandBlockKind::Chain
+        };
+        new_command_block(
+            kind,
+            command.get_name_as_json(),
+            command.command,
+            command.conditional,
+            true,
+            direction,
+            coordinate + offset,
+        )
+    };
+    for (command, conditional_run) in commands.into_iter().zip(conditional_runs) {
+        if command.chain == ChainPlacement::NewAbove {
+            // Command blocks are placed and thus triggered ordered by their Y coordinate, so a chain
+            // on a higher layer starts after all chains on lower layers.
+            let above = curve[index..]
+                .iter()
+                .position(|(coordinate, _)| Some(coordinate.1) > layer);
+            index += above.unwrap_or(curve.len() - index);
+        }
+        if conditional_run > 0 {
+            // A conditional command block checks the block behind it, so the command and the
+            // following conditional commands must be placed in a straight line. Otherwise, the
+            // condition would check a block next to the chain after a corner.
+            match find_straight_line(&curve[index..], conditional_run + 1) {
+                Some(padding) if command.chain == ChainPlacement::Continue => {
+                    for &position in &curve[index..index + padding] {
+                        blocks.push(new_command_block(Command::new(""), position));
+                    }
+                    index += padding;
+                }
+                Some(padding) => index += padding,
+                None => {
+                    let pos = match curve.get(index) {
+                        Some(&(coordinate, _)) => coordinate + offset,
+                        None => blocks.last().map_or(offset, |block: &Block| block.pos),
+                    };
+                    return Err(PlacementError {
+                        pos: pos.into(),
+                        message: "starts conditional commands that can't be placed in a straight \
+                            line, so their conditions would check the wrong command blocks",
+                    });
+                }
+            }
+        }
+        let Some(&position) = curve.get(index) else {
+            if multiple_chains {
+                return Err(PlacementError {
+                    pos: blocks
+                        .last()
+                        .map_or(offset, |block: &Block| block.pos)
+                        .into(),
+                    message: "is the last one that fits into the command block area, but the \
+                        chains of the batch have more commands",
+                });
+            }
+            break;
+        };
+        layer = Some(position.0 .1);
+        blocks.push(new_command_block(command, position));
+        index += 1;
+    }
+    Ok(blocks)
 }
 
-pub(crate) struct CommandBlock {
-    pub(crate) command: Command,
-    pub(crate) coordinate: Coordinate3<i32>,
-    pub(crate) direction: Direction3,
+/// A command block in a generated structure that would not execute as intended, for example because
+/// the chains of a batch don't fit into the command block area or because its chain is broken. The
+/// latter is only detected if
+/// [validate_placement](crate::MinecraftConnectionBuilder::validate_placement) is enabled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlacementError {
+    pos: BlockPos,
+    message: &'static str,
+}
+
+impl PlacementError {
+    /// The position of the command block relative to the origin of the structure.
+    pub fn pos(&self) -> BlockPos {
+        self.pos
+    }
+}
+
+impl Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The command block at {} {}", self.pos, self.message)
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+/// Checks that every command block in the `structure` has a valid `facing` and that every chain
+/// command block is faced by the command block before it, so the chain is contiguous.
+pub(crate) fn validate_chains(structure: &Structure) -> Result<(), PlacementError> {
+    let mut facings = HashMap::new();
+    let mut chain_blocks = Vec::new();
+    for block in &structure.blocks {
+        let palette_block = &structure.palette[block.state as usize];
+        let chain = match palette_block.name.as_str() {
+            "minecraft:chain_command_block" => true,
+            "minecraft:command_block" | "minecraft:repeating_command_block" => false,
+            _ => continue,
+        };
+        let pos = Coordinate3(block.pos[0], block.pos[1], block.pos[2]);
+        let facing = palette_block
+            .properties
+            .get("facing")
+            .and_then(|facing| facing.parse::<Direction3>().ok())
+            .ok_or(PlacementError {
+                pos: pos.into(),
+                message: "has no valid facing",
+            })?;
+        facings.insert(<(i32, i32, i32)>::from(pos), facing);
+        if chain {
+            chain_blocks.push(pos);
+        }
+    }
+    for pos in chain_blocks {
+        let connected = Direction3::ALL.into_iter().any(|direction| {
+            let previous = pos - direction.as_coordinate(1, 0);
+            facings.get(&previous.into()) == Some(&direction)
+        });
+        if !connected {
+            return Err(PlacementError {
+                pos: pos.into(),
+                message: "is a chain command block, but no command block faces it",
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of conditional commands that directly follow each of the `commands`.
+fn count_conditional_runs(commands: &[Command]) -> Vec<usize> {
+    let mut runs = vec![0; commands.len()];
+    for index in (0..commands.len().saturating_sub(1)).rev() {
+        if commands[index + 1].conditional {
+            runs[index] = runs[index + 1] + 1;
+        }
+    }
+    runs
+}
+
+/// Returns the number of positions in the `curve` that have to be skipped, so the next `len`
+/// positions form a straight line.
+fn find_straight_line(curve: &[(Coordinate3<i32>, Direction3)], len: usize) -> Option<usize> {
+    curve.windows(len).position(|window| {
+        window
+            .iter()
+            .all(|(_, direction)| *direction == window[0].1)
+    })
 }
 
 /// An [Iterator] producing a space filling curve with a zig zag pattern in the form of a cuboid.
@@ -249,3 +617,332 @@ fn direction_in_cuboid_curve(
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::nbt::Value;
+
+    #[test]
+    fn test_generate_command_blocks_with_multiple_chains() {
+        // given:
+        let area = CommandBlockArea {
+            offset: Coordinate3(0, 0, 0),
+            size: Coordinate3(2, 3, 2),
+        };
+        let mut second_chain = Command::new("say second");
+        second_chain.chain = ChainPlacement::New;
+        let mut clean_up = Command::new("say clean up");
+        clean_up.chain = ChainPlacement::NewAbove;
+        let commands = [Command::new("say first"), second_chain, clean_up];
+
+        // when:
+        let warnings = Warnings::default();
+        let blocks = generate_command_blocks(commands.into_iter(), 3, &area, &warnings).unwrap();
+
+        // then:
+        let actual = blocks
+            .iter()
+            .map(|block| (block.name.as_str(), block.pos))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            [
+                ("minecraft:command_block", Coordinate3(0, 0, 0)),
+                ("minecraft:command_block", Coordinate3(1, 0, 0)),
+                ("minecraft:command_block", Coordinate3(0, 1, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_command_blocks_with_chains_exceeding_area() {
+        // given:
+        let area = CommandBlockArea {
+            offset: Coordinate3(0, 0, 0),
+            size: Coordinate3(2, 2, 1),
+        };
+        let mut clean_up = Command::new("say clean up");
+        clean_up.chain = ChainPlacement::NewAbove;
+        let commands = [
+            Command::new("say 1"),
+            Command::new("say 2"),
+            Command::new("say 3"),
+            clean_up,
+        ];
+
+        // when:
+        let warnings = Warnings::default();
+        let actual = generate_command_blocks(commands.into_iter(), 4, &area, &warnings);
+
+        // then:
+        let pos = actual.err().map(|error| error.pos());
+        assert_eq!(pos, Some(BlockPos::new(1, 1, 0)));
+    }
+
+    #[test]
+    fn test_generate_command_blocks_with_conditional_commands_not_in_line() {
+        // given:
+        let area = CommandBlockArea {
+            offset: Coordinate3(0, 0, 0),
+            size: Coordinate3(2, 2, 2),
+        };
+        let commands = [
+            Command::new("say 1"),
+            Command::new("say 2"),
+            Command::new("say 3").conditional(),
+            Command::new("say 4").conditional(),
+        ];
+
+        // when:
+        let warnings = Warnings::default();
+        let actual = generate_command_blocks(commands.into_iter(), 4, &area, &warnings);
+
+        // then:
+        let pos = actual.err().map(|error| error.pos());
+        assert_eq!(pos, Some(BlockPos::new(1, 0, 0)));
+    }
+
+    #[test]
+    fn test_generate_command_blocks_with_conditional_commands() {
+        // given:
+        let area = CommandBlockArea {
+            offset: Coordinate3(0, 0, 0),
+            size: Coordinate3(4, 2, 2),
+        };
+        let commands = [
+            Command::new("say 1"),
+            Command::new("say 2"),
+            Command::new("say 3"),
+            Command::new("say 4").conditional(),
+            Command::new("say 5").conditional(),
+            Command::new("say 6"),
+        ];
+
+        // when:
+        let warnings = Warnings::default();
+        let blocks = generate_command_blocks(commands.into_iter(), 6, &area, &warnings).unwrap();
+
+        // then:
+        let commands = blocks.iter().map(get_command).collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            ["say 1", "say 2", "", "", "say 3", "say 4", "say 5", "say 6"]
+        );
+        for (index, block) in blocks.iter().enumerate() {
+            let conditional = block.properties.get("conditional").map(String::as_str);
+            if conditional == Some("true") {
+                let facing = get_facing(block);
+                let behind = block.pos - facing.as_coordinate(1, 0);
+                assert_eq!(behind, blocks[index - 1].pos, "{}", get_command(block));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_structure_with_minecarts() {
+        // given:
+        let mut cart = Command::named("cart", "say hi");
+        cart.chain = ChainPlacement::Minecart;
+        let commands = [Command::new("say block"), cart];
+
+        // when:
+        let structure = generate_structure(
+            "test",
+            1,
+            commands.into_iter(),
+            2,
+            &CommandBlockArea::default(),
+            &Warnings::default(),
+        )
+        .unwrap();
+
+        // then:
+        let command_blocks = structure
+            .blocks
+            .iter()
+            .filter(|block| {
+                structure.palette[block.state as usize].name == "minecraft:command_block"
+            })
+            .count();
+        assert_eq!(command_blocks, 2); // The basic structure contains one as well
+        assert_eq!(structure.entities.len(), 1);
+        let cart = &structure.entities[0];
+        assert_eq!(cart.pos, vec![0.5, 5.0, 0.5]);
+        match &cart.nbt {
+            Value::Compound(nbt) => {
+                assert_eq!(nbt["Command"], Value::String("say hi".to_string()));
+                assert_eq!(
+                    nbt["CustomName"],
+                    Value::String(r#"{"text":"cart"}"#.to_string())
+                );
+            }
+            _ => panic!(),
+        }
+    }
+
+    fn get_facing(block: &Block) -> Direction3 {
+        let facing = &block.properties["facing"];
+        [
+            Direction3::Up,
+            Direction3::Down,
+            Direction3::North,
+            Direction3::East,
+            Direction3::South,
+            Direction3::West,
+        ]
+        .into_iter()
+        .find(|direction| direction.to_string() == *facing)
+        .unwrap()
+    }
+
+    fn get_command(block: &Block) -> &str {
+        match &block.nbt {
+            Some(Value::Compound(nbt)) => match &nbt["Command"] {
+                Value::String(command) => command,
+                _ => panic!(),
+            },
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_command_block_area_validate() {
+        // given:
+        let valid = CommandBlockArea {
+            offset: Coordinate3(8, 0, 0),
+            size: Coordinate3(8, 100, 8),
+        };
+        let outside_chunk = CommandBlockArea {
+            offset: Coordinate3(0, 0, 9),
+            size: Coordinate3(16, 255, 8),
+        };
+        let overlapping_building = CommandBlockArea {
+            offset: Coordinate3(0, 5, 0),
+            size: Coordinate3(1, 1, 1),
+        };
+
+        // when / then:
+        assert_eq!(
+            CommandBlockArea::default().validate(None, DEFAULT_BUILDING_Y),
+            Ok(())
+        );
+        assert_eq!(valid.validate(None, DEFAULT_BUILDING_Y), Ok(()));
+        assert!(outside_chunk.validate(None, DEFAULT_BUILDING_Y).is_err());
+        assert!(overlapping_building
+            .validate(None, DEFAULT_BUILDING_Y)
+            .is_err());
+    }
+
+    #[test]
+    fn test_command_block_area_new() {
+        // given:
+        let version = MinecraftVersion::new(1, 18, 0);
+
+        // when:
+        let area = CommandBlockArea::new(Some(version), DEFAULT_BUILDING_Y, None, None);
+
+        // then:
+        assert_eq!(area.validate(Some(version), DEFAULT_BUILDING_Y), Ok(()));
+        assert!(area.validate(None, DEFAULT_BUILDING_Y).is_err());
+        assert_eq!(area.size, Coordinate3(16, 319, 8));
+        assert_eq!(
+            area.clean_up_commands("test"),
+            [
+                "execute at @e[type=area_effect_cloud,tag=minect_connection+test,sort=nearest,limit=1] \
+                align xyz positioned ~ ~-5 ~ run \
+                fill ~0 ~0 ~8 ~15 ~255 ~15 stone replace #minect_internal:command_blocks",
+                "execute at @e[type=area_effect_cloud,tag=minect_connection+test,sort=nearest,limit=1] \
+                align xyz positioned ~ ~-5 ~ run \
+                fill ~0 ~256 ~8 ~15 ~318 ~15 stone replace #minect_internal:command_blocks",
+            ]
+        );
+        assert!(CommandBlockArea::new(None, DEFAULT_BUILDING_Y, None, None)
+            .clean_up_commands("test")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_building_y() {
+        // given:
+        let version = MinecraftVersion::new(1, 18, 0);
+
+        // when / then:
+        assert_eq!(validate_building_y(None, DEFAULT_BUILDING_Y), Ok(()));
+        assert_eq!(validate_building_y(Some(version), -63), Ok(()));
+        assert_eq!(validate_building_y(Some(version), 313), Ok(()));
+        assert!(validate_building_y(None, -63).is_err());
+        assert!(validate_building_y(Some(version), 314).is_err());
+    }
+
+    #[test]
+    fn test_protection_commands() {
+        // when:
+        let commands = protection_commands("test", false);
+
+        // then:
+        assert_eq!(commands.len(), 6);
+        assert_eq!(
+            commands[5],
+            "execute at @e[type=area_effect_cloud,tag=minect_connection+test,sort=nearest,limit=1] \
+            align xyz positioned ~ ~-5 ~ run fill ~-1 ~ ~-1 ~1 ~7 ~1 barrier keep"
+        );
+    }
+
+    #[test]
+    fn test_summon_connection_entity_from_rail_command() {
+        // when:
+        let command = summon_connection_entity_from_rail_command("test");
+
+        // then:
+        assert!(command.starts_with(
+            "execute positioned ~ ~-2 ~ run execute positioned ~ ~2 ~ align xyz unless entity"
+        ));
+        assert!(command.contains("Tags:[minect,minect_connection,minect_connection+test]"));
+    }
+
+    #[test]
+    fn test_validate_chains() {
+        // given:
+        let area = CommandBlockArea {
+            offset: Coordinate3(0, 0, 8),
+            size: Coordinate3(3, 3, 2),
+        };
+        let mut new_chain = Command::new("say new");
+        new_chain.chain = ChainPlacement::NewAbove;
+        let commands = (0..8)
+            .map(|index| Command::new(format!("say {}", index)))
+            .chain([new_chain, Command::new("say 9").conditional()])
+            .collect::<Vec<_>>();
+        let structure = generate_structure(
+            "test",
+            1,
+            commands.into_iter(),
+            10,
+            &area,
+            &Warnings::default(),
+        )
+        .unwrap();
+        let mut broken = structure.clone();
+        let chain_state = broken
+            .palette
+            .iter()
+            .position(|block| block.name == "minecraft:chain_command_block")
+            .unwrap();
+        let chain_block = broken
+            .blocks
+            .iter_mut()
+            .find(|block| block.state == chain_state as i32)
+            .unwrap();
+        chain_block.pos[2] += 4;
+        let pos = BlockPos::new(chain_block.pos[0], chain_block.pos[1], chain_block.pos[2]);
+
+        // when:
+        let valid = validate_chains(&structure);
+        let broken = validate_chains(&broken);
+
+        // then:
+        assert_eq!(valid, Ok(()));
+        assert_eq!(broken.map_err(|error| error.pos()), Err(pos));
+    }
+}
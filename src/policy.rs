@@ -0,0 +1,174 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Restricting which commands can be executed, see
+//! [command_policy](crate::MinecraftConnectionBuilder::command_policy).
+
+use crate::{
+    command::{classify, has_store_subcommand, CommandKind},
+    Command,
+};
+
+/// The decision of a [command_policy](crate::MinecraftConnectionBuilder::command_policy) whether a
+/// [Command](crate::Command) may be executed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PolicyDecision {
+    /// The command may be executed.
+    Allow,
+    /// The command must not be executed.
+    Deny,
+}
+
+impl From<bool> for PolicyDecision {
+    /// Converts `true` to [Allow](PolicyDecision::Allow) and `false` to
+    /// [Deny](PolicyDecision::Deny).
+    fn from(allow: bool) -> PolicyDecision {
+        if allow {
+            PolicyDecision::Allow
+        } else {
+            PolicyDecision::Deny
+        }
+    }
+}
+
+/// A policy preset that only allows commands that query information without changing the world,
+/// like `data get`, `scoreboard players get` or `time query`.
+///
+/// ```no_run
+/// # use minect::{policy::read_only, MinecraftConnection};
+/// let connection = MinecraftConnection::builder("example", "world")
+///     .command_policy(read_only)
+///     .build();
+/// ```
+pub fn read_only(command: &Command) -> PolicyDecision {
+    is_query(&classify(command.get_command())).into()
+}
+
+/// Returns `true` if the command only queries information without changing the world. Commands
+/// generated by [query_scoreboard_command](crate::command::query_scoreboard_command) are also
+/// considered queries.
+fn is_query(kind: &CommandKind) -> bool {
+    match kind {
+        CommandKind::Execute { subcommands, run } => {
+            // An execute command without run only checks conditions
+            !has_store_subcommand(subcommands) && run.iter().all(|run| is_query(run))
+        }
+        CommandKind::Data { action, .. } => *action == "get",
+        CommandKind::Gamerule { value, .. } => value.is_none(),
+        CommandKind::ScoreboardObjectives { action, .. } => *action == "list",
+        CommandKind::ScoreboardPlayers { action, value, .. } => match *action {
+            "add" => *value == Some("0"),
+            "get" | "list" => true,
+            _ => false,
+        },
+        CommandKind::Tag { action, .. } => *action == "list",
+        CommandKind::Unknown { name, args } => matches!(
+            (*name, args.as_slice()),
+            ("datapack", ["list", ..])
+                | ("difficulty", [])
+                | ("list", _)
+                | ("locate", _)
+                | ("locatebiome", _)
+                | ("seed", [])
+                | ("team", ["list", ..])
+                | ("time", ["query", _])
+                | ("worldborder", ["get"])
+        ),
+        _ => false,
+    }
+}
+
+/// A policy preset that denies commands that can destroy large parts of a world: `kill` as well as
+/// `fill` and `clone` commands that affect more than `max_volume` blocks or whose volume can't be
+/// determined. The `destroy` mode of `fill` and `setblock` is denied too.
+///
+/// ```no_run
+/// # use minect::{policy::non_destructive, MinecraftConnection};
+/// let connection = MinecraftConnection::builder("example", "world")
+///     .command_policy(non_destructive(4096))
+///     .build();
+/// ```
+pub fn non_destructive(max_volume: u64) -> impl Fn(&Command) -> PolicyDecision + Send + Sync {
+    move |command| (!is_destructive(&classify(command.get_command()), max_volume)).into()
+}
+
+fn is_destructive(kind: &CommandKind, max_volume: u64) -> bool {
+    match kind {
+        CommandKind::Execute { run, .. } => run
+            .as_deref()
+            .is_some_and(|run| is_destructive(run, max_volume)),
+        CommandKind::Kill { .. } => true,
+        CommandKind::Fill { volume, mode, .. } => {
+            *mode == Some("destroy") || !fits(*volume, max_volume)
+        }
+        CommandKind::Clone { volume, .. } => !fits(*volume, max_volume),
+        CommandKind::Setblock { mode, .. } => *mode == Some("destroy"),
+        // Commands with missing arguments
+        CommandKind::Unknown { name, .. } => matches!(*name, "kill" | "fill" | "clone"),
+        _ => false,
+    }
+}
+
+/// Whether the `volume` is known and at most `max_volume`.
+fn fits(volume: Option<u64>, max_volume: u64) -> bool {
+    volume.is_some_and(|volume| volume <= max_volume)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_only() {
+        let allowed = |command: &str| read_only(&Command::new(command)) == PolicyDecision::Allow;
+        assert!(allowed("data get entity @p Pos"));
+        assert!(allowed("execute if entity @e[type=pig]"));
+        assert!(allowed(
+            "execute as @a run scoreboard players get @s points"
+        ));
+        assert!(!allowed("setblock 0 64 0 stone"));
+        assert!(!allowed(
+            "execute store result score x y run time query daytime"
+        ));
+        assert!(!allowed("execute as @a run kill @s"));
+        assert!(!allowed(
+            "execute as @a run execute store result score x y run time query daytime"
+        ));
+        assert!(!allowed(
+            "execute if score run list matches 1 run setblock 0 64 0 stone"
+        ));
+        assert!(allowed(r#"execute if entity @e[name="a store b"]"#));
+        assert!(allowed(r#"data get entity @e[name="a b",limit=1] Pos"#));
+    }
+
+    #[test]
+    fn test_non_destructive() {
+        let policy = non_destructive(1000);
+        let allowed = |command: &str| policy(&Command::new(command)) == PolicyDecision::Allow;
+        assert!(allowed("fill 0 64 0 9 73 9 stone"));
+        assert!(allowed("fill ~ ~ ~ ~9 ~9 ~9 air"));
+        assert!(!allowed("fill 0 64 0 10 73 9 stone"));
+        assert!(!allowed("fill ~ ~ ~ 9 73 9 stone"));
+        assert!(!allowed("setblock 0 64 0 stone destroy"));
+        assert!(!allowed("execute at @p run kill @e"));
+        assert!(!allowed(r#"kill @e[name="a b"]"#));
+        assert!(!allowed("clone 0 64 0 10 73 9 0 80 0"));
+        assert!(allowed("clone ~ ~ ~ ~9 ~9 ~9 ~ ~20 ~"));
+        assert!(allowed("say hi"));
+    }
+}
@@ -0,0 +1,302 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+#[cfg(feature = "connection")]
+use crate::geometry::Coordinate3;
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// The position of a block in a Minecraft world.
+///
+/// A [BlockPos] is formatted as absolute coordinates like `-8 64 12`, so it can be used directly in
+/// commands. It can also be parsed from this format.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BlockPos {
+    /// The x coordinate.
+    pub x: i32,
+    /// The y coordinate.
+    pub y: i32,
+    /// The z coordinate.
+    pub z: i32,
+}
+
+impl BlockPos {
+    /// Creates a [BlockPos] from its coordinates.
+    pub const fn new(x: i32, y: i32, z: i32) -> BlockPos {
+        BlockPos { x, y, z }
+    }
+
+    /// Returns the position that is offset by the given amounts.
+    pub const fn offset(self, dx: i32, dy: i32, dz: i32) -> BlockPos {
+        BlockPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// The chunk that contains this position.
+    pub const fn chunk(self) -> ChunkPos {
+        ChunkPos::new(self.x.div_euclid(16), self.z.div_euclid(16))
+    }
+}
+
+impl Add for BlockPos {
+    type Output = BlockPos;
+
+    fn add(self, rhs: BlockPos) -> BlockPos {
+        self.offset(rhs.x, rhs.y, rhs.z)
+    }
+}
+
+impl AddAssign for BlockPos {
+    fn add_assign(&mut self, rhs: BlockPos) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for BlockPos {
+    type Output = BlockPos;
+
+    fn sub(self, rhs: BlockPos) -> BlockPos {
+        self + -rhs
+    }
+}
+
+impl SubAssign for BlockPos {
+    fn sub_assign(&mut self, rhs: BlockPos) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for BlockPos {
+    type Output = BlockPos;
+
+    fn neg(self) -> BlockPos {
+        BlockPos::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl From<(i32, i32, i32)> for BlockPos {
+    fn from((x, y, z): (i32, i32, i32)) -> BlockPos {
+        BlockPos::new(x, y, z)
+    }
+}
+
+impl From<BlockPos> for (i32, i32, i32) {
+    fn from(pos: BlockPos) -> (i32, i32, i32) {
+        (pos.x, pos.y, pos.z)
+    }
+}
+
+#[cfg(feature = "connection")]
+impl From<Coordinate3<i32>> for BlockPos {
+    fn from(coordinate: Coordinate3<i32>) -> BlockPos {
+        BlockPos::new(coordinate.0, coordinate.1, coordinate.2)
+    }
+}
+
+#[cfg(feature = "connection")]
+impl From<BlockPos> for Coordinate3<i32> {
+    fn from(pos: BlockPos) -> Coordinate3<i32> {
+        Coordinate3(pos.x, pos.y, pos.z)
+    }
+}
+
+impl FromStr for BlockPos {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(' ');
+        let mut next = || parts.next().ok_or(())?.parse().map_err(|_| ());
+        let pos = BlockPos::new(next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Ok(pos)
+    }
+}
+
+impl Display for BlockPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+/// The position of a chunk in a Minecraft world. A chunk is a column of 16 by 16 blocks.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ChunkPos {
+    /// The x coordinate of the chunk, which is the x coordinate of its blocks divided by 16.
+    pub x: i32,
+    /// The z coordinate of the chunk, which is the z coordinate of its blocks divided by 16.
+    pub z: i32,
+}
+
+impl ChunkPos {
+    /// Creates a [ChunkPos] from its coordinates.
+    pub const fn new(x: i32, z: i32) -> ChunkPos {
+        ChunkPos { x, z }
+    }
+
+    /// The block with the lowest x and z coordinate in this chunk at the given `y` coordinate.
+    pub const fn min_block(self, y: i32) -> BlockPos {
+        BlockPos::new(self.x * 16, y, self.z * 16)
+    }
+
+    /// The block with the highest x and z coordinate in this chunk at the given `y` coordinate.
+    pub const fn max_block(self, y: i32) -> BlockPos {
+        self.min_block(y).offset(15, 0, 15)
+    }
+
+    /// Whether the given `pos` is inside this chunk.
+    pub const fn contains(self, pos: BlockPos) -> bool {
+        let chunk = pos.chunk();
+        chunk.x == self.x && chunk.z == self.z
+    }
+}
+
+impl From<(i32, i32)> for ChunkPos {
+    fn from((x, z): (i32, i32)) -> ChunkPos {
+        ChunkPos::new(x, z)
+    }
+}
+
+impl From<ChunkPos> for (i32, i32) {
+    fn from(pos: ChunkPos) -> (i32, i32) {
+        (pos.x, pos.z)
+    }
+}
+
+impl From<BlockPos> for ChunkPos {
+    fn from(pos: BlockPos) -> ChunkPos {
+        pos.chunk()
+    }
+}
+
+impl Display for ChunkPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.x, self.z)
+    }
+}
+
+/// A cuboid region of blocks between two corners (both inclusive).
+///
+/// A [Region] is formatted as the coordinates of both corners like `0 64 0 15 70 15`, so it can be
+/// used directly in commands like [fill_command](crate::command::fill_command).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Region {
+    min: BlockPos,
+    max: BlockPos,
+}
+
+impl Region {
+    /// Creates the [Region] spanned by the corners `a` and `b`.
+    pub fn new(a: impl Into<BlockPos>, b: impl Into<BlockPos>) -> Region {
+        let (a, b) = (a.into(), b.into());
+        Region {
+            min: BlockPos::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+            max: BlockPos::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+        }
+    }
+
+    /// The corner with the lowest coordinates.
+    pub fn min(&self) -> BlockPos {
+        self.min
+    }
+
+    /// The corner with the highest coordinates.
+    pub fn max(&self) -> BlockPos {
+        self.max
+    }
+
+    /// The number of blocks along each axis.
+    pub fn size(&self) -> BlockPos {
+        self.max - self.min + BlockPos::new(1, 1, 1)
+    }
+
+    /// The number of blocks in this region.
+    pub fn volume(&self) -> u64 {
+        let size = self.size();
+        size.x as u64 * size.y as u64 * size.z as u64
+    }
+
+    /// Whether the given `pos` is inside this region.
+    pub fn contains(&self, pos: BlockPos) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    /// Returns this region moved by `offset`.
+    pub fn offset(&self, offset: BlockPos) -> Region {
+        Region {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Returns an [Iterator] over all chunks that intersect this region.
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkPos> {
+        let (min, max) = (self.min.chunk(), self.max.chunk());
+        (min.x..=max.x).flat_map(move |x| (min.z..=max.z).map(move |z| ChunkPos::new(x, z)))
+    }
+}
+
+impl From<BlockPos> for Region {
+    fn from(pos: BlockPos) -> Region {
+        Region::new(pos, pos)
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk() {
+        assert_eq!(BlockPos::new(0, 64, 15).chunk(), ChunkPos::new(0, 0));
+        assert_eq!(BlockPos::new(16, 64, -1).chunk(), ChunkPos::new(1, -1));
+        assert_eq!(BlockPos::new(-17, 64, -16).chunk(), ChunkPos::new(-2, -1));
+    }
+
+    #[test]
+    fn test_region() {
+        // given:
+        let region = Region::new((15, 70, -1), (0, 64, 16));
+
+        // then:
+        assert_eq!(region.to_string(), "0 64 -1 15 70 16");
+        assert_eq!(region.size(), BlockPos::new(16, 7, 18));
+        assert_eq!(region.volume(), 16 * 7 * 18);
+        assert!(region.contains(BlockPos::new(15, 64, -1)));
+        assert!(!region.contains(BlockPos::new(16, 64, -1)));
+        assert_eq!(
+            region.chunks().collect::<Vec<_>>(),
+            [
+                ChunkPos::new(0, -1),
+                ChunkPos::new(0, 0),
+                ChunkPos::new(0, 1)
+            ]
+        );
+    }
+}
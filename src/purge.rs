@@ -0,0 +1,323 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    connect::{
+        add_to_function_tag, expand_template, read_function_tag, remove_from_function_tag,
+        retain_in_function_tag,
+    },
+    datapack_layout::DatapackLayout,
+    file_backend::{FileBackend, LocalFileBackend},
+    function_file::BATCH_TAG,
+    io::{io_error, write_with_permissions, FilePermissions, IoErrorAtPath},
+    objectives::REMOVE_OBJECTIVES_TAG,
+    validate_identifier, Command, ExecuteCommandsError, MinecraftConnection,
+    CUSTOM_STRUCTURES_NAMESPACE, NAMESPACE,
+};
+use std::path::{Path, PathBuf};
+
+pub(crate) const PURGE_TAG: &str = "data/minect_internal/tags/functions/purge.json";
+const PURGE_TAG_TEMPLATE: &str = r#"{"values":["minect_internal:purge/-connection_id-"]}"#;
+const PURGE_FUNCTION: &str = "data/minect_internal/functions/purge/-connection_id-.mcfunction";
+const PURGE_FUNCTION_PREFIX: &str = "minect_internal:purge/";
+
+/// Removes all artifacts of the connection with the given `identifier` from the world in
+/// `world_dir`. This is useful when an identifier is retired, because otherwise its files remain in
+/// the world indefinitely.
+///
+/// This removes:
+/// * the structure files of the connection (including those of the urgent lane and those left
+///   behind by [load_structure](crate::MinecraftConnection::load_structure)),
+/// * all functions that were generated for the connection in the Minect datapack and
+/// * all references to these functions from function tags.
+///
+/// The connection building and connection entity in Minecraft can't be removed from outside of the
+/// game. Instead a cleanup function is added to the Minect datapack, which removes them the next
+/// time the datapack is loaded, for example when executing `reload`. The next
+/// [connect](crate::MinecraftConnection::connect) of another connection in the same world executes
+/// the cleanup as well and then removes the function from the datapack. If the `identifier` itself
+/// is connected again, the function is removed without executing it.
+///
+/// No [MinecraftConnection](crate::MinecraftConnection) with this `identifier` should be used while
+/// or after calling this function.
+///
+/// # Panics
+///
+/// Panics if `identifier` contains an invalid character (see
+/// [MinecraftConnection::builder](crate::MinecraftConnection::builder)).
+///
+/// # Errors
+///
+/// This function will return an error if an [io::Error](std::io::Error) occurs.
+pub fn purge_identifier(
+    world_dir: impl AsRef<Path>,
+    identifier: &str,
+) -> Result<(), IoErrorAtPath> {
+    purge_identifier_with_backend(&LocalFileBackend, world_dir, identifier)
+}
+
+/// Like [purge_identifier], but accesses the world directory through the given `backend`, for
+/// example an [SftpFileBackend](crate::SftpFileBackend) if the world is on a remote server.
+///
+/// # Panics
+///
+/// Panics if `identifier` contains an invalid character (see
+/// [MinecraftConnection::builder](crate::MinecraftConnection::builder)).
+///
+/// # Errors
+///
+/// This function will return an error if an [io::Error](std::io::Error) occurs.
+pub fn purge_identifier_with_backend(
+    backend: &dyn FileBackend,
+    world_dir: impl AsRef<Path>,
+    identifier: &str,
+) -> Result<(), IoErrorAtPath> {
+    validate_identifier(identifier);
+    let world_dir = world_dir.as_ref();
+
+    let structures_dir = world_dir
+        .join("generated")
+        .join(NAMESPACE)
+        .join("structures");
+    remove_dir_if_exists(backend, structures_dir.join(identifier))?;
+    remove_dir_if_exists(
+        backend,
+        structures_dir.join(format!("{}_urgent", identifier)),
+    )?;
+    let custom_structures_dir = world_dir
+        .join("generated")
+        .join(CUSTOM_STRUCTURES_NAMESPACE)
+        .join("structures")
+        .join(identifier);
+    remove_dir_if_exists(backend, custom_structures_dir)?;
+
+    let datapack_dir = world_dir.join("datapacks").join(NAMESPACE);
+    if !backend.is_dir(&datapack_dir) {
+        return Ok(());
+    }
+    let layout = DatapackLayout::detect(backend, &datapack_dir);
+    for identifier in [identifier.to_string(), format!("{}_urgent", identifier)] {
+        purge_functions(backend, &datapack_dir, layout, &identifier)?;
+    }
+    let path = layout.join(&datapack_dir, &expand_template(PURGE_FUNCTION, identifier));
+    let contents = expand_template(purge_function_template(), identifier);
+    write_with_permissions(backend, path, &contents, &FilePermissions::default())?;
+    add_to_function_tag(
+        backend,
+        layout.join(&datapack_dir, PURGE_TAG),
+        &expand_template(PURGE_TAG_TEMPLATE, identifier),
+    )
+}
+
+fn purge_function_template() -> &'static str {
+    include_datapack_template!("data/minect_internal/functions/purge/-connection_id-.mcfunction")
+}
+
+/// Executes the cleanup functions that [purge_identifier] registered in `#minect_internal:purge`
+/// with the connection building of `connection` and removes them from the datapack, so they don't
+/// run on every reload. The cleanup of the connection's own identifier is removed without executing
+/// it, because the identifier is in use again.
+pub(crate) fn execute_pending_purges(
+    connection: &mut MinecraftConnection,
+) -> Result<(), ExecuteCommandsError> {
+    let backend = connection.file_backend.clone();
+    let datapack_dir = connection.datapack_dir.clone();
+    let layout = DatapackLayout::detect(&*backend, &datapack_dir);
+    let tag = layout.join(&datapack_dir, PURGE_TAG);
+    if !backend.is_file(&tag) {
+        return Ok(());
+    }
+    let identifiers = read_function_tag(&*backend, &tag)?
+        .into_iter()
+        .filter_map(|value| Some(value.strip_prefix(PURGE_FUNCTION_PREFIX)?.to_string()))
+        .collect::<Vec<_>>();
+    if identifiers.is_empty() {
+        return Ok(());
+    }
+
+    // Functions can only be executed after a reload, so the commands are executed directly
+    let commands = identifiers
+        .iter()
+        .filter(|identifier| **identifier != connection.identifier)
+        .flat_map(|identifier| purge_commands(identifier))
+        .collect::<Vec<_>>();
+    if !commands.is_empty() {
+        connection.execute_internal_commands(commands)?;
+    }
+
+    for identifier in &identifiers {
+        let template = expand_template(PURGE_TAG_TEMPLATE, identifier);
+        remove_from_function_tag(&*backend, &tag, &template)?;
+        let path = layout.join(&datapack_dir, &expand_template(PURGE_FUNCTION, identifier));
+        if backend.is_file(&path) {
+            backend
+                .remove_file(&path)
+                .map_err(io_error("Failed to remove file", &path))?;
+        }
+    }
+    Ok(())
+}
+
+fn purge_commands(identifier: &str) -> Vec<Command> {
+    expand_template(purge_function_template(), identifier)
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Command::new)
+        .collect()
+}
+
+fn purge_functions(
+    backend: &dyn FileBackend,
+    datapack_dir: &Path,
+    layout: DatapackLayout,
+    identifier: &str,
+) -> Result<(), IoErrorAtPath> {
+    let prefix = format!("minect_internal:connection/{}/", identifier);
+    for tag in [
+        "data/minect_internal/tags/functions/connect/choose_chunk.json",
+        "data/minect_internal/tags/functions/connect/prompt.json",
+        "data/minect_internal/tags/functions/disconnect/prompt.json",
+        BATCH_TAG,
+        REMOVE_OBJECTIVES_TAG,
+    ] {
+        let path = layout.join(datapack_dir, tag);
+        if backend.is_file(&path) {
+            retain_in_function_tag(backend, path, |value| !value.starts_with(&prefix))?;
+        }
+    }
+    remove_dir_if_exists(
+        backend,
+        connection_functions_dir(datapack_dir, layout, identifier),
+    )
+}
+
+fn connection_functions_dir(
+    datapack_dir: &Path,
+    layout: DatapackLayout,
+    identifier: &str,
+) -> PathBuf {
+    layout
+        .join(datapack_dir, "data/minect_internal/functions/connection")
+        .join(identifier)
+}
+
+fn remove_dir_if_exists(backend: &dyn FileBackend, path: PathBuf) -> Result<(), IoErrorAtPath> {
+    if backend.is_dir(&path) {
+        backend
+            .remove_dir_all(&path)
+            .map_err(io_error("Failed to remove directory", path))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        file_backend::LocalFileBackend, io::write, spool::count_pending_structures, utils::TempDir,
+    };
+    use std::fs::{create_dir_all, read_to_string};
+
+    #[test]
+    fn test_purge_identifier() {
+        // given:
+        let world_dir = TempDir::new("purge");
+        let datapack_dir = world_dir.join("datapacks").join(NAMESPACE);
+        let structures_dir = world_dir.join("generated/minect/structures/old");
+        create_dir_all(&structures_dir).unwrap();
+        let custom_structures_dir = world_dir.join("generated/minect_custom/structures/old");
+        create_dir_all(&custom_structures_dir).unwrap();
+        create_dir_all(
+            connection_functions_dir(&datapack_dir, DatapackLayout::Plural, "old").join("batch/3"),
+        )
+        .unwrap();
+        create_dir_all(connection_functions_dir(
+            &datapack_dir,
+            DatapackLayout::Plural,
+            "new",
+        ))
+        .unwrap();
+        let batch_tag = datapack_dir.join(BATCH_TAG);
+        write(
+            &batch_tag,
+            r#"{"values":["minect_internal:connection/old/batch/3/guard","minect_internal:connection/new/batch/1/guard"]}"#,
+        )
+        .unwrap();
+
+        // when:
+        purge_identifier(&world_dir, "old").unwrap();
+
+        // then:
+        let batch_tag = read_to_string(batch_tag).unwrap();
+        let purge_tag = read_to_string(datapack_dir.join(PURGE_TAG)).unwrap();
+        assert!(!structures_dir.exists());
+        assert!(!custom_structures_dir.exists());
+        assert!(!batch_tag.contains("connection/old/"));
+        assert!(batch_tag.contains("connection/new/"));
+        assert!(purge_tag.contains("minect_internal:purge/old"));
+    }
+
+    #[test]
+    fn test_execute_pending_purges() {
+        // given:
+        let dir = TempDir::new("execute_pending_purges");
+        let world_dir = dir.join("saves/world");
+        let mut connection = MinecraftConnection::builder("new", &world_dir).build();
+        connection.create_datapack().unwrap();
+        purge_identifier(&world_dir, "old").unwrap();
+
+        // when:
+        execute_pending_purges(&mut connection).unwrap();
+
+        // then:
+        let datapack_dir = world_dir.join("datapacks").join(NAMESPACE);
+        let purge_tag = read_to_string(datapack_dir.join(PURGE_TAG)).unwrap();
+        assert!(!purge_tag.contains("minect_internal:purge/old"));
+        let purge_function = expand_template(PURGE_FUNCTION, "old");
+        assert!(!datapack_dir.join(purge_function).exists());
+        let structures_dir = world_dir.join("generated/minect/structures/new");
+        assert_eq!(
+            count_pending_structures(&LocalFileBackend, &structures_dir),
+            1
+        );
+    }
+
+    #[test]
+    fn test_execute_pending_purges_of_own_identifier() {
+        // given:
+        let dir = TempDir::new("execute_pending_purges_of_own_identifier");
+        let world_dir = dir.join("saves/world");
+        let mut connection = MinecraftConnection::builder("old", &world_dir).build();
+        connection.create_datapack().unwrap();
+        purge_identifier(&world_dir, "old").unwrap();
+
+        // when:
+        execute_pending_purges(&mut connection).unwrap();
+
+        // then:
+        let datapack_dir = world_dir.join("datapacks").join(NAMESPACE);
+        let purge_tag = read_to_string(datapack_dir.join(PURGE_TAG)).unwrap();
+        assert!(!purge_tag.contains("minect_internal:purge/old"));
+        let structures_dir = world_dir.join("generated/minect/structures/old");
+        assert_eq!(
+            count_pending_structures(&LocalFileBackend, &structures_dir),
+            0
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    log::{InterceptorAction, LogEvent, ObserverError},
+    names, CommandResultEvent, LogVolume, MinecraftConnection, MinectWarning,
+};
+use tokio_stream::Stream;
+
+/// A read-only view of a connection, created with [MinecraftConnection::observer_only]. This is
+/// useful for monitoring deployments that must not alter the world.
+///
+/// A [MinecraftObserver] only reads the log file. Because it has no methods that write to the world
+/// directory, this is enforced at compile time:
+///
+/// ```compile_fail
+/// # use minect::{Command, MinecraftConnection};
+/// let mut observer = MinecraftConnection::builder("example", "world").build().observer_only();
+/// observer.execute_commands([Command::new("say hi")]);
+/// ```
+pub struct MinecraftObserver {
+    connection: MinecraftConnection,
+}
+
+impl MinecraftObserver {
+    pub(crate) fn new(connection: MinecraftConnection) -> MinecraftObserver {
+        MinecraftObserver { connection }
+    }
+
+    /// The identifier of the observed connection.
+    pub fn get_identifier(&self) -> &str {
+        self.connection.get_identifier()
+    }
+
+    /// The volume of log output produced by the observed connection (see
+    /// [MinecraftConnection::log_volume]).
+    pub fn log_volume(&self) -> LogVolume {
+        self.connection.log_volume()
+    }
+
+    /// Returns a [Stream] of [MinectWarning]s (see [MinecraftConnection::warnings]).
+    pub fn warnings(&self) -> impl Stream<Item = MinectWarning> {
+        self.connection.warnings()
+    }
+
+    /// Adds an `interceptor` that can modify or drop [LogEvent]s before they are sent to listeners
+    /// (see [MinecraftConnection::add_interceptor]).
+    pub fn add_interceptor(
+        &mut self,
+        interceptor: impl Fn(&mut LogEvent) -> InterceptorAction + Send + Sync + 'static,
+    ) {
+        self.connection.add_interceptor(interceptor)
+    }
+
+    /// Returns a [Stream] of errors that occur while reading the log file (see
+    /// [MinecraftConnection::add_error_listener]).
+    pub fn add_error_listener(&mut self) -> impl Stream<Item = ObserverError> {
+        self.connection.add_error_listener()
+    }
+
+    /// Returns a [Stream] of all [LogEvent]s (see [MinecraftConnection::add_listener]).
+    pub fn add_listener(&mut self) -> impl Stream<Item = LogEvent> {
+        self.connection.add_listener()
+    }
+
+    /// Returns a [Stream] of [LogEvent]s with [executor](LogEvent::executor) equal to the given
+    /// `name` (see [MinecraftConnection::add_named_listener]).
+    pub fn add_named_listener(&mut self, name: impl Into<String>) -> impl Stream<Item = LogEvent> {
+        self.connection.add_named_listener(name)
+    }
+
+    /// Like [add_named_listener](Self::add_named_listener), but for the given `name` in the given
+    /// `namespace` (see [names::namespaced]).
+    pub fn namespaced_listener(
+        &mut self,
+        namespace: impl AsRef<str>,
+        name: impl AsRef<str>,
+    ) -> impl Stream<Item = LogEvent> {
+        self.add_named_listener(names::namespaced(namespace, name))
+    }
+
+    /// Returns a [Stream] of [CommandResultEvent]s (see
+    /// [MinecraftConnection::add_command_result_listener]).
+    pub fn add_command_result_listener(&mut self) -> impl Stream<Item = CommandResultEvent> {
+        self.connection.add_command_result_listener()
+    }
+}
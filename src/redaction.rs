@@ -0,0 +1,71 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::{borrow::Cow, sync::RwLock};
+
+type Redactor = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+static REDACTOR: RwLock<Option<Redactor>> = RwLock::new(None);
+
+/// Sets a function that is applied to the Minecraft command whenever a
+/// [Command](crate::Command) is formatted with [Display](std::fmt::Display) or
+/// [Debug](std::fmt::Debug), for example in log messages and error messages of Minect. This can be
+/// used to hide sensitive values like tokens that are embedded in commands.
+///
+/// The redactor applies to the whole program and replaces any previously set redactor. The commands
+/// that are executed in Minecraft are not affected.
+pub fn set_command_redactor(redactor: impl Fn(&str) -> String + Send + Sync + 'static) {
+    *REDACTOR.write().unwrap() = Some(Box::new(redactor));
+}
+
+/// Removes the redactor set with [set_command_redactor], so commands are formatted verbatim again.
+pub fn clear_command_redactor() {
+    *REDACTOR.write().unwrap() = None;
+}
+
+pub(crate) fn redact(command: &str) -> Cow<'_, str> {
+    match &*REDACTOR.read().unwrap() {
+        Some(redactor) => Cow::Owned(redactor(command)),
+        None => Cow::Borrowed(command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Command;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn test_display_applies_redactor() {
+        // given:
+        set_command_redactor(|command| command.replace("secret-token", "***"));
+        let command = Command::named("auth", "tellraw @a \"secret-token\"");
+
+        // when:
+        let display = command.to_string();
+        let debug = format!("{:?}", command);
+        clear_command_redactor();
+
+        // then:
+        assert_eq!(display, "[auth] tellraw @a \"***\"");
+        assert!(!debug.contains("secret-token"));
+        assert_eq!(command.to_string(), "[auth] tellraw @a \"secret-token\"");
+    }
+}
@@ -0,0 +1,189 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    warnings::{MinectWarning, Warnings},
+    Command,
+};
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Keeps track of batches that were written, but not yet acknowledged by Minecraft, so their
+/// commands can be executed again in a new batch if the acknowledgement takes too long.
+pub(crate) struct UnacknowledgedBatches {
+    retry_after: Duration,
+    state: Mutex<State>,
+    pending: Mutex<Vec<Vec<Command>>>,
+}
+
+struct State {
+    batches: BTreeMap<u64, UnacknowledgedBatch>,
+    /// When the last batch was acknowledged. Structures are loaded in order, so the oldest batch
+    /// can only be loaded after all previous batches.
+    progressed_at: Instant,
+}
+
+struct UnacknowledgedBatch {
+    /// `None` once the commands were taken to be executed again.
+    commands: Option<Vec<Command>>,
+    written_at: Instant,
+}
+
+impl UnacknowledgedBatches {
+    pub(crate) fn new(retry_after: Duration) -> UnacknowledgedBatches {
+        UnacknowledgedBatches {
+            retry_after,
+            state: Mutex::new(State {
+                batches: BTreeMap::new(),
+                progressed_at: Instant::now(),
+            }),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn insert(&self, id: u64, commands: Vec<Command>) {
+        let batch = UnacknowledgedBatch {
+            commands: Some(commands),
+            written_at: Instant::now(),
+        };
+        self.state.lock().unwrap().batches.insert(id, batch);
+    }
+
+    /// Forgets the batch with the given `id` and all batches before it, because structures are
+    /// always loaded in order.
+    pub(crate) fn acknowledge(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.batches.retain(|other_id, _| is_after(*other_id, id));
+        state.progressed_at = Instant::now();
+    }
+
+    /// Forgets the batch with the given `id` and returns its commands, unless they were already
+    /// taken to be executed again.
+    pub(crate) fn take(&self, id: u64) -> Option<Vec<Command>> {
+        let batch = self.state.lock().unwrap().batches.remove(&id)?;
+        batch.commands
+    }
+
+    /// Forgets all batches and returns their commands in the order they were written.
+    pub(crate) fn take_all(&self) -> Vec<Vec<Command>> {
+        let batches = std::mem::take(&mut self.state.lock().unwrap().batches);
+        let pending = self.take_pending();
+        pending
+            .into_iter()
+            .chain(batches.into_values().filter_map(|batch| batch.commands))
+            .collect()
+    }
+
+    /// Takes the commands of the oldest batch to execute them again (see
+    /// [take_pending](Self::take_pending)), if it was not acknowledged within `retry_after`.
+    ///
+    /// Later batches are never retried while an older batch is pending, because they are only
+    /// queued behind it. The oldest batch is retried at most once, the new batch acknowledges it.
+    pub(crate) fn retry_expired(&self, warnings: &Warnings) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let progressed_at = state.progressed_at;
+        let (id, batch) = match state.batches.iter_mut().next() {
+            Some(entry) => entry,
+            None => return,
+        };
+        let waiting_since = batch.written_at.max(progressed_at);
+        if now.duration_since(waiting_since) < self.retry_after {
+            return;
+        }
+        if let Some(commands) = batch.commands.take() {
+            warnings.report(MinectWarning::BatchRetried { id: *id });
+            self.pending.lock().unwrap().push(commands);
+        }
+    }
+
+    /// Returns the commands of batches that have to be executed again in a new batch.
+    pub(crate) fn take_pending(&self) -> Vec<Vec<Command>> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+}
+
+/// Whether `id` was generated after `other` taking into account that ids wrap around.
+pub(crate) fn is_after(id: u64, other: u64) -> bool {
+    (id.wrapping_sub(other) as i64) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_is_after() {
+        assert!(is_after(1, 0));
+        assert!(!is_after(0, 0));
+        assert!(!is_after(0, 1));
+        assert!(is_after(0, u64::MAX));
+    }
+
+    #[test]
+    fn test_retry_expired_only_retries_the_oldest_batch_once() {
+        // given:
+        let unacknowledged = UnacknowledgedBatches::new(Duration::from_millis(10));
+        let warnings = Warnings::default();
+        unacknowledged.insert(1, vec![Command::new("say 1")]);
+        unacknowledged.insert(2, vec![Command::new("say 2")]);
+        sleep(Duration::from_millis(20));
+
+        // when:
+        unacknowledged.retry_expired(&warnings);
+        unacknowledged.retry_expired(&warnings);
+
+        // then:
+        assert_eq!(
+            unacknowledged.take_pending(),
+            vec![vec![Command::new("say 1")]]
+        );
+        assert_eq!(unacknowledged.take(1), None);
+    }
+
+    #[test]
+    fn test_retry_expired_waits_for_batches_queued_behind_an_acknowledged_batch() {
+        // given:
+        let unacknowledged = UnacknowledgedBatches::new(Duration::from_millis(50));
+        let warnings = Warnings::default();
+        unacknowledged.insert(1, vec![Command::new("say 1")]);
+        unacknowledged.insert(2, vec![Command::new("say 2")]);
+        sleep(Duration::from_millis(60));
+        unacknowledged.acknowledge(1);
+
+        // when:
+        unacknowledged.retry_expired(&warnings);
+
+        // then:
+        assert!(unacknowledged.take_pending().is_empty());
+
+        // when:
+        sleep(Duration::from_millis(60));
+        unacknowledged.retry_expired(&warnings);
+
+        // then:
+        assert_eq!(
+            unacknowledged.take_pending(),
+            vec![vec![Command::new("say 2")]]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of other processes that use the same connection identifier, see
+//! [share_identifier](crate::MinecraftConnectionBuilder::share_identifier).
+//!
+//! Every batch of a connection records a random session id and the current gametime in the
+//! `minect_global` scoreboard. When connecting, these scores are echoed through the log, which
+//! reveals whether another session executed a batch recently.
+
+use crate::{
+    command::{
+        query_scoreboard_command, store_result_in_score, time_query_command, QueryScoreboardOutput,
+        TimeQuery, TimeQueryOutput,
+    },
+    log::LogEvent,
+    Command,
+};
+use std::{
+    collections::hash_map::RandomState,
+    fmt::{self, Display},
+    hash::{BuildHasher, Hasher},
+    time::SystemTime,
+};
+
+const OBJECTIVE: &str = "minect_global";
+
+/// A session that executed a batch within this many game ticks (one minute) is considered active.
+pub(crate) const ACTIVE_SESSION_TICKS: i64 = 20 * 60;
+
+/// The number of commands returned by [session_commands].
+pub(crate) const SESSION_COMMANDS_LEN: usize = 2;
+
+/// Another process that actively uses the identifier of a connection. This is returned by
+/// [ConnectError::identifier_in_use](crate::ConnectError::identifier_in_use).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IdentifierInUse {
+    /// The connection identifier.
+    pub identifier: String,
+    /// The random id of the other session.
+    pub session: i32,
+    /// The number of game ticks since the other session executed its last batch.
+    pub ticks_since_last_batch: i64,
+    _private: (),
+}
+
+impl Display for IdentifierInUse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The identifier {} is in use by session {}, which executed a batch {} ticks ago",
+            self.identifier, self.session, self.ticks_since_last_batch
+        )
+    }
+}
+
+/// Generates a random session id that is never `0`, because `0` is the score of an unused
+/// identifier.
+pub(crate) fn new_session_id() -> i32 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+    match hasher.finish() as i32 {
+        0 => 1,
+        session => session,
+    }
+}
+
+fn session_holder(identifier: &str) -> String {
+    format!("minect_session+{}", identifier)
+}
+
+fn session_tick_holder(identifier: &str) -> String {
+    format!("minect_session_tick+{}", identifier)
+}
+
+/// Commands that record the `session` and the current gametime when a batch is executed.
+pub(crate) fn session_commands(identifier: &str, session: i32) -> [Command; SESSION_COMMANDS_LEN] {
+    [
+        Command::new(format!(
+            "scoreboard players set {} {} {}",
+            session_holder(identifier),
+            OBJECTIVE,
+            session
+        )),
+        Command::new(store_result_in_score(
+            time_query_command(TimeQuery::Gametime),
+            session_tick_holder(identifier),
+            OBJECTIVE,
+        )),
+    ]
+}
+
+/// Commands that echo the last session of the connection and the current gametime through the log.
+pub(crate) fn handshake_commands(identifier: &str, name: &str) -> [Command; 3] {
+    [
+        Command::named(
+            name,
+            query_scoreboard_command(session_holder(identifier), OBJECTIVE),
+        ),
+        Command::named(
+            name,
+            query_scoreboard_command(session_tick_holder(identifier), OBJECTIVE),
+        ),
+        Command::named(name, time_query_command(TimeQuery::Gametime)),
+    ]
+}
+
+/// Collects the output of the [handshake_commands].
+pub(crate) struct Handshake<'l> {
+    identifier: &'l str,
+    session: Option<i32>,
+    tick: Option<i32>,
+    gametime: Option<i32>,
+}
+
+impl Handshake<'_> {
+    pub(crate) fn new(identifier: &str) -> Handshake<'_> {
+        Handshake {
+            identifier,
+            session: None,
+            tick: None,
+            gametime: None,
+        }
+    }
+
+    /// Returns `true` once the output of all [handshake_commands] was received.
+    pub(crate) fn push(&mut self, event: &LogEvent) -> bool {
+        if let Ok(output) = event.output.parse::<QueryScoreboardOutput>() {
+            if output.entity == session_holder(self.identifier) {
+                self.session = Some(output.score);
+            } else if output.entity == session_tick_holder(self.identifier) {
+                self.tick = Some(output.score);
+            }
+        } else if let Ok(output) = event.output.parse::<TimeQueryOutput>() {
+            self.gametime = Some(output.time);
+        }
+        self.session.is_some() && self.tick.is_some() && self.gametime.is_some()
+    }
+
+    /// Returns the other session if it executed a batch within the last `active_ticks`.
+    /// `own_session` is ignored, so reconnecting does not detect itself.
+    pub(crate) fn other_session(
+        &self,
+        own_session: Option<i32>,
+        active_ticks: i64,
+    ) -> Option<IdentifierInUse> {
+        let session = self.session.filter(|session| *session != 0)?;
+        if Some(session) == own_session {
+            return None;
+        }
+        let ticks_since_last_batch = i64::from(self.gametime?) - i64::from(self.tick?);
+        if !(0..active_ticks).contains(&ticks_since_last_batch) {
+            return None;
+        }
+        Some(IdentifierInUse {
+            identifier: self.identifier.to_string(),
+            session,
+            ticks_since_last_batch,
+            _private: (),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(session: i32, tick: i32, gametime: i32) -> Handshake<'static> {
+        Handshake {
+            identifier: "example",
+            session: Some(session),
+            tick: Some(tick),
+            gametime: Some(gametime),
+        }
+    }
+
+    #[test]
+    fn test_other_session_active() {
+        // when:
+        let actual = handshake(42, 1000, 1100).other_session(Some(7), ACTIVE_SESSION_TICKS);
+
+        // then:
+        let actual = actual.unwrap();
+        assert_eq!(actual.session, 42);
+        assert_eq!(actual.ticks_since_last_batch, 100);
+    }
+
+    #[test]
+    fn test_other_session_ignores_inactive_unused_and_own_sessions() {
+        // given:
+        let inactive = handshake(42, 1000, 1000 + ACTIVE_SESSION_TICKS as i32);
+        let unused = handshake(0, 1000, 1100);
+        let own = handshake(42, 1000, 1100);
+
+        // when:
+        let inactive = inactive.other_session(None, ACTIVE_SESSION_TICKS);
+        let unused = unused.other_session(None, ACTIVE_SESSION_TICKS);
+        let own = own.other_session(Some(42), ACTIVE_SESSION_TICKS);
+
+        // then:
+        assert_eq!(inactive, None);
+        assert_eq!(unused, None);
+        assert_eq!(own, None);
+    }
+}
@@ -0,0 +1,143 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::command::{scoreboard_displayname_command, scoreboard_setdisplay_command, DisplaySlot};
+use std::collections::BTreeMap;
+
+/// The rows of a scoreboard sidebar that were last sent to Minecraft, so that
+/// [update](Self::update) only generates commands for rows that changed. This allows dashboards to
+/// refresh live stats frequently without flooding Minecraft with commands.
+///
+/// Each row is a fake score holder on the sidebar objective. Minecraft sorts the rows by score in
+/// descending order. Row names are inserted verbatim into commands, so they must not contain
+/// whitespace.
+///
+/// A [MinecraftConnection](crate::MinecraftConnection) creates a [Sidebar] with
+/// [show_sidebar](crate::MinecraftConnection::show_sidebar) and executes the updates with
+/// [update_sidebar](crate::MinecraftConnection::update_sidebar).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sidebar {
+    objective: String,
+    rows: BTreeMap<String, i32>,
+}
+
+impl Sidebar {
+    /// Creates a [Sidebar] for the given scoreboard `objective` without any rows. The objective is
+    /// not created by this function.
+    pub fn new(objective: impl Into<String>) -> Sidebar {
+        Sidebar {
+            objective: objective.into(),
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// The scoreboard objective that holds the rows of this sidebar.
+    pub fn objective(&self) -> &str {
+        &self.objective
+    }
+
+    /// The rows that are currently shown, mapping the row name to its score.
+    pub fn rows(&self) -> &BTreeMap<String, i32> {
+        &self.rows
+    }
+
+    /// Generates the commands that create the dummy objective, set its display name to `title` and
+    /// show it in the sidebar.
+    pub fn show_commands(&self, title: &str) -> [String; 3] {
+        [
+            format!("scoreboard objectives add {} dummy", self.objective),
+            scoreboard_displayname_command(&self.objective, title),
+            scoreboard_setdisplay_command(DisplaySlot::Sidebar, Some(&self.objective)),
+        ]
+    }
+
+    /// Generates the command that clears the sidebar display slot.
+    pub fn hide_command(&self) -> String {
+        scoreboard_setdisplay_command(DisplaySlot::Sidebar, None)
+    }
+
+    /// Replaces the rows of this sidebar with `rows` and returns the commands that bring Minecraft
+    /// from the previous rows to the new ones: rows that are new or have a different score are set
+    /// and rows that are no longer present are reset. Unchanged rows produce no commands, so the
+    /// result is empty if nothing changed.
+    pub fn update(
+        &mut self,
+        rows: impl IntoIterator<Item = (impl Into<String>, i32)>,
+    ) -> Vec<String> {
+        let rows = rows
+            .into_iter()
+            .map(|(name, score)| (name.into(), score))
+            .collect::<BTreeMap<_, _>>();
+        let mut commands = Vec::new();
+        for name in self.rows.keys().filter(|name| !rows.contains_key(*name)) {
+            commands.push(format!(
+                "scoreboard players reset {} {}",
+                name, self.objective
+            ));
+        }
+        for (name, score) in &rows {
+            if self.rows.get(name) != Some(score) {
+                commands.push(format!(
+                    "scoreboard players set {} {} {}",
+                    name, self.objective, score
+                ));
+            }
+        }
+        self.rows = rows;
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        // given:
+        let mut sidebar = Sidebar::new("stats");
+        sidebar.update([("tps", 20), ("players", 3), ("entities", 150)]);
+
+        // when:
+        let actual = sidebar.update([("tps", 19), ("players", 3), ("chunks", 400)]);
+
+        // then:
+        assert_eq!(
+            actual,
+            [
+                "scoreboard players reset entities stats",
+                "scoreboard players set chunks stats 400",
+                "scoreboard players set tps stats 19",
+            ]
+        );
+        assert_eq!(sidebar.rows().len(), 3);
+    }
+
+    #[test]
+    fn test_update_unchanged() {
+        // given:
+        let mut sidebar = Sidebar::new("stats");
+        sidebar.update([("tps", 20)]);
+
+        // when:
+        let actual = sidebar.update([("tps", 20)]);
+
+        // then:
+        assert!(actual.is_empty());
+    }
+}
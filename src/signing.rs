@@ -0,0 +1,551 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Signing of batches with an HMAC marker, see
+//! [sign_batches](crate::MinecraftConnectionBuilder::sign_batches).
+//!
+//! The marker of a structure file consists of two commands. The first stores the id of the batch,
+//! the id of the next batch and the HMAC-SHA256 of the structure in the storage
+//! `minect:integrity` under the connection identifier. The MAC covers the connection identifier,
+//! the id of the batch and every block and entity of the structure with its position, block state
+//! and NBT, so a signed structure file can neither be modified nor replayed under another id. The
+//! second command executes the function `minect_internal:connection/<identifier>/verify_integrity`,
+//! which alerts players with the tag `minect_moderator` if the batch was not announced as the next
+//! batch by the previous one.
+//!
+//! Because functions can't compute an HMAC, Minecraft can only detect replayed or reordered batches.
+//! The MAC itself is verified outside of Minecraft with [verify_structure_file]. Batches executed
+//! with [ExecutionStrategy::FunctionFile](crate::ExecutionStrategy::FunctionFile) start with a
+//! comment containing the MAC of the function, which is verified with [verify_function_file].
+
+use crate::{
+    io::{io_error, IoErrorAtPath},
+    structure::nbt::{Structure, StructureBlock},
+    structure_id::{format_structure_id, parse_structure_file},
+    utils::io_invalid_data,
+    Command,
+};
+use hmac::{Hmac, Mac};
+use nbt::Value;
+use sha2::Sha256;
+use std::{
+    fmt::Write,
+    fs::{read_to_string, File},
+    io::BufReader,
+    path::Path,
+};
+
+/// The number of commands of the marker of a structure file.
+pub(crate) const MARKER_LEN: usize = 2;
+const MARKER_PREFIX: &str = "data modify storage minect:integrity ";
+const MAC_PREFIX: &str = ",mac:\"";
+const MAC_SUFFIX: &str = "\"}";
+const FUNCTION_MARKER_PREFIX: &str = "# minect mac ";
+const STRUCTURE_DOMAIN: &[u8] = b"minect structure";
+const FUNCTION_DOMAIN: &[u8] = b"minect function";
+
+pub(crate) struct BatchSigner {
+    key: Vec<u8>,
+}
+
+impl BatchSigner {
+    pub(crate) fn new(key: Vec<u8>) -> BatchSigner {
+        BatchSigner { key }
+    }
+
+    /// The marker commands of the batch with the given `id`. The MAC is filled in by
+    /// [sign](Self::sign).
+    pub(crate) fn placeholder(identifier: &str, id: u64, next_id: u64) -> [Command; MARKER_LEN] {
+        let mac = "0".repeat(64);
+        [
+            Command::new(marker_command(identifier, id, next_id, &mac)),
+            Command::new(format!(
+                "function minect_internal:connection/{}/verify_integrity",
+                identifier
+            )),
+        ]
+    }
+
+    /// Sets the MAC of the marker command in `structure`, which is the structure file of the batch
+    /// with the given `id`.
+    pub(crate) fn sign(&self, identifier: &str, id: u64, structure: &mut Structure) {
+        let mac = to_hex(
+            &compute_mac(&self.key, identifier, id, structure)
+                .finalize()
+                .into_bytes(),
+        );
+        for command in commands_mut(structure) {
+            if let Some(signed) = with_mac(command, &mac) {
+                *command = signed;
+            }
+        }
+    }
+
+    /// The first line of the function file of the batch with the given `id` that consists of the
+    /// given `lines`.
+    pub(crate) fn sign_function(&self, identifier: &str, id: u64, lines: &[String]) -> String {
+        let mac = compute_function_mac(&self.key, identifier, id, lines);
+        format!(
+            "{}{}",
+            FUNCTION_MARKER_PREFIX,
+            to_hex(&mac.finalize().into_bytes())
+        )
+    }
+}
+
+fn marker_command(identifier: &str, id: u64, next_id: u64, mac: &str) -> String {
+    format!(
+        "{}\"{}\" merge value {{id:\"{}\",next:\"{}\"{}{}{}",
+        MARKER_PREFIX,
+        identifier,
+        format_structure_id(id),
+        format_structure_id(next_id),
+        MAC_PREFIX,
+        mac,
+        MAC_SUFFIX
+    )
+}
+
+/// Returns the MAC of the marker `command` or `None` if `command` is not a marker.
+fn get_mac(command: &str) -> Option<&str> {
+    let (_, mac) = command
+        .strip_prefix(MARKER_PREFIX)?
+        .rsplit_once(MAC_PREFIX)?;
+    mac.strip_suffix(MAC_SUFFIX)
+}
+
+/// Returns the marker `command` with the given `mac` or `None` if `command` is not a marker.
+fn with_mac(command: &str, mac: &str) -> Option<String> {
+    get_mac(command)?;
+    let (prefix, _) = command.rsplit_once(MAC_PREFIX)?;
+    Some(format!("{}{}{}{}", prefix, MAC_PREFIX, mac, MAC_SUFFIX))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|chunk| match chunk {
+            [_, _] => u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the commands of all command blocks and command block minecarts in `structure`.
+fn commands(structure: &Structure) -> impl Iterator<Item = &str> {
+    structure
+        .blocks
+        .iter()
+        .filter_map(|block| block.nbt.as_ref())
+        .chain(structure.entities.iter().map(|entity| &entity.nbt))
+        .filter_map(|nbt| match nbt {
+            Value::Compound(compound) => match compound.get("Command") {
+                Some(Value::String(command)) => Some(command.as_str()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn commands_mut(structure: &mut Structure) -> impl Iterator<Item = &mut String> {
+    structure
+        .blocks
+        .iter_mut()
+        .filter_map(|block| block.nbt.as_mut())
+        .chain(structure.entities.iter_mut().map(|entity| &mut entity.nbt))
+        .filter_map(|nbt| match nbt {
+            Value::Compound(compound) => match compound.get_mut("Command") {
+                Some(Value::String(command)) => Some(command),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn new_mac(key: &[u8], domain: &[u8], identifier: &str, id: u64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    let mut buf = Vec::new();
+    write_bytes(&mut buf, domain);
+    write_bytes(&mut buf, identifier.as_bytes());
+    write_bytes(&mut buf, format_structure_id(id).as_bytes());
+    mac.update(&buf);
+    mac
+}
+
+/// Computes the MAC of `structure` with the MAC of its marker set to the placeholder.
+fn compute_mac(key: &[u8], identifier: &str, id: u64, structure: &Structure) -> Hmac<Sha256> {
+    let mut mac = new_mac(key, STRUCTURE_DOMAIN, identifier, id);
+    let mut buf = Vec::new();
+    write_ints(&mut buf, &structure.size);
+
+    let mut blocks = structure.blocks.iter().collect::<Vec<_>>();
+    blocks.sort_by(|a, b| a.pos.cmp(&b.pos));
+    write_len(&mut buf, blocks.len());
+    for block in blocks {
+        write_block(&mut buf, structure, block);
+    }
+
+    write_len(&mut buf, structure.entities.len());
+    for entity in &structure.entities {
+        write_len(&mut buf, entity.pos.len());
+        for pos in &entity.pos {
+            buf.extend(pos.to_bits().to_be_bytes());
+        }
+        write_ints(&mut buf, &entity.block_pos);
+        write_nbt(&mut buf, &with_placeholder(&entity.nbt));
+    }
+    mac.update(&buf);
+    mac
+}
+
+fn write_block(buf: &mut Vec<u8>, structure: &Structure, block: &StructureBlock) {
+    write_ints(buf, &block.pos);
+    match usize::try_from(block.state)
+        .ok()
+        .and_then(|state| structure.palette.get(state))
+    {
+        Some(state) => {
+            buf.push(1);
+            write_bytes(buf, state.name.as_bytes());
+            write_len(buf, state.properties.len());
+            for (key, value) in &state.properties {
+                write_bytes(buf, key.as_bytes());
+                write_bytes(buf, value.as_bytes());
+            }
+        }
+        None => {
+            buf.push(0);
+            buf.extend(block.state.to_be_bytes());
+        }
+    }
+    match &block.nbt {
+        Some(nbt) => {
+            buf.push(1);
+            write_nbt(buf, &with_placeholder(nbt));
+        }
+        None => buf.push(0),
+    }
+}
+
+/// Returns `nbt` with the MAC of a marker command set to the placeholder.
+fn with_placeholder(nbt: &Value) -> Value {
+    let mut nbt = nbt.clone();
+    if let Value::Compound(compound) = &mut nbt {
+        if let Some(Value::String(command)) = compound.get_mut("Command") {
+            if let Some(placeholder) = with_mac(command, &"0".repeat(64)) {
+                *command = placeholder;
+            }
+        }
+    }
+    nbt
+}
+
+fn write_len(buf: &mut Vec<u8>, len: usize) {
+    buf.extend((len as u64).to_be_bytes());
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(buf, bytes.len());
+    buf.extend(bytes);
+}
+
+fn write_ints(buf: &mut Vec<u8>, ints: &[i32]) {
+    write_len(buf, ints.len());
+    for int in ints {
+        buf.extend(int.to_be_bytes());
+    }
+}
+
+/// Writes `value` with its type and compound keys in sorted order, so equal NBT always results in
+/// the same bytes.
+fn write_nbt(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Byte(value) => {
+            buf.push(1);
+            buf.extend(value.to_be_bytes());
+        }
+        Value::Short(value) => {
+            buf.push(2);
+            buf.extend(value.to_be_bytes());
+        }
+        Value::Int(value) => {
+            buf.push(3);
+            buf.extend(value.to_be_bytes());
+        }
+        Value::Long(value) => {
+            buf.push(4);
+            buf.extend(value.to_be_bytes());
+        }
+        Value::Float(value) => {
+            buf.push(5);
+            buf.extend(value.to_bits().to_be_bytes());
+        }
+        Value::Double(value) => {
+            buf.push(6);
+            buf.extend(value.to_bits().to_be_bytes());
+        }
+        Value::ByteArray(values) => {
+            buf.push(7);
+            write_len(buf, values.len());
+            buf.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        }
+        Value::String(value) => {
+            buf.push(8);
+            write_bytes(buf, value.as_bytes());
+        }
+        Value::List(values) => {
+            buf.push(9);
+            write_len(buf, values.len());
+            for value in values {
+                write_nbt(buf, value);
+            }
+        }
+        Value::Compound(compound) => {
+            buf.push(10);
+            let mut entries = compound.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(key, _)| *key);
+            write_len(buf, entries.len());
+            for (key, value) in entries {
+                write_bytes(buf, key.as_bytes());
+                write_nbt(buf, value);
+            }
+        }
+        Value::IntArray(values) => {
+            buf.push(11);
+            write_ints(buf, values);
+        }
+        Value::LongArray(values) => {
+            buf.push(12);
+            write_len(buf, values.len());
+            buf.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        }
+    }
+}
+
+fn compute_function_mac(key: &[u8], identifier: &str, id: u64, lines: &[String]) -> Hmac<Sha256> {
+    let mut mac = new_mac(key, FUNCTION_DOMAIN, identifier, id);
+    let mut buf = Vec::new();
+    write_len(&mut buf, lines.len());
+    for line in lines {
+        write_bytes(&mut buf, line.as_bytes());
+    }
+    mac.update(&buf);
+    mac
+}
+
+fn verify(key: &[u8], identifier: &str, id: u64, structure: &Structure) -> bool {
+    let mut macs = commands(structure).filter_map(get_mac);
+    match (macs.next().and_then(from_hex), macs.next()) {
+        (Some(mac), None) => compute_mac(key, identifier, id, structure)
+            .verify_slice(&mac)
+            .is_ok(),
+        _ => false,
+    }
+}
+
+/// Verifies that the structure file at `path` was written by a connection that
+/// [signs batches](crate::MinecraftConnectionBuilder::sign_batches) with the given `key` and was not
+/// modified afterwards. The connection identifier and the id of the batch are taken from the path
+/// (`generated/minect/structures/<identifier>/<id>.nbt`), so a structure file that was copied to
+/// another id or connection does not verify. Returns `false` if the path is not the path of a
+/// structure file of a connection, the structure file has no marker or the marker does not match
+/// the structure.
+///
+/// With [ExecutionStrategy::FunctionFile](crate::ExecutionStrategy::FunctionFile) the commands are
+/// stored in a function file, so only the command executing the function is verified. The function
+/// file is verified with [verify_function_file].
+///
+/// # Errors
+///
+/// This function will return an error if the file can't be read or is not a structure file.
+pub fn verify_structure_file(key: &[u8], path: impl AsRef<Path>) -> Result<bool, IoErrorAtPath> {
+    let path = path.as_ref();
+    let identifier = path
+        .parent()
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str());
+    let (identifier, id) = match (identifier, parse_structure_file(path)) {
+        (Some(identifier), Some(id)) => (identifier, id),
+        _ => return Ok(false),
+    };
+    let file = File::open(path).map_err(io_error("Failed to open file", path))?;
+    let structure = nbt::from_gzip_reader::<_, Structure>(BufReader::new(file))
+        .map_err(io_invalid_data)
+        .map_err(io_error("Failed to parse structure file", path))?;
+    Ok(verify(key, identifier, id, &structure))
+}
+
+/// Verifies that the function file at `path` was written by a connection that
+/// [signs batches](crate::MinecraftConnectionBuilder::sign_batches) with the given `key` and was not
+/// modified afterwards. Like with [verify_structure_file] the connection identifier and the id of
+/// the batch are taken from the path
+/// (`.../functions/connection/<identifier>/batch/<id>/run.mcfunction`). Returns `false` if the path
+/// is not the path of a batch function, the function has no MAC or the MAC does not match.
+///
+/// # Errors
+///
+/// This function will return an error if the file can't be read.
+pub fn verify_function_file(key: &[u8], path: impl AsRef<Path>) -> Result<bool, IoErrorAtPath> {
+    let path = path.as_ref();
+    let batch_dir = path.parent();
+    let id = batch_dir
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.parse().ok());
+    let identifier = batch_dir
+        .and_then(Path::parent)
+        .filter(|dir| dir.file_name() == Some("batch".as_ref()))
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str());
+    let (identifier, id) = match (identifier, id) {
+        (Some(identifier), Some(id)) => (identifier, id),
+        _ => return Ok(false),
+    };
+    let contents = read_to_string(path).map_err(io_error("Failed to read file", path))?;
+    Ok(verify_function(key, identifier, id, &contents))
+}
+
+fn verify_function(key: &[u8], identifier: &str, id: u64, contents: &str) -> bool {
+    let (first, rest) = contents.split_once('\n').unwrap_or((contents, ""));
+    let mac = match first
+        .strip_prefix(FUNCTION_MARKER_PREFIX)
+        .and_then(from_hex)
+    {
+        Some(mac) => mac,
+        None => return false,
+    };
+    let lines = rest.split('\n').map(str::to_string).collect::<Vec<_>>();
+    compute_function_mac(key, identifier, id, &lines)
+        .verify_slice(&mac)
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn command_block(command: impl Into<String>, x: i32) -> StructureBlock {
+        let nbt = HashMap::from_iter([("Command".to_string(), Value::String(command.into()))]);
+        StructureBlock {
+            state: 0,
+            pos: vec![x, 0, 0],
+            nbt: Some(Value::Compound(nbt)),
+        }
+    }
+
+    fn signed_structure(key: &[u8], identifier: &str, id: u64) -> Structure {
+        let [marker, verify_integrity] = BatchSigner::placeholder(identifier, id, id + 1);
+        let commands = [
+            marker.command,
+            verify_integrity.command,
+            "say hi".to_string(),
+        ];
+        let mut structure = Structure {
+            data_version: 0,
+            size: vec![3, 1, 1],
+            palette: Vec::new(),
+            blocks: (0..)
+                .zip(commands)
+                .map(|(x, command)| command_block(command, x))
+                .collect(),
+            entities: Vec::new(),
+        };
+        BatchSigner::new(key.to_vec()).sign(identifier, id, &mut structure);
+        structure
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        // when:
+        let structure = signed_structure(b"secret", "example", 5);
+
+        // then:
+        assert!(verify(b"secret", "example", 5, &structure));
+        assert!(!verify(b"other", "example", 5, &structure));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        // given:
+        let mut structure = signed_structure(b"secret", "example", 5);
+
+        // when:
+        structure.blocks[2] = command_block("op Herobrine", 2);
+
+        // then:
+        assert!(!verify(b"secret", "example", 5, &structure));
+    }
+
+    #[test]
+    fn test_verify_detects_replay() {
+        // when:
+        let structure = signed_structure(b"secret", "example", 5);
+
+        // then:
+        assert!(!verify(b"secret", "example", 6, &structure));
+        assert!(!verify(b"secret", "other", 5, &structure));
+    }
+
+    #[test]
+    fn test_verify_detects_rearranged_blocks() {
+        // given:
+        let mut structure = signed_structure(b"secret", "example", 5);
+
+        // when:
+        structure.blocks[2].pos = vec![2, 1, 0];
+
+        // then:
+        assert!(!verify(b"secret", "example", 5, &structure));
+    }
+
+    #[test]
+    fn test_verify_detects_changed_block_state() {
+        // given:
+        let mut structure = signed_structure(b"secret", "example", 5);
+
+        // when:
+        structure.blocks[2].state = 1;
+
+        // then:
+        assert!(!verify(b"secret", "example", 5, &structure));
+    }
+
+    #[test]
+    fn test_verify_function() {
+        // given:
+        let lines = vec!["say hi".to_string(), "say ho".to_string()];
+        let signer = BatchSigner::new(b"secret".to_vec());
+        let mac = signer.sign_function("example", 5, &lines);
+        let contents = format!("{}\n{}", mac, lines.join("\n"));
+        let tampered = format!("{}\nsay hi\nop Herobrine", mac);
+
+        // then:
+        assert!(verify_function(b"secret", "example", 5, &contents));
+        assert!(!verify_function(b"secret", "example", 6, &contents));
+        assert!(!verify_function(b"secret", "example", 5, &tampered));
+    }
+}
@@ -0,0 +1,182 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+    retry::UnacknowledgedBatches,
+    structure_id::{first_id_of_run, run_of},
+    warnings::{MinectWarning, Warnings},
+    Command,
+};
+use std::sync::{Mutex, RwLock};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+/// A batch that Minecraft did not acknowledge, although a later batch of the same connection was
+/// acknowledged. This usually means Minecraft considered the structure file invalid and skipped it.
+///
+/// These events can be received with
+/// [MinecraftConnection::add_batch_skipped_listener](crate::MinecraftConnection::add_batch_skipped_listener).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchSkipped {
+    /// The structure id of the skipped batch.
+    pub id: u64,
+    /// Whether the commands of the batch are executed again (see
+    /// [regenerate_skipped_batches](crate::MinecraftConnectionBuilder::regenerate_skipped_batches)).
+    pub regenerated: bool,
+    _private: (),
+}
+
+/// Detects skipped batches from the sequence of acknowledged structure ids.
+pub(crate) struct SkippedBatches {
+    regenerate: bool,
+    /// Only batches whose id is a multiple of this are expected to be acknowledged.
+    acknowledge_every: u64,
+    last_acknowledged: Mutex<Option<u64>>,
+    listeners: RwLock<Vec<UnboundedSender<BatchSkipped>>>,
+    pending: Mutex<Vec<Vec<Command>>>,
+}
+
+impl SkippedBatches {
+    pub(crate) fn new(regenerate: bool, acknowledge_every: u64) -> SkippedBatches {
+        SkippedBatches {
+            regenerate,
+            acknowledge_every,
+            last_acknowledged: Mutex::new(None),
+            listeners: RwLock::new(Vec::new()),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn add_listener(&self) -> impl Stream<Item = BatchSkipped> {
+        let (sender, receiver) = unbounded_channel();
+        self.listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// Must be called before the acknowledgement is passed to the [UnacknowledgedBatches], so the
+    /// commands of skipped batches can still be taken from them.
+    pub(crate) fn acknowledge(
+        &self,
+        id: u64,
+        unacknowledged: Option<&UnacknowledgedBatches>,
+        warnings: &Warnings,
+    ) {
+        let last = self.last_acknowledged.lock().unwrap().replace(id);
+        let last = match last {
+            Some(last) => last,
+            None => return,
+        };
+        // The first batch of a run follows the last batch of the previous run
+        let last = if run_of(id) != run_of(last) {
+            first_id_of_run(run_of(id)).wrapping_sub(1)
+        } else {
+            last
+        };
+        // Ids wrap around, a difference of more than half the range means an earlier id
+        let skipped = id.wrapping_sub(last).wrapping_sub(1);
+        if skipped as i64 <= 0 {
+            return;
+        }
+        let skipped_ids = (1..=skipped)
+            .map(|offset| last.wrapping_add(offset))
+            .filter(|skipped_id| skipped_id % self.acknowledge_every == 0);
+        for skipped_id in skipped_ids {
+            warnings.report(MinectWarning::StructureSkipped { id: skipped_id });
+            let commands = unacknowledged
+                .filter(|_| self.regenerate)
+                .and_then(|unacknowledged| unacknowledged.take(skipped_id));
+            let regenerated = commands.is_some();
+            if let Some(commands) = commands {
+                self.pending.lock().unwrap().push(commands);
+            }
+            let event = BatchSkipped {
+                id: skipped_id,
+                regenerated,
+                _private: (),
+            };
+            let mut listeners = self.listeners.write().unwrap();
+            listeners.retain(|sender| sender.send(event.clone()).is_ok());
+        }
+    }
+
+    /// Returns the commands of skipped batches that have to be executed again.
+    pub(crate) fn take_pending(&self) -> Vec<Vec<Command>> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_acknowledge_detects_skipped_batches() {
+        // given:
+        let skipped_batches = SkippedBatches::new(false, 1);
+        let events = skipped_batches.add_listener();
+        let warnings = Warnings::default();
+
+        // when:
+        skipped_batches.acknowledge(u64::MAX, None, &warnings);
+        skipped_batches.acknowledge(2, None, &warnings);
+        skipped_batches.acknowledge(1, None, &warnings);
+        drop(skipped_batches);
+
+        // then:
+        let actual = block_on(events.map(|event| event.id).collect::<Vec<_>>());
+        assert_eq!(actual, [0, 1]);
+    }
+
+    #[test]
+    fn test_acknowledge_ignores_batches_without_acknowledgement() {
+        // given:
+        let skipped_batches = SkippedBatches::new(false, 4);
+        let events = skipped_batches.add_listener();
+        let warnings = Warnings::default();
+
+        // when:
+        skipped_batches.acknowledge(4, None, &warnings);
+        skipped_batches.acknowledge(8, None, &warnings);
+        skipped_batches.acknowledge(16, None, &warnings);
+        drop(skipped_batches);
+
+        // then:
+        let actual = block_on(events.map(|event| event.id).collect::<Vec<_>>());
+        assert_eq!(actual, [12]);
+    }
+
+    #[test]
+    fn test_acknowledge_continues_at_first_batch_of_run() {
+        // given:
+        let skipped_batches = SkippedBatches::new(false, 1);
+        let events = skipped_batches.add_listener();
+        let warnings = Warnings::default();
+
+        // when:
+        skipped_batches.acknowledge(41, None, &warnings);
+        skipped_batches.acknowledge(42, None, &warnings);
+        skipped_batches.acknowledge(first_id_of_run(1) + 1, None, &warnings);
+        drop(skipped_batches);
+
+        // then:
+        let actual = block_on(events.map(|event| event.id).collect::<Vec<_>>());
+        assert_eq!(actual, [first_id_of_run(1)]);
+    }
+}
@@ -0,0 +1,372 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A minimal parser for SNBT (stringified NBT) as it is printed by commands like `data get`.
+
+use crate::json::escape_json;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Snbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    List(Vec<Snbt>),
+    ByteArray(Vec<i8>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+    Compound(Vec<(String, Snbt)>),
+}
+
+impl Snbt {
+    pub(crate) fn get(&self, key: &str) -> Option<&Snbt> {
+        match self {
+            Snbt::Compound(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Snbt::Byte(value) => Some(value.into()),
+            Snbt::Short(value) => Some(value.into()),
+            Snbt::Int(value) => Some(value.into()),
+            Snbt::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "connection"), allow(dead_code))]
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Snbt::Float(value) => Some(value.into()),
+            Snbt::Double(value) => Some(value),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Snbt::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Snbt]> {
+        match self {
+            Snbt::List(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Snbt {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let value = parser.parse_value().ok_or(())?;
+        parser.skip_whitespace();
+        if parser.pos == s.len() {
+            Ok(value)
+        } else {
+            Err(())
+        }
+    }
+}
+
+#[cfg(feature = "connection")]
+impl From<Snbt> for ::nbt::Value {
+    fn from(value: Snbt) -> ::nbt::Value {
+        use ::nbt::Value;
+        match value {
+            Snbt::Byte(value) => Value::Byte(value),
+            Snbt::Short(value) => Value::Short(value),
+            Snbt::Int(value) => Value::Int(value),
+            Snbt::Long(value) => Value::Long(value),
+            Snbt::Float(value) => Value::Float(value),
+            Snbt::Double(value) => Value::Double(value),
+            Snbt::String(value) => Value::String(value),
+            Snbt::List(values) => Value::List(values.into_iter().map(Value::from).collect()),
+            Snbt::ByteArray(values) => Value::ByteArray(values),
+            Snbt::IntArray(values) => Value::IntArray(values),
+            Snbt::LongArray(values) => Value::LongArray(values),
+            Snbt::Compound(entries) => Value::Compound(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+struct Parser<'s> {
+    input: &'s str,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.skip_whitespace();
+        if self.peek()? == expected {
+            self.pos += expected.len_utf8();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn try_consume(&mut self, expected: &str) -> bool {
+        if self.input[self.pos..].starts_with(expected) {
+            self.pos += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self) -> Option<Snbt> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => self.parse_compound(),
+            '[' => self.parse_list_or_array(),
+            '"' | '\'' => self.parse_quoted_string().map(Snbt::String),
+            _ => Some(parse_unquoted_value(self.parse_unquoted_string()?)),
+        }
+    }
+
+    fn parse_compound(&mut self) -> Option<Snbt> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.try_consume("}") {
+            return Some(Snbt::Compound(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = match self.peek()? {
+                '"' | '\'' => self.parse_quoted_string()?,
+                _ => self.parse_unquoted_string()?.to_string(),
+            };
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            entries.push((key, value));
+            self.skip_whitespace();
+            if !self.try_consume(",") {
+                self.expect('}')?;
+                return Some(Snbt::Compound(entries));
+            }
+        }
+    }
+
+    fn parse_list_or_array(&mut self) -> Option<Snbt> {
+        self.expect('[')?;
+        if self.try_consume("B;") {
+            let values = self.parse_elements(|v| v.as_i64().and_then(|v| v.try_into().ok()))?;
+            Some(Snbt::ByteArray(values))
+        } else if self.try_consume("I;") {
+            let values = self.parse_elements(|v| v.as_i64().and_then(|v| v.try_into().ok()))?;
+            Some(Snbt::IntArray(values))
+        } else if self.try_consume("L;") {
+            let values = self.parse_elements(|v| v.as_i64())?;
+            Some(Snbt::LongArray(values))
+        } else {
+            Some(Snbt::List(self.parse_elements(Some)?))
+        }
+    }
+
+    fn parse_elements<T>(&mut self, convert: impl Fn(Snbt) -> Option<T>) -> Option<Vec<T>> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.try_consume("]") {
+            return Some(values);
+        }
+        loop {
+            values.push(convert(self.parse_value()?)?);
+            self.skip_whitespace();
+            if !self.try_consume(",") {
+                self.expect(']')?;
+                return Some(values);
+            }
+        }
+    }
+
+    fn parse_quoted_string(&mut self) -> Option<String> {
+        let quote = self.peek()?;
+        self.pos += quote.len_utf8();
+        let mut string = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((index, c)) = chars.next() {
+            if c == '\\' {
+                let (_, escaped) = chars.next()?;
+                string.push(escaped);
+            } else if c == quote {
+                self.pos += index + c.len_utf8();
+                return Some(string);
+            } else {
+                string.push(c);
+            }
+        }
+        None
+    }
+
+    fn parse_unquoted_string(&mut self) -> Option<&'s str> {
+        let start = self.pos;
+        while let Some(c) = self.peek().filter(|c| is_allowed_in_unquoted_string(*c)) {
+            self.pos += c.len_utf8();
+        }
+        if self.pos == start {
+            None
+        } else {
+            Some(&self.input[start..self.pos])
+        }
+    }
+}
+
+fn is_allowed_in_unquoted_string(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+}
+
+fn parse_unquoted_value(token: &str) -> Snbt {
+    fn parse_number(token: &str) -> Option<Snbt> {
+        let (number, suffix) = token.split_at(token.len() - 1);
+        match suffix {
+            "b" | "B" => number.parse().ok().map(Snbt::Byte),
+            "s" | "S" => number.parse().ok().map(Snbt::Short),
+            "l" | "L" => number.parse().ok().map(Snbt::Long),
+            "f" | "F" => number.parse().ok().map(Snbt::Float),
+            "d" | "D" => number.parse().ok().map(Snbt::Double),
+            _ => token
+                .parse()
+                .ok()
+                .map(Snbt::Int)
+                .or_else(|| token.parse().ok().map(Snbt::Double)),
+        }
+    }
+    match token {
+        "true" => Snbt::Byte(1),
+        "false" => Snbt::Byte(0),
+        _ => parse_number(token).unwrap_or_else(|| Snbt::String(token.to_string())),
+    }
+}
+
+impl Display for Snbt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_joined<T: Display>(
+            f: &mut fmt::Formatter<'_>,
+            prefix: &str,
+            values: &[T],
+            suffix: &str,
+        ) -> fmt::Result {
+            f.write_str(prefix)?;
+            for (index, value) in values.iter().enumerate() {
+                if index != 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}{}", value, suffix)?;
+            }
+            f.write_str("]")
+        }
+        match self {
+            Snbt::Byte(value) => write!(f, "{}b", value),
+            Snbt::Short(value) => write!(f, "{}s", value),
+            Snbt::Int(value) => write!(f, "{}", value),
+            Snbt::Long(value) => write!(f, "{}L", value),
+            Snbt::Float(value) => write!(f, "{}f", value),
+            Snbt::Double(value) => write!(f, "{}d", value),
+            Snbt::String(value) => write!(f, "\"{}\"", escape_json(value)),
+            Snbt::List(values) => write_joined(f, "[", values, ""),
+            Snbt::ByteArray(values) => write_joined(f, "[B;", values, "B"),
+            Snbt::IntArray(values) => write_joined(f, "[I;", values, ""),
+            Snbt::LongArray(values) => write_joined(f, "[L;", values, "L"),
+            Snbt::Compound(entries) => {
+                f.write_str("{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index != 0 {
+                        f.write_str(",")?;
+                    }
+                    write_key(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_key(f: &mut impl fmt::Write, key: &str) -> fmt::Result {
+    if !key.is_empty() && key.chars().all(is_allowed_in_unquoted_string) {
+        f.write_str(key)
+    } else {
+        write!(f, "\"{}\"", escape_json(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_to_string() {
+        // given:
+        let string = r#"[{Slot: 0b, id: "minecraft:stone", Count: 64b}, {Slot: 1b, id: "minecraft:diamond_sword", Count: 1b, tag: {Damage: 0, display: {Name: '{"text":"Sword"}'}}}]"#;
+
+        // when:
+        let actual = string.parse::<Snbt>().unwrap();
+
+        // then:
+        assert_eq!(
+            actual.to_string(),
+            r#"[{Slot:0b,id:"minecraft:stone",Count:64b},{Slot:1b,id:"minecraft:diamond_sword",Count:1b,tag:{Damage:0,display:{Name:"{\"text\":\"Sword\"}"}}}]"#
+        );
+    }
+
+    #[test]
+    fn test_arrays() {
+        // given:
+        let string = "{UUID: [I; 1, -2, 3, 4], Pos: [0.5d, 64.0d, -0.5d]}";
+
+        // when:
+        let actual = string.parse::<Snbt>().unwrap();
+
+        // then:
+        assert_eq!(actual.get("UUID"), Some(&Snbt::IntArray(vec![1, -2, 3, 4])));
+        assert_eq!(
+            actual.get("Pos").and_then(Snbt::as_list),
+            Some(&[Snbt::Double(0.5), Snbt::Double(64.0), Snbt::Double(-0.5)][..])
+        );
+    }
+}
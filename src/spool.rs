@@ -0,0 +1,299 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A write-ahead queue of batches on disk, see
+//! [spool_dir](crate::MinecraftConnectionBuilder::spool_dir).
+//!
+//! Each batch is stored in a file named `<id>.batch`. The first line of the file contains the
+//! [ExecutionStrategy] and every following line contains a command as a JSON array of its name
+//! (or `null`) and the command itself.
+
+#[cfg(feature = "encryption")]
+use crate::encryption::{is_encrypted, SpoolKey};
+use crate::{
+    file_backend::FileBackend,
+    io::{create_dir_all, io_error, remove_file, rename, write, IoErrorAtPath},
+    lock_file, read_incremented_id,
+    structure_id::parse_structure_file,
+    utils::io_invalid_data,
+    write_id, ChainPlacement, Command, ExecutionStrategy,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read, read_dir, File},
+    path::{Path, PathBuf},
+};
+
+const BATCH_EXTENSION: &str = "batch";
+const FAILED_EXTENSION: &str = "failed";
+
+#[derive(Clone, Debug)]
+pub(crate) struct Spool {
+    dir: PathBuf,
+    max_pending_structures: usize,
+    #[cfg(feature = "encryption")]
+    key: Option<SpoolKey>,
+}
+
+impl Spool {
+    pub(crate) fn new(dir: PathBuf, max_pending_structures: usize) -> Spool {
+        Spool {
+            dir,
+            max_pending_structures,
+            #[cfg(feature = "encryption")]
+            key: None,
+        }
+    }
+
+    /// Encrypts all batches that are appended afterwards with the given `key`.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn encrypted(mut self, key: Option<SpoolKey>) -> Spool {
+        self.key = key;
+        self
+    }
+
+    pub(crate) fn max_pending_structures(&self) -> usize {
+        self.max_pending_structures
+    }
+
+    /// Locks the spool to prevent multiple processes from writing the same batch. The lock is
+    /// released when the returned [File] is dropped.
+    pub(crate) fn lock(&self) -> Result<File, IoErrorAtPath> {
+        create_dir_all(&self.dir)?;
+        lock_file(self.dir.join("lock"))
+    }
+
+    /// Appends a batch with the given `commands` to the end of the queue.
+    pub(crate) fn append(
+        &self,
+        strategy: ExecutionStrategy,
+        commands: &[Command],
+    ) -> Result<(), IoErrorAtPath> {
+        create_dir_all(&self.dir)?;
+        let id_path = self.dir.join("id.txt");
+        let mut id_file = lock_file(&id_path)?; // Automatically unlocked by dropping id_file at the end of this function.
+        let id = read_incremented_id(&mut id_file, &id_path)?;
+
+        let mut content = strategy_to_str(strategy).to_string();
+        for command in commands {
+            content.push('\n');
+            let entry = (command.get_name(), command.get_command());
+            let flags = CommandFlags {
+                new_chain: command.chain == ChainPlacement::New,
+                conditional: command.conditional,
+            };
+            let entry = if flags == CommandFlags::default() {
+                serde_json::to_string(&entry)
+            } else {
+                serde_json::to_string(&(entry.0, entry.1, flags))
+            };
+            content.push_str(&entry.unwrap());
+        }
+
+        // Like structure files, batches are written to a temporary file first, so a batch is never
+        // read partially.
+        #[cfg(feature = "encryption")]
+        let content = match &self.key {
+            Some(key) => key.encrypt(content.as_bytes()),
+            None => content.into_bytes(),
+        };
+        let tmp_path = self.dir.join("tmp");
+        write(&tmp_path, &content)?;
+        rename(tmp_path, self.batch_file(id))?;
+
+        write_id(&mut id_file, &id_path, id)
+    }
+
+    /// Returns the ids of all batches in the queue in the order they were appended.
+    pub(crate) fn batches(&self) -> Result<Vec<u64>, IoErrorAtPath> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let entries =
+            read_dir(&self.dir).map_err(io_error("Failed to read directory", &self.dir))?;
+        let mut ids = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| parse_id(&entry.path(), BATCH_EXTENSION))
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    pub(crate) fn read(&self, id: u64) -> Result<(ExecutionStrategy, Vec<Command>), IoErrorAtPath> {
+        let path = self.batch_file(id);
+        let content = read(&path).map_err(io_error("Failed to read file", &path))?;
+        #[cfg(feature = "encryption")]
+        let content = match &self.key {
+            Some(key) if is_encrypted(&content) => key
+                .decrypt(&content)
+                .ok_or_else(|| io_invalid_data("Failed to decrypt batch"))
+                .map_err(io_error("Failed to parse spooled batch", &path))?,
+            _ => content,
+        };
+        String::from_utf8(content)
+            .ok()
+            .and_then(|content| parse_batch(&content))
+            .ok_or_else(|| io_invalid_data("Invalid batch"))
+            .map_err(io_error("Failed to parse spooled batch", &path))
+    }
+
+    pub(crate) fn remove(&self, id: u64) -> Result<(), IoErrorAtPath> {
+        remove_file(self.batch_file(id))
+    }
+
+    /// Moves the batch with the given `id` out of the queue, so a batch that can never be written
+    /// does not block the batches after it. Returns the new path of the batch.
+    pub(crate) fn quarantine(&self, id: u64) -> Result<PathBuf, IoErrorAtPath> {
+        let path = self.dir.join(format!("{}.{}", id, FAILED_EXTENSION));
+        rename(self.batch_file(id), &path)?;
+        Ok(path)
+    }
+
+    fn batch_file(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, BATCH_EXTENSION))
+    }
+}
+
+fn parse_batch(content: &str) -> Option<(ExecutionStrategy, Vec<Command>)> {
+    let mut lines = content.lines();
+    let strategy = strategy_from_str(lines.next()?)?;
+    let commands = lines
+        .map(|line| {
+            let (name, command, flags) =
+                serde_json::from_str::<(Option<String>, String, CommandFlags)>(line)
+                    .or_else(|_| {
+                        serde_json::from_str::<(Option<String>, String)>(line)
+                            .map(|(name, command)| (name, command, CommandFlags::default()))
+                    })
+                    .ok()?;
+            let mut command = match name {
+                Some(name) => Command::named(name, command),
+                None => Command::new(command),
+            };
+            if flags.new_chain {
+                command.chain = ChainPlacement::New;
+            }
+            command.conditional = flags.conditional;
+            Some(command)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((strategy, commands))
+}
+
+/// The placement of a spooled [Command], which is only written if it differs from the default.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+struct CommandFlags {
+    #[serde(default)]
+    new_chain: bool,
+    #[serde(default)]
+    conditional: bool,
+}
+
+fn strategy_to_str(strategy: ExecutionStrategy) -> &'static str {
+    match strategy {
+        ExecutionStrategy::Automatic => "Automatic",
+        ExecutionStrategy::CommandBlocks => "CommandBlocks",
+        ExecutionStrategy::FunctionFile => "FunctionFile",
+        ExecutionStrategy::Minecart => "Minecart",
+    }
+}
+
+fn strategy_from_str(string: &str) -> Option<ExecutionStrategy> {
+    match string {
+        "Automatic" => Some(ExecutionStrategy::Automatic),
+        "CommandBlocks" => Some(ExecutionStrategy::CommandBlocks),
+        "FunctionFile" => Some(ExecutionStrategy::FunctionFile),
+        "Minecart" => Some(ExecutionStrategy::Minecart),
+        _ => None,
+    }
+}
+
+/// Returns the number of structure files in `structures_dir` that were not yet loaded by Minecraft.
+pub(crate) fn count_pending_structures(backend: &dyn FileBackend, structures_dir: &Path) -> usize {
+    backend
+        .read_dir(structures_dir)
+        .map(|paths| {
+            paths
+                .iter()
+                .filter(|path| parse_structure_file(path).is_some())
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+fn parse_id(path: &Path, extension: &str) -> Option<u64> {
+    if path.extension()? != extension {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_backend::MemoryFileBackend;
+
+    #[test]
+    fn test_parse_batch() {
+        // given:
+        let content = "FunctionFile\n[null,\"say hi\"]\n[\"name\",\"say \\\"named\\\"\"]";
+
+        // when:
+        let (strategy, commands) = parse_batch(content).unwrap();
+
+        // then:
+        assert_eq!(strategy, ExecutionStrategy::FunctionFile);
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].get_name(), None);
+        assert_eq!(commands[0].get_command(), "say hi");
+        assert_eq!(commands[1].get_name(), Some("name"));
+        assert_eq!(commands[1].get_command(), "say \"named\"");
+    }
+
+    #[test]
+    fn test_parse_batch_with_flags() {
+        // given:
+        let content = "CommandBlocks\n[null,\"say first\"]\n[null,\"say second\",{\"new_chain\":true}]\n[null,\"say third\",{\"conditional\":true}]";
+
+        // when:
+        let (_, commands) = parse_batch(content).unwrap();
+
+        // then:
+        assert_eq!(commands[0].chain, ChainPlacement::Continue);
+        assert_eq!(commands[1].chain, ChainPlacement::New);
+        assert!(!commands[1].conditional);
+        assert!(commands[2].conditional);
+    }
+
+    #[test]
+    fn test_count_pending_structures_of_file_backend() {
+        // given:
+        let backend = MemoryFileBackend::default();
+        let structures_dir = Path::new("generated/minect/structures/test");
+        backend.create_dir_all(structures_dir).unwrap();
+        backend.write(&structures_dir.join("1.nbt"), b"").unwrap();
+        backend.write(&structures_dir.join("2.nbt"), b"").unwrap();
+        backend.write(&structures_dir.join("3.tmp"), b"").unwrap();
+
+        // when:
+        let actual = count_pending_structures(&backend, structures_dir);
+
+        // then:
+        assert_eq!(actual, 2);
+    }
+}
@@ -0,0 +1,157 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Combinators for [Stream]s of [LogEvent]s, as returned by
+//! [MinecraftConnection::add_listener](crate::MinecraftConnection::add_listener) and
+//! [MinecraftConnection::add_named_listener](crate::MinecraftConnection::add_named_listener).
+//!
+//! ```no_run
+//! use minect::{command::SummonNamedEntityOutput, stream::parse_outputs, MinecraftConnection};
+//! use tokio_stream::StreamExt;
+//!
+//! # async fn example(connection: &mut MinecraftConnection) {
+//! let events = connection.add_listener();
+//! let mut summoned = Box::pin(parse_outputs::<SummonNamedEntityOutput>(events));
+//! while let Some(output) = summoned.next().await {
+//!     println!("Summoned {}", output.name);
+//! }
+//! # }
+//! ```
+
+use crate::{command::SummonNamedEntityOutput, log::LogEvent};
+use futures::{Stream, StreamExt};
+use std::{str::FromStr, time::Duration};
+use tokio::time::{error::Elapsed, timeout};
+
+/// Parses the [output](LogEvent::output) of every [LogEvent] as `T` and skips all [LogEvent]s that
+/// can't be parsed.
+pub fn parse_outputs<T: FromStr>(events: impl Stream<Item = LogEvent>) -> impl Stream<Item = T> {
+    events.filter_map(|event| async move { event.output.parse().ok() })
+}
+
+/// Returns the first [LogEvent] whose [executor](LogEvent::executor) is `name` or [None] if the
+/// stream ends before.
+pub async fn first_matching(
+    events: impl Stream<Item = LogEvent>,
+    name: impl AsRef<str>,
+) -> Option<LogEvent> {
+    let name = name.as_ref();
+    let mut events = Box::pin(events.filter(|event| {
+        let matches = event.executor == name;
+        async move { matches }
+    }));
+    events.next().await
+}
+
+/// Limits the time to wait for each [LogEvent]. If no [LogEvent] is received within `duration`
+/// after the previous one, an [Elapsed] error is yielded and the timer starts again. The stream ends
+/// when the underlying stream ends.
+pub fn timeout_each(
+    events: impl Stream<Item = LogEvent>,
+    duration: Duration,
+) -> impl Stream<Item = Result<LogEvent, Elapsed>> {
+    futures::stream::unfold(Box::pin(events), move |mut events| async move {
+        match timeout(duration, events.next()).await {
+            Ok(Some(event)) => Some((Ok(event), events)),
+            Ok(None) => None,
+            Err(elapsed) => Some((Err(elapsed), events)),
+        }
+    })
+}
+
+/// Groups [LogEvent]s into batches that are terminated by a marker. A marker is a [LogEvent] whose
+/// output is a [SummonNamedEntityOutput] with the name `marker` (see
+/// [summon_named_entity_command](crate::command::summon_named_entity_command)). Each batch contains
+/// all [LogEvent]s since the previous marker, excluding the markers themselves.
+///
+/// Because commands are executed in order, appending a marker command to a list of commands makes
+/// it possible to know when all of their output was received. [LogEvent]s after the last marker are
+/// discarded when the underlying stream ends.
+pub fn batched_by_marker(
+    events: impl Stream<Item = LogEvent>,
+    marker: impl Into<String>,
+) -> impl Stream<Item = Vec<LogEvent>> {
+    let marker = marker.into();
+    futures::stream::unfold(Box::pin(events), move |mut events| {
+        let marker = marker.clone();
+        async move {
+            let mut batch = Vec::new();
+            while let Some(event) = events.next().await {
+                match event.output.parse::<SummonNamedEntityOutput>() {
+                    Ok(output) if output.name == marker => return Some((batch, events)),
+                    _ => batch.push(event),
+                }
+            }
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, stream::iter};
+
+    fn event(executor: &str, output: &str) -> LogEvent {
+        format!(
+            "[21:05:40] [Server thread/INFO]: [{}: {}]",
+            executor, output
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_first_matching() {
+        // given:
+        let events = iter([
+            event("a", "first"),
+            event("b", "second"),
+            event("b", "third"),
+        ]);
+
+        // when:
+        let actual = block_on(first_matching(events, "b"));
+
+        // then:
+        assert_eq!(actual.unwrap().output, "second");
+    }
+
+    #[test]
+    fn test_batched_by_marker() {
+        // given:
+        let events = iter([
+            event("test", "first"),
+            event("test", "Summoned new done"),
+            event("test", "second"),
+            event("test", "third"),
+            event("test", "Summoned new done"),
+            event("test", "incomplete"),
+        ]);
+
+        // when:
+        let actual = block_on(batched_by_marker(events, "done").collect::<Vec<_>>());
+
+        // then:
+        let outputs = actual
+            .iter()
+            .map(|batch| batch.iter().map(|e| e.output.as_str()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, [vec!["first"], vec!["second", "third"]]);
+    }
+}
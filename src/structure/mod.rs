@@ -19,7 +19,8 @@
 pub(crate) mod nbt;
 
 use crate::{
-    geometry3::{Coordinate3, Direction3},
+    command::MINECART_TAGS,
+    geometry::{Coordinate3, Direction3},
     structure::nbt::{PaletteBlock, Structure, StructureBlock, StructureEntity},
 };
 use ::nbt::Value;
@@ -31,16 +32,19 @@ use std::{
 pub(crate) struct StructureBuilder {
     size: Coordinate3<i32>,
     palette: Vec<PaletteBlock>,
+    palette_indices: HashMap<PaletteBlock, i32>,
     blocks: Vec<StructureBlock>,
     entities: Vec<StructureEntity>,
 }
 
 impl StructureBuilder {
-    pub(crate) fn new() -> StructureBuilder {
+    /// Creates a [StructureBuilder] with enough capacity for the given number of blocks.
+    pub(crate) fn with_capacity(blocks: usize) -> StructureBuilder {
         StructureBuilder {
             size: Coordinate3(0, 0, 0),
             palette: Vec::new(),
-            blocks: Vec::new(),
+            palette_indices: HashMap::new(),
+            blocks: Vec::with_capacity(blocks),
             entities: Vec::new(),
         }
     }
@@ -53,14 +57,16 @@ impl StructureBuilder {
             nbt,
         } = block;
         let palette_block = PaletteBlock { name, properties };
-        let index = if let Some(index) = self.palette.iter().position(|it| *it == palette_block) {
-            index
-        } else {
-            self.palette.push(palette_block);
-            self.palette.len() - 1
-        };
+        let palette = &mut self.palette;
+        let index = *self
+            .palette_indices
+            .entry(palette_block)
+            .or_insert_with_key(|palette_block| {
+                palette.push(palette_block.clone());
+                palette.len() as i32 - 1
+            });
         let block = StructureBlock {
-            state: index as i32,
+            state: index,
             pos: pos.into(),
             nbt,
         };
@@ -68,7 +74,6 @@ impl StructureBuilder {
         self.size = Coordinate3::max(self.size, pos + Coordinate3(1, 1, 1));
     }
 
-    #[allow(dead_code)]
     pub(crate) fn add_entity(&mut self, entity: Entity) {
         let pos: Vec<f64> = entity.pos.into();
         let block_pos = pos.iter().map(|it| *it as i32).collect();
@@ -161,3 +166,34 @@ pub(crate) struct Entity {
     pub(crate) pos: Coordinate3<f64>,
     pub(crate) nbt: Value,
 }
+
+/// Creates a command block minecart that executes `command` once and is then removed by the Minect
+/// datapack, like the minecarts summoned by
+/// [logged_cart_command](crate::command::logged_cart_command).
+pub(crate) fn new_command_block_minecart(
+    name: Option<String>,
+    command: String,
+    pos: Coordinate3<f64>,
+) -> Entity {
+    let tags = MINECART_TAGS
+        .iter()
+        .map(|tag| Value::String(tag.to_string()))
+        .collect();
+    let mut nbt = HashMap::from_iter([
+        (
+            "id".to_string(),
+            Value::String("minecraft:command_block_minecart".to_string()),
+        ),
+        ("Command".to_string(), Value::String(command)),
+        ("Tags".to_string(), Value::List(tags)),
+        ("LastExecution".to_string(), Value::Long(1)),
+        ("TrackOutput".to_string(), Value::Byte(0)),
+    ]);
+    if let Some(name) = name {
+        nbt.insert("CustomName".to_string(), Value::String(name));
+    }
+    Entity {
+        pos,
+        nbt: Value::Compound(nbt),
+    }
+}
@@ -30,7 +30,7 @@ pub(crate) struct Structure {
     pub(crate) entities: Vec<StructureEntity>,
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub(crate) struct PaletteBlock {
     #[serde(rename = "Name")]
     pub(crate) name: String,
@@ -0,0 +1,105 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Structure ids are consecutive [u64]s that are shared by all runs of a program, because every
+//! structure loads the next one. If
+//! [namespace_ids_per_run](crate::MinecraftConnectionBuilder::namespace_ids_per_run) is enabled,
+//! the upper 32 bits of an id hold the number of the run that wrote it and the lower 32 bits count
+//! the structures of that run. Such ids are formatted as `run-<run>-<counter>`, so
+//! structure files of different runs can be told apart.
+
+use std::path::{Path, PathBuf};
+
+const RUN_SHIFT: u32 = 32;
+const RUN_PREFIX: &str = "run-";
+
+/// Returns the run that wrote the structure with the given `id` or `0` if ids are not namespaced.
+pub(crate) fn run_of(id: u64) -> u32 {
+    (id >> RUN_SHIFT) as u32
+}
+
+/// Returns the id of the first structure of the given `run`.
+pub(crate) fn first_id_of_run(run: u32) -> u64 {
+    (run as u64) << RUN_SHIFT
+}
+
+/// Returns the run that follows the one that wrote the structure with the given `id`.
+pub(crate) fn next_run(id: u64) -> u32 {
+    run_of(id).checked_add(1).unwrap_or(1)
+}
+
+pub(crate) fn format_structure_id(id: u64) -> String {
+    match run_of(id) {
+        0 => id.to_string(),
+        run => format!("{}{}-{}", RUN_PREFIX, run, id as u32),
+    }
+}
+
+pub(crate) fn parse_structure_id(string: &str) -> Option<u64> {
+    match string.strip_prefix(RUN_PREFIX) {
+        Some(namespaced) => {
+            let (run, counter) = namespaced.split_once('-')?;
+            let run = run.parse::<u32>().ok().filter(|run| *run != 0)?;
+            Some(first_id_of_run(run) | counter.parse::<u32>().ok()? as u64)
+        }
+        None => string.parse().ok(),
+    }
+}
+
+pub(crate) fn structure_file(structures_dir: &Path, id: u64) -> PathBuf {
+    structures_dir.join(format!("{}.nbt", format_structure_id(id)))
+}
+
+/// Parses the id of a structure file, returns [None] for other files.
+pub(crate) fn parse_structure_file(path: &Path) -> Option<u64> {
+    if path.extension()? != "nbt" {
+        return None;
+    }
+    parse_structure_id(path.file_stem()?.to_str()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse_structure_id() {
+        // given:
+        let plain = 42;
+        let namespaced = first_id_of_run(3) + 7;
+
+        // when:
+        let plain_string = format_structure_id(plain);
+        let namespaced_string = format_structure_id(namespaced);
+
+        // then:
+        assert_eq!(plain_string, "42");
+        assert_eq!(namespaced_string, "run-3-7");
+        assert_eq!(parse_structure_id(&plain_string), Some(plain));
+        assert_eq!(parse_structure_id(&namespaced_string), Some(namespaced));
+        assert_eq!(parse_structure_id("run-0-7"), None);
+        assert_eq!(parse_structure_id("run-3"), None);
+    }
+
+    #[test]
+    fn test_next_run() {
+        assert_eq!(next_run(41), 1);
+        assert_eq!(next_run(first_id_of_run(3) + 7), 4);
+        assert_eq!(next_run(first_id_of_run(u32::MAX)), 1);
+    }
+}
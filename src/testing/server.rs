@@ -0,0 +1,364 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Runs a vanilla Minecraft server for integration tests.
+//!
+//! ```no_run
+//! use minect::{testing::server::TestServer, Command};
+//!
+//! let server = TestServer::builder("1.20.1").start()?;
+//! let mut connection = server.connection_builder("test").build();
+//! connection.execute_commands([Command::new("reload")])?;
+//! // ...
+//! server.stop()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::{
+    utils::io_invalid_data, MinecraftConnection, MinecraftConnectionBuilder, MinecraftVersion,
+};
+use log::{debug, warn};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const VERSION_MANIFEST_URL: &str =
+    "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+const WORLD_NAME: &str = "world";
+
+/// A builder to start a [TestServer] is obtained via [TestServer::builder].
+pub struct TestServerBuilder {
+    version: String,
+    dir: Option<PathBuf>,
+    cache_dir: PathBuf,
+    java: PathBuf,
+    properties: BTreeMap<String, String>,
+    startup_timeout: Duration,
+}
+
+impl TestServerBuilder {
+    fn new(version: String) -> TestServerBuilder {
+        let properties = [
+            ("enable-command-block", "true"),
+            ("gamemode", "creative"),
+            ("generate-structures", "false"),
+            ("level-name", WORLD_NAME),
+            ("level-type", "flat"),
+            ("online-mode", "false"),
+            ("spawn-protection", "0"),
+        ];
+        TestServerBuilder {
+            version,
+            dir: None,
+            cache_dir: std::env::temp_dir().join("minect_server_cache"),
+            java: PathBuf::from("java"),
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            startup_timeout: Duration::from_secs(300),
+        }
+    }
+
+    /// The directory in which the server is run. The directory is not removed after the server is
+    /// stopped.
+    ///
+    /// Default: A new directory in the temporary directory of the operating system that is removed
+    /// after the server is stopped.
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> TestServerBuilder {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// The directory in which downloaded server jars are kept, so they are only downloaded once.
+    ///
+    /// Default: `minect_server_cache` in the temporary directory of the operating system.
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> TestServerBuilder {
+        self.cache_dir = cache_dir.into();
+        self
+    }
+
+    /// The Java executable that is used to run the server.
+    ///
+    /// Default: `java`.
+    pub fn java(mut self, java: impl Into<PathBuf>) -> TestServerBuilder {
+        self.java = java.into();
+        self
+    }
+
+    /// Sets a property in the `server.properties` file. By default command blocks are enabled, the
+    /// world is flat without structures and the server runs in offline mode on a free port.
+    pub fn property(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> TestServerBuilder {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// How long to wait for the server to start.
+    ///
+    /// Default: 5 minutes.
+    pub fn startup_timeout(mut self, startup_timeout: Duration) -> TestServerBuilder {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Downloads the server if needed, starts it and waits until it is ready. This blocks the
+    /// current thread, so in async code it should be called via `spawn_blocking` or similar.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version does not exist, the download fails, the
+    /// server can't be started or it does not start within the
+    /// [startup_timeout](Self::startup_timeout).
+    pub fn start(mut self) -> io::Result<TestServer> {
+        let jar = self.download_jar()?;
+        let (dir, remove_dir) = match self.dir.take() {
+            Some(dir) => (dir, false),
+            None => (temp_server_dir(), true),
+        };
+        fs::create_dir_all(&dir)?;
+        fs::copy(&jar, dir.join("server.jar"))?;
+        fs::write(dir.join("eula.txt"), "eula=true\n")?;
+        if !self.properties.contains_key("server-port") {
+            let port = free_port()?.to_string();
+            self.properties.insert("server-port".to_string(), port);
+        }
+        let properties = self
+            .properties
+            .iter()
+            .map(|(key, value)| format!("{}={}\n", key, value))
+            .collect::<String>();
+        fs::write(dir.join("server.properties"), properties)?;
+
+        let log_file = dir.join("logs").join("latest.log");
+        let _ = fs::remove_file(&log_file);
+        let process = Command::new(&self.java)
+            .args(["-jar", "server.jar", "nogui"])
+            .current_dir(&dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let mut server = TestServer {
+            world_dir: dir.join(WORLD_NAME),
+            log_file,
+            dir,
+            version: self.version,
+            process: Some(process),
+            remove_dir,
+        };
+        server.wait_until_ready(self.startup_timeout)?;
+        Ok(server)
+    }
+
+    fn download_jar(&self) -> io::Result<PathBuf> {
+        let jar = self.cache_dir.join(format!("server-{}.jar", self.version));
+        if jar.is_file() {
+            return Ok(jar);
+        }
+        fs::create_dir_all(&self.cache_dir)?;
+        let manifest = get_json(VERSION_MANIFEST_URL)?;
+        let version_url = manifest["versions"]
+            .as_array()
+            .and_then(|versions| {
+                versions
+                    .iter()
+                    .find(|version| version["id"] == self.version.as_str())
+            })
+            .and_then(|version| version["url"].as_str())
+            .ok_or_else(|| {
+                let message = format!("Unknown Minecraft version {}", self.version);
+                io::Error::new(io::ErrorKind::NotFound, message)
+            })?;
+        let version = get_json(version_url)?;
+        let server_url = version["downloads"]["server"]["url"]
+            .as_str()
+            .ok_or_else(|| {
+                let message = format!("No server available for Minecraft {}", self.version);
+                io::Error::new(io::ErrorKind::NotFound, message)
+            })?;
+        debug!("Downloading {}", server_url);
+        let tmp_path = jar.with_extension("jar.tmp");
+        let mut response = ureq::get(server_url).call().map_err(io::Error::other)?;
+        io::copy(
+            &mut response.body_mut().as_reader(),
+            &mut File::create(&tmp_path)?,
+        )?;
+        fs::rename(tmp_path, &jar)?;
+        Ok(jar)
+    }
+}
+
+/// A running Minecraft server for integration tests. The server is stopped when this is dropped,
+/// but [stop](Self::stop) should be preferred to shut it down gracefully.
+pub struct TestServer {
+    dir: PathBuf,
+    world_dir: PathBuf,
+    log_file: PathBuf,
+    version: String,
+    process: Option<Child>,
+    remove_dir: bool,
+}
+
+impl TestServer {
+    /// Creates a [TestServerBuilder] for the given Minecraft version, for example `"1.20.1"`.
+    pub fn builder(version: impl Into<String>) -> TestServerBuilder {
+        TestServerBuilder::new(version.into())
+    }
+
+    /// The directory in which the server runs.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The world directory of the server.
+    pub fn world_dir(&self) -> &Path {
+        &self.world_dir
+    }
+
+    /// The log file of the server.
+    pub fn log_file(&self) -> &Path {
+        &self.log_file
+    }
+
+    /// Creates a [MinecraftConnectionBuilder] that is configured for this server.
+    pub fn connection_builder(&self, identifier: impl Into<String>) -> MinecraftConnectionBuilder {
+        let builder =
+            MinecraftConnection::builder(identifier, &self.world_dir).log_file(&self.log_file);
+        match self.version.parse::<MinecraftVersion>() {
+            Ok(version) => builder.minecraft_version(version),
+            Err(_) => builder,
+        }
+    }
+
+    /// Executes a command in the server console, for example `"reload"`.
+    pub fn console(&mut self, command: &str) -> io::Result<()> {
+        let stdin = self
+            .process
+            .as_mut()
+            .and_then(|process| process.stdin.as_mut())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "Server is not running"))?;
+        writeln!(stdin, "{}", command)?;
+        stdin.flush()
+    }
+
+    /// Stops the server gracefully and removes its directory if it was created automatically.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.console("stop")?;
+        if let Some(mut process) = self.process.take() {
+            process.wait()?;
+        }
+        self.remove_dir()
+    }
+
+    fn wait_until_ready(&mut self, startup_timeout: Duration) -> io::Result<()> {
+        let start = Instant::now();
+        while start.elapsed() < startup_timeout {
+            if let Some(process) = &mut self.process {
+                if let Some(status) = process.try_wait()? {
+                    let message = format!("Minecraft server exited with {}", status);
+                    return Err(io::Error::other(message));
+                }
+            }
+            let log = fs::read_to_string(&self.log_file).unwrap_or_default();
+            if log.lines().any(is_done_line) {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "Minecraft server did not start within {:?}",
+                startup_timeout
+            ),
+        ))
+    }
+
+    fn remove_dir(&self) -> io::Result<()> {
+        if self.remove_dir {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+        if let Err(e) = self.remove_dir() {
+            warn!("Failed to remove {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+fn get_json(url: &str) -> io::Result<Value> {
+    let mut response = ureq::get(url).call().map_err(io::Error::other)?;
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(io::Error::other)?;
+    serde_json::from_str(&body).map_err(io_invalid_data)
+}
+
+fn temp_server_dir() -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+    std::env::temp_dir().join(format!("minect_server_{}_{}", std::process::id(), nanos))
+}
+
+fn free_port() -> io::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+/// Whether `line` is the line that Minecraft logs after the server started, for example:
+/// `[12:00:00] [Server thread/INFO]: Done (3.456s)! For help, type "help"`.
+fn is_done_line(line: &str) -> bool {
+    line.contains("]: Done (") && line.contains("For help, type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_done_line() {
+        assert!(is_done_line(
+            r#"[12:00:00] [Server thread/INFO]: Done (3.456s)! For help, type "help""#
+        ));
+        assert!(!is_done_line(
+            "[12:00:00] [Server thread/INFO]: Preparing spawn area: 0%"
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::file_backend::FileBackend;
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// The function that uninstalls Minect in Minecraft. It removes all connections and objectives and
+/// disables the Minect datapack.
+pub(crate) const UNINSTALL_FUNCTION: &str = "function minect:uninstall_completely";
+
+/// The result of
+/// [MinecraftConnection::uninstall_and_verify](crate::MinecraftConnection::uninstall_and_verify).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UninstallReport {
+    /// Directories of Minect that could not be removed from the world directory. The reason is
+    /// logged with the `log` crate.
+    pub remaining_paths: Vec<PathBuf>,
+    _private: (),
+}
+
+impl UninstallReport {
+    /// Removes the given directories of Minect and reports all that still exist afterwards.
+    pub(crate) fn remove(
+        backend: &dyn FileBackend,
+        paths: impl IntoIterator<Item = PathBuf>,
+    ) -> UninstallReport {
+        let remaining_paths = paths
+            .into_iter()
+            .filter(|path| !remove_if_exists(backend, path))
+            .collect();
+        UninstallReport {
+            remaining_paths,
+            _private: (),
+        }
+    }
+
+    /// Whether nothing of Minect was left behind in the world directory.
+    pub fn is_complete(&self) -> bool {
+        self.remaining_paths.is_empty()
+    }
+}
+
+/// Returns `true` if `path` does not exist anymore.
+fn remove_if_exists(backend: &dyn FileBackend, path: &Path) -> bool {
+    if backend.is_dir(path) {
+        if let Err(e) = backend.remove_dir_all(path) {
+            warn!("Failed to remove directory {}: {}", path.display(), e);
+        }
+    }
+    !backend.is_dir(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn test_remove() {
+        // given:
+        let dir = TempDir::new("uninstall");
+        let datapack_dir = dir.join("datapacks").join("minect");
+        let generated_dir = dir.join("generated").join("minect");
+        create_dir_all(datapack_dir.join("data")).unwrap();
+        write(datapack_dir.join("pack.mcmeta"), "{}").unwrap();
+
+        // when:
+        let report =
+            UninstallReport::remove(&LocalFileBackend, [datapack_dir.clone(), generated_dir]);
+
+        // then:
+        assert!(report.is_complete());
+        assert!(!datapack_dir.exists());
+    }
+}
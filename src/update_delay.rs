@@ -0,0 +1,323 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{retry::is_after, Command};
+use std::{
+    collections::BTreeMap,
+    ops::RangeInclusive,
+    sync::{Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// The duration of a game tick.
+const TICK: Duration = Duration::from_millis(50);
+
+/// The number of batches that are written, but not yet loaded, from which the connection is
+/// considered busy. With [acknowledge_every](crate::MinecraftConnectionBuilder::acknowledge_every)
+/// the batches after the last acknowledged one may already be loaded, so they are not counted.
+const HIGH_BACKLOG: usize = 2;
+
+/// The time between writing and loading a batch from which the connection is considered slow.
+const HIGH_LATENCY: Duration = Duration::from_millis(250);
+
+/// A change of the update delay of a connection by the
+/// [adaptive_update_delay](crate::MinecraftConnectionBuilder::adaptive_update_delay) controller.
+///
+/// These events can be received with
+/// [MinecraftConnection::add_update_delay_listener](crate::MinecraftConnection::add_update_delay_listener).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UpdateDelayChanged {
+    /// The new update delay in game ticks.
+    pub update_delay: u32,
+    /// Why the update delay was changed.
+    pub reason: UpdateDelayReason,
+    _private: (),
+}
+
+/// Why the [adaptive_update_delay](crate::MinecraftConnectionBuilder::adaptive_update_delay)
+/// controller changed the update delay.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpdateDelayReason {
+    /// Too many batches are waiting to be loaded.
+    Backlog,
+    /// The last batch took too long to be loaded.
+    Latency,
+    /// No batch was executed for a while.
+    Idle,
+}
+
+/// Adjusts the update delay of a single connection to its load. Every batch ends with commands that
+/// store the delay chosen by the controller in the scores of the connection entity and tag it with
+/// `minect_adaptive`. Such a connection building is updated by `minect_internal:adaptive_update`
+/// with its own delay instead of the world-wide `update_delay`, so other connections are not
+/// affected. Once no batch was loaded for `idle_after`, Minecraft raises the delay to the maximum.
+///
+/// Lowering the delay reacts to the backlog and latency of a single batch, while raising it requires
+/// a sustained idle period. This hysteresis avoids toggling between the bounds.
+pub(crate) struct UpdateDelayController {
+    identifier: String,
+    bounds: RangeInclusive<u32>,
+    idle_after: Duration,
+    acknowledge_every: u64,
+    state: Mutex<ControllerState>,
+    listeners: RwLock<Vec<UnboundedSender<UpdateDelayChanged>>>,
+}
+
+struct ControllerState {
+    update_delay: u32,
+    last_written: Option<Instant>,
+    /// The time at which each batch that was not yet loaded was written.
+    unloaded: BTreeMap<u64, Instant>,
+    last_latency: Duration,
+}
+
+impl UpdateDelayController {
+    pub(crate) fn new(
+        identifier: impl Into<String>,
+        bounds: RangeInclusive<u32>,
+        idle_after: Duration,
+        acknowledge_every: u64,
+    ) -> UpdateDelayController {
+        UpdateDelayController {
+            identifier: identifier.into(),
+            acknowledge_every,
+            state: Mutex::new(ControllerState {
+                update_delay: *bounds.end(),
+                last_written: None,
+                unloaded: BTreeMap::new(),
+                last_latency: Duration::ZERO,
+            }),
+            bounds,
+            idle_after,
+            listeners: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn add_listener(&self) -> UnboundedReceiverStream<UpdateDelayChanged> {
+        let (sender, receiver) = unbounded_channel();
+        self.listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    /// The number of commands returned from [written](Self::written).
+    pub(crate) fn commands_len(&self) -> usize {
+        4
+    }
+
+    /// Records that the batch with the given `id` is written and returns the commands that have to
+    /// be executed at the end of the batch. They are appended after the commands of the batch, so
+    /// they don't change which command a [conditional](Command::conditional) command depends on.
+    pub(crate) fn written(&self, id: u64, now: Instant) -> Vec<Command> {
+        let mut state = self.state.lock().unwrap();
+        self.relax_if_idle(&mut state, now);
+        state.last_written = Some(now);
+        state.unloaded.insert(id, now);
+        let reason = if state.unloaded.len() >= HIGH_BACKLOG.saturating_add(self.unknown_loads()) {
+            Some(UpdateDelayReason::Backlog)
+        } else if state.last_latency >= HIGH_LATENCY {
+            Some(UpdateDelayReason::Latency)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            self.change(&mut state, *self.bounds.start(), reason);
+        }
+        let connection = format!(
+            "@e[type=area_effect_cloud,tag=minect_connection+{}]",
+            self.identifier
+        );
+        Vec::from_iter([
+            Command::new(format!("tag {} add minect_adaptive", connection)),
+            Command::new(format!(
+                "scoreboard players set {} minect_delay {}",
+                connection, state.update_delay
+            )),
+            Command::new(format!(
+                "scoreboard players set {} minect_max_delay {}",
+                connection,
+                self.bounds.end()
+            )),
+            Command::new(format!(
+                "scoreboard players set {} minect_idle {}",
+                connection,
+                self.idle_ticks()
+            )),
+        ])
+    }
+
+    /// Records that the batch with the given `id` and all batches before it were loaded.
+    pub(crate) fn loaded(&self, id: u64, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(written) = state.unloaded.get(&id) {
+            state.last_latency = now.saturating_duration_since(*written);
+        }
+        state.unloaded.retain(|other_id, _| is_after(*other_id, id));
+    }
+
+    /// Called regularly to notice when Minecraft raised the delay because the connection was idle.
+    pub(crate) fn poll(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        self.relax_if_idle(&mut state, now);
+    }
+
+    fn relax_if_idle(&self, state: &mut ControllerState, now: Instant) {
+        let idle = match state.last_written {
+            Some(last_written) => now.saturating_duration_since(last_written) >= self.idle_after,
+            None => false,
+        };
+        if idle && state.unloaded.len() <= self.unknown_loads() {
+            // The latency of the last burst says nothing about the next one
+            state.last_latency = Duration::ZERO;
+            self.change(state, *self.bounds.end(), UpdateDelayReason::Idle);
+        }
+    }
+
+    fn change(&self, state: &mut ControllerState, update_delay: u32, reason: UpdateDelayReason) {
+        if state.update_delay == update_delay {
+            return;
+        }
+        state.update_delay = update_delay;
+        let event = UpdateDelayChanged {
+            update_delay,
+            reason,
+            _private: (),
+        };
+        self.listeners
+            .write()
+            .unwrap()
+            .retain(|sender| sender.send(event).is_ok());
+    }
+
+    /// The number of batches that may have been loaded without being acknowledged.
+    fn unknown_loads(&self) -> usize {
+        usize::try_from(self.acknowledge_every - 1).unwrap_or(usize::MAX)
+    }
+
+    fn idle_ticks(&self) -> u128 {
+        let ticks = self.idle_after.as_millis() / TICK.as_millis();
+        ticks.max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_backlog_lowers_and_idle_raises_update_delay() {
+        // given:
+        let controller = UpdateDelayController::new("a", 1..=20, Duration::from_secs(5), 1);
+        let events = controller.add_listener();
+        let start = Instant::now();
+        let seconds = |seconds| start + Duration::from_secs(seconds);
+
+        // when:
+        controller.written(1, seconds(0));
+        let commands = controller.written(2, seconds(0));
+        controller.loaded(2, seconds(1));
+        controller.poll(seconds(3));
+        controller.poll(seconds(6));
+
+        // then:
+        let commands = commands
+            .iter()
+            .map(Command::get_command)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            [
+                "tag @e[type=area_effect_cloud,tag=minect_connection+a] add minect_adaptive",
+                "scoreboard players set @e[type=area_effect_cloud,tag=minect_connection+a] minect_delay 1",
+                "scoreboard players set @e[type=area_effect_cloud,tag=minect_connection+a] minect_max_delay 20",
+                "scoreboard players set @e[type=area_effect_cloud,tag=minect_connection+a] minect_idle 100",
+            ]
+        );
+        drop(controller);
+        let actual = block_on(
+            events
+                .map(|event| (event.update_delay, event.reason))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            actual,
+            [
+                (1, UpdateDelayReason::Backlog),
+                (20, UpdateDelayReason::Idle)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latency_lowers_update_delay() {
+        // given:
+        let controller = UpdateDelayController::new("a", 2..=20, Duration::from_secs(5), 1);
+        let start = Instant::now();
+
+        // when:
+        let first = controller.written(1, start);
+        controller.loaded(1, start + Duration::from_secs(1));
+        let second = controller.written(2, start + Duration::from_secs(2));
+
+        // then:
+        assert_eq!(
+            first[1].get_command(),
+            "scoreboard players set @e[type=area_effect_cloud,tag=minect_connection+a] minect_delay 20"
+        );
+        assert_eq!(
+            second[1].get_command(),
+            "scoreboard players set @e[type=area_effect_cloud,tag=minect_connection+a] minect_delay 2"
+        );
+    }
+
+    #[test]
+    fn test_unacknowledged_batches_neither_lower_nor_keep_update_delay() {
+        // given:
+        let controller = UpdateDelayController::new("a", 2..=20, Duration::from_secs(5), 3);
+        let events = controller.add_listener();
+        let start = Instant::now();
+        let seconds = |seconds| start + Duration::from_secs(seconds);
+
+        // when:
+        controller.written(1, seconds(0));
+        controller.written(2, seconds(0));
+        controller.written(3, seconds(0));
+        controller.loaded(3, seconds(1));
+        controller.written(4, seconds(2));
+        controller.written(5, seconds(2));
+        controller.poll(seconds(8));
+
+        // then:
+        drop(controller);
+        let actual = block_on(
+            events
+                .map(|event| (event.update_delay, event.reason))
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            actual,
+            [
+                (2, UpdateDelayReason::Latency),
+                (20, UpdateDelayReason::Idle)
+            ]
+        );
+    }
+}
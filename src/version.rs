@@ -0,0 +1,131 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// A version of Minecraft Java Edition, for example `1.20.4`.
+///
+/// Versions can be parsed from and formatted to strings like `"1.20.4"` or `"1.20"`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MinecraftVersion {
+    major: u16,
+    minor: u16,
+    patch: u16,
+}
+
+impl MinecraftVersion {
+    /// Creates a [MinecraftVersion] like `1.20.4` from its components `1`, `20` and `4`.
+    pub const fn new(major: u16, minor: u16, patch: u16) -> MinecraftVersion {
+        MinecraftVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The lowest Y coordinate at which blocks can be placed in the overworld.
+    pub fn min_build_height(self) -> i32 {
+        if self >= MinecraftVersion::new(1, 18, 0) {
+            -64
+        } else {
+            0
+        }
+    }
+
+    /// The Y coordinate above the highest block that can be placed in the overworld.
+    pub fn max_build_height(self) -> i32 {
+        if self >= MinecraftVersion::new(1, 18, 0) {
+            320
+        } else {
+            256
+        }
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('.');
+        let major = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let minor = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let patch = match parts.next() {
+            Some(patch) => patch.parse().map_err(|_| ())?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Ok(MinecraftVersion::new(major, minor, patch))
+    }
+}
+
+impl Display for MinecraftVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.patch == 0 {
+            write!(f, "{}.{}", self.major, self.minor)
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MinecraftVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MinecraftVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string.parse().map_err(|()| {
+            serde::de::Error::custom(format!("Invalid Minecraft version: {}", string))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_str_to_string() {
+        // given:
+        let strings = ["1.17", "1.20.4"];
+
+        // when:
+        let actual = strings.map(|s| s.parse::<MinecraftVersion>().unwrap());
+
+        // then:
+        assert_eq!(
+            actual,
+            [
+                MinecraftVersion::new(1, 17, 0),
+                MinecraftVersion::new(1, 20, 4)
+            ]
+        );
+        assert_eq!(actual.map(|version| version.to_string()), strings);
+        assert!(actual[0] < actual[1]);
+    }
+}
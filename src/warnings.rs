@@ -0,0 +1,164 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use crate::io::IoErrorAtPath;
+use log::{error, warn};
+use std::{fmt::Display, path::PathBuf, sync::RwLock};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+/// A problem that Minect worked around, but that an application may want to react to, for example
+/// by splitting a batch that contained too many commands.
+///
+/// Warnings are logged with the `log` crate and can also be received with
+/// [MinecraftConnection::warnings](crate::MinecraftConnection::warnings).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MinectWarning {
+    /// A batch contained more commands than fit into the command block area, so only the first
+    /// `max` commands were executed and the rest was ignored.
+    TooManyCommands {
+        /// The number of commands in the batch, including commands that Minect added implicitly.
+        commands: usize,
+        /// The number of commands that were executed.
+        max: usize,
+    },
+    /// Minecraft did not acknowledge the structure with the given `id` in time, so its commands are
+    /// executed again in a new batch (see
+    /// [retry_unacknowledged_after](crate::MinecraftConnectionBuilder::retry_unacknowledged_after)).
+    BatchRetried {
+        /// The structure id of the batch.
+        id: u64,
+    },
+    /// Minecraft skipped the structure with the given `id` (see
+    /// [BatchSkipped](crate::BatchSkipped)).
+    StructureSkipped {
+        /// The structure id of the skipped batch.
+        id: u64,
+    },
+    /// A batch logged more than `threshold` bytes (see
+    /// [log_volume_threshold](crate::MinecraftConnectionBuilder::log_volume_threshold)).
+    LogVolumeExceeded {
+        /// The configured threshold in bytes.
+        threshold: u64,
+    },
+    /// A batch from the [spool_dir](crate::MinecraftConnectionBuilder::spool_dir) can never be
+    /// written, so it was moved to `path` to execute the batches after it.
+    SpooledBatchQuarantined {
+        /// The new path of the batch.
+        path: PathBuf,
+        /// A description of the error.
+        message: String,
+    },
+    /// A file operation that Minect performs in the background failed, for example removing a
+    /// structure file that Minecraft already loaded.
+    IoFailed {
+        /// The file or directory.
+        path: PathBuf,
+        /// A description of the error.
+        message: String,
+    },
+}
+
+impl MinectWarning {
+    pub(crate) fn io_failed(error: IoErrorAtPath) -> MinectWarning {
+        MinectWarning::IoFailed {
+            message: error.to_string(),
+            path: error.path,
+        }
+    }
+}
+
+impl Display for MinectWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MinectWarning::TooManyCommands { commands, max } => write!(
+                f,
+                "Attempted to execute {} commands. \
+                 Only the first {} commands will be executed. \
+                 The rest will be ignored.",
+                commands, max
+            ),
+            MinectWarning::BatchRetried { id } => write!(
+                f,
+                "Structure {} was not acknowledged in time, executing its commands again",
+                id
+            ),
+            MinectWarning::StructureSkipped { id } => {
+                write!(f, "Structure {} was skipped by Minecraft", id)
+            }
+            MinectWarning::LogVolumeExceeded { threshold } => write!(
+                f,
+                "A batch exceeded the log volume threshold of {} bytes. Consider using \
+                silent commands or disabling logging for noisy commands.",
+                threshold
+            ),
+            MinectWarning::SpooledBatchQuarantined { path, message } => {
+                write!(f, "Moved spooled batch to {}: {}", path.display(), message)
+            }
+            MinectWarning::IoFailed { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Logs [MinectWarning]s and sends them to all listeners.
+#[derive(Default)]
+pub(crate) struct Warnings {
+    listeners: RwLock<Vec<UnboundedSender<MinectWarning>>>,
+}
+
+impl Warnings {
+    pub(crate) fn add_listener(&self) -> impl Stream<Item = MinectWarning> {
+        let (sender, receiver) = unbounded_channel();
+        self.listeners.write().unwrap().push(sender);
+        UnboundedReceiverStream::new(receiver)
+    }
+
+    pub(crate) fn report(&self, warning: MinectWarning) {
+        if let MinectWarning::IoFailed { .. } = warning {
+            error!("{}", warning);
+        } else {
+            warn!("{}", warning);
+        }
+        self.listeners
+            .write()
+            .unwrap()
+            .retain(|sender| sender.send(warning.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_report() {
+        // given:
+        let warnings = Warnings::default();
+        let listener = warnings.add_listener();
+
+        // when:
+        warnings.report(MinectWarning::StructureSkipped { id: 3 });
+        drop(warnings);
+
+        // then:
+        let actual = block_on(listener.collect::<Vec<_>>());
+        assert_eq!(actual, [MinectWarning::StructureSkipped { id: 3 }]);
+    }
+}
@@ -0,0 +1,162 @@
+// Minect is library that allows a program to connect to a running Minecraft instance without
+// requiring any Minecraft mods.
+//
+// © Copyright (C) 2021-2023 Adrodoc <adrodoc55@googlemail.com> & skess42 <skagaros@gmail.com>
+//
+// This file is part of Minect.
+//
+// Minect is free software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// Minect is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even
+// the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General
+// Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Minect.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Identifies the world that a connection is created for, so the interactive installer can abort
+//! if a player confirms it in a different world.
+
+use crate::file_backend::FileBackend;
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+/// A player confirmed the interactive installer in a different world than the one the connection
+/// was created for. This is returned by
+/// [ConnectError::wrong_world](crate::ConnectError::wrong_world).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WrongWorld {
+    /// The name of the world the connection was created for, as stored in its `level.dat`.
+    pub expected_name: String,
+    /// The seed of the world the connection was created for.
+    pub expected_seed: i64,
+    /// The seed of the world in which the installer was confirmed.
+    pub actual_seed: i64,
+    _private: (),
+}
+
+impl WrongWorld {
+    pub(crate) fn new(expected: WorldFingerprint, actual_seed: i64) -> WrongWorld {
+        WrongWorld {
+            expected_name: expected.level_name,
+            expected_seed: expected.seed,
+            actual_seed,
+            _private: (),
+        }
+    }
+}
+
+impl Display for WrongWorld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The installer was confirmed in a world with seed {}, but the connection is for the \
+            world '{}' with seed {}",
+            self.actual_seed, self.expected_name, self.expected_seed
+        )
+    }
+}
+
+/// The name and seed of a world, read from its `level.dat`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct WorldFingerprint {
+    pub(crate) level_name: String,
+    pub(crate) seed: i64,
+}
+
+#[derive(Deserialize)]
+struct LevelDat {
+    #[serde(rename = "Data")]
+    data: LevelData,
+}
+
+#[derive(Deserialize)]
+struct LevelData {
+    #[serde(rename = "LevelName")]
+    level_name: String,
+    /// Since Minecraft 1.16 the seed is stored in the world generation settings.
+    #[serde(rename = "WorldGenSettings")]
+    world_gen_settings: Option<WorldGenSettings>,
+    #[serde(rename = "RandomSeed")]
+    random_seed: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct WorldGenSettings {
+    seed: i64,
+}
+
+impl WorldFingerprint {
+    /// Returns [None] if the `level.dat` in `world_dir` does not exist yet or can't be parsed.
+    pub(crate) fn read(backend: &dyn FileBackend, world_dir: &Path) -> Option<WorldFingerprint> {
+        let contents = backend.read(&world_dir.join("level.dat")).ok()?;
+        let level: LevelDat = nbt::from_reader(GzDecoder::new(&contents[..])).ok()?;
+        let seed = level
+            .data
+            .world_gen_settings
+            .map(|settings| settings.seed)
+            .or(level.data.random_seed)?;
+        Some(WorldFingerprint {
+            level_name: level.data.level_name,
+            seed,
+        })
+    }
+
+    /// The range that matches the result of the `seed` command in this world. Minecraft truncates
+    /// the seed to an `i32` when storing it in a score.
+    pub(crate) fn seed_range(fingerprint: Option<&WorldFingerprint>) -> String {
+        match fingerprint {
+            Some(fingerprint) => (fingerprint.seed as i32).to_string(),
+            None => format!("{}..", i32::MIN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{file_backend::LocalFileBackend, utils::TempDir};
+    use flate2::{write::GzEncoder, Compression};
+    use nbt::{Blob, Value};
+    use std::{collections::HashMap, fs::write};
+
+    #[test]
+    fn test_read_fingerprint() {
+        // given:
+        let world_dir = TempDir::new("world");
+        let settings = HashMap::from_iter([("seed".to_string(), Value::Long(-5_000_000_000))]);
+        let data = HashMap::from_iter([
+            (
+                "LevelName".to_string(),
+                Value::String("My World".to_string()),
+            ),
+            ("WorldGenSettings".to_string(), Value::Compound(settings)),
+            ("DataVersion".to_string(), Value::Int(3465)),
+        ]);
+        let mut blob = Blob::new();
+        blob.insert("Data", Value::Compound(data)).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        blob.to_writer(&mut encoder).unwrap();
+        write(world_dir.join("level.dat"), encoder.finish().unwrap()).unwrap();
+
+        // when:
+        let actual = WorldFingerprint::read(&LocalFileBackend, &world_dir);
+
+        // then:
+        let expected = WorldFingerprint {
+            level_name: "My World".to_string(),
+            seed: -5_000_000_000,
+        };
+        assert_eq!(actual, Some(expected));
+        assert_eq!(
+            WorldFingerprint::seed_range(actual.as_ref()),
+            (-5_000_000_000_i64 as i32).to_string()
+        );
+    }
+}
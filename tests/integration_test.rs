@@ -153,7 +153,7 @@ async fn test_logged_block_commands_are_executed_within_the_same_tick() -> io::R
     fn_commands.extend(named_logged_block_commands(listener_name, &command));
 
     create_test_function(fn_name, &fn_commands).await?;
-    connection.execute_commands([Command::new("reload")])?;
+    assert_eq!(connection.reload().await?, []);
 
     let mut events = connection.add_named_listener(listener_name);
     let commands = [
@@ -203,7 +203,7 @@ async fn test_scheduled_logged_block_commands_are_executed_within_the_same_tick(
     fn_commands.extend(named_logged_block_commands(listener_name, &command));
 
     create_test_function(fn_name, &fn_commands).await?;
-    connection.execute_commands([Command::new("reload")])?;
+    assert_eq!(connection.reload().await?, []);
 
     let mut events = connection.add_named_listener(listener_name);
     let commands = [
@@ -227,3 +227,62 @@ async fn test_scheduled_logged_block_commands_are_executed_within_the_same_tick(
 
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn test_execute_chains() -> io::Result<()> {
+    before_each_test().await;
+    // given:
+    let mut connection = new_connection();
+    let events = connection.add_listener();
+    let chains = [
+        vec![
+            Command::new("say running test_execute_chains"),
+            Command::named("first", summon_named_entity_command("first")),
+        ],
+        vec![Command::named(
+            "second",
+            summon_named_entity_command("second"),
+        )],
+    ];
+
+    // when:
+    connection.execute_chains(chains)?;
+
+    // then:
+    let executors = events
+        .map(|event| event.executor)
+        .filter(|executor| executor == "first" || executor == "second")
+        .take(2)
+        .collect::<Vec<_>>();
+    let executors = timeout(TEST_TIMEOUT, executors).await?;
+    assert_eq!(executors, ["first", "second"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_execute_and_stream() -> io::Result<()> {
+    before_each_test().await;
+    // given:
+    let mut connection = new_connection();
+    let commands = [
+        Command::new("say running test_execute_and_stream"),
+        Command::named("first", summon_named_entity_command("first")),
+        Command::named("second", summon_named_entity_command("second")),
+    ];
+
+    // when:
+    let events = connection.execute_and_stream(commands)?;
+
+    // then:
+    let events = timeout(TEST_TIMEOUT, events.collect::<Vec<_>>()).await?;
+    let executors = events
+        .iter()
+        .map(|event| event.executor.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(executors, ["first", "second"]);
+
+    Ok(())
+}